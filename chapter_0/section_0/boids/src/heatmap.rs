@@ -0,0 +1,102 @@
+//! Density heatmap behind the flock
+//!
+//! With "Density heatmap" on in the options window the arena is split into coarse
+//! cells, the boids in each are counted every frame, and the counts, eased over a few
+//! frames so the picture doesn't flicker, are drawn behind the flock as one
+//! [`FieldMaterial`] quad. Waves of crowding running through a flock and clusters
+//! forming and breaking up show as moving bright patches.
+use bevy::prelude::*;
+use rhysics_common::boundary::Boundary;
+use rhysics_common::field::{field_quad, FieldGrid, FieldMaterial, FieldMaterialPlugin};
+use rhysics_common::spatial::SpatialGrid;
+
+const HEATMAP_CELL: f32 = 20.0;          // Side of a heatmap cell (px)
+const HEATMAP_EASING: f32 = 0.2;         // Share of the way to this frame's counts each frame
+const HEATMAP_Z: f32 = -2.0;             // Behind boids and obstacles
+
+/// Draws the [`DensityHeatmap`]; needs a renderer, so only the windowed app adds it
+pub struct HeatmapPlugin;
+
+impl Plugin for HeatmapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(FieldMaterialPlugin)
+            .init_resource::<DensityHeatmap>()
+            .add_systems(Update, update_heatmap);
+    }
+}
+
+/// Whether the heatmap is shown, and what it is drawing with
+#[derive(Resource, Default)]
+pub struct DensityHeatmap {
+    pub show: bool,
+    drawn: Option<HeatmapQuad>,
+}
+
+/// The counts and the quad showing them
+struct HeatmapQuad {
+    grid: FieldGrid,
+    image: Handle<Image>,
+    material: Handle<FieldMaterial>,
+    entity: Entity,
+    rect: Rect,
+}
+
+/// Count the boids in each cell and ease the heatmap towards the counts, making the
+/// quad again when the arena is resized and removing it when the heatmap is off
+pub fn update_heatmap(
+    mut heatmap: ResMut<DensityHeatmap>,
+    mut commands: Commands,
+    grid: Res<SpatialGrid>,
+    boundary: Res<Boundary>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<FieldMaterial>>,
+) {
+    let rect = boundary.rect;
+    let stale = heatmap.drawn.as_ref().is_some_and(|drawn| !heatmap.show || drawn.rect != rect);
+    if stale {
+        if let Some(drawn) = heatmap.drawn.take() {
+            commands.entity(drawn.entity).despawn();
+        }
+    }
+    if !heatmap.show {
+        return;
+    }
+    let drawn = heatmap.drawn.get_or_insert_with(|| {
+        let size = rect.size();
+        let grid = FieldGrid::new(
+            (size.x / HEATMAP_CELL).ceil().max(1.0) as u32,
+            (size.y / HEATMAP_CELL).ceil().max(1.0) as u32,
+        );
+        let image = images.add(grid.image());
+        let material = materials.add(FieldMaterial::new(image.clone()));
+        let entity = commands
+            .spawn((
+                field_quad(size, &mut meshes, material.clone()),
+                Transform::from_translation(rect.center().extend(HEATMAP_Z)),
+            ))
+            .id();
+        HeatmapQuad { grid, image, material, entity, rect }
+    });
+
+    let (width, height) = (drawn.grid.width, drawn.grid.height);
+    let mut counts = vec![0.0; drawn.grid.cells.len()];
+    for (_, position) in grid.entries() {
+        let cell = ((*position - rect.min) / HEATMAP_CELL).floor();
+        let x = (cell.x.max(0.0) as u32).min(width - 1);
+        let y = (cell.y.max(0.0) as u32).min(height - 1);
+        counts[drawn.grid.index(x, y)] += 1.0;
+    }
+    for (cell, count) in drawn.grid.cells.iter_mut().zip(counts) {
+        cell.x += (count - cell.x) * HEATMAP_EASING;
+    }
+
+    if let Some(image) = images.get_mut(&drawn.image) {
+        drawn.grid.write_to(image);
+    }
+    // The busiest cell is the top of the colormap, so the picture reads the same at any flock size
+    let peak = drawn.grid.cells.iter().map(|cell| cell.x).fold(1.0, f32::max);
+    if let Some(material) = materials.get_mut(&drawn.material) {
+        material.range = (0.0, peak);
+    }
+}
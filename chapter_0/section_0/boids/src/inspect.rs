@@ -0,0 +1,119 @@
+//! Looking inside one boid's head
+//!
+//! With "Inspect boids" on in the options window a click picks the boid under the
+//! pointer instead of placing an obstacle. The picked boid's view radius, lines to the
+//! neighbors it currently sees and its alignment, cohesion and separation as colored
+//! arrows are drawn over the flock, and a side panel lists every steering term. That
+//! shows rule by rule why the boid turns the way it does.
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_egui::input::EguiWantsInput;
+use rhysics_common::boundary::{Boundary, BoundaryMode};
+use rhysics_common::pointer::Pointer;
+use rhysics_common::spatial::SpatialGrid;
+use rhysics_common::world_scale::WorldScale;
+use rhysics_common::Velocity;
+
+use crate::mouse::MouseInteraction;
+use crate::species::{Species, SpeciesTable};
+use crate::vision::{half_fov_cos, in_view, nearest_in_view};
+use crate::{Boid, SteeringForces};
+
+const SELECT_RADIUS: f32 = 10.0;         // How near the pointer a boid has to be to be picked
+const ARROW_SCALE: f32 = 2.0;            // Arrow length per unit of steering
+const VIEW_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.4);
+const NEIGHBOR_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.25);
+const ALIGNMENT_COLOR: Color = Color::srgb(0.3, 0.6, 1.0);
+const COHESION_COLOR: Color = Color::srgb(0.3, 1.0, 0.4);
+const SEPARATION_COLOR: Color = Color::srgb(1.0, 0.35, 0.3);
+const TOTAL_COLOR: Color = Color::WHITE;
+
+/// The boid being inspected, and its steering as `update_boids` last worked it out
+#[derive(Resource, Debug, Default)]
+pub struct BoidInspector {
+    pub enabled: bool,
+    pub selected: Option<Entity>,
+    pub forces: Option<SteeringForces>,
+}
+
+/// Pick the boid nearest a click, or none if the click missed
+#[allow(clippy::too_many_arguments)]
+pub fn select_boid(
+    mut inspector: ResMut<BoidInspector>,
+    pointer: Res<Pointer>,
+    interaction: Res<MouseInteraction>,
+    egui_input: Option<Res<EguiWantsInput>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    scale: Res<WorldScale>,
+    grid: Res<SpatialGrid>,
+) {
+    if !inspector.enabled {
+        if inspector.selected.is_some() {
+            inspector.selected = None;
+        }
+        return;
+    }
+    if !pointer.just_released || !interaction.tapped() || egui_input.is_some_and(|input| input.wants_any_pointer_input()) {
+        return;
+    }
+    let Some(position) = window
+        .single()
+        .ok()
+        .and_then(|window| pointer.position.map(|position| scale.screen_to_world(window, position)))
+    else {
+        return;
+    };
+    inspector.selected = grid
+        .query_radius(position, SELECT_RADIUS)
+        .min_by(|(_, a), (_, b)| a.distance_squared(position).total_cmp(&b.distance_squared(position)))
+        .map(|(entity, _)| entity);
+}
+
+/// Draw the inspected boid's view, the neighbors it sees and its flocking arrows
+pub fn draw_inspected_boid(
+    inspector: Res<BoidInspector>,
+    boids: Query<(&Transform, &Velocity, &Species), With<Boid>>,
+    table: Res<SpeciesTable>,
+    grid: Res<SpatialGrid>,
+    boundary: Res<Boundary>,
+    boundary_mode: Res<BoundaryMode>,
+    mut gizmos: Gizmos,
+) {
+    let Some((transform, velocity, species)) = inspector.selected.and_then(|entity| boids.get(entity).ok()) else {
+        return;
+    };
+    let rules = table.rules(species.0);
+    let position = transform.translation.truncate();
+    // A topological boid's reach is however far its nearest neighbors are
+    if rules.nearest == 0 {
+        gizmos.circle_2d(position, rules.view_radius, VIEW_COLOR);
+    }
+
+    // The same neighbors `update_boids` counts, across the seam on a torus
+    let period = (*boundary_mode == BoundaryMode::Wrap).then_some(boundary.rect);
+    let cone = half_fov_cos(rules.fov_degrees);
+    let neighbors: Vec<(usize, Vec2)> = if rules.nearest > 0 {
+        nearest_in_view(&grid, position, velocity.0, rules.nearest, cone, period)
+    } else {
+        grid.query_radius_wrapped(position, rules.view_radius, period)
+            .filter(|&(_, other)| other != position && in_view(velocity.0, other - position, cone))
+            .collect()
+    };
+    for (_, other) in neighbors {
+        gizmos.line_2d(position, other, NEIGHBOR_COLOR);
+    }
+
+    let Some(forces) = inspector.forces else {
+        return;
+    };
+    for (force, color) in [
+        (forces.alignment, ALIGNMENT_COLOR),
+        (forces.cohesion, COHESION_COLOR),
+        (forces.separation, SEPARATION_COLOR),
+        (forces.total(), TOTAL_COLOR),
+    ] {
+        if force != Vec2::ZERO {
+            gizmos.arrow_2d(position, position + force * ARROW_SCALE, color);
+        }
+    }
+}
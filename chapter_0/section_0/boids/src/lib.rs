@@ -1,98 +1,489 @@
-use bevy::math::bounding::{Aabb2d, BoundingCircle, BoundingVolume, IntersectsVolume};
 use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use bevy::gizmos::config::GizmoConfigStore;
 use bevy::window::PrimaryWindow;
 use rhysics_common::*;
+use rhysics_common::boundary::{Boundary, BoundaryMode, BoundaryPlugin, BoundaryWall, Bounded};
+use rhysics_common::broadphase::Broadphase;
+use rhysics_common::collision::{self, find_contacts_where, ColliderShape};
+use rhysics_common::colormap::{ColorBy, ColorByPlugin};
+use rhysics_common::field_arrows::FieldArrowsPlugin;
+use rhysics_common::force_field::{FieldRegion, ForceField, PointAttractor, UniformWind, Vortex};
+use rhysics_common::console::{ConsoleCommand, ConsolePlugin, ConsoleRegistry};
+use rhysics_common::capture::CapturePlugin;
+use rhysics_common::controls::{ResetSimulation, SimulationControlsPlugin};
+use rhysics_common::datalog::{DataLogger, DataLoggerPlugin};
+use rhysics_common::diagnostics::{begin_span, end_span, DiagnosticsOverlayPlugin};
+use rhysics_common::headless::{self, HeadlessPlugin};
+use rhysics_common::kernels::{self, SteeringSums};
+use rhysics_common::parallel;
+use rhysics_common::physics::{run_physics_step, PhysicsPlugin, PhysicsSet, PhysicsStep};
+use rhysics_common::replay::{Recorded, ReplayPlugin};
+use rhysics_common::save::{SaveStatePlugin, Saved};
+use rhysics_common::pointer::{Pointer, PointerPlugin};
+use rhysics_common::presets::PresetPlugin;
+use rhysics_common::share::{LaunchConfig, ShareParams, SharePlugin, SimulationSeed};
+use rhysics_common::spatial::{rebuild_spatial_grid, SpatialGrid, SpatialGridPlugin, SpatialIndexed};
+use rhysics_common::spawn_layout::SpawnRegion;
+use rhysics_common::timestep::physics_stepped;
+use rhysics_common::trail::TrailPlugin;
+use rhysics_common::world_scale::{WorldScale, WorldScalePlugin};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+#[cfg(feature = "gpu-compute")]
+use rhysics_common::gpu::{GpuComputePlugin, GpuKernel, GpuParticles, GpuResults};
+#[cfg(feature = "gpu-compute")]
+use bevy::render::storage::ShaderStorageBuffer;
+#[cfg(feature = "gpu-compute")]
+use bevy::ecs::entity::EntityHashMap;
+#[cfg(feature = "inspector")]
+use rhysics_common::inspector::InspectorPlugin;
+
+mod heatmap;
+mod inspect;
+mod migration;
+mod mouse;
+mod obstacle;
+mod perch;
+mod predator;
+mod scenario;
+mod solid;
+mod species;
+mod stats;
+mod trails;
+mod ui;
+mod vision;
+mod wander;
+mod wind;
+use crate::heatmap::HeatmapPlugin;
+use crate::inspect::{draw_inspected_boid, select_boid, BoidInspector};
+use crate::migration::{advance_waypoint, draw_waypoints, migrate, Migration};
+use crate::mouse::{update_mouse_interaction, MouseInteraction};
+use crate::obstacle::{avoid_obstacles, place_obstacles, snapshot_obstacles, Obstacle, ObstacleQuery};
+use crate::perch::{perch, Perched, Perching};
+use crate::predator::{catch_prey, chase_prey, spawn_predator, spawn_predator_on_key, Predator};
+use crate::scenario::{run_scenario, ScenarioPlayer};
+use crate::solid::{separate_solid_boids, SolidBoids};
+use crate::species::{flock_sums_of, FlockSums, Species, SpeciesTable, MAX_SPECIES};
+use crate::stats::{update_flock_stats, FlockStats};
+use crate::trails::{sync_boid_trails, BoidTrails};
+use crate::vision::{draw_vision_cone, half_fov_cos, in_view, nearest_in_view, VisionCone};
+use crate::wander::{drift_wander, wander_force, Wander};
+use crate::wind::{sync_wind, wind_from_console, AmbientWind};
+use crate::ui::UiPlugin;
 
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
 const BACKGROUND_COLOR: Color = Color::srgb(0.1, 0.1, 0.1);
 const BOID_DIAMETER: f32 = 5.;
+const BOID_LENGTH: f32 = 8.;             // Nose to tail of a boid's drawn triangle, by default
 const BORDER_THICKNESS: f32 = 10.0;
 const MAX_SPEED: f32 = 300.0;           // Maximum velocity magnitude
+const MAX_FORCE: f32 = 600.0;           // Most a boid can change its velocity in a second
 const VIEW_RADIUS: f32 = 50.0;         // How far boids can "see" neighbors
+const FOV_DEGREES: f32 = 360.0;        // Width of the cone they see in, around their heading
+const TOPOLOGICAL_NEIGHBORS: usize = 7;  // Nearest boids a starling-like boid reacts to
 const ALIGN_WEIGHT: f32 = 15.0;          // Steer towards average heading
 const COHESION_WEIGHT: f32 = 15.0;       // Steer towards center of neighbors
 const SEPARATION_WEIGHT: f32 = 17.0;     // Avoid crowding neighbors
 const WINDOW_AVOIDANCE_DISTANCE: f32 = 10.0;   // Start avoiding when this close to border
 const WINDOW_AVOIDANCE_WEIGHT: f32 = 30.0;     // How strongly to avoid borders
+const SOFT_AVOIDANCE_DISTANCE: f32 = 80.0;     // Margin to turn back in from when the walls are open
 const MOUSE_ATTRACTION_WEIGHT: f32 = 30.0;  // Steer towards mouse cursor
 const MOUSE_ATTRACTION_DISTANCE: f32 = 100.0; // Distance at which mouse attraction is applied
+const FLEE_WEIGHT: f32 = 60.0;              // Steer away from predators
+const FLEE_RADIUS: f32 = 120.0;             // Distance at which boids notice a predator
+const WANDER_WEIGHT: f32 = 5.0;              // Steer towards each boid's own drifting wander point
+const OBSTACLE_AVOIDANCE_WEIGHT: f32 = 50.0; // How strongly to steer around obstacles ahead
+const BOID_COLOR: Color = Color::srgb(1.0, 0.0, 0.0);
 const BORDER_COLOR: Color = Color::srgb(0.8, 0.8, 0.8);
+const FIELD_RADIUS: f32 = 300.0;          // How far a spawned vortex or attractor reaches
+const VORTEX_STRENGTH: f32 = 40.0;        // Swirl at the edge of a vortex's core
+const VORTEX_CORE: f32 = 100.0;           // Radius of a vortex's core
+const ATTRACTOR_STRENGTH: f32 = 300000.0; // Pull of an attractor, falling off as 1/r²
+const ATTRACTOR_SOFTENING: f32 = 50.0;    // Inside this distance an attractor stops pulling harder
+const FLOCK_SIZE: usize = 1000;            // Boids spawned at startup, unless the link gives `count`
+const MAX_FLOCK_SIZE: usize = 50_000;       // Most boids the population slider offers; links can ask for more
+const FLOCK_STEP: usize = 2000;             // Most boids spawned or despawned in one frame when the size changes
+const SPEED_SHADES: u32 = 32;               // Shared materials along the speed colormap
+const STEERING_CHUNK_SIZE: usize = 64;      // Fewest boids worth handing to another thread
+
+/// One species' flocking weights and limits, tuned live from the options window; the
+/// constants above are their defaults
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoidSettings {
+    pub align_weight: f32,
+    pub cohesion_weight: f32,
+    pub separation_weight: f32,
+    /// How far boids see their neighbors; beyond the grid's cell size, queries scan more cells
+    pub view_radius: f32,
+    /// Width of the vision cone around the heading; 360 sees all around
+    pub fov_degrees: f32,
+    /// Flock with only this many nearest boids in view, however far (topological);
+    /// 0 flocks with every boid within the view radius (metric)
+    pub nearest: usize,
+    pub max_speed: f32,
+    /// Most acceleration steering can give, in px/s²; lower turns in wider arcs
+    pub max_force: f32,
+    pub mouse_attraction_weight: f32,
+    pub mouse_attraction_distance: f32,
+    pub flee_weight: f32,
+    /// How close a predator gets before boids flee; the nearer, the harder they turn
+    pub flee_radius: f32,
+    pub wander_weight: f32,
+}
+
+impl Default for BoidSettings {
+    fn default() -> Self {
+        Self {
+            align_weight: ALIGN_WEIGHT,
+            cohesion_weight: COHESION_WEIGHT,
+            separation_weight: SEPARATION_WEIGHT,
+            view_radius: VIEW_RADIUS,
+            fov_degrees: FOV_DEGREES,
+            nearest: 0,
+            max_speed: MAX_SPEED,
+            max_force: MAX_FORCE,
+            mouse_attraction_weight: MOUSE_ATTRACTION_WEIGHT,
+            mouse_attraction_distance: MOUSE_ATTRACTION_DISTANCE,
+            flee_weight: FLEE_WEIGHT,
+            flee_radius: FLEE_RADIUS,
+            wander_weight: WANDER_WEIGHT,
+        }
+    }
+}
+
+impl BoidSettings {
+    /// Write the settings into a link, each key after `prefix`
+    pub fn write_params(&self, params: &mut ShareParams, prefix: &str) {
+        params.set(&format!("{prefix}align"), self.align_weight);
+        params.set(&format!("{prefix}cohesion"), self.cohesion_weight);
+        params.set(&format!("{prefix}separation"), self.separation_weight);
+        params.set(&format!("{prefix}view"), self.view_radius);
+        params.set(&format!("{prefix}fov"), self.fov_degrees);
+        params.set(&format!("{prefix}k"), self.nearest);
+        params.set(&format!("{prefix}speed"), self.max_speed);
+        params.set(&format!("{prefix}force"), self.max_force);
+        params.set(&format!("{prefix}attract"), self.mouse_attraction_weight);
+        params.set(&format!("{prefix}reach"), self.mouse_attraction_distance);
+        params.set(&format!("{prefix}flee"), self.flee_weight);
+        params.set(&format!("{prefix}fear"), self.flee_radius);
+        params.set(&format!("{prefix}wander"), self.wander_weight);
+    }
+
+    /// The number a rule's link key names, e.g. `cohesion`, for the console to change
+    pub fn rule_mut(&mut self, key: &str) -> Option<&mut f32> {
+        match key {
+            "align" => Some(&mut self.align_weight),
+            "cohesion" => Some(&mut self.cohesion_weight),
+            "separation" => Some(&mut self.separation_weight),
+            "view" => Some(&mut self.view_radius),
+            "fov" => Some(&mut self.fov_degrees),
+            "speed" => Some(&mut self.max_speed),
+            "force" => Some(&mut self.max_force),
+            "attract" => Some(&mut self.mouse_attraction_weight),
+            "reach" => Some(&mut self.mouse_attraction_distance),
+            "flee" => Some(&mut self.flee_weight),
+            "fear" => Some(&mut self.flee_radius),
+            "wander" => Some(&mut self.wander_weight),
+            _ => None,
+        }
+    }
+
+    /// Read back what [`BoidSettings::write_params`] wrote with the same `prefix`
+    pub fn read_params(&mut self, params: &ShareParams, prefix: &str) {
+        params.read_into(&format!("{prefix}align"), &mut self.align_weight);
+        params.read_into(&format!("{prefix}cohesion"), &mut self.cohesion_weight);
+        params.read_into(&format!("{prefix}separation"), &mut self.separation_weight);
+        params.read_into(&format!("{prefix}view"), &mut self.view_radius);
+        params.read_into(&format!("{prefix}fov"), &mut self.fov_degrees);
+        params.read_into(&format!("{prefix}k"), &mut self.nearest);
+        params.read_into(&format!("{prefix}speed"), &mut self.max_speed);
+        params.read_into(&format!("{prefix}force"), &mut self.max_force);
+        params.read_into(&format!("{prefix}attract"), &mut self.mouse_attraction_weight);
+        params.read_into(&format!("{prefix}reach"), &mut self.mouse_attraction_distance);
+        params.read_into(&format!("{prefix}flee"), &mut self.flee_weight);
+        params.read_into(&format!("{prefix}fear"), &mut self.flee_radius);
+        params.read_into(&format!("{prefix}wander"), &mut self.wander_weight);
+    }
+}
 
-#[cfg_attr(target_arch = "wasm32", wasm_bindgen(start))]
+#[cfg_attr(all(target_arch = "wasm32", feature = "standalone"), wasm_bindgen(start))]
 pub fn run() {
-    App::new()
-        .add_plugins(DefaultPlugins.set(default_window_plugin("Chapter 0.0 - Boids")))
-        .insert_resource(ClearColor(BACKGROUND_COLOR))
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins.set(default_window_plugin("Chapter 0.0 - Boids")))
+        .add_plugins(UiPlugin)
+        // Boid density drawn behind the flock, from the options window
+        .add_plugins(HeatmapPlugin);
+    add_simulation(&mut app);
+    // Neighbor sums on the GPU where compute shaders are available
+    #[cfg(feature = "gpu-compute")]
+    app.add_plugins(GpuComputePlugin::new(GpuKernel::Steering { radius: VIEW_RADIUS }));
+    // F2 opens an editor for any boid's components
+    #[cfg(feature = "inspector")]
+    app.add_plugins(InspectorPlugin);
+    app.run();
+}
+
+/// Fly the flock for `steps` physics ticks without a window, e.g. to log its
+/// polarization; returns the app to read the results from
+pub fn run_headless(steps: u32) -> App {
+    let mut app = App::new();
+    app.add_plugins(HeadlessPlugin::default());
+    add_simulation(&mut app);
+    headless::run_steps(&mut app, steps);
+    app
+}
+
+/// Everything but the window and renderer, shared by [`run`] and [`run_headless`]
+fn add_simulation(app: &mut App) {
+    app.insert_resource(ClearColor(BACKGROUND_COLOR))
+        // A link with `?seed=...` spawns the same flock, flying with the same weights
+        // (`?species=3` splits it into three species)
+        .init_resource::<SpeciesTable>()
+        .add_plugins(SharePlugin::<SpeciesTable>::default())
+        .add_plugins(boid_presets())
+        .add_plugins(WorldScalePlugin)
+        // Boids follow the cursor, or a finger on a touch screen
+        .add_plugins(PointerPlugin)
+        // Borders follow the window when it is resized
+        .add_plugins(BoundaryPlugin::default().with_wall_thickness(BORDER_THICKNESS))
+        // Cells as wide as a boid can see, so a neighbor query touches at most 3x3 cells
+        .add_plugins(SpatialGridPlugin::new(VIEW_RADIUS))
+        .add_plugins(ConsolePlugin)
+        .insert_resource(ConsoleRegistry::default().with_command("spawn", "spawn boid <count> | spawn vortex | attractor | predator [x y]")
+            .with_command("boundary", "boundary reflect | wrap | absorb | open")
+            .with_command("wind", "wind off | uniform [speed] [direction] | vortex [speed] | flip")
+            .with_command("scale", "scale <align | cohesion | separation | view | speed | force | wander | flee> <factor>"))
+        .add_plugins(SimulationControlsPlugin)
+        .add_plugins(CapturePlugin)
+        // F3 shows where the frame goes: steering, collisions or drawing
+        .add_plugins(DiagnosticsOverlayPlugin)
+        // Scrub back to replay whatever the flock just did
+        .add_plugins(ReplayPlugin)
+        // F5 saves the flock, F9 brings it back
+        .add_plugins(SaveStatePlugin)
+        // Polarization over time, exportable as CSV from the Data window
+        .add_plugins(DataLoggerPlugin)
+        .add_plugins(ColorByPlugin)
+        .init_resource::<BoidAssets>()
+        .init_resource::<BoidSize>()
+        .add_systems(Update, resize_boids)
+        .init_resource::<FlockSize>()
+        .init_resource::<VisionCone>()
+        // Fading paths behind each boid, off until the options window turns them on
+        .add_plugins(TrailPlugin)
+        .init_resource::<BoidTrails>()
+        .add_systems(Update, sync_boid_trails)
+        // Speed, polarization, spacing and flock count, refreshed every second
+        .init_resource::<FlockStats>()
+        .add_systems(Update, update_flock_stats)
+        .add_systems(PostUpdate, draw_vision_cone.run_if(resource_exists::<GizmoConfigStore>))
+        // Waypoints the flock can be sent round instead of milling about
+        .init_resource::<Migration>()
+        // The pointer attracts, repels or is ignored, `M` to cycle
+        .init_resource::<MouseInteraction>()
+        // Click a boid to see its view, neighbors and steering terms
+        .init_resource::<BoidInspector>()
+        // Boids as discs that can't overlap, off until the options window turns it on
+        .init_resource::<SolidBoids>()
+        // Boids that fly down onto the ground rest there a while, off until turned on
+        .init_resource::<Perching>()
+        // A breeze or a swirl across the whole arena, shown by the field arrows
+        .init_resource::<AmbientWind>()
+        // Timed console lines and captions for a demo that plays out the same every time
+        .init_resource::<ScenarioPlayer>()
+        .add_plugins(FieldArrowsPlugin::<UniformWind>::default())
+        .add_plugins(FieldArrowsPlugin::<Vortex>::default())
+        .add_systems(PostUpdate, draw_inspected_boid.run_if(resource_exists::<GizmoConfigStore>))
+        .add_systems(PostUpdate, draw_waypoints.run_if(resource_exists::<GizmoConfigStore>))
         .add_systems(Startup, (setup, setup_boids, setup_borders).chain())
-        .add_systems(Update, (update_boids, check_for_collisions, apply_velocity).chain())
-        .run();
+        // Input, console and scenario changes to the world
+        .add_systems(
+            Update,
+            (
+                run_scenario,
+                reset_flock,
+                wind_from_console,
+                sync_wind,
+                spawn_from_console,
+                match_flock_size,
+                spawn_predator_on_key,
+                update_mouse_interaction,
+                select_boid,
+                place_obstacles,
+                boundary_from_console,
+                scale_from_console,
+            )
+                .chain(),
+        )
+        // One step of the flock per physics tick, so the controls bar pauses, steps and
+        // slows only the flock while the windows keep running
+        .add_plugins(
+            PhysicsPlugin::default()
+                .without_acceleration()
+                .without_velocity()
+                .without_rotation()
+                .without_projection(),
+        )
+        .add_systems(
+            PhysicsStep,
+            (
+                (
+                    // Where the boids are now, not where they were at the start of the frame
+                    rebuild_spatial_grid,
+                    drift_wander,
+                    begin_span("steering"),
+                    update_boids,
+                    advance_waypoint,
+                    chase_prey,
+                    end_span("steering"),
+                )
+                    .chain()
+                    .in_set(PhysicsSet::Forces),
+                (
+                    begin_span("collisions"),
+                    check_for_collisions.run_if(resource_equals(BoundaryMode::Reflect).or(any_with_component::<Obstacle>)),
+                    end_span("collisions"),
+                    apply_velocity,
+                )
+                    .chain()
+                    .in_set(PhysicsSet::Integrate),
+                (separate_solid_boids, perch, catch_prey).chain().in_set(PhysicsSet::Constrain),
+            ),
+        )
+        .add_systems(FixedUpdate, log_polarization.after(run_physics_step).run_if(physics_stepped));
+}
+
+/// Flocking regimes to start from, alongside the reader's saved presets
+fn boid_presets() -> PresetPlugin<SpeciesTable> {
+    PresetPlugin::new("boids")
+        .with_preset("Default", &SpeciesTable::default())
+        .with_preset(
+            // Strong alignment and cohesion over a short view: one dense, fast-turning ball
+            "Tight school",
+            &SpeciesTable::single(BoidSettings {
+                align_weight: 30.0,
+                cohesion_weight: 30.0,
+                separation_weight: 25.0,
+                view_radius: 35.0,
+                fov_degrees: 300.0,
+                max_speed: 250.0,
+                ..default()
+            }),
+        )
+        .with_preset(
+            // Far sight and weak cohesion: wide sheets that fold and ripple
+            "Loose murmuration",
+            &SpeciesTable::single(BoidSettings {
+                align_weight: 20.0,
+                cohesion_weight: 6.0,
+                separation_weight: 12.0,
+                view_radius: 120.0,
+                max_speed: 400.0,
+                max_force: 250.0,
+                ..default()
+            }),
+        )
+        .with_preset(
+            // Cohesion without alignment and sharp turns: a buzzing cloud
+            "Chaotic swarm",
+            &SpeciesTable::single(BoidSettings {
+                align_weight: 1.0,
+                cohesion_weight: 25.0,
+                separation_weight: 30.0,
+                view_radius: 60.0,
+                max_speed: 500.0,
+                max_force: 4000.0,
+                ..default()
+            }),
+        )
 }
 
 #[derive(Component)]
+#[require(Species, Wander)]
 struct Boid;
 
-// Default must be implemented to define this as a required component for the Border component below
-#[derive(Component, Default)]
-struct Collider;
+/// Mesh every boid draws with, and a material per species, so spawning boids adds no assets
+#[derive(Resource)]
+struct BoidAssets {
+    mesh: Handle<Mesh>,
+    materials: Vec<Handle<ColorMaterial>>,
+}
 
-// This is a collection of the components that define a "Border" in our game
-#[derive(Component)]
-#[require(Sprite, Transform, Collider)]
-struct Border;
+impl FromWorld for BoidAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(boid_triangle(BOID_LENGTH));
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+        let materials = (0..MAX_SPECIES).map(|species| materials.add(SpeciesTable::color(species))).collect();
+        Self { mesh, materials }
+    }
+}
 
-/// Which side of the arena is this border located on?
-enum BorderLocation {
-    Left,
-    Right,
-    Bottom,
-    Top,
+/// A triangle `length` long pointing along +x, the way boids are turned to fly, so
+/// their heading shows
+fn boid_triangle(length: f32) -> Triangle2d {
+    let half = length / 2.;
+    Triangle2d::new(
+        Vec2::new(half, 0.0),
+        Vec2::new(-half, half * 0.6),
+        Vec2::new(-half, -half * 0.6),
+    )
 }
 
-impl BorderLocation {
-    /// (x, y) dimensions of the border, used in `transform.scale()`
-    fn size(&self, window_width: f32, window_height: f32) -> Vec2 {
-        match self {
-            BorderLocation::Left | BorderLocation::Right => {
-                Vec2::new(BORDER_THICKNESS, window_height)
-            }
-            BorderLocation::Bottom | BorderLocation::Top => {
-                Vec2::new(window_width, BORDER_THICKNESS)
-            }
-        }
+/// How long boids are drawn (px), from the options window; only the look changes, they
+/// collide and keep apart at [`BOID_DIAMETER`]
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct BoidSize(pub f32);
+
+impl Default for BoidSize {
+    fn default() -> Self {
+        Self(BOID_LENGTH)
     }
+}
 
-    fn position(&self, window_width: f32, window_height: f32) -> Vec2 {
-        match self {
-            // Position borders so their INNER EDGE is at the window edge (not their center)
-            // This means the border center must be BORDER_THICKNESS/2 outside the window edge
-            BorderLocation::Left => Vec2::new(- (window_width / 2.) - BORDER_THICKNESS / 2., 0.),
-            BorderLocation::Right => Vec2::new(window_width / 2. + BORDER_THICKNESS / 2., 0.),
-            BorderLocation::Bottom => Vec2::new(0., - (window_height / 2.) - BORDER_THICKNESS / 2.),
-            BorderLocation::Top => Vec2::new(0., window_height / 2. + BORDER_THICKNESS / 2.),
-        }
+/// Reshape the mesh all boids share when [`BoidSize`] changes
+fn resize_boids(size: Res<BoidSize>, assets: Res<BoidAssets>, mut meshes: ResMut<Assets<Mesh>>) {
+    if !size.is_changed() {
+        return;
+    }
+    if let Some(mesh) = meshes.get_mut(&assets.mesh) {
+        *mesh = boid_triangle(size.0).into();
     }
 }
 
+/// How many boids the flock should have; [`match_flock_size`] spawns or despawns a few
+/// thousand a frame until it does
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlockSize(pub usize);
+
+impl Default for FlockSize {
+    fn default() -> Self {
+        Self(FLOCK_SIZE)
+    }
+}
+
+// Default must be implemented to define this as a required component for the Border component below
+#[derive(Component, Default)]
+struct Collider;
+
+// This is a collection of the components that define a "Border" in our game
+#[derive(Component)]
+#[require(Sprite, Transform, Collider)]
+struct Border;
+
 impl Border {
     // This "builder method" allows us to reuse logic across our border entities,
     // making our code easier to read and less prone to bugs when we change the logic
-    // Notice the use of Sprite and Transform alongside Border, overwriting the default values defined for the required components
-    fn new(location: BorderLocation, window_width: f32, window_height: f32) -> (Border, Sprite, Transform) {
+    // The BoundaryPlugin sizes and places the sprite and collider on the window's edge,
+    // and moves them again whenever the window is resized
+    fn new(wall: BoundaryWall) -> (Border, Sprite, BoundaryWall, ColliderShape) {
         (
             Border,
             Sprite::from_color(BORDER_COLOR, Vec2::ONE),
-            Transform {
-                // We need to convert our Vec2 into a Vec3, by giving it a z-coordinate
-                // This is used to determine the order of our sprites
-                translation: location.position(window_width, window_height).extend(0.0),
-                // The z-scale of 2D objects must always be 1.0,
-                // or their ordering will be affected in surprising ways.
-                // See https://github.com/bevyengine/bevy/issues/4149
-                scale: location.size(window_width, window_height).extend(1.0),
-                ..default()
-            },
+            wall,
+            ColliderShape::aabb(Vec2::splat(0.5)),
         )
     }
 }
@@ -102,50 +493,190 @@ fn setup(mut commands: Commands) {
     log::info!("Boids simulation started!");
 }
 
-fn setup_borders(
+fn setup_borders(mut commands: Commands) {
+    for wall in BoundaryWall::ALL {
+        commands.spawn(Border::new(wall));
+    }
+}
+
+fn setup_boids(
     mut commands: Commands,
-    window_q: Query<&Window, With<PrimaryWindow>>,
+    assets: Res<BoidAssets>,
+    species: Res<SpeciesTable>,
+    boundary: Res<Boundary>,
+    seed: Res<SimulationSeed>,
+    launch: Res<LaunchConfig>,
+    mut flock_size: ResMut<FlockSize>,
 ) {
-    let Ok(window) = window_q.single() else {
+    log::info!("Spawning flock with seed {} (open the page with ?seed={} to see it again)", seed.0, seed.0);
+    let mut rng = StdRng::seed_from_u64(seed.0);
+    flock_size.0 = launch.get("count").unwrap_or(FLOCK_SIZE);
+    spawn_boids(&mut commands, &assets, species.count(), &mut rng, boundary.size(), flock_size.0);
+}
+
+/// Everything a reset clears: the flock and whatever was spawned beside it
+type Spawned = Or<(With<Boid>, With<Vortex>, With<PointAttractor>, With<Predator>, With<Obstacle>)>;
+
+/// Replaces the flock with one of the same size spawned from the seed, and clears
+/// spawned fields, predators and obstacles, when the control bar's Reset is pressed
+#[allow(clippy::too_many_arguments)]
+fn reset_flock(
+    mut resets: MessageReader<ResetSimulation>,
+    mut commands: Commands,
+    assets: Res<BoidAssets>,
+    species: Res<SpeciesTable>,
+    boundary: Res<Boundary>,
+    seed: Res<SimulationSeed>,
+    flock_size: Res<FlockSize>,
+    spawned: Query<Entity, Spawned>,
+) {
+    if resets.read().count() == 0 {
         return;
-    };
-    let window_width = window.width();
-    let window_height = window.height();
-    commands.spawn(Border::new(BorderLocation::Left, window_width, window_height));
-    commands.spawn(Border::new(BorderLocation::Right, window_width, window_height));
-    commands.spawn(Border::new(BorderLocation::Bottom, window_width, window_height));
-    commands.spawn(Border::new(BorderLocation::Top, window_width, window_height));
+    }
+    for entity in &spawned {
+        commands.entity(entity).despawn();
+    }
+    let mut rng = StdRng::seed_from_u64(seed.0);
+    spawn_boids(&mut commands, &assets, species.count(), &mut rng, boundary.size(), flock_size.0);
 }
 
-fn setup_boids(
+/// Spawn or despawn boids towards the [`FlockSize`], at most [`FLOCK_STEP`] a frame so
+/// a big change doesn't stall one frame
+fn match_flock_size(
+    mut commands: Commands,
+    flock_size: Res<FlockSize>,
+    boids: Query<Entity, With<Boid>>,
+    assets: Res<BoidAssets>,
+    species: Res<SpeciesTable>,
+    boundary: Res<Boundary>,
+    seed: Res<SimulationSeed>,
+) {
+    let current = boids.iter().len();
+    let target = flock_size.0;
+    if current < target {
+        // Offset by the flock size so each batch differs but stays reproducible
+        let mut rng = StdRng::seed_from_u64(seed.0.wrapping_add(current as u64));
+        let count = (target - current).min(FLOCK_STEP);
+        spawn_boids(&mut commands, &assets, species.count(), &mut rng, boundary.size(), count);
+    } else {
+        for boid in boids.iter().take((current - target).min(FLOCK_STEP)) {
+            commands.entity(boid).despawn();
+        }
+    }
+}
+
+/// Handles `spawn boid <count>`, `spawn vortex`, `spawn attractor` and `spawn predator`
+/// from the console; fields and predators appear at the `x y` given, else under the
+/// cursor, or in the middle without one, and boids grow the [`FlockSize`]
+fn spawn_from_console(
+    mut console_commands: MessageReader<ConsoleCommand>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     window_q: Query<&Window, With<PrimaryWindow>>,
+    scale: Res<WorldScale>,
+    mut flock_size: ResMut<FlockSize>,
 ) {
     let Ok(window) = window_q.single() else {
         return;
     };
-    let window_width = window.width();
-    let window_height = window.height();
-    // Spawn boids in random positions in window
-    let spawn_width = window_width - BOID_DIAMETER * 2.;
-    let spawn_height = window_height - BOID_DIAMETER * 2.;
-    for _ in 0..1000 {
-        commands.spawn((
-            Mesh2d(meshes.add(Circle::default())),
-            MeshMaterial2d(materials.add(Color::srgb(1.0, 0.0, 0.0))),
-            Transform::from_translation(Vec3::new(
-                rand::random::<f32>() * spawn_width as f32 - spawn_width as f32 / 2.0,
-                rand::random::<f32>() * spawn_height as f32 - spawn_height as f32 / 2.0,
-                0.0,
-            )).with_scale(Vec3::splat(BOID_DIAMETER / 2.)),
-            Velocity(Vec2::new(
-                rand::random::<f32>() * 400.0 - 200.0,
-                rand::random::<f32>() * 400.0 - 200.0,
-            )),
+    for command in console_commands.read() {
+        if command.name != "spawn" {
+            continue;
+        }
+        let at = command.arg(1).zip(command.arg(2)).map(|(x, y)| Vec2::new(x, y));
+        let center = Transform::from_translation(at.or_else(|| scale.cursor_position(window)).unwrap_or_default().extend(0.0));
+        let region = FieldRegion::Circle { radius: FIELD_RADIUS };
+        match command.args.first().map(String::as_str) {
+            Some("vortex") => {
+                commands.spawn((Vortex::new(VORTEX_STRENGTH, VORTEX_CORE), region, center));
+                continue;
+            }
+            Some("attractor") => {
+                let attractor = PointAttractor::new(ATTRACTOR_STRENGTH).with_softening(ATTRACTOR_SOFTENING);
+                commands.spawn((attractor, region, center));
+                continue;
+            }
+            Some("predator") => {
+                spawn_predator(&mut commands, &mut meshes, &mut materials, center.translation.truncate());
+                continue;
+            }
+            Some("boid") => {}
+            _ => continue,
+        }
+        let count: usize = command.arg(1).unwrap_or(1);
+        flock_size.0 += count;
+    }
+}
+
+/// Handles `scale <rule> <factor>` from the console, multiplying a rule of every
+/// species, e.g. `scale cohesion 2` to double cohesion
+fn scale_from_console(mut console_commands: MessageReader<ConsoleCommand>, mut table: ResMut<SpeciesTable>) {
+    for command in console_commands.read().filter(|command| command.name == "scale") {
+        let (Some(rule), Some(factor)) = (command.args.first(), command.arg::<f32>(1)) else {
+            log::warn!("Usage: scale <rule> <factor>");
+            continue;
+        };
+        let mut edited = table.clone();
+        for rules in &mut edited.rules {
+            let Some(value) = rules.rule_mut(rule) else {
+                log::warn!("No rule called {rule}");
+                break;
+            };
+            *value *= factor;
+        }
+        if edited != *table {
+            *table = edited;
+        }
+    }
+}
+
+/// Handles `boundary <mode>` from the console
+fn boundary_from_console(mut console_commands: MessageReader<ConsoleCommand>, mut mode: ResMut<BoundaryMode>) {
+    for command in console_commands.read().filter(|command| command.name == "boundary") {
+        match command.args.first().map(|name| name.parse::<BoundaryMode>()) {
+            Some(Ok(new_mode)) => *mode = new_mode,
+            Some(Err(err)) => log::warn!("{err}"),
+            None => log::info!("Boundary mode is {}", mode.name()),
+        }
+    }
+}
+
+/// Spawn `count` boids at random positions and velocities inside the visible area, taking
+/// turns between the first `species` species
+fn spawn_boids(
+    commands: &mut Commands,
+    assets: &BoidAssets,
+    species: usize,
+    rng: &mut StdRng,
+    arena_size: Vec2,
+    count: usize,
+) {
+    // Spawn boids in random positions inside the borders
+    let positions = SpawnRegion::centered(arena_size - BOID_DIAMETER * 2.);
+    let velocities = SpawnRegion::centered(Vec2::splat(400.0));
+    for index in 0..count {
+        let kind = index % species;
+        // Boids of a species share a mesh and material, so each species draws as one
+        // instanced batch, which huge flocks need
+        let mut boid = commands.spawn((
+            Mesh2d(assets.mesh.clone()),
+            MeshMaterial2d(assets.materials[kind % MAX_SPECIES].clone()),
+            Transform::from_translation(positions.sample(rng).extend(0.0)),
+            ColliderShape::circle(BOID_DIAMETER / 2.),
+            Velocity(velocities.sample(rng)),
             Boid,
+            Species(kind),
+            SpatialIndexed,
+            Recorded,
+            Saved,
+            Bounded,
         ));
+        // With several species the color tells them apart; a single one is colored by
+        // speed from a few shared shades, fast boids bright and slow ones dark
+        if species == 1 {
+            boid.insert(ColorBy::speed().with_shades(SPEED_SHADES));
+        }
     }
 }
 
@@ -156,141 +687,301 @@ fn apply_velocity(mut query: Query<(&mut Transform, &Velocity)>, time: Res<Time>
     }
 }
 
+/// Reflects boids off obstacles, and off the borders while they are walls
 fn check_for_collisions(
-    mut boid_query: Query<(&mut Velocity, &Transform), With<Boid>>,
-    collider_query: Query<&Transform, With<Collider>>,
+    mut boid_query: Query<&mut Velocity, With<Boid>>,
+    border_query: Query<Has<Border>, With<Collider>>,
+    collider_query: Query<(Entity, &ColliderShape, &Transform)>,
+    boundary_mode: Res<BoundaryMode>,
 ) {
-    for (mut boid_velocity, boid_transform) in &mut boid_query {
-        for collider_transform in &collider_query {
-            let boid_center = boid_transform.translation.truncate();
-            // Circle::default() has radius 0.5, so actual visual radius = 0.5 * scale
-            let boid_radius = 0.5 * boid_transform.scale.x;
-            let border_center = collider_transform.translation.truncate();
-            let border_half_size = collider_transform.scale.truncate() / 2.;
-            
-            let collision = boid_collision(
-                BoundingCircle::new(boid_center, boid_radius),
-                Aabb2d::new(border_center, border_half_size),
-            );
-
-            if let Some(collision) = collision {
-                // Reflect the boids's velocity when it collides
-                let mut reflect_x = false;
-                let mut reflect_y = false;
-
-                // Reflect only if the velocity is in the opposite direction of the collision
-                // This prevents the boid from getting stuck inside the bar
-                match collision {
-                    Collision::Left => reflect_x = boid_velocity.0.x > 0.0,
-                    Collision::Right => reflect_x = boid_velocity.0.x < 0.0,
-                    Collision::Top => reflect_y = boid_velocity.0.y < 0.0,
-                    Collision::Bottom => reflect_y = boid_velocity.0.y > 0.0,
-                }
+    let walls = *boundary_mode == BoundaryMode::Reflect;
+    let colliders: Vec<_> = collider_query
+        .iter()
+        .map(|(entity, shape, transform)| (entity, *shape, collision::isometry(transform)))
+        .collect();
+    // Which colliders a boid can hit: obstacles, and the borders while they are walls
+    let solid: Vec<Option<bool>> = colliders
+        .iter()
+        .map(|(entity, ..)| border_query.get(*entity).ok().map(|border| walls || !border))
+        .collect();
+    // Only boid-border and boid-obstacle pairs are resolved here, so the narrowphase
+    // skips the boid-boid pairs that make up most of a dense flock
+    let keep = |a: usize, b: usize| {
+        (solid[a] == Some(true) && solid[b].is_none()) || (solid[b] == Some(true) && solid[a].is_none())
+    };
 
-                // Reflect velocity on the x-axis if we hit something on the x-axis
-                if reflect_x {
-                    boid_velocity.0.x = -boid_velocity.0.x;
-                }
+    for (a, b, contact) in find_contacts_where(&colliders, Broadphase::SweepAndPrune, keep) {
+        // The normal points from the boid into the border
+        let (boid, normal) = if border_query.contains(b) { (a, contact.normal) } else { (b, -contact.normal) };
+        let Ok(mut boid_velocity) = boid_query.get_mut(boid) else {
+            continue;
+        };
 
-                // Reflect velocity on the y-axis if we hit something on the y-axis
-                if reflect_y {
-                    boid_velocity.0.y = -boid_velocity.0.y;
-                }
-            }
+        // Reflect the boid's velocity only if it is moving into the border
+        // This prevents the boid from getting stuck inside the bar
+        let approach = boid_velocity.0.dot(normal);
+        if approach > 0.0 {
+            boid_velocity.0 -= 2.0 * approach * normal;
+        }
+    }
+}
+
+/// Log how aligned the flock is: the length of the mean heading, 1 when every boid
+/// flies the same way and near 0 when they scatter
+fn log_polarization(query: Query<&Velocity, With<Boid>>, mut logger: ResMut<DataLogger>) {
+    let (sum, count) = query
+        .iter()
+        .fold((Vec2::ZERO, 0), |(sum, count), velocity| (sum + velocity.0.normalize_or_zero(), count + 1));
+    if count > 0 {
+        logger.log("polarization", sum.length() / count as f32);
+    }
+}
+
+/// Each term of one boid's steering, before they are summed into the velocity it wants
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SteeringForces {
+    pub alignment: Vec2,
+    pub cohesion: Vec2,
+    pub separation: Vec2,
+    /// Away from the arena's edges
+    pub edges: Vec2,
+    pub mouse: Vec2,
+    /// Console-spawned vortices and attractors
+    pub fields: Vec2,
+    pub flee: Vec2,
+    pub obstacles: Vec2,
+    pub migration: Vec2,
+    pub wander: Vec2,
+}
+
+impl SteeringForces {
+    /// Every term with its name, in the order they are listed
+    pub fn terms(&self) -> [(&'static str, Vec2); 10] {
+        [
+            ("Alignment", self.alignment),
+            ("Cohesion", self.cohesion),
+            ("Separation", self.separation),
+            ("Edges", self.edges),
+            ("Mouse", self.mouse),
+            ("Fields", self.fields),
+            ("Flee", self.flee),
+            ("Obstacles", self.obstacles),
+            ("Migration", self.migration),
+            ("Wander", self.wander),
+        ]
+    }
+
+    pub fn total(&self) -> Vec2 {
+        self.terms().iter().map(|(_, force)| *force).sum()
+    }
+}
+
+/// Every force field of kind `F`, with its placement and the region it acts in
+type FieldQuery<'w, 's, F> = Query<'w, 's, (&'static F, &'static Transform, Option<&'static FieldRegion>), Without<Boid>>;
+
+/// Everything besides the flock itself that steers a boid
+#[derive(SystemParam)]
+struct Influences<'w, 's> {
+    migration: Res<'w, Migration>,
+    mouse: Res<'w, MouseInteraction>,
+    vortices: FieldQuery<'w, 's, Vortex>,
+    winds: FieldQuery<'w, 's, UniformWind>,
+    attractors: FieldQuery<'w, 's, PointAttractor>,
+    predators: Query<'w, 's, &'static Transform, (With<Predator>, Without<Boid>)>,
+    obstacles: ObstacleQuery<'w, 's>,
+}
+
+/// The boids behind each of the last few GPU uploads, to line read-back sums up with
+/// the boids they were computed for
+#[cfg(feature = "gpu-compute")]
+#[derive(Default)]
+struct GpuUploads(Vec<(u32, Vec<Entity>)>);
+
+#[cfg(feature = "gpu-compute")]
+impl GpuUploads {
+    /// Results stay in flight for a frame or two
+    const KEPT: usize = 4;
+
+    fn record(&mut self, generation: u32, boids: Vec<Entity>) {
+        if self.0.len() == Self::KEPT {
+            self.0.remove(0);
         }
+        self.0.push((generation, boids));
+    }
+
+    /// `sums` in the order of `boids`, if they were computed for every one of them
+    fn match_up(&self, sums: GpuResults<SteeringSums>, boids: &[Entity]) -> Option<Vec<SteeringSums>> {
+        let (_, uploaded) = self.0.iter().find(|(generation, _)| *generation == sums.generation)?;
+        let by_boid: EntityHashMap<SteeringSums> = uploaded.iter().copied().zip(sums.values).collect();
+        boids.iter().map(|boid| by_boid.get(boid).copied()).collect()
     }
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn update_boids(
-    mut query: Query<(&mut Boid, &mut Transform, &mut Velocity)>,
+    mut query: Query<(Entity, &mut Transform, &mut Velocity, &Species, Has<Perched>, &Wander), With<Boid>>,
     window_query: Query<&Window, With<PrimaryWindow>>,
+    pointer: Res<Pointer>,
+    scale: Res<WorldScale>,
+    boundary: Res<Boundary>,
+    boundary_mode: Res<BoundaryMode>,
+    grid: Res<SpatialGrid>,
+    species_table: Res<SpeciesTable>,
+    influences: Influences,
+    mut inspector: ResMut<BoidInspector>,
+    time: Res<Time>,
+    #[cfg(feature = "gpu-compute")] gpu: Option<ResMut<GpuParticles>>,
+    #[cfg(feature = "gpu-compute")] mut buffers: ResMut<Assets<ShaderStorageBuffer>>,
+    #[cfg(feature = "gpu-compute")] mut uploads: Local<GpuUploads>,
 ) {
-    // Get window dimensions and mouse position
+    // Get the window for the mouse position; the edges come from the boundary
     let Ok(window) = window_query.single() else {
         return;
     };
-    let window_width = window.width();
-    let window_height = window.height();
+    let edges = boundary.rect;
+    let period = (*boundary_mode == BoundaryMode::Wrap).then_some(edges);
+    // Steer away from the walls, or with the walls open, back in from a wider margin
+    let avoidance_distance = match *boundary_mode {
+        BoundaryMode::Reflect => WINDOW_AVOIDANCE_DISTANCE,
+        BoundaryMode::Open => SOFT_AVOIDANCE_DISTANCE,
+        _ => 0.0,
+    };
     
-    // Get mouse position in world coordinates (if cursor is in window)
-    let mouse_world_pos = window.cursor_position().map(|screen_pos| {
-        // Convert screen coordinates to world coordinates
-        // Screen: (0,0) is top-left, (width, height) is bottom-right
-        // World: (0,0) is center
-        Vec2::new(
-            screen_pos.x - window_width / 2.0,
-            window_height / 2.0 - screen_pos.y,  // Y is inverted
-        )
-    });
+    // Mouse or finger position in world coordinates (if in the window, or touching it)
+    let mouse_world_pos = pointer.position.map(|position| scale.screen_to_world(window, position));
+    let mouse_strength = influences.mouse.strength();
     
-    // Snapshot all positions and velocities
-    let boid_data: Vec<(Vec3, Vec2)> = query.iter()
-        .map(|(_, transform, velocity)| (transform.translation, velocity.0))
-        .collect();
+    // Snapshot all positions, velocities and species, in the grid's order so its indices line up
+    let entries = grid.entries();
+    let positions: Vec<Vec2> = entries.iter().map(|(_, position)| *position).collect();
+    let (velocities, species): (Vec<Vec2>, Vec<usize>) = entries.iter()
+        .map(|(entity, _)| query.get(*entity).map_or((Vec2::ZERO, 0), |(_, _, velocity, species, ..)| (velocity.0, species.0)))
+        .unzip();
+    let mixed = species_table.count() > 1;
 
-    for (i, (_, mut transform, mut velocity)) in query.iter_mut().enumerate() {
-        let mut alignment = Vec2::ZERO;
-        let mut cohesion = Vec3::ZERO;
-        let mut separation = Vec3::ZERO;
-        let mut neighbors = 0;
+    // Neighbor sums from the GPU if the last compute pass has them for these boids; the
+    // kernel doesn't know about species, vision cones, nearest neighbors or the seam of a
+    // torus, so only a single species that sees all around within its radius in a world
+    // without wrap-around uses them, and otherwise nothing is uploaded
+    #[cfg(feature = "gpu-compute")]
+    let gpu_usable = !mixed
+        && period.is_none()
+        && species_table.rules.iter().all(|rules| rules.fov_degrees >= 360.0 && rules.nearest == 0);
+    #[cfg(feature = "gpu-compute")]
+    let gpu_sums = gpu.filter(|_| gpu_usable).and_then(|mut gpu| {
+        let boids: Vec<Entity> = entries.iter().map(|(entity, _)| *entity).collect();
+        let sums = gpu.steering_sums().and_then(|sums| uploads.match_up(sums, &boids));
+        gpu.kernel = GpuKernel::Steering { radius: species_table.rules(0).view_radius };
+        let generation = gpu.upload(&mut buffers, &positions, &velocities, &[]);
+        uploads.record(generation, boids);
+        sums
+    });
+    #[cfg(not(feature = "gpu-compute"))]
+    let gpu_sums: Option<Vec<SteeringSums>> = None;
+    let all_sums: Vec<FlockSums> = match gpu_sums {
+        Some(sums) => sums.into_iter().map(FlockSums::from).collect(),
+        None => {
+            let indices: Vec<usize> = (0..positions.len()).collect();
+            // Only the boids in nearby grid cells are candidates
+            parallel::par_map(&indices, STEERING_CHUNK_SIZE, |&i| {
+                let (pos, own) = (positions[i], species[i]);
+                let rules = species_table.rules(own);
+                let (mut radius, cone) = (rules.view_radius, half_fov_cos(rules.fov_degrees));
+                // On a torus the flock sees across the seam, at the neighbor's nearest copy;
+                // topological boids take their nearest few wherever they are
+                let mut metric;
+                let mut topological;
+                let neighbors: &mut dyn Iterator<Item = (usize, Vec2)> = if rules.nearest > 0 {
+                    radius = f32::INFINITY;
+                    topological = nearest_in_view(&grid, pos, velocities[i], rules.nearest, cone, period).into_iter();
+                    &mut topological
+                } else {
+                    metric = grid
+                        .query_radius_wrapped(pos, radius, period)
+                        .filter(|&(_, other)| in_view(velocities[i], other - pos, cone));
+                    &mut metric
+                };
+                if mixed {
+                    let interaction = species_table.interaction.get(own).unwrap_or(&species_table.interaction[0]);
+                    let neighbors = neighbors.map(|(index, other)| (other, velocities[index], species[index]));
+                    flock_sums_of(pos, neighbors, radius, interaction)
+                } else {
+                    let neighbors = neighbors.map(|(index, other)| (other, velocities[index]));
+                    kernels::steering_sums_of(pos, neighbors, radius).into()
+                }
+            })
+        }
+    };
+    // Fields spawned from the console push on the steering like any other force
+    let t = time.elapsed_secs();
+    let vortices = snapshot_fields(&influences.vortices);
+    let attractors = snapshot_fields(&influences.attractors);
+    let winds = snapshot_fields(&influences.winds);
+    let predators: Vec<Vec2> = influences.predators.iter().map(|transform| transform.translation.truncate()).collect();
+    let obstacles = snapshot_obstacles(&influences.obstacles);
+    let (goal, migration_weight) = (influences.migration.goal(&boundary), influences.migration.weight);
+    let dt = time.delta_secs();
 
-        // Flocking behavior with other boids
-        for (j, (other_pos, other_vel)) in boid_data.iter().enumerate() {
-            if i == j {
-                continue;
-            }
+    let wanders = entries.iter().map(|(entity, _)| query.get(*entity).map_or(0.0, |(.., wander)| wander.0));
 
-            let diff = *other_pos - transform.translation;
-            let dist = diff.length();
+    let boids: Vec<(Vec2, Vec2, usize, f32, FlockSums)> = positions
+        .iter()
+        .zip(&velocities)
+        .zip(&species)
+        .zip(wanders)
+        .zip(all_sums)
+        .map(|((((&pos, &velocity), &species), wander), sums)| (pos, velocity, species, wander, sums))
+        .collect();
 
-            if dist < VIEW_RADIUS && dist > 0.0 {
-                alignment += *other_vel;
-                cohesion += *other_pos;
-                separation -= diff / (dist * dist);
-                neighbors += 1;
-            }
-        }
+    // Every term of one boid's steering, from the snapshot alone
+    let steer = |&(pos, velocity, species, wander, sums): &(Vec2, Vec2, usize, f32, FlockSums)| {
+        let settings = species_table.rules(species);
+        let mut alignment = Vec2::ZERO;
+        let mut cohesion = Vec2::ZERO;
 
-        if neighbors > 0 {
-            let n = neighbors as f32;
-            alignment = (alignment / n).normalize_or_zero() * ALIGN_WEIGHT;
-            cohesion = ((cohesion / n) - transform.translation).normalize_or_zero() * COHESION_WEIGHT;
-            separation = separation.normalize_or_zero() * SEPARATION_WEIGHT;
+        // Flocking behavior with other boids; only those of species this one flocks with
+        // pull it along, but it keeps clear of all of them
+        if sums.weight > 0.0 {
+            let n = sums.weight;
+            alignment = (sums.alignment / n).normalize_or_zero() * settings.align_weight;
+            cohesion = ((sums.cohesion / n) - pos).normalize_or_zero() * settings.cohesion_weight;
         }
+        let separation = sums.separation.normalize_or_zero() * settings.separation_weight;
         
         // Calculate distance to each border edge and apply avoidance force
         let mut avoidance = Vec2::ZERO;
-        let pos = transform.translation.truncate();
 
-        let left_edge = -window_width / 2.0;
-        let right_edge = window_width / 2.0;
-        let bottom_edge = -window_height / 2.0;
-        let top_edge = window_height / 2.0;
+        let left_edge = edges.min.x;
+        let right_edge = edges.max.x;
+        let bottom_edge = edges.min.y;
+        let top_edge = edges.max.y;
         
-        if pos.x - left_edge < WINDOW_AVOIDANCE_DISTANCE {
+        // Past an open edge the distance goes negative and the push only grows
+        if pos.x - left_edge < avoidance_distance {
             let distance = pos.x - left_edge;
-            avoidance.x += (1.0_f32 - distance / WINDOW_AVOIDANCE_DISTANCE).max(0.0);
+            avoidance.x += (1.0_f32 - distance / avoidance_distance).max(0.0);
         }
-        if right_edge - pos.x < WINDOW_AVOIDANCE_DISTANCE {
+        if right_edge - pos.x < avoidance_distance {
             let distance = right_edge - pos.x;
-            avoidance.x -= (1.0_f32 - distance / WINDOW_AVOIDANCE_DISTANCE).max(0.0);
+            avoidance.x -= (1.0_f32 - distance / avoidance_distance).max(0.0);
         }
-        if pos.y - bottom_edge < WINDOW_AVOIDANCE_DISTANCE {
+        if pos.y - bottom_edge < avoidance_distance {
             let distance = pos.y - bottom_edge;
-            avoidance.y += (1.0_f32 - distance / WINDOW_AVOIDANCE_DISTANCE).max(0.0);
+            avoidance.y += (1.0_f32 - distance / avoidance_distance).max(0.0);
         }
-        if top_edge - pos.y < WINDOW_AVOIDANCE_DISTANCE {
+        if top_edge - pos.y < avoidance_distance {
             let distance = top_edge - pos.y;
-            avoidance.y -= (1.0_f32 - distance / WINDOW_AVOIDANCE_DISTANCE).max(0.0);
+            avoidance.y -= (1.0_f32 - distance / avoidance_distance).max(0.0);
         }
         
         avoidance = avoidance.normalize_or_zero() * WINDOW_AVOIDANCE_WEIGHT;
 
-        // Mouse attraction - steer towards cursor
+        // Mouse attraction - steer towards cursor, or away from it in repel mode, harder
+        // while the button is held
         let mouse_attraction = if let Some(mouse_pos) = mouse_world_pos {
             let direction = mouse_pos - pos;
-            if direction.length() < MOUSE_ATTRACTION_DISTANCE {
-                direction.normalize_or_zero() * MOUSE_ATTRACTION_WEIGHT
+            if direction.length() < settings.mouse_attraction_distance {
+                direction.normalize_or_zero() * settings.mouse_attraction_weight * mouse_strength
             } else {
                 Vec2::ZERO
             }
@@ -298,42 +989,90 @@ fn update_boids(
             Vec2::ZERO
         };
 
-        // Combine all forces and update velocity
-        velocity.0 = (alignment + cohesion.truncate() + separation.truncate() + avoidance + mouse_attraction).clamp_length_max(MAX_SPEED);
+        let fields = field_push(&vortices, pos, t) + field_push(&attractors, pos, t) + field_push(&winds, pos, t);
 
-        // Update visual rotation
-        transform.rotation = Quat::from_rotation_z(velocity.0.y.atan2(velocity.0.x));
-    }
-}
+        // Flee from every predator in sight, hardest from the nearest
+        let flee: Vec2 = predators
+            .iter()
+            .map(|predator| pos - *predator)
+            .filter(|away| away.length() < settings.flee_radius)
+            .map(|away| away.normalize_or_zero() * (1.0 - away.length() / settings.flee_radius))
+            .sum();
+        let flee = flee.normalize_or_zero() * settings.flee_weight;
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
-enum Collision {
-    Left,
-    Right,
-    Top,
-    Bottom,
-}
+        // Steer around an obstacle ahead before reaching it
+        let obstacle_avoidance = avoid_obstacles(&obstacles, pos, velocity) * OBSTACLE_AVOIDANCE_WEIGHT;
 
-// Returns `Some` if `boid` collides with `bounding_box`.
-// The returned `Collision` is the side of `bounding_box` that `boid` hit.
-fn boid_collision(boid: BoundingCircle, bounding_box: Aabb2d) -> Option<Collision> {
-    if !boid.intersects(&bounding_box) {
-        return None;
-    }
+        // Head for the current waypoint while migrating
+        let migration = migrate(goal, pos) * migration_weight;
 
-    let closest = bounding_box.closest_point(boid.center());
-    let offset = boid.center() - closest;
-    let side = if offset.x.abs() > offset.y.abs() {
-        if offset.x < 0. {
-            Collision::Left
-        } else {
-            Collision::Right
+        // A little restlessness of its own
+        let wander = wander_force(velocity, wander) * settings.wander_weight;
+
+        SteeringForces {
+            alignment,
+            cohesion,
+            separation,
+            edges: avoidance,
+            mouse: mouse_attraction,
+            fields,
+            flee,
+            obstacles: obstacle_avoidance,
+            migration,
+            wander,
         }
-    } else if offset.y > 0. {
-        Collision::Top
-    } else {
-        Collision::Bottom
     };
 
-    Some(side)
+    // Each boid only reads the snapshot, so the steering runs in parallel
+    let new_velocities = parallel::par_map(&boids, STEERING_CHUNK_SIZE, |boid| {
+        let (velocity, settings) = (boid.1, species_table.rules(boid.2));
+        // Combine all forces into the velocity the boid would like to have
+        let desired = steer(boid).total().clamp_length_max(settings.max_speed);
+
+        // Reynolds steering: accelerate towards the desired velocity, no harder than the
+        // max force, so boids keep their momentum and turn in arcs
+        if dt <= 0.0 {
+            return velocity;
+        }
+        let steering = ((desired - velocity) / dt).clamp_length_max(settings.max_force);
+        (velocity + steering * dt).clamp_length_max(settings.max_speed)
+    });
+
+    // The inspected boid's terms again, for its arrows and breakdown
+    let inspected = inspector.selected.and_then(|selected| entries.iter().position(|(entity, _)| *entity == selected));
+    inspector.forces = inspected.map(|index| steer(&boids[index]));
+
+    // Write the results back in snapshot order; the steering above is the costly part
+    for ((entity, _), new_velocity) in entries.iter().zip(new_velocities) {
+        // Perched boids sit still until they take off
+        let Ok((_, mut transform, mut velocity, _, perched, _)) = query.get_mut(*entity) else {
+            continue;
+        };
+        if perched {
+            continue;
+        }
+        velocity.0 = new_velocity;
+
+        // Point the triangle along the velocity; a boid that stops keeps its last heading
+        if velocity.0 != Vec2::ZERO {
+            transform.rotation = Quat::from_rotation_z(velocity.0.to_angle());
+        }
+    }
+}
+
+/// Copy out each field with its center and region, for the parallel steering to read
+fn snapshot_fields<F: ForceField + Component + Copy>(fields: &FieldQuery<F>) -> Vec<(F, Vec2, Option<FieldRegion>)> {
+    fields
+        .iter()
+        .map(|(field, center, region)| (*field, center.translation.truncate(), region.copied()))
+        .collect()
+}
+
+/// Total push on a boid at `pos` from the fields that reach it
+fn field_push<F: ForceField>(fields: &[(F, Vec2, Option<FieldRegion>)], pos: Vec2, t: f32) -> Vec2 {
+    fields
+        .iter()
+        .filter(|(_, center, region)| region.is_none_or(|region| region.contains(pos - *center)))
+        .map(|(field, center, _)| field.force(pos - *center, Vec2::ZERO, 1.0, t))
+        .sum()
 }
@@ -1,4 +1,12 @@
-// Native binary entry point
+// Native binary entry point; `--headless <steps>` runs without a window, and
+// `--params "seed=42&..."` starts from the same settings as a web link
 fn main() {
-    boids::run();
+    let args: Vec<String> = std::env::args().collect();
+    match args.iter().position(|arg| arg == "--headless") {
+        Some(index) => {
+            let steps = args.get(index + 1).and_then(|steps| steps.parse().ok()).unwrap_or(1000);
+            boids::run_headless(steps);
+        }
+        None => boids::run(),
+    }
 }
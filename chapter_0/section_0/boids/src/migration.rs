@@ -0,0 +1,97 @@
+//! Migration around a loop of waypoints
+//!
+//! With "Migrate" on in the options window every boid is pulled towards the current
+//! waypoint of [`Migration`] on top of its flocking, so the flock travels instead of
+//! milling about. Once a good part of the flock has arrived the next waypoint takes
+//! over, and after the last one the loop starts again. Waypoints are kept relative to
+//! the arena, so they follow the window when it is resized.
+use bevy::prelude::*;
+use rhysics_common::boundary::Boundary;
+use rhysics_common::spatial::SpatialGrid;
+
+const WAYPOINT_COLOR: Color = Color::srgba(0.4, 0.9, 1.0, 0.5);
+const CURRENT_WAYPOINT_COLOR: Color = Color::srgb(0.4, 0.9, 1.0);
+
+/// The waypoints the flock migrates between, and how hard it is pulled along
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct Migration {
+    pub enabled: bool,
+    /// Positions as fractions of the arena's half size, from -1 to 1 on each axis
+    pub waypoints: Vec<Vec2>,
+    /// Index of the waypoint the flock is heading for
+    pub current: usize,
+    pub weight: f32,
+    /// How close to a waypoint counts as arrived
+    pub reach: f32,
+    /// Share of the flock that has to arrive before moving on
+    pub arrived_share: f32,
+}
+
+impl Default for Migration {
+    /// Round the four corners of the arena, anticlockwise
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            waypoints: vec![
+                Vec2::new(-0.6, -0.6),
+                Vec2::new(0.6, -0.6),
+                Vec2::new(0.6, 0.6),
+                Vec2::new(-0.6, 0.6),
+            ],
+            current: 0,
+            weight: 20.0,
+            reach: 80.0,
+            arrived_share: 0.25,
+        }
+    }
+}
+
+impl Migration {
+    /// World position of waypoint `index` in `boundary`
+    pub fn waypoint(&self, index: usize, boundary: &Boundary) -> Option<Vec2> {
+        let half_size = boundary.size() / 2.;
+        self.waypoints
+            .get(index)
+            .map(|fraction| boundary.rect.center() + *fraction * half_size)
+    }
+
+    /// Where the flock is heading, while migration is on
+    pub fn goal(&self, boundary: &Boundary) -> Option<Vec2> {
+        self.enabled.then(|| self.waypoint(self.current, boundary)).flatten()
+    }
+}
+
+/// Steering towards `goal` for a boid at `position`, at full strength anywhere
+pub fn migrate(goal: Option<Vec2>, position: Vec2) -> Vec2 {
+    goal.map_or(Vec2::ZERO, |goal| (goal - position).normalize_or_zero())
+}
+
+/// Move on to the next waypoint once enough of the flock is within reach of this one
+pub fn advance_waypoint(mut migration: ResMut<Migration>, grid: Res<SpatialGrid>, boundary: Res<Boundary>) {
+    let Some(goal) = migration.goal(&boundary) else {
+        return;
+    };
+    let flock = grid.entries().len();
+    let arrived = grid.query_radius_indices(goal, migration.reach).count();
+    if flock > 0 && arrived as f32 >= flock as f32 * migration.arrived_share {
+        migration.current = (migration.current + 1) % migration.waypoints.len();
+    }
+}
+
+/// Mark the waypoints, the current one brighter with its reach around it
+pub fn draw_waypoints(migration: Res<Migration>, boundary: Res<Boundary>, mut gizmos: Gizmos) {
+    if !migration.enabled {
+        return;
+    }
+    for index in 0..migration.waypoints.len() {
+        let Some(position) = migration.waypoint(index, &boundary) else {
+            continue;
+        };
+        if index == migration.current {
+            gizmos.circle_2d(position, migration.reach, CURRENT_WAYPOINT_COLOR);
+            gizmos.circle_2d(position, 4.0, CURRENT_WAYPOINT_COLOR);
+        } else {
+            gizmos.circle_2d(position, 4.0, WAYPOINT_COLOR);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! What the pointer does to the flock
+//!
+//! The pointer either draws nearby boids in, scatters them, or is ignored, chosen in
+//! the options window or cycled with `M`. Holding the mouse button (or a finger) down
+//! makes the pull or push grow over a second or so, up to [`HOLD_BOOST`] times as
+//! strong; a quick click still places an obstacle.
+use bevy::prelude::*;
+use bevy_egui::input::EguiWantsInput;
+use rhysics_common::pointer::Pointer;
+
+/// How much stronger a held pointer gets
+pub const HOLD_BOOST: f32 = 4.0;
+const HOLD_RAMP: f32 = 1.5;              // Seconds to reach the full boost
+const TAP_TIME: f32 = 0.25;              // Presses shorter than this are clicks, not holds
+
+/// Whether the pointer attracts or repels boids
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MouseMode {
+    #[default]
+    Attract,
+    /// Scatter the flock away from the pointer
+    Repel,
+    Off,
+}
+
+impl MouseMode {
+    pub const ALL: [MouseMode; 3] = [MouseMode::Attract, MouseMode::Repel, MouseMode::Off];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MouseMode::Attract => "Attract",
+            MouseMode::Repel => "Repel",
+            MouseMode::Off => "Off",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            MouseMode::Attract => MouseMode::Repel,
+            MouseMode::Repel => MouseMode::Off,
+            MouseMode::Off => MouseMode::Attract,
+        }
+    }
+}
+
+/// The pointer's mode, and how long it has been held down
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub struct MouseInteraction {
+    pub mode: MouseMode,
+    /// Seconds the pointer has been pressed, kept through the frame it is released
+    pub held: f32,
+    /// Whether the press started on the simulation rather than an egui window
+    pub on_canvas: bool,
+}
+
+impl MouseInteraction {
+    /// Factor on the mouse attraction weight: negative to repel, growing while held
+    pub fn strength(&self) -> f32 {
+        let sign = match self.mode {
+            MouseMode::Attract => 1.0,
+            MouseMode::Repel => -1.0,
+            MouseMode::Off => 0.0,
+        };
+        let hold = ((self.held - TAP_TIME) / HOLD_RAMP).clamp(0.0, 1.0);
+        sign * (1.0 + (HOLD_BOOST - 1.0) * hold)
+    }
+
+    /// Whether the press that just ended was a quick click rather than a hold
+    pub fn tapped(&self) -> bool {
+        self.on_canvas && self.held < TAP_TIME
+    }
+}
+
+/// `M` cycles the mode; time how long the pointer is held, in real time so it works
+/// while paused, ignoring presses that start on egui
+pub fn update_mouse_interaction(
+    keys: Res<ButtonInput<KeyCode>>,
+    pointer: Res<Pointer>,
+    egui_input: Option<Res<EguiWantsInput>>,
+    time: Res<Time<Real>>,
+    mut interaction: ResMut<MouseInteraction>,
+) {
+    let keys_free = egui_input.as_ref().is_none_or(|input| !input.wants_any_keyboard_input());
+    if keys_free && keys.just_pressed(KeyCode::KeyM) {
+        interaction.mode = interaction.mode.next();
+        log::info!("Mouse mode: {}", interaction.mode.name());
+    }
+    if pointer.just_pressed {
+        interaction.on_canvas = egui_input.is_none_or(|input| !input.wants_any_pointer_input());
+        interaction.held = 0.0;
+    }
+    if pointer.pressed && interaction.on_canvas {
+        interaction.held += time.delta_secs();
+    } else if !pointer.just_released {
+        interaction.held = 0.0;
+    }
+}
@@ -0,0 +1,105 @@
+//! Obstacles placed with the mouse
+//!
+//! A left click (or tap) drops a round [`Obstacle`] where the pointer is, shift-click a
+//! rectangular one, and a right click removes the obstacle nearest the cursor; holding
+//! the button instead boosts the pointer's pull (see [`crate::mouse`]). Boids
+//! look ahead along their heading with a raycast and steer off any obstacle the ray
+//! hits, harder the closer it is, so they flow around obstacles instead of bouncing
+//! off them; `check_for_collisions` still reflects the few that hit one anyway.
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_egui::input::EguiWantsInput;
+use rhysics_common::collision::{self, ColliderShape};
+use rhysics_common::pointer::Pointer;
+use rhysics_common::world_scale::WorldScale;
+
+use crate::inspect::BoidInspector;
+use crate::mouse::MouseInteraction;
+use crate::{Boid, Collider};
+
+const OBSTACLE_RADIUS: f32 = 30.0;                                // Radius of a round obstacle
+const OBSTACLE_HALF_SIZE: Vec2 = Vec2::new(60.0, 20.0);           // Half the size of a rectangular one
+const OBSTACLE_COLOR: Color = Color::srgb(0.45, 0.45, 0.5);
+const OBSTACLE_LOOKAHEAD: f32 = 60.0;                             // How far ahead boids look for obstacles
+
+/// Something boids steer around; a `Collider` so they bounce off it if they don't
+#[derive(Component)]
+#[require(Collider)]
+pub struct Obstacle;
+
+/// A left click places an obstacle when released, shift-click a rectangular one, right
+/// click removes the nearest; clicks on egui windows and long holds are left alone
+#[allow(clippy::too_many_arguments)]
+pub fn place_obstacles(
+    pointer: Res<Pointer>,
+    interaction: Res<MouseInteraction>,
+    inspector: Res<BoidInspector>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    egui_input: Option<Res<EguiWantsInput>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    scale: Res<WorldScale>,
+    obstacles: Query<(Entity, &Transform), With<Obstacle>>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    if egui_input.is_some_and(|input| input.wants_any_pointer_input()) {
+        return;
+    }
+    let Ok(window) = window.single() else {
+        return;
+    };
+    let Some(position) = pointer.position.map(|position| scale.screen_to_world(window, position)) else {
+        return;
+    };
+
+    // While inspecting, a click picks a boid instead
+    if pointer.just_released && interaction.tapped() && !inspector.enabled {
+        let (mesh, shape) = if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+            (meshes.add(Rectangle::from_size(OBSTACLE_HALF_SIZE * 2.)), ColliderShape::aabb(OBSTACLE_HALF_SIZE))
+        } else {
+            (meshes.add(Circle::new(OBSTACLE_RADIUS)), ColliderShape::circle(OBSTACLE_RADIUS))
+        };
+        commands.spawn((
+            Obstacle,
+            shape,
+            Mesh2d(mesh),
+            MeshMaterial2d(materials.add(OBSTACLE_COLOR)),
+            Transform::from_translation(position.extend(-1.0)),
+        ));
+    }
+
+    if buttons.just_pressed(MouseButton::Right) {
+        let nearest = obstacles.iter().min_by(|(_, a), (_, b)| {
+            let a = a.translation.truncate().distance_squared(position);
+            let b = b.translation.truncate().distance_squared(position);
+            a.total_cmp(&b)
+        });
+        if let Some((entity, _)) = nearest {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Every obstacle with its shape and placement; it leaves out boids so it can run
+/// beside a query that moves them
+pub type ObstacleQuery<'w, 's> =
+    Query<'w, 's, (Entity, &'static ColliderShape, &'static Transform), (With<Obstacle>, Without<Boid>)>;
+
+/// Every obstacle with its placement, for [`avoid_obstacles`]
+pub fn snapshot_obstacles(obstacles: &ObstacleQuery) -> Vec<(Entity, ColliderShape, Isometry2d)> {
+    obstacles
+        .iter()
+        .map(|(entity, shape, transform)| (entity, *shape, collision::isometry(transform)))
+        .collect()
+}
+
+/// Steering away from the first obstacle ahead of a boid at `position` flying along
+/// `velocity`, from 0 at the lookahead distance up to 1 right at the surface
+pub fn avoid_obstacles(obstacles: &[(Entity, ColliderShape, Isometry2d)], position: Vec2, velocity: Vec2) -> Vec2 {
+    collision::raycast(obstacles, position, velocity, OBSTACLE_LOOKAHEAD).map_or(Vec2::ZERO, |hit| {
+        // The normal faces back along the ray; turning onto it slides the boid along the surface
+        hit.normal * (1.0 - hit.distance / OBSTACLE_LOOKAHEAD)
+    })
+}
@@ -0,0 +1,71 @@
+//! Perching on the ground
+//!
+//! In Reynolds' original film the boids could land. With "Perch" on in the options
+//! window a boid that flies down onto the bottom of the arena settles there: its
+//! velocity drops to zero, a [`Perched`] timer starts, and `update_boids` leaves it be.
+//! After a random rest it springs back up and the flock's rules take over again, so
+//! a flock skimming the ground leaves a row of sitters that trickle back into the air.
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rhysics_common::boundary::Boundary;
+use rhysics_common::controls::ResetSimulation;
+use rhysics_common::share::SimulationSeed;
+use rhysics_common::Velocity;
+
+use crate::{Boid, BOID_DIAMETER};
+
+const PERCH_HEIGHT: f32 = 8.0;           // Height above the bottom edge boids sit at, clear of the wall
+const PERCH_SECONDS: (f32, f32) = (1.0, 5.0); // Shortest and longest rest
+const TAKEOFF_SPEED: f32 = 150.0;        // Speed a boid springs up at when its rest is over
+
+/// Whether boids land on the bottom of the arena
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub struct Perching {
+    pub enabled: bool,
+}
+
+/// A boid sitting on the ground until its timer runs out
+#[derive(Component, Debug, Clone)]
+pub struct Perched(pub Timer);
+
+/// Land boids that reach the ground, and send perched ones back up when their rest is
+/// over, perching is turned off, or something moved them off the ground
+#[allow(clippy::too_many_arguments)]
+pub fn perch(
+    perching: Res<Perching>,
+    mut commands: Commands,
+    mut boids: Query<(Entity, &mut Transform, &mut Velocity, Option<&mut Perched>), With<Boid>>,
+    boundary: Res<Boundary>,
+    time: Res<Time>,
+    seed: Res<SimulationSeed>,
+    mut resets: MessageReader<ResetSimulation>,
+    mut rng: Local<Option<StdRng>>,
+) {
+    if resets.read().count() > 0 {
+        *rng = None;
+    }
+    let rng = rng.get_or_insert_with(|| StdRng::seed_from_u64(seed.0));
+    let ground = boundary.rect.min.y + PERCH_HEIGHT;
+    for (entity, mut transform, mut velocity, perched) in &mut boids {
+        match perched {
+            Some(mut perched) => {
+                let lifted = transform.translation.y > ground + BOID_DIAMETER;
+                if perched.0.tick(time.delta()).just_finished() || !perching.enabled || lifted {
+                    commands.entity(entity).remove::<Perched>();
+                    // Up and a little to one side
+                    let direction = Vec2::new(rng.random_range(-0.5..=0.5), 1.0).normalize();
+                    velocity.0 = direction * TAKEOFF_SPEED;
+                }
+            }
+            // Even one the bottom wall just bounced back up
+            None if perching.enabled && transform.translation.y <= ground => {
+                velocity.0 = Vec2::ZERO;
+                transform.translation.y = ground;
+                let rest = rng.random_range(PERCH_SECONDS.0..=PERCH_SECONDS.1);
+                commands.entity(entity).insert(Perched(Timer::from_seconds(rest, TimerMode::Once)));
+            }
+            None => {}
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Predators that hunt the flock
+//!
+//! `P` (or `spawn predator` in the console) drops a [`Predator`] under the cursor. It
+//! turns towards the nearest boid and catches any it touches; a caught boid comes back
+//! somewhere else in the arena, so the flock keeps its size. Boids within
+//! [`BoidSettings::flee_radius`](crate::BoidSettings) of a predator steer away from it
+//! in `update_boids`, which splits and reforms the flock around each attack.
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_egui::input::EguiWantsInput;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rhysics_common::boundary::{Boundary, Bounded};
+use rhysics_common::share::SimulationSeed;
+use rhysics_common::spatial::SpatialGrid;
+use rhysics_common::spawn_layout::SpawnRegion;
+use rhysics_common::world_scale::WorldScale;
+use rhysics_common::Velocity;
+
+use crate::{Boid, BOID_DIAMETER};
+
+const PREDATOR_SIZE: f32 = 14.0;         // Length of a predator's triangle
+const PREDATOR_SPEED: f32 = 70.0;        // A little faster than a calm flock
+const PREDATOR_TURN_RATE: f32 = 3.0;     // How quickly a predator turns onto a new target (1/s)
+const CATCH_RADIUS: f32 = (PREDATOR_SIZE + BOID_DIAMETER) / 2.;
+const PREDATOR_COLOR: Color = Color::srgb(1.0, 0.8, 0.1);
+
+/// Chases the nearest boid
+#[derive(Component)]
+pub struct Predator;
+
+/// Spawn a predator at `position`, heading right
+pub fn spawn_predator(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+    position: Vec2,
+) {
+    let half = PREDATOR_SIZE / 2.;
+    commands.spawn((
+        // Pointing along +x, the way `chase_prey` turns it
+        Mesh2d(meshes.add(Triangle2d::new(
+            Vec2::new(half, 0.0),
+            Vec2::new(-half, half * 0.6),
+            Vec2::new(-half, -half * 0.6),
+        ))),
+        MeshMaterial2d(materials.add(PREDATOR_COLOR)),
+        Transform::from_translation(position.extend(1.0)),
+        Velocity(Vec2::X * PREDATOR_SPEED),
+        Predator,
+        Bounded,
+    ));
+}
+
+/// `P` drops a predator under the cursor, or in the middle without one
+pub fn spawn_predator_on_key(
+    keys: Res<ButtonInput<KeyCode>>,
+    egui_input: Option<Res<EguiWantsInput>>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    scale: Res<WorldScale>,
+) {
+    let keys_free = egui_input.is_none_or(|input| !input.wants_any_keyboard_input());
+    if !keys_free || !keys.just_pressed(KeyCode::KeyP) {
+        return;
+    }
+    let position = window.single().ok().and_then(|window| scale.cursor_position(window)).unwrap_or_default();
+    spawn_predator(&mut commands, &mut meshes, &mut materials, position);
+}
+
+/// Turn every predator towards the nearest boid
+pub fn chase_prey(
+    mut predators: Query<(&mut Transform, &mut Velocity), With<Predator>>,
+    grid: Res<SpatialGrid>,
+    time: Res<Time>,
+) {
+    let turn = (PREDATOR_TURN_RATE * time.delta_secs()).min(1.0);
+    for (mut transform, mut velocity) in &mut predators {
+        let position = transform.translation.truncate();
+        // Few predators and one pass over the flock each, so no need for the grid's cells
+        let nearest = grid
+            .entries()
+            .iter()
+            .map(|(_, boid)| *boid)
+            .min_by(|a, b| a.distance_squared(position).total_cmp(&b.distance_squared(position)));
+        if let Some(target) = nearest {
+            // Always at full speed; only the heading eases round
+            let desired = (target - position).normalize_or_zero() * PREDATOR_SPEED;
+            let heading = velocity.0.lerp(desired, turn).normalize_or_zero();
+            if heading != Vec2::ZERO {
+                velocity.0 = heading * PREDATOR_SPEED;
+            }
+        }
+        transform.rotation = Quat::from_rotation_z(velocity.0.y.atan2(velocity.0.x));
+    }
+}
+
+/// Move every boid a predator touches to a random place in the arena
+pub fn catch_prey(
+    predators: Query<&Transform, With<Predator>>,
+    mut boids: Query<&mut Transform, (With<Boid>, Without<Predator>)>,
+    grid: Res<SpatialGrid>,
+    boundary: Res<Boundary>,
+    seed: Res<SimulationSeed>,
+    mut rng: Local<Option<StdRng>>,
+) {
+    let rng = rng.get_or_insert_with(|| StdRng::seed_from_u64(seed.0));
+    let arena = SpawnRegion::centered(boundary.size() - BOID_DIAMETER * 2.);
+    for predator in &predators {
+        for (entity, _) in grid.query_radius(predator.translation.truncate(), CATCH_RADIUS) {
+            if let Ok(mut boid) = boids.get_mut(entity) {
+                boid.translation = arena.sample(rng).extend(boid.translation.z);
+            }
+        }
+    }
+}
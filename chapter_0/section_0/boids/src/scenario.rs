@@ -0,0 +1,155 @@
+//! Scripted scenarios for narrated demos
+//!
+//! A [`Scenario`] is a list of timed events written in RON, each running a console line
+//! (`spawn predator 0 0`, `wind flip`, `scale cohesion 2`) and showing a caption, so a
+//! demo is the same commands a reader could type. Playing one resets the flock from
+//! the seed and fires each event once its time has passed on the simulation clock,
+//! which pauses and speeds up with the control bar, so every run of a scenario plays
+//! out the same way. Stopping it, or playing it again, puts back the flocking rules
+//! and wind it changed.
+use bevy::prelude::*;
+use rhysics_common::console::Console;
+use rhysics_common::controls::ResetSimulation;
+use rhysics_common::timestep::PhysicsTime;
+use serde::Deserialize;
+
+use crate::species::SpeciesTable;
+use crate::wind::AmbientWind;
+
+/// Scenarios built into the chapter, in the order the options window lists them
+const BUILT_IN: [&str; 1] = [include_str!("../scenarios/predator_and_wind.ron")];
+
+/// A named list of timed events
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Scenario {
+    pub name: String,
+    pub events: Vec<ScenarioEvent>,
+}
+
+/// What happens at one moment of a scenario
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ScenarioEvent {
+    /// Seconds of simulated time after the scenario starts
+    pub at: f32,
+    /// Console line to run
+    #[serde(default)]
+    pub run: Option<String>,
+    /// Caption shown until the next one
+    #[serde(default)]
+    pub say: Option<String>,
+}
+
+impl Scenario {
+    /// Parse a scenario from RON, with its events sorted by time
+    pub fn from_ron(text: &str) -> Result<Self, ron::error::SpannedError> {
+        let mut scenario: Scenario = ron::from_str(text)?;
+        scenario.events.sort_by(|a, b| a.at.total_cmp(&b.at));
+        Ok(scenario)
+    }
+}
+
+/// The scenarios on offer and the one playing
+#[derive(Resource, Debug)]
+pub struct ScenarioPlayer {
+    pub scenarios: Vec<Scenario>,
+    /// Caption of the latest event that had one
+    pub caption: Option<String>,
+    request: Option<ScenarioRequest>,
+    playing: Option<Playing>,
+    /// Rules and wind from before the scenario changed them
+    restore: Option<(SpeciesTable, AmbientWind)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ScenarioRequest {
+    Play(usize),
+    Stop,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Playing {
+    scenario: usize,
+    elapsed: f32,
+    next: usize,
+}
+
+impl Default for ScenarioPlayer {
+    fn default() -> Self {
+        let scenarios = BUILT_IN
+            .iter()
+            .filter_map(|text| {
+                Scenario::from_ron(text)
+                    .inspect_err(|err| log::warn!("Skipping a scenario that doesn't parse: {err}"))
+                    .ok()
+            })
+            .collect();
+        Self {
+            scenarios,
+            caption: None,
+            request: None,
+            playing: None,
+            restore: None,
+        }
+    }
+}
+
+impl ScenarioPlayer {
+    /// Start scenario `index` from the beginning on the next update
+    pub fn play(&mut self, index: usize) {
+        self.request = Some(ScenarioRequest::Play(index));
+    }
+
+    /// Stop the scenario playing on the next update
+    pub fn stop(&mut self) {
+        self.request = Some(ScenarioRequest::Stop);
+    }
+
+    /// Index of the scenario playing and how far into it
+    pub fn playing(&self) -> Option<(usize, f32)> {
+        self.playing.map(|playing| (playing.scenario, playing.elapsed))
+    }
+}
+
+/// Start and stop scenarios, and run each event's console line once its time comes
+pub fn run_scenario(
+    mut player: ResMut<ScenarioPlayer>,
+    mut console: ResMut<Console>,
+    mut table: ResMut<SpeciesTable>,
+    mut wind: ResMut<AmbientWind>,
+    mut resets: MessageWriter<ResetSimulation>,
+    time: Res<Time>,
+    physics_time: Res<PhysicsTime>,
+) {
+    let player = &mut *player;
+    if let Some(request) = player.request.take() {
+        if let Some((rules, before)) = player.restore.take() {
+            *table = rules;
+            *wind = before;
+        }
+        player.playing = None;
+        player.caption = None;
+        if let ScenarioRequest::Play(scenario) = request {
+            if scenario < player.scenarios.len() {
+                player.restore = Some((table.clone(), *wind));
+                player.playing = Some(Playing { scenario, elapsed: 0.0, next: 0 });
+                // The flock the seed spawns, so every run starts alike
+                resets.write(ResetSimulation);
+            }
+        }
+    }
+
+    let Some(playing) = player.playing.as_mut() else {
+        return;
+    };
+    playing.elapsed += physics_time.scaled(time.delta_secs());
+    let events = &player.scenarios[playing.scenario].events;
+    while let Some(event) = events.get(playing.next).filter(|event| event.at <= playing.elapsed) {
+        if let Some(line) = &event.run {
+            console.submit(line.clone());
+        }
+        if let Some(caption) = &event.say {
+            player.caption = Some(caption.clone());
+        }
+        playing.next += 1;
+    }
+}
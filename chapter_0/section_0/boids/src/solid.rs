@@ -0,0 +1,98 @@
+//! Boids that can't overlap
+//!
+//! Separation only asks boids to keep apart, and in a dense flock, or one squeezed
+//! into a corner by the pointer, they still slide over each other. With "Solid boids"
+//! on in the options window every pair nearer than [`BOID_DIAMETER`] after the boids
+//! move is pushed apart along the line between them, each boid taking half of the
+//! overlap. Each boid sums its share of every overlap it is in, found through the
+//! spatial grid, and the sums are applied together a few times over, so a crowd
+//! settles into packed discs instead of one pair at a time. Only positions are
+//! corrected; the steering keeps the velocities.
+use bevy::prelude::*;
+use rhysics_common::boundary::{Boundary, BoundaryMode};
+use rhysics_common::parallel;
+use rhysics_common::spatial::SpatialGrid;
+
+use crate::species::SpeciesTable;
+use crate::{Boid, BOID_DIAMETER, STEERING_CHUNK_SIZE};
+
+const SOLID_ITERATIONS: usize = 4;       // Rounds of pushing apart each step
+
+/// Whether boids are solid discs
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub struct SolidBoids {
+    pub enabled: bool,
+}
+
+/// Push overlapping boids apart until none overlap, or [`SOLID_ITERATIONS`] run out
+pub fn separate_solid_boids(
+    solid: Res<SolidBoids>,
+    mut boids: Query<&mut Transform, With<Boid>>,
+    grid: Res<SpatialGrid>,
+    table: Res<SpeciesTable>,
+    boundary: Res<Boundary>,
+    boundary_mode: Res<BoundaryMode>,
+    time: Res<Time>,
+) {
+    if !solid.enabled {
+        return;
+    }
+    let entries = grid.entries();
+    // The grid has where the boids were before this step's move, so look as much
+    // further as two of them could have closed in since
+    let fastest = table.rules.iter().map(|rules| rules.max_speed).fold(0.0, f32::max);
+    let reach = BOID_DIAMETER + 2.0 * fastest * time.delta_secs();
+    let period = (*boundary_mode == BoundaryMode::Wrap).then_some(boundary.rect);
+    let indices: Vec<usize> = (0..entries.len()).collect();
+
+    // Each boid's candidates, with the shift to the neighbor's nearest copy on a torus
+    let candidates = parallel::par_map(&indices, STEERING_CHUNK_SIZE, |&i| {
+        grid.query_radius_wrapped(entries[i].1, reach, period)
+            .filter(|&(j, _)| j != i)
+            .map(|(j, image)| (j, image - entries[j].1))
+            .collect::<Vec<_>>()
+    });
+
+    let mut positions: Vec<Vec2> = entries
+        .iter()
+        .map(|(entity, position)| boids.get(*entity).map_or(*position, |transform| transform.translation.truncate()))
+        .collect();
+    for _ in 0..SOLID_ITERATIONS {
+        let corrections = parallel::par_map(&indices, STEERING_CHUNK_SIZE, |&i| {
+            candidates[i]
+                .iter()
+                .map(|&(j, shift)| {
+                    let away = positions[i] - (positions[j] + shift);
+                    let distance = away.length();
+                    if distance >= BOID_DIAMETER {
+                        return Vec2::ZERO;
+                    }
+                    // Boids on the same spot part along x, each the opposite way
+                    let normal = if distance > 0.0 {
+                        away / distance
+                    } else if i < j {
+                        Vec2::NEG_X
+                    } else {
+                        Vec2::X
+                    };
+                    normal * (BOID_DIAMETER - distance) / 2.0
+                })
+                .sum::<Vec2>()
+        });
+        if corrections.iter().all(|correction| *correction == Vec2::ZERO) {
+            break;
+        }
+        for (position, correction) in positions.iter_mut().zip(corrections) {
+            *position += correction;
+        }
+    }
+
+    for ((entity, _), position) in entries.iter().zip(positions) {
+        // Untouched boids aren't marked changed
+        if let Ok(mut transform) = boids.get_mut(*entity) {
+            if transform.translation.truncate() != position {
+                transform.translation = position.extend(transform.translation.z);
+            }
+        }
+    }
+}
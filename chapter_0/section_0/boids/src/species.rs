@@ -0,0 +1,175 @@
+//! Several species of boid in one flock
+//!
+//! Each boid has a [`Species`], and each species its own color and [`BoidSettings`].
+//! Separation works across species, so no boid flies into another, but a boid only
+//! aligns with and closes in on a neighbor as much as [`SpeciesTable::interaction`]
+//! says its species flocks with the neighbor's: 1 as with its own kind, 0 not at all.
+//! With the default 0 between species they sort themselves into separate flocks;
+//! raising it mixes them again.
+use bevy::prelude::*;
+use rhysics_common::kernels::SteeringSums;
+use rhysics_common::share::{ShareParams, ShareSettings};
+
+use crate::{BoidSettings, BOID_COLOR};
+
+/// Most species the options window offers
+pub const MAX_SPECIES: usize = 4;
+/// The first is the single-species flock's color
+const SPECIES_COLORS: [Color; MAX_SPECIES] = [
+    BOID_COLOR,
+    Color::srgb(0.2, 0.6, 1.0),
+    Color::srgb(0.3, 0.9, 0.3),
+    Color::srgb(1.0, 0.8, 0.1),
+];
+
+/// Index of a boid's species in the [`SpeciesTable`]
+#[derive(Component, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Species(pub usize);
+
+/// Every species' rules and how they flock with each other
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct SpeciesTable {
+    pub rules: Vec<BoidSettings>,
+    /// `interaction[i][j]`, from 0 to 1: how much species `i` aligns with and closes
+    /// in on species `j`
+    pub interaction: Vec<Vec<f32>>,
+}
+
+impl Default for SpeciesTable {
+    /// One species, flocking as the constants say
+    fn default() -> Self {
+        Self {
+            rules: vec![BoidSettings::default()],
+            interaction: vec![vec![1.0]],
+        }
+    }
+}
+
+impl SpeciesTable {
+    /// One species flocking by `rules`
+    pub fn single(rules: BoidSettings) -> Self {
+        Self {
+            rules: vec![rules],
+            ..default()
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Grow or shrink to `count` species, keeping the rules and interactions of the
+    /// ones that stay; new species copy the first one's rules and keep to themselves
+    pub fn set_count(&mut self, count: usize) {
+        let count = count.clamp(1, MAX_SPECIES);
+        let first = self.rules[0].clone();
+        self.rules.resize(count, first);
+        self.interaction.resize(count, Vec::new());
+        for (i, row) in self.interaction.iter_mut().enumerate() {
+            let added = row.is_empty();
+            row.resize(count, 0.0);
+            if added {
+                row[i] = 1.0;
+            }
+        }
+    }
+
+    /// Rules for `species`; boids of a species that was removed use the first one's
+    pub fn rules(&self, species: usize) -> &BoidSettings {
+        self.rules.get(species).unwrap_or(&self.rules[0])
+    }
+
+    pub fn color(species: usize) -> Color {
+        SPECIES_COLORS[species % MAX_SPECIES]
+    }
+}
+
+impl ShareSettings for SpeciesTable {
+    /// The first species' rules keep the plain keys (`align=...`), the others are
+    /// prefixed with their index (`1.align=...`), and `mix01` is an interaction. The
+    /// count is always written, so a one-species preset brings a mixed flock back to one
+    fn write_params(&self, params: &mut ShareParams) {
+        params.set("species", self.count());
+        for (i, rules) in self.rules.iter().enumerate() {
+            rules.write_params(params, &prefix(i));
+        }
+        for (i, row) in self.interaction.iter().enumerate() {
+            for (j, weight) in row.iter().enumerate() {
+                if i != j {
+                    params.set(&format!("mix{i}{j}"), weight);
+                }
+            }
+        }
+    }
+
+    fn read_params(&mut self, params: &ShareParams) {
+        let mut count = self.count();
+        params.read_into("species", &mut count);
+        self.set_count(count);
+        for (i, rules) in self.rules.iter_mut().enumerate() {
+            rules.read_params(params, &prefix(i));
+        }
+        for (i, row) in self.interaction.iter_mut().enumerate() {
+            for (j, weight) in row.iter_mut().enumerate() {
+                params.read_into(&format!("mix{i}{j}"), weight);
+            }
+        }
+    }
+}
+
+fn prefix(species: usize) -> String {
+    if species == 0 {
+        String::new()
+    } else {
+        format!("{species}.")
+    }
+}
+
+/// A boid's neighbor sums, with neighbors counted for alignment and cohesion by how
+/// much its species flocks with theirs
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct FlockSums {
+    /// Weighted sums of neighbor velocities and positions
+    pub alignment: Vec2,
+    pub cohesion: Vec2,
+    /// Total weight of the neighbors in those sums
+    pub weight: f32,
+    /// Offsets away from every neighbor, whatever its species
+    pub separation: Vec2,
+}
+
+impl From<SteeringSums> for FlockSums {
+    /// Every neighbor at full weight, as with a single species
+    fn from(sums: SteeringSums) -> Self {
+        Self {
+            alignment: sums.alignment,
+            cohesion: sums.cohesion,
+            weight: sums.count as f32,
+            separation: sums.separation,
+        }
+    }
+}
+
+/// [`FlockSums`] for a boid at `position` whose species flocks with the others as
+/// `interaction` (its row of [`SpeciesTable::interaction`]) says, over candidate
+/// `(position, velocity, species)` neighbors
+pub fn flock_sums_of(
+    position: Vec2,
+    candidates: impl IntoIterator<Item = (Vec2, Vec2, usize)>,
+    radius: f32,
+    interaction: &[f32],
+) -> FlockSums {
+    let mut sums = FlockSums::default();
+    for (other_pos, other_vel, species) in candidates {
+        let diff = other_pos - position;
+        let dist = diff.length();
+        if dist < radius && dist > 0.0 {
+            let weight = interaction.get(species).copied().unwrap_or(0.0);
+            sums.alignment += other_vel * weight;
+            sums.cohesion += other_pos * weight;
+            sums.weight += weight;
+            sums.separation -= diff / (dist * dist);
+        }
+    }
+    sums
+}
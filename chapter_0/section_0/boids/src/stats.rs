@@ -0,0 +1,121 @@
+//! Numbers that describe the flock
+//!
+//! Once a second of simulated time [`FlockStats`] is refreshed with the flock's average
+//! speed, its polarization (1 when every boid flies the same way, near 0 when they
+//! scatter), the mean distance from each boid to its nearest neighbor, and how many
+//! separate flocks there are: groups linked by boids within sight of each other. The
+//! Flock Statistics window shows them, and can plot them over time in the Plots window.
+use bevy::prelude::*;
+use rhysics_common::boundary::{Boundary, BoundaryMode};
+use rhysics_common::parallel;
+use rhysics_common::plot::PlotPanel;
+use rhysics_common::spatial::SpatialGrid;
+use rhysics_common::Velocity;
+
+use crate::species::SpeciesTable;
+use crate::{Boid, STEERING_CHUNK_SIZE};
+
+const STATS_INTERVAL: f32 = 1.0;         // Seconds between refreshes
+
+/// The flock's latest statistics, and whether to show and plot them
+#[derive(Resource, Debug, Clone)]
+pub struct FlockStats {
+    pub show: bool,
+    /// Push each refresh to the Plots window
+    pub plot: bool,
+    pub average_speed: f32,
+    pub polarization: f32,
+    /// Over the boids with a neighbor in sight
+    pub mean_nearest_distance: Option<f32>,
+    pub clusters: usize,
+    timer: Timer,
+}
+
+impl Default for FlockStats {
+    fn default() -> Self {
+        Self {
+            show: false,
+            plot: false,
+            average_speed: 0.0,
+            polarization: 0.0,
+            mean_nearest_distance: None,
+            clusters: 0,
+            timer: Timer::from_seconds(STATS_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+/// Refresh [`FlockStats`] every [`STATS_INTERVAL`] while the window is open
+#[allow(clippy::too_many_arguments)]
+pub fn update_flock_stats(
+    mut stats: ResMut<FlockStats>,
+    boids: Query<&Velocity, With<Boid>>,
+    grid: Res<SpatialGrid>,
+    table: Res<SpeciesTable>,
+    boundary: Res<Boundary>,
+    boundary_mode: Res<BoundaryMode>,
+    plots: Option<ResMut<PlotPanel>>,
+    time: Res<Time>,
+) {
+    if !stats.show || !stats.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let entries = grid.entries();
+    let velocities: Vec<Vec2> = entries
+        .iter()
+        .map(|(entity, _)| boids.get(*entity).map_or(Vec2::ZERO, |velocity| velocity.0))
+        .collect();
+    let count = velocities.len().max(1) as f32;
+    stats.average_speed = velocities.iter().map(|velocity| velocity.length()).sum::<f32>() / count;
+    stats.polarization = velocities.iter().map(|velocity| velocity.normalize_or_zero()).sum::<Vec2>().length() / count;
+
+    // Boids in sight of each other, seen across the seam on a torus
+    let radius = table.rules(0).view_radius;
+    let period = (*boundary_mode == BoundaryMode::Wrap).then_some(boundary.rect);
+    let indices: Vec<usize> = (0..entries.len()).collect();
+    let neighbors = parallel::par_map(&indices, STEERING_CHUNK_SIZE, |&i| {
+        let position = entries[i].1;
+        grid.query_radius_wrapped(position, radius, period)
+            .filter(|&(j, _)| j != i)
+            .map(|(j, other)| (j, other.distance(position)))
+            .collect::<Vec<_>>()
+    });
+
+    let nearest: Vec<f32> = neighbors
+        .iter()
+        .filter_map(|near| near.iter().map(|&(_, distance)| distance).min_by(f32::total_cmp))
+        .collect();
+    stats.mean_nearest_distance = (!nearest.is_empty()).then(|| nearest.iter().sum::<f32>() / nearest.len() as f32);
+    stats.clusters = count_clusters(&neighbors);
+
+    if let (true, Some(mut plots)) = (stats.plot, plots) {
+        let t = time.elapsed_secs();
+        plots.push("Speed (px/s)", "average", t, stats.average_speed);
+        plots.push("Polarization", "order", t, stats.polarization);
+        if let Some(distance) = stats.mean_nearest_distance {
+            plots.push("Nearest neighbor (px)", "mean", t, distance);
+        }
+        plots.push("Flocks", "clusters", t, stats.clusters as f32);
+    }
+}
+
+/// Connected groups of boids, each linked to its neighbors, by union-find
+fn count_clusters(neighbors: &[Vec<(usize, f32)>]) -> usize {
+    let mut parents: Vec<usize> = (0..neighbors.len()).collect();
+    fn root(parents: &mut [usize], mut i: usize) -> usize {
+        while parents[i] != i {
+            parents[i] = parents[parents[i]];
+            i = parents[i];
+        }
+        i
+    }
+    for (i, near) in neighbors.iter().enumerate() {
+        for &(j, _) in near {
+            let (a, b) = (root(&mut parents, i), root(&mut parents, j));
+            if a != b {
+                parents[a] = b;
+            }
+        }
+    }
+    (0..neighbors.len()).filter(|&i| root(&mut parents, i) == i).count()
+}
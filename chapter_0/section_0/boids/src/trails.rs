@@ -0,0 +1,57 @@
+//! Motion trails behind the boids
+//!
+//! With "Trails" on in the options window every boid gets a common [`Trail`] in its
+//! species' color, drawn as one fading gizmo polyline each, so where flocks met and
+//! where they split stays on screen for a moment. Turning them off removes the trails
+//! again, and a wrap to the other edge starts a boid's trail over.
+use bevy::prelude::*;
+use rhysics_common::trail::Trail;
+
+use crate::species::{Species, SpeciesTable};
+use crate::Boid;
+
+const TRAIL_SPACING: f32 = 4.0;          // Distance between recorded points
+const TRAIL_ALPHA: f32 = 0.4;            // Opacity at the boid's end
+const TRAIL_BREAK: f32 = 100.0;          // Jumps longer than this are wraps, not flight
+
+/// Whether boids leave trails, and how many points long
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct BoidTrails {
+    pub show: bool,
+    pub length: usize,
+}
+
+impl Default for BoidTrails {
+    fn default() -> Self {
+        Self { show: false, length: 30 }
+    }
+}
+
+/// Give every boid a [`Trail`] while trails are on, including ones spawned later, keep
+/// their length in step with the setting, and take them away when trails are off
+pub fn sync_boid_trails(
+    settings: Res<BoidTrails>,
+    mut commands: Commands,
+    mut boids: Query<(Entity, &Species, Option<&mut Trail>), With<Boid>>,
+) {
+    for (entity, species, trail) in &mut boids {
+        match (settings.show, trail) {
+            (true, None) => {
+                let color = SpeciesTable::color(species.0).with_alpha(TRAIL_ALPHA);
+                let trail = Trail::new(settings.length, TRAIL_SPACING)
+                    .with_color(color)
+                    .with_break_distance(TRAIL_BREAK);
+                commands.entity(entity).insert(trail);
+            }
+            (true, Some(mut trail)) => {
+                if trail.max_points != settings.length {
+                    trail.max_points = settings.length;
+                }
+            }
+            (false, Some(_)) => {
+                commands.entity(entity).remove::<Trail>();
+            }
+            (false, None) => {}
+        }
+    }
+}
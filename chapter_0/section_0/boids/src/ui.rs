@@ -0,0 +1,424 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPlugin, EguiPrimaryContextPass};
+use rhysics_common::boundary::BoundaryMode;
+use rhysics_common::controls::ResetSimulation;
+use rhysics_common::plot::PlotPanelPlugin;
+use rhysics_common::presets::{preset_picker, PresetLibrary};
+use rhysics_common::share::{share_button, SimulationSeed};
+use rhysics_common::Velocity;
+use crate::heatmap::DensityHeatmap;
+use crate::inspect::BoidInspector;
+use crate::migration::Migration;
+use crate::mouse::{MouseInteraction, MouseMode};
+use crate::perch::Perching;
+use crate::scenario::ScenarioPlayer;
+use crate::solid::SolidBoids;
+use crate::species::{Species, SpeciesTable, MAX_SPECIES};
+use crate::stats::FlockStats;
+use crate::trails::BoidTrails;
+use crate::vision::VisionCone;
+use crate::wind::{AmbientWind, WindKind};
+use crate::{BoidSettings, BoidSize, FlockSize, MAX_FLOCK_SIZE, TOPOLOGICAL_NEIGHBORS};
+
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .add_plugins(EguiPlugin::default())
+        .add_plugins(PlotPanelPlugin)
+        .add_systems(EguiPrimaryContextPass, (boid_settings_window, flock_stats_window, inspector_panel, scenario_caption));
+    }
+}
+
+/// How the flock is drawn, and what's drawn over it
+#[derive(SystemParam)]
+struct Overlays<'w> {
+    size: ResMut<'w, BoidSize>,
+    cone: ResMut<'w, VisionCone>,
+    trails: ResMut<'w, BoidTrails>,
+    stats: ResMut<'w, FlockStats>,
+    heatmap: ResMut<'w, DensityHeatmap>,
+    inspector: ResMut<'w, BoidInspector>,
+}
+
+/// The flock's size and everything around it
+#[derive(SystemParam)]
+struct Surroundings<'w> {
+    flock_size: ResMut<'w, FlockSize>,
+    boundary_mode: ResMut<'w, BoundaryMode>,
+    wind: ResMut<'w, AmbientWind>,
+    migration: ResMut<'w, Migration>,
+    mouse: ResMut<'w, MouseInteraction>,
+    solid: ResMut<'w, SolidBoids>,
+    scenario: ResMut<'w, ScenarioPlayer>,
+    perching: ResMut<'w, Perching>,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn boid_settings_window(
+    mut contexts: EguiContexts,
+    mut table: ResMut<SpeciesTable>,
+    seed: Res<SimulationSeed>,
+    mut resets: MessageWriter<ResetSimulation>,
+    mut presets: ResMut<PresetLibrary<SpeciesTable>>,
+    mut overlays: Overlays,
+    mut surroundings: Surroundings,
+    mut selected: Local<usize>,
+) -> Result {
+    egui::Window::new("Boid Options").show(contexts.ctx_mut()?, |ui| {
+        // Edit a copy and only write it back when something moves, so the table isn't
+        // marked changed every frame
+        let mut edited = table.clone();
+
+        // Boids come and go a few thousand a frame until the flock has this many
+        let mut size = surroundings.flock_size.0;
+        let most = MAX_FLOCK_SIZE.max(size);
+        ui.add(egui::Slider::new(&mut size, 0..=most).logarithmic(true).text("Boids"));
+        if size != surroundings.flock_size.0 {
+            surroundings.flock_size.0 = size;
+        }
+        // Bigger triangles show headings better in a sparse flock, smaller ones a crowded one
+        let mut length = overlays.size.0;
+        ui.add(egui::Slider::new(&mut length, 2.0..=24.0).text("Boid size (px)"));
+        if length != overlays.size.0 {
+            overlays.size.0 = length;
+        }
+        // Separation alone lets crowded boids slide over each other
+        let mut solid = surroundings.solid.enabled;
+        if ui.checkbox(&mut solid, "Solid boids (no overlaps)").changed() {
+            surroundings.solid.enabled = solid;
+        }
+        // Land on the bottom edge and rest a few seconds, as in Reynolds' film
+        let mut perching = surroundings.perching.enabled;
+        if ui.checkbox(&mut perching, "Perch on the ground").changed() {
+            surroundings.perching.enabled = perching;
+        }
+
+        // Trails show flocks merging and splitting
+        let mut trail_settings = *overlays.trails;
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut trail_settings.show, "Trails");
+            ui.add_enabled(
+                trail_settings.show,
+                egui::Slider::new(&mut trail_settings.length, 2..=200).text("Length (points)"),
+            );
+        });
+        if trail_settings != *overlays.trails {
+            *overlays.trails = trail_settings;
+        }
+
+        // Send the flock round the arena's waypoints instead of milling about
+        let (mut enabled, mut weight) = (surroundings.migration.enabled, surroundings.migration.weight);
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut enabled, "Migrate");
+            ui.add_enabled(enabled, egui::Slider::new(&mut weight, 0.0..=100.0).text("Pull"));
+        });
+        if (enabled, weight) != (surroundings.migration.enabled, surroundings.migration.weight) {
+            surroundings.migration.enabled = enabled;
+            surroundings.migration.weight = weight;
+        }
+
+        let (mut show_stats, mut show_heatmap) = (overlays.stats.show, overlays.heatmap.show);
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut show_stats, "Statistics");
+            // Crowding waves and clusters as bright patches behind the flock
+            ui.checkbox(&mut show_heatmap, "Density heatmap");
+        });
+        if show_stats != overlays.stats.show {
+            overlays.stats.show = show_stats;
+        }
+        if show_heatmap != overlays.heatmap.show {
+            overlays.heatmap.show = show_heatmap;
+        }
+        // Clicks pick a boid to take apart instead of placing obstacles
+        let mut inspecting = overlays.inspector.enabled;
+        if ui.checkbox(&mut inspecting, "Inspect boids (click one)").changed() {
+            overlays.inspector.enabled = inspecting;
+        }
+
+        ui.separator();
+
+        // A new number of species respawns the flock, shared out between them
+        let mut count = edited.count();
+        ui.add(egui::Slider::new(&mut count, 1..=MAX_SPECIES).text("Species"));
+        if count != edited.count() {
+            edited.set_count(count);
+        }
+        *selected = (*selected).min(count - 1);
+        if count > 1 {
+            ui.horizontal(|ui| {
+                ui.label("Edit:");
+                for species in 0..count {
+                    let text = egui::RichText::new(format!("Species {}", species + 1)).color(color32(species));
+                    ui.selectable_value(&mut *selected, species, text);
+                }
+            });
+        }
+
+        // How the arena keeps the flock in: walls to bounce off and steer clear of, a
+        // torus, or no walls and only a gentle turn back inside
+        let mut mode = *surroundings.boundary_mode;
+        egui::ComboBox::from_label("Edges")
+            .selected_text(edge_label(mode))
+            .show_ui(ui, |ui| {
+                for option in [BoundaryMode::Reflect, BoundaryMode::Wrap, BoundaryMode::Open] {
+                    ui.selectable_value(&mut mode, option, edge_label(option));
+                }
+            });
+        if mode != *surroundings.boundary_mode {
+            *surroundings.boundary_mode = mode;
+        }
+
+        // Wind the flock has to fly in: a steady breeze, or a swirl round the middle
+        let mut wind = *surroundings.wind;
+        egui::ComboBox::from_label("Wind")
+            .selected_text(wind.kind.name())
+            .show_ui(ui, |ui| {
+                for kind in WindKind::ALL {
+                    ui.selectable_value(&mut wind.kind, kind, kind.name());
+                }
+            });
+        if wind.kind != WindKind::Off {
+            ui.add(egui::Slider::new(&mut wind.speed, -100.0..=100.0).text("Wind speed (px/s)"));
+        }
+        if wind.kind == WindKind::Uniform {
+            ui.add(egui::Slider::new(&mut wind.direction_degrees, 0.0..=360.0).text("Wind direction (°)"));
+        }
+        if wind != *surroundings.wind {
+            *surroundings.wind = wind;
+        }
+
+        // Scripted demos: predators, wind and rule changes on cue, with captions
+        let playing = surroundings.scenario.playing();
+        let (mut play, mut stop) = (None, false);
+        ui.horizontal(|ui| {
+            ui.label("Scenario:");
+            for (index, scenario) in surroundings.scenario.scenarios.iter().enumerate() {
+                if ui.button(format!("▶ {}", scenario.name)).clicked() {
+                    play = Some(index);
+                }
+            }
+            if let Some((_, elapsed)) = playing {
+                ui.label(format!("{elapsed:.0} s"));
+                stop = ui.button("Stop").clicked();
+            }
+        });
+        if let Some(index) = play {
+            surroundings.scenario.play(index);
+        } else if stop {
+            surroundings.scenario.stop();
+        }
+
+        ui.heading("Flocking Rules");
+        ui.separator();
+        let rules = &mut edited.rules[*selected];
+
+        // Each rule steers towards its own goal; their balance decides the flock's shape
+        ui.add(egui::Slider::new(&mut rules.align_weight, 0.0..=50.0).text("Alignment"));
+        ui.add(egui::Slider::new(&mut rules.cohesion_weight, 0.0..=50.0).text("Cohesion"));
+        ui.add(egui::Slider::new(&mut rules.separation_weight, 0.0..=50.0).text("Separation"));
+        // Everyone within a radius, or like starlings only the nearest few however far:
+        // a thinned-out topological flock still pulls back together
+        let mut topological = rules.nearest > 0;
+        egui::ComboBox::from_label("Perception")
+            .selected_text(if topological { "Nearest neighbors" } else { "View radius" })
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut topological, false, "View radius");
+                ui.selectable_value(&mut topological, true, "Nearest neighbors");
+            });
+        if topological != (rules.nearest > 0) {
+            rules.nearest = if topological { TOPOLOGICAL_NEIGHBORS } else { 0 };
+        }
+        if topological {
+            ui.add(egui::Slider::new(&mut rules.nearest, 1..=20).text("Neighbors (k)"));
+        } else {
+            // A wider view links far boids into one big flock, a narrow one breaks it up
+            ui.add(egui::Slider::new(&mut rules.view_radius, 5.0..=200.0).text("View radius (px)"));
+        }
+        // Narrow cones ignore the boids behind, and flocks stretch into chains
+        ui.add(egui::Slider::new(&mut rules.fov_degrees, 0.0..=360.0).text("Field of view (°)"));
+        let mut show = overlays.cone.show;
+        if ui.checkbox(&mut show, "Show vision cone").changed() {
+            overlays.cone.show = show;
+        }
+        ui.add(egui::Slider::new(&mut rules.max_speed, 10.0..=1000.0).text("Max speed (px/s)"));
+        // Low forces keep momentum and swing round in wide arcs, high ones turn on the spot
+        ui.add(egui::Slider::new(&mut rules.max_force, 10.0..=5000.0).logarithmic(true).text("Max force (px/s²)"));
+        // Each boid's own smooth random steering, against perfectly uniform flight
+        ui.add(egui::Slider::new(&mut rules.wander_weight, 0.0..=50.0).text("Wander"));
+
+        ui.separator();
+
+        // Pull towards the cursor, or a finger on a touch screen; repel scatters the flock
+        // instead, and holding the button down makes either stronger
+        let mut mode = surroundings.mouse.mode;
+        ui.horizontal(|ui| {
+            ui.label("Mouse (M):");
+            for option in MouseMode::ALL {
+                ui.selectable_value(&mut mode, option, option.name());
+            }
+        });
+        if mode != surroundings.mouse.mode {
+            surroundings.mouse.mode = mode;
+        }
+        ui.add(egui::Slider::new(&mut rules.mouse_attraction_weight, -50.0..=100.0).text("Mouse attraction"));
+        ui.add(egui::Slider::new(&mut rules.mouse_attraction_distance, 0.0..=500.0).text("Mouse reach (px)"));
+
+        ui.separator();
+
+        // Boids turn away from predators (`P`) that come this close
+        ui.add(egui::Slider::new(&mut rules.flee_weight, 0.0..=150.0).text("Flee"));
+        ui.add(egui::Slider::new(&mut rules.flee_radius, 0.0..=400.0).text("Flee radius (px)"));
+
+        ui.separator();
+
+        if ui.button("Defaults").clicked() {
+            *rules = BoidSettings::default();
+        }
+
+        // Row flocks with column: 0 only keeps clear, 1 flocks as with its own kind
+        if count > 1 {
+            ui.collapsing("Interactions", |ui| {
+                egui::Grid::new("species_interactions").show(ui, |ui| {
+                    ui.label("");
+                    for column in 0..count {
+                        ui.colored_label(color32(column), format!("{}", column + 1));
+                    }
+                    ui.end_row();
+                    for (row, weights) in edited.interaction.iter_mut().enumerate() {
+                        ui.colored_label(color32(row), format!("{}", row + 1));
+                        for weight in weights.iter_mut() {
+                            ui.add(egui::DragValue::new(weight).speed(0.01).range(0.0..=1.0));
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        }
+
+        ui.separator();
+
+        // Curated regimes and the reader's own, loaded into the rules being edited
+        preset_picker(ui, &mut *presets, &mut edited);
+
+        if edited.count() != table.count() {
+            resets.write(ResetSimulation);
+        }
+        if edited != *table {
+            *table = edited;
+        }
+
+        // Link that reopens the simulation with these rules
+        share_button(ui, &*table, &seed);
+    });
+    Ok(())
+}
+
+/// The latest [`FlockStats`], with a switch to plot them over time
+fn flock_stats_window(mut contexts: EguiContexts, mut stats: ResMut<FlockStats>) -> Result {
+    if !stats.show {
+        return Ok(());
+    }
+    let mut open = true;
+    let mut plot = stats.plot;
+    egui::Window::new("Flock Statistics").open(&mut open).show(contexts.ctx_mut()?, |ui| {
+        egui::Grid::new("flock_stats").show(ui, |ui| {
+            ui.label("Average speed");
+            ui.label(format!("{:.1} px/s", stats.average_speed));
+            ui.end_row();
+            // Order parameter: 1 when all fly one way, near 0 when they scatter
+            ui.label("Polarization");
+            ui.label(format!("{:.3}", stats.polarization));
+            ui.end_row();
+            ui.label("Nearest neighbor");
+            ui.label(stats.mean_nearest_distance.map_or("-".to_string(), |distance| format!("{distance:.1} px")));
+            ui.end_row();
+            // Groups linked by boids within sight of each other
+            ui.label("Flocks");
+            ui.label(stats.clusters.to_string());
+            ui.end_row();
+        });
+        ui.checkbox(&mut plot, "Plot over time");
+    });
+    if !open {
+        stats.show = false;
+    }
+    if plot != stats.plot {
+        stats.plot = plot;
+    }
+    Ok(())
+}
+
+/// The inspected boid's steering, term by term
+fn inspector_panel(
+    mut contexts: EguiContexts,
+    inspector: Res<BoidInspector>,
+    boids: Query<(&Velocity, &Species)>,
+) -> Result {
+    let Some(entity) = inspector.selected else {
+        return Ok(());
+    };
+    let Ok((velocity, species)) = boids.get(entity) else {
+        return Ok(());
+    };
+    egui::SidePanel::right("boid_inspector").show(contexts.ctx_mut()?, |ui| {
+        ui.heading("Inspected Boid");
+        ui.colored_label(color32(species.0), format!("Species {}", species.0 + 1));
+        ui.label(format!("Speed: {:.1} px/s", velocity.0.length()));
+        ui.label(format!("Heading: {:.0}°", velocity.0.to_angle().to_degrees()));
+        ui.separator();
+        let Some(forces) = inspector.forces else {
+            return;
+        };
+        // Arrows: alignment blue, cohesion green, separation red, total white
+        egui::Grid::new("boid_forces").striped(true).show(ui, |ui| {
+            ui.strong("Term");
+            ui.strong("x");
+            ui.strong("y");
+            ui.strong("|f|");
+            ui.end_row();
+            let total = ("Total", forces.total());
+            for (name, force) in forces.terms().into_iter().chain([total]) {
+                ui.label(name);
+                ui.label(format!("{:.1}", force.x));
+                ui.label(format!("{:.1}", force.y));
+                ui.label(format!("{:.1}", force.length()));
+                ui.end_row();
+            }
+        });
+    });
+    Ok(())
+}
+
+/// What each containment looks like to the flock
+fn edge_label(mode: BoundaryMode) -> &'static str {
+    match mode {
+        BoundaryMode::Reflect => "Reflect + avoid",
+        BoundaryMode::Wrap => "Wrap around",
+        BoundaryMode::Open => "Avoid only",
+        BoundaryMode::Absorb => "Absorb",
+    }
+}
+
+/// The species' color for egui
+fn color32(species: usize) -> egui::Color32 {
+    let [r, g, b, _] = SpeciesTable::color(species).to_srgba().to_u8_array();
+    egui::Color32::from_rgb(r, g, b)
+}
+
+/// The playing scenario's latest caption, across the top of the screen
+fn scenario_caption(mut contexts: EguiContexts, player: Res<ScenarioPlayer>) -> Result {
+    let Some(caption) = player.playing().and(player.caption.as_deref()) else {
+        return Ok(());
+    };
+    egui::Window::new("Scenario")
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 20.0])
+        .title_bar(false)
+        .resizable(false)
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.label(egui::RichText::new(caption).size(18.0));
+        });
+    Ok(())
+}
@@ -0,0 +1,104 @@
+//! What a boid can see
+//!
+//! Real birds and fish don't see behind themselves, so a boid only reacts to neighbors
+//! inside a cone of [`BoidSettings::fov_degrees`](crate::BoidSettings) around its
+//! heading, as well as within its view radius. Narrow cones make followers that don't
+//! care about the boids behind them, which stretches flocks into lines and chains.
+//! With "Show vision cone" on in the options window the cone of the boid nearest the
+//! cursor is drawn.
+//!
+//! Starlings don't react to every bird within some distance but to their six or seven
+//! nearest, however far those are. With [`BoidSettings::nearest`](crate::BoidSettings)
+//! set, a boid flocks with only that many nearest boids in its cone instead of everyone
+//! within its view radius. A flock thinned out by a predator then still pulls back
+//! together, where with a radius the stragglers lose sight of it.
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use rhysics_common::pointer::Pointer;
+use rhysics_common::spatial::SpatialGrid;
+use rhysics_common::world_scale::WorldScale;
+use rhysics_common::Velocity;
+
+use crate::species::{Species, SpeciesTable};
+use crate::Boid;
+
+const CONE_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.6);
+const TOPOLOGICAL_REACH: f32 = 250.0;    // Farthest a nearest neighbor can be, so lone boids' searches end
+
+/// Whether to draw a boid's vision cone
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub struct VisionCone {
+    pub show: bool,
+}
+
+/// Cosine of half the field of view, for [`in_view`]
+pub fn half_fov_cos(fov_degrees: f32) -> f32 {
+    (fov_degrees.clamp(0.0, 360.0).to_radians() / 2.0).cos()
+}
+
+/// Whether a neighbor at `offset` from a boid flying along `heading` is inside its
+/// cone; a boid that isn't moving sees all around
+pub fn in_view(heading: Vec2, offset: Vec2, half_fov_cos: f32) -> bool {
+    half_fov_cos <= -1.0
+        || heading == Vec2::ZERO
+        || heading.dot(offset) >= half_fov_cos * heading.length() * offset.length()
+}
+
+/// The `k` nearest boids in the cone of a boid at `position` flying along `heading`,
+/// as grid indices with their nearest copy on the torus `period`
+pub fn nearest_in_view(
+    grid: &SpatialGrid,
+    position: Vec2,
+    heading: Vec2,
+    k: usize,
+    half_fov_cos: f32,
+    period: Option<Rect>,
+) -> Vec<(usize, Vec2)> {
+    // Across the seam only the nearest copy counts, which holds within half the arena
+    let reach = period.map_or(TOPOLOGICAL_REACH, |period| TOPOLOGICAL_REACH.min(period.size().min_element() / 2.0));
+    grid.query_nearest_wrapped(position, k, reach, period, |_, other| {
+        other != position && in_view(heading, other - position, half_fov_cos)
+    })
+}
+
+/// Draw the vision cone of the boid nearest the pointer, or of any boid without one
+pub fn draw_vision_cone(
+    cone: Res<VisionCone>,
+    boids: Query<(&Transform, &Velocity, &Species), With<Boid>>,
+    table: Res<SpeciesTable>,
+    pointer: Res<Pointer>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    scale: Res<WorldScale>,
+    mut gizmos: Gizmos,
+) {
+    if !cone.show {
+        return;
+    }
+    let target = window
+        .single()
+        .ok()
+        .and_then(|window| pointer.position.map(|position| scale.screen_to_world(window, position)))
+        .unwrap_or_default();
+    let Some((transform, velocity, species)) = boids.iter().min_by(|(a, _, _), (b, _, _)| {
+        let a = a.translation.truncate().distance_squared(target);
+        let b = b.translation.truncate().distance_squared(target);
+        a.total_cmp(&b)
+    }) else {
+        return;
+    };
+    let rules = table.rules(species.0);
+    let position = transform.translation.truncate();
+    let fov = rules.fov_degrees.clamp(0.0, 360.0).to_radians();
+    let heading = velocity.0.try_normalize().unwrap_or(Vec2::X);
+    if fov >= std::f32::consts::TAU {
+        gizmos.circle_2d(position, rules.view_radius, CONE_COLOR);
+        return;
+    }
+    // The arc's middle points along the isometry's +y
+    let rotation = Rot2::radians(heading.to_angle() - std::f32::consts::FRAC_PI_2);
+    gizmos.arc_2d(Isometry2d::new(position, rotation), fov, rules.view_radius, CONE_COLOR);
+    for side in [-0.5, 0.5] {
+        let edge = Rot2::radians(side * fov) * heading;
+        gizmos.line_2d(position, position + edge * rules.view_radius, CONE_COLOR);
+    }
+}
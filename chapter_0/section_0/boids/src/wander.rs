@@ -0,0 +1,45 @@
+//! Wandering, so no two boids fly exactly alike
+//!
+//! Reynolds' wander: each boid steers towards a point on a circle ahead of it, and
+//! that point drifts round the circle in a random walk kept in the boid's [`Wander`].
+//! The heading it asks for changes smoothly rather than jittering every frame, and
+//! with [`BoidSettings::wander_weight`](crate::BoidSettings) above zero the flock
+//! keeps some individual restlessness instead of settling into perfectly uniform flight.
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rhysics_common::controls::ResetSimulation;
+use rhysics_common::share::SimulationSeed;
+
+const WANDER_DISTANCE: f32 = 2.0;        // Distance of the circle's center ahead, in circle radii
+const WANDER_RATE: f32 = 4.0;            // Fastest the point drifts round the circle (rad/s)
+
+/// Angle of a boid's wander point on its circle, from straight ahead
+#[derive(Component, Default, Debug, Clone, Copy, PartialEq)]
+pub struct Wander(pub f32);
+
+/// Move every boid's wander point a random step round its circle; a reset starts the
+/// steps over from the seed, so a replayed scenario wanders the same way
+pub fn drift_wander(
+    mut wanderers: Query<&mut Wander>,
+    time: Res<Time>,
+    seed: Res<SimulationSeed>,
+    mut resets: MessageReader<ResetSimulation>,
+    mut rng: Local<Option<StdRng>>,
+) {
+    if resets.read().count() > 0 {
+        *rng = None;
+    }
+    let rng = rng.get_or_insert_with(|| StdRng::seed_from_u64(seed.0));
+    let step = WANDER_RATE * time.delta_secs();
+    for mut wander in &mut wanderers {
+        wander.0 += rng.random_range(-1.0..=1.0) * step;
+    }
+}
+
+/// Unit steering towards the wander point of a boid flying along `heading`
+pub fn wander_force(heading: Vec2, angle: f32) -> Vec2 {
+    let forward = heading.try_normalize().unwrap_or(Vec2::X);
+    let offset = Rot2::radians(angle) * forward;
+    (forward * WANDER_DISTANCE + offset).normalize_or_zero()
+}
@@ -0,0 +1,125 @@
+//! Ambient wind over the whole arena
+//!
+//! The options window can blow a steady [`UniformWind`] across the arena or turn it
+//! into a [`Vortex`] around the middle. Either is an ordinary common force field on an
+//! [`AmbientWindField`] entity, pushing boids in `update_boids` like the fields spawned
+//! from the console and drawn by the field arrows. In a crosswind a flock keeps its
+//! shape while drifting, and steers upwind to reach the pointer or a waypoint.
+use bevy::prelude::*;
+use rhysics_common::boundary::Boundary;
+use rhysics_common::console::ConsoleCommand;
+use rhysics_common::force_field::{UniformWind, Vortex};
+
+/// Which wind blows
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WindKind {
+    #[default]
+    Off,
+    /// The same everywhere, along the wind's direction
+    Uniform,
+    /// Swirling round the middle of the arena, anticlockwise for positive speeds
+    Vortex,
+}
+
+impl WindKind {
+    pub const ALL: [WindKind; 3] = [WindKind::Off, WindKind::Uniform, WindKind::Vortex];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WindKind::Off => "Off",
+            WindKind::Uniform => "Uniform",
+            WindKind::Vortex => "Vortex",
+        }
+    }
+}
+
+/// The wind the options window sets
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct AmbientWind {
+    pub kind: WindKind,
+    /// Push on a boid at rest (px/s), or swirl at the edge of the vortex's core
+    pub speed: f32,
+    /// Where a uniform wind blows to, anticlockwise from +x
+    pub direction_degrees: f32,
+}
+
+impl Default for AmbientWind {
+    fn default() -> Self {
+        Self {
+            kind: WindKind::Off,
+            speed: 20.0,
+            direction_degrees: 0.0,
+        }
+    }
+}
+
+/// The entity carrying the ambient wind's field
+#[derive(Component)]
+pub struct AmbientWindField;
+
+/// Replace the wind's field whenever the settings or the arena change, or it was
+/// cleared away by a reset
+pub fn sync_wind(
+    wind: Res<AmbientWind>,
+    boundary: Res<Boundary>,
+    fields: Query<Entity, With<AmbientWindField>>,
+    mut commands: Commands,
+) {
+    let missing = fields.is_empty() && wind.kind != WindKind::Off;
+    if !wind.is_changed() && !boundary.is_changed() && !missing {
+        return;
+    }
+    for entity in &fields {
+        commands.entity(entity).despawn();
+    }
+    let center = Transform::from_translation(boundary.rect.center().extend(0.0));
+    match wind.kind {
+        WindKind::Off => {}
+        WindKind::Uniform => {
+            // Against a boid at rest the force is k·wind, so k = 1 pushes at the wind's speed
+            let velocity = Vec2::from_angle(wind.direction_degrees.to_radians()) * wind.speed;
+            commands.spawn((AmbientWindField, UniformWind::new(velocity, 1.0), center));
+        }
+        WindKind::Vortex => {
+            let core = boundary.size().min_element() / 4.;
+            commands.spawn((AmbientWindField, Vortex::new(wind.speed, core), center));
+        }
+    }
+}
+
+/// Handles `wind off`, `wind uniform [speed] [direction]`, `wind vortex [speed]` and
+/// `wind flip`, which turns a uniform wind round or a vortex the other way
+pub fn wind_from_console(mut console_commands: MessageReader<ConsoleCommand>, mut wind: ResMut<AmbientWind>) {
+    for command in console_commands.read().filter(|command| command.name == "wind") {
+        let mut changed = *wind;
+        match command.args.first().map(String::as_str) {
+            Some("off") => changed.kind = WindKind::Off,
+            Some("uniform") => {
+                changed.kind = WindKind::Uniform;
+                if let Some(speed) = command.arg(1) {
+                    changed.speed = speed;
+                }
+                if let Some(degrees) = command.arg(2) {
+                    changed.direction_degrees = degrees;
+                }
+            }
+            Some("vortex") => {
+                changed.kind = WindKind::Vortex;
+                if let Some(speed) = command.arg(1) {
+                    changed.speed = speed;
+                }
+            }
+            Some("flip") if changed.kind == WindKind::Uniform => {
+                changed.direction_degrees = (changed.direction_degrees + 180.0).rem_euclid(360.0);
+            }
+            Some("flip") => changed.speed = -changed.speed,
+            _ => {
+                log::info!("Wind is {} at {} px/s", wind.kind.name(), wind.speed);
+                continue;
+            }
+        }
+        if changed != *wind {
+            *wind = changed;
+        }
+    }
+}
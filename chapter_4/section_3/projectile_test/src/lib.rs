@@ -1,17 +1,84 @@
-use bevy::math::bounding::{Aabb2d, BoundingCircle, BoundingVolume, IntersectsVolume};
+use bevy::gizmos::config::GizmoConfigStore;
 use bevy::prelude::*;
 use rhysics_common::*;
+use rhysics_common::analytic;
+use rhysics_common::camera::{CameraController, CameraControllerPlugin, FitInView};
+use rhysics_common::collision::{ColliderShape, CollisionEvent, ContactSolver, ContinuousCollision, PhysicsMaterial};
+use rhysics_common::collision_sound::CollisionSoundPlugin;
+use rhysics_common::comparison::{Comparison, ComparisonPlugin, ComparisonSide};
+use rhysics_common::field_arrows::{draw_vector_field, visible_area, FieldArrows};
+use rhysics_common::forces::{Drag, Gravity, GravityScale, NoGravity};
+use rhysics_common::console::{ConsoleCommand, ConsolePlugin, ConsoleRegistry};
+use rhysics_common::capture::CapturePlugin;
+use rhysics_common::controls::{ResetSimulation, SimulationControlsPlugin};
+use rhysics_common::datalog::{DataLogger, DataLoggerPlugin};
+use rhysics_common::headless::{self, HeadlessPlugin};
+use rhysics_common::integrator::{stage_acceleration, BodyState, Integrator};
+use rhysics_common::interpolation::{InterpolationPlugin, TransformInterpolation};
+use rhysics_common::plot::{PlotPanel, PlotPanelPlugin};
+use rhysics_common::pick::{Draggable, PickDragPlugin};
+use rhysics_common::physics::{run_physics_step, PhysicsPlugin, PhysicsSet, PhysicsStep};
+use rhysics_common::pool::EntityPool;
+use rhysics_common::presets::PresetPlugin;
+use rhysics_common::quiz::{Question, Quiz, QuizPlugin};
+use rhysics_common::report::{ReportMetrics, SimReportPlugin};
+use rhysics_common::share::{ShareParams, SharePlugin, ShareSettings};
+use rhysics_common::timestep::{physics_stepped, PhysicsRate, PhysicsRatePlugin, PhysicsTime};
+use rhysics_common::trail::{Trail, TrailPlugin};
+use rhysics_common::tutorial::{Tutorial, TutorialPlugin, TutorialStep};
+use rhysics_common::units;
+use rhysics_common::watchdog::{ConservedQuantities, EnergyWatchdog, EnergyWatchdogPlugin};
+use rhysics_common::world_scale::{WorldScale, WorldScalePlugin};
 mod ui;
 
+#[cfg(feature = "inspector")]
+use rhysics_common::inspector::InspectorPlugin;
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
 use crate::ui::UiPlugin;
 
-#[derive(Resource)]
+const PIXELS_PER_METER: f32 = 4.0;
+/// Height of the launch point above the ground (m)
+const LAUNCH_HEIGHT: f32 = 50.0;
+/// Length of the ground (m)
+const GROUND_LENGTH: f32 = 2500.0;
+/// Air at sea level (kg/m³)
+const SEA_LEVEL_AIR_DENSITY: f32 = 1.225;
+/// Drag coefficient of a smooth sphere
+const SPHERE_DRAG_COEFFICIENT: f32 = 0.47;
+/// Cross-section of the default ball, about 25 cm across (m²)
+const BALL_AREA: f32 = 0.05;
+/// Points kept in the path drawn behind each projectile, and meters between them
+const TRAIL_POINTS: usize = 500;
+const TRAIL_SPACING: f32 = 0.25;
+// Drawn sizes, in pixels whatever the scale
+const PROJECTILE_DIAMETER: f32 = 10.0;
+const MARKER_DIAMETER: f32 = 5.0;
+const GROUND_THICKNESS: f32 = 10.0;
+
+#[derive(Resource, Clone)]
 pub struct ProjectileSettings {
     pub initial_velocity: Velocity,
-    pub gravitational_constant: f32,
+    /// Multiplies the shared `Gravity`, so the comparison can fly under another world's pull
+    pub gravity_scale: f32,
+    /// Drag coefficient C_d of the projectile's shape
+    pub drag_coefficient: f32,
+    /// Density ρ of the air (kg/m³); 0 is a vacuum
+    pub air_density: f32,
+    /// Cross-sectional area A facing the airflow (m²)
+    pub area: f32,
+    /// Wind speed (m/s); one wind blows across both sides of the comparison
+    pub wind: f32,
+    /// Where the wind blows to, anticlockwise from +x: 0 is a tailwind for a shot to the
+    /// right, 180 a headwind and 90 an updraft (°)
+    pub wind_direction: f32,
+    /// Spin given at launch (rad/s, anticlockwise); backspin on a shot to the right is
+    /// positive and lifts it, topspin dips it
+    pub spin: f32,
+    /// Bounciness of the projectile against the ground
+    pub restitution: f32,
+    pub friction: f32,
     pub launched: bool,
 }
 
@@ -19,228 +86,718 @@ impl Default for ProjectileSettings {
     fn default() -> Self {
         Self {
             initial_velocity: Velocity(Vec2::new(30.0, 30.0)),
-            gravitational_constant: -9.81,
+            gravity_scale: 1.0,
+            drag_coefficient: SPHERE_DRAG_COEFFICIENT,
+            air_density: 0.0,
+            area: BALL_AREA,
+            wind: 0.0,
+            wind_direction: 0.0,
+            spin: 0.0,
+            restitution: 1.0,
+            friction: 0.0,
             launched: false
         }
     }
 }
 
-#[derive(Component, Default)]
-struct Collider;
+impl ShareSettings for ProjectileSettings {
+    fn write_params(&self, params: &mut ShareParams) {
+        params.set("vx", self.initial_velocity.0.x);
+        params.set("vy", self.initial_velocity.0.y);
+        params.set("cd", self.drag_coefficient);
+        params.set("rho", self.air_density);
+        params.set("area", self.area);
+        params.set("wind", self.wind);
+        params.set("wdir", self.wind_direction);
+        params.set("spin", self.spin);
+        params.set("e", self.restitution);
+        params.set("mu", self.friction);
+    }
+
+    fn read_params(&mut self, params: &ShareParams) {
+        params.read_into("vx", &mut self.initial_velocity.0.x);
+        params.read_into("vy", &mut self.initial_velocity.0.y);
+        params.read_into("cd", &mut self.drag_coefficient);
+        params.read_into("rho", &mut self.air_density);
+        params.read_into("area", &mut self.area);
+        params.read_into("wind", &mut self.wind);
+        params.read_into("wdir", &mut self.wind_direction);
+        params.read_into("spin", &mut self.spin);
+        params.read_into("e", &mut self.restitution);
+        params.read_into("mu", &mut self.friction);
+    }
+}
 
 #[derive(Component, Default)]
 struct Launched(bool);
 
+/// Magnus lift on a spinning ball, F = S ω × (v − w), with S = ½ρAr (kg) from the
+/// air and the ball's size; the wind is the one in the projectile's [`Drag`]
+#[derive(Component, Default, Clone, Copy)]
+struct Magnus(f32);
+
+impl Magnus {
+    /// Force on a ball spinning at `spin` and moving at `relative` through the air
+    fn force(&self, spin: f32, relative: Vec2) -> Vec2 {
+        // ω points out of the screen, so ω × v is v turned a quarter anticlockwise
+        self.0 * spin * relative.perp()
+    }
+}
+
 #[derive(Component)]
-#[require(Mesh2d, MeshMaterial2d<ColorMaterial>, Transform, Collider, Velocity, Launched)]
+#[require(Mesh2d, MeshMaterial2d<ColorMaterial>, Transform, ColliderShape, ContinuousCollision, PhysicsMaterial, Drag, Magnus, Velocity, Rotation, AngularVelocity, Mass, GravityScale, Launched, TransformInterpolation, ComparisonSide)]
 struct Projectile;
 
+/// Stripe from the projectile's center to its edge, so its spin shows
+#[derive(Component)]
+#[require(Mesh2d, MeshMaterial2d<ColorMaterial>, Transform, ComparisonSide)]
+struct Seam;
+
 #[derive(Component)]
 #[require(Mesh2d, MeshMaterial2d<ColorMaterial>, Transform)]
 struct TrajectoryMarker;
 
 #[derive(Component)]
-#[require(Transform, Collider)]
+#[require(Transform, ColliderShape)]
 struct Ground;
 
-/// Predicts the trajectory for each second
-fn predicted_trajectory(settings: &ProjectileSettings, seconds: i32) -> Vec<Vec2> {
-    let mut trajectory = Vec::new();
-    let v0 = settings.initial_velocity.0;
-    let a = Vec2::new(0.0, settings.gravitational_constant);
-    
-    for t in 1..=seconds {
-        let t = t as f32;
-        // Kinematic equation: position = v0*t + 0.5*a*t^2
-        let position = v0 * t + 0.5 * a * t * t;
-        trajectory.push(position);
+impl ProjectileSettings {
+    /// Vertical gravitational acceleration under `gravity` (m/s²)
+    fn gravity(&self, gravity: &Gravity) -> f32 {
+        gravity.0.y * self.gravity_scale
     }
-    trajectory
+
+    /// Quadratic air drag in still air, F = −½ρC_dA|v|v
+    fn drag(&self) -> Drag {
+        Drag::quadratic(0.5 * self.air_density * self.drag_coefficient * self.area)
+    }
+
+    /// Magnus lift for a ball of cross-section A, radius √(A/π)
+    fn magnus(&self) -> Magnus {
+        let radius = (self.area / std::f32::consts::PI).sqrt();
+        Magnus(0.5 * self.air_density * self.area * radius)
+    }
+
+    /// Velocity of the air while the projectile flies (m/s)
+    fn wind_velocity(&self) -> Vec2 {
+        Vec2::from_angle(self.wind_direction.to_radians()) * self.wind
+    }
+
+    /// Whether the projectile's mechanical energy should stay put: no air to drag,
+    /// blow or lift it, and bounces that give back all they take
+    fn conserves_energy(&self) -> bool {
+        self.air_density == 0.0 && self.restitution >= 1.0 && self.friction == 0.0
+    }
+
+    /// The closed-form vacuum trajectory for these settings
+    fn analytic(&self, gravity: &Gravity) -> analytic::Projectile {
+        analytic::Projectile::new(self.initial_velocity.0, self.gravity(gravity))
+    }
+}
+
+/// Drag and Magnus lift on a projectile moving at `velocity` and spinning at `spin`,
+/// both on its velocity relative to the drag's wind
+fn air_force(drag: &Drag, magnus: &Magnus, spin: f32, velocity: Vec2) -> Vec2 {
+    drag.force(velocity) + magnus.force(spin, velocity - drag.wind)
+}
+
+/// Predicts the trajectory for each second, stepping with the same integrator, physics
+/// rate, drag, spin and `wind` as the simulation, so the markers show where the simulated projectile
+/// will be rather than the exact parabola; with drag there is no closed form to show
+fn predicted_trajectory(
+    settings: &ProjectileSettings,
+    wind: Vec2,
+    gravity: &Gravity,
+    integrator: &Integrator,
+    rate: &PhysicsRate,
+    seconds: i32,
+) -> Vec<Vec2> {
+    let dt = rate.dt();
+    let gravity = gravity.0 * settings.gravity_scale;
+    // Drag on the velocity relative to the air, so the wind pushes as well as slows
+    let drag = settings.drag().with_wind(wind);
+    let magnus = settings.magnus();
+    let mass = Mass::default().0;
+    let mut state = BodyState::new(Vec2::ZERO, settings.initial_velocity.0);
+    let mut step = 0;
+    (1..=seconds)
+        .map(|t| {
+            while step < rate.steps_in(t as f32) {
+                state = integrator.step(state, step as f32 * dt, dt, |_, state| {
+                    gravity + air_force(&drag, &magnus, settings.spin, state.velocity) / mass
+                });
+                step += 1;
+            }
+            state.position
+        })
+        .collect()
+}
+
+/// Starting points for the settings, alongside the reader's saved presets
+fn projectile_presets() -> PresetPlugin<ProjectileSettings> {
+    PresetPlugin::new("projectile_test")
+        .with_preset("Default", &ProjectileSettings::default())
+        .with_preset(
+            "Steep lob",
+            &ProjectileSettings {
+                initial_velocity: Velocity(Vec2::new(10.0, 60.0)),
+                ..default()
+            },
+        )
+        .with_preset(
+            "Into a headwind",
+            &ProjectileSettings {
+                air_density: SEA_LEVEL_AIR_DENSITY,
+                wind: 15.0,
+                wind_direction: 180.0,
+                ..default()
+            },
+        )
+        .with_preset(
+            "Curveball",
+            &ProjectileSettings {
+                air_density: SEA_LEVEL_AIR_DENSITY,
+                spin: -100.0,
+                ..default()
+            },
+        )
+        .with_preset(
+            "Dead ball",
+            &ProjectileSettings {
+                restitution: 0.3,
+                friction: 0.6,
+                ..default()
+            },
+        )
+}
+
+/// Walkthrough of launching a projectile, shown on startup (F1 toggles it)
+fn projectile_tutorial() -> Tutorial {
+    Tutorial::new(vec![
+        TutorialStep::new(
+            "Initial velocity",
+            "The X and Y sliders set the launch velocity. The dotted markers show where the projectile will be after each second of flight.",
+        )
+        .highlight_control("Initial Velocity"),
+        TutorialStep::new(
+            "Launch",
+            "Press Launch and compare the projectile's path with the predicted markers.",
+        )
+        .highlight_control("Launch")
+        .wait_until(|world| world.resource::<ProjectileSettings>().launched),
+        TutorialStep::new(
+            "Gravity",
+            "Press Reset, then lower the gravity slider. A weaker pull gives a longer, flatter parabola.",
+        )
+        .highlight_control("Gravity")
+        .wait_until(|world| !world.resource::<ProjectileSettings>().launched),
+        TutorialStep::new(
+            "Explore",
+            "Try to find the launch angle that gives the greatest range for a fixed launch speed.",
+        ),
+    ])
+}
+
+/// Checkpoint questions for the section
+fn projectile_quiz() -> Quiz {
+    Quiz::new(vec![
+        Question::numeric(
+            "In a vacuum, which launch angle above the horizontal gives the greatest range for a fixed launch speed?",
+            45.0,
+            1.0,
+        )
+        .with_unit("°")
+        .with_hint("The range is proportional to sin(2θ)"),
+        Question::measured(
+            "With the current settings, how long does the projectile stay in the air before returning to launch height?",
+            0.1,
+            |world| world.resource::<ProjectileSettings>().analytic(world.resource::<Gravity>()).flight_time(),
+        )
+        .with_unit("s")
+        .with_hint("At the top of the flight the vertical velocity is zero: t = 2 v_y / g"),
+        Question::interactive(
+            "Set the launch velocity so the projectile rises at least 100 m above the launch point.",
+            |world| {
+                let settings = world.resource::<ProjectileSettings>();
+                settings.analytic(world.resource::<Gravity>()).max_height().is_some_and(|height| height >= 100.0)
+            },
+        )
+        .with_hint("The peak height is v_y² / 2g"),
+    ])
+}
+
+/// Settings reachable from the developer console
+fn projectile_console() -> ConsoleRegistry {
+    ConsoleRegistry::default()
+        .with_param(
+            "gravity",
+            "m/s²",
+            |world| world.resource::<Gravity>().0.y,
+            |world, value| world.resource_mut::<Gravity>().0.y = value,
+        )
+        .with_param(
+            "vx",
+            "m/s",
+            |world| world.resource::<ProjectileSettings>().initial_velocity.0.x,
+            |world, value| world.resource_mut::<ProjectileSettings>().initial_velocity.0.x = value,
+        )
+        .with_param(
+            "vy",
+            "m/s",
+            |world| world.resource::<ProjectileSettings>().initial_velocity.0.y,
+            |world, value| world.resource_mut::<ProjectileSettings>().initial_velocity.0.y = value,
+        )
+        .with_param(
+            "cd",
+            "",
+            |world| world.resource::<ProjectileSettings>().drag_coefficient,
+            |world, value| world.resource_mut::<ProjectileSettings>().drag_coefficient = value,
+        )
+        .with_param(
+            "density",
+            "kg/m³",
+            |world| world.resource::<ProjectileSettings>().air_density,
+            |world, value| world.resource_mut::<ProjectileSettings>().air_density = value,
+        )
+        .with_param(
+            "area",
+            "m²",
+            |world| world.resource::<ProjectileSettings>().area,
+            |world, value| world.resource_mut::<ProjectileSettings>().area = value,
+        )
+        .with_param(
+            "wind",
+            "m/s",
+            |world| world.resource::<ProjectileSettings>().wind,
+            |world, value| world.resource_mut::<ProjectileSettings>().wind = value,
+        )
+        .with_param(
+            "wind_direction",
+            "°",
+            |world| world.resource::<ProjectileSettings>().wind_direction,
+            |world, value| world.resource_mut::<ProjectileSettings>().wind_direction = value,
+        )
+        .with_param(
+            "spin",
+            "rad/s",
+            |world| world.resource::<ProjectileSettings>().spin,
+            |world, value| world.resource_mut::<ProjectileSettings>().spin = value,
+        )
+        .with_param(
+            "restitution",
+            "",
+            |world| world.resource::<ProjectileSettings>().restitution,
+            |world, value| world.resource_mut::<ProjectileSettings>().restitution = value,
+        )
+        .with_param(
+            "friction",
+            "",
+            |world| world.resource::<ProjectileSettings>().friction,
+            |world, value| world.resource_mut::<ProjectileSettings>().friction = value,
+        )
+        .with_command("launch", "launch")
+        .with_command("reset", "reset")
 }
 
-#[cfg_attr(target_arch = "wasm32", wasm_bindgen(start))]
+#[cfg_attr(all(target_arch = "wasm32", feature = "standalone"), wasm_bindgen(start))]
 pub fn run() {
-    App::new()
-        .add_plugins(DefaultPlugins.set(default_window_plugin(
-            "Chapter 4.3 - Projectile Test"
-        )))
-        .init_resource::<ProjectileSettings>()
-        .add_plugins(UiPlugin)
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins.set(default_window_plugin(
+        "Chapter 4.3 - Projectile Test"
+    )))
+    .add_plugins(UiPlugin)
+    .add_plugins(CollisionSoundPlugin);
+    add_simulation(&mut app);
+    // F2 opens an editor for the projectile's components
+    #[cfg(feature = "inspector")]
+    app.add_plugins(InspectorPlugin);
+    app.run();
+}
+
+/// Launch the projectile and fly it for `steps` physics ticks without a window; the
+/// run's report is written on the way out, and the app is returned to read the rest
+pub fn run_headless(steps: u32) -> App {
+    let mut app = App::new();
+    app.add_plugins(HeadlessPlugin::default());
+    add_simulation(&mut app);
+    app.world_mut().resource_mut::<ProjectileSettings>().launched = true;
+    headless::run_steps(&mut app, steps);
+    app
+}
+
+/// Everything but the window, renderer and settings UI, shared by [`run`] and
+/// [`run_headless`]
+fn add_simulation(app: &mut App) {
+    app.init_resource::<ProjectileSettings>()
+        .init_resource::<EntityPool<TrajectoryMarker>>()
+        .add_plugins(SharePlugin::<ProjectileSettings>::default())
+        .add_plugins(SharePlugin::<Gravity>::default())
+        .add_plugins(projectile_presets())
+        .add_plugins(ComparisonPlugin::<ProjectileSettings>::default())
+        .insert_resource(Comparison::new(ProjectileSettings {
+            // Moon gravity on the right by default
+            gravity_scale: 1.62 / constants::GRAVITY,
+            ..default()
+        }))
+        .add_plugins(PhysicsRatePlugin)
+        .add_plugins(PhysicsPlugin::default().with_collisions())
+        // The wind drawn as arrows of the air's velocity
+        .init_resource::<FieldArrows>()
+        .add_systems(PostUpdate, draw_wind.run_if(resource_exists::<GizmoConfigStore>))
+        .add_plugins(WorldScalePlugin)
+        .insert_resource(WorldScale::pixels_per_meter(PIXELS_PER_METER))
+        // Long shots leave the window; pan and zoom to follow them, F to fit
+        .add_plugins(CameraControllerPlugin)
+        // Grab the projectile mid-flight and pull it somewhere else
+        .add_plugins(PickDragPlugin)
+        .add_plugins(InterpolationPlugin)
+        .add_plugins(TrailPlugin)
+        .add_plugins(EnergyWatchdogPlugin)
+        .insert_resource(EnergyWatchdog::energy_only())
+        .add_plugins(TutorialPlugin)
+        .insert_resource(projectile_tutorial())
+        .add_plugins(QuizPlugin)
+        .insert_resource(projectile_quiz())
+        .add_plugins(SimReportPlugin::<ProjectileSettings>::new("projectile_test"))
+        .add_plugins(ConsolePlugin)
+        .insert_resource(projectile_console())
+        .add_plugins(SimulationControlsPlugin)
+        .add_plugins(CapturePlugin)
+        .add_plugins(DataLoggerPlugin)
+        .add_plugins(PlotPanelPlugin)
         .add_systems(Startup, (setup, setup_projectile).chain())
         .add_systems(
             Update,
-            (despawn_trajectory_markers, update_launch)
+            (sync_comparison_projectile, release_trajectory_markers, update_launch)
                 .chain()
-                .run_if(resource_changed::<ProjectileSettings>)
-        )
-        .add_systems(
-            FixedUpdate,
-            (apply_gravity, apply_velocity).chain()
+                .run_if(
+                    resource_changed::<ProjectileSettings>
+                        .or(resource_changed::<Comparison<ProjectileSettings>>)
+                        .or(resource_changed::<Gravity>)
+                        .or(resource_changed::<PhysicsRate>)
+                        .or(resource_changed::<Integrator>)
+                )
         )
-        .add_systems(Update, check_for_collisions)
-        .run();
+        .add_systems(PhysicsStep, apply_magnus.in_set(PhysicsSet::Forces))
+        .add_systems(PhysicsStep, integrate_projectile.in_set(PhysicsSet::Integrate).after(apply_forces))
+        .add_systems(FixedUpdate, (measure_energy, count_bounces).after(run_physics_step).run_if(physics_stepped))
+        .add_systems(Update, (launch_from_console, reset_from_controls));
 }
 
-fn setup(commands: Commands) {
-    spawn_camera(commands);
+fn setup(mut commands: Commands) {
+    commands.spawn((Camera2d, CameraController::default()));
+}
+
+fn projectile_bundle(
+    side: ComparisonSide,
+    scale: &WorldScale,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+) -> impl Bundle {
+    let color = match side {
+        ComparisonSide::Left => Color::srgb(0.0, 1.0, 0.0),
+        ComparisonSide::Right => Color::srgb(1.0, 0.6, 0.0),
+    };
+    let radius = scale.pixels(PROJECTILE_DIAMETER) / 2.0;
+    let seam = (
+        Seam,
+        side,
+        Mesh2d(meshes.add(Rectangle::new(radius, radius * 0.3))),
+        MeshMaterial2d(materials.add(Color::BLACK)),
+        // From the center to one edge, so a half turn looks different
+        Transform::from_xyz(radius / 2.0, 0.0, 0.1),
+    );
+    (
+        Projectile,
+        side,
+        Mesh2d(meshes.add(Circle::new(radius))),
+        MeshMaterial2d(materials.add(color)),
+        Transform::from_translation(Vec3::ZERO),
+        ColliderShape::circle(radius),
+        // Held at the launch point until launched
+        NoGravity,
+        FitInView,
+        Trail::new(TRAIL_POINTS, TRAIL_SPACING).with_color(color),
+        Draggable::spring(40.0, 8.0),
+        children![seam],
+    )
 }
 
 fn setup_projectile(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    scale: Res<WorldScale>,
 ) {
     // Spawn projectile at the origin
-    commands.spawn((
-        Projectile,
-        Mesh2d(meshes.add(Circle::default())),
-        MeshMaterial2d(materials.add(Color::srgb(0.0, 1.0, 0.0))),
-        Transform::from_translation(Vec3::ZERO).with_scale(Vec3::splat(10.0)),
-    ));
+    commands.spawn(projectile_bundle(ComparisonSide::Left, &scale, &mut meshes, &mut materials));
 
-    // Spawn ground
+    // Spawn ground, with its top surface LAUNCH_HEIGHT below the origin
+    let thickness = scale.pixels(GROUND_THICKNESS);
     commands.spawn((
         Ground,
-        Mesh2d(meshes.add(Rectangle::new(1.0, 1.0))),
+        Mesh2d(meshes.add(Rectangle::new(GROUND_LENGTH, thickness))),
         MeshMaterial2d(materials.add(Color::srgb(0.0, 0.5, 0.5))),
-        Transform::from_translation(Vec3::new(0.0, -201.0, 0.0))
-            .with_scale(Vec3::new(10000.0, 10.0, 1.0)),
+        Transform::from_translation(Vec3::new(0.0, -LAUNCH_HEIGHT - thickness / 2.0, 0.0)),
+        ColliderShape::aabb(Vec2::new(GROUND_LENGTH, thickness) / 2.0),
     ));
 }
 
-fn apply_gravity(
-    mut query: Query<(&mut Velocity, &Launched), With<Projectile>>,
+/// Arrows of the wind's velocity over the view while it blows
+fn draw_wind(
+    mut gizmos: Gizmos,
+    arrows: Res<FieldArrows>,
     settings: Res<ProjectileSettings>,
-    time: Res<Time>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
 ) {
-    for (mut velocity, launched) in &mut query {
-        // Only apply gravity when launched
-        if launched.0 {
-            velocity.0.y += settings.gravitational_constant * time.delta_secs();
+    let wind = settings.wind_velocity();
+    if !arrows.visible || wind == Vec2::ZERO {
+        return;
+    }
+    for (camera, camera_transform) in cameras.iter().filter(|(camera, _)| camera.is_active) {
+        if let Some((area, meters_per_pixel)) = visible_area(camera, camera_transform) {
+            draw_vector_field(&mut gizmos, area, meters_per_pixel, &arrows, |_| wind);
         }
     }
 }
 
-fn apply_velocity(
-    mut query: Query<(&mut Transform, &Velocity), With<Projectile>>,
-    time: Res<Time>,
+/// Puts the projectile back on the launch point when the control bar's Reset is pressed
+fn reset_from_controls(
+    mut resets: MessageReader<ResetSimulation>,
+    mut settings: ResMut<ProjectileSettings>,
+    mut plots: ResMut<PlotPanel>,
 ) {
-    for (mut transform, velocity) in &mut query {
-        transform.translation.x += velocity.0.x * time.delta_secs();
-        transform.translation.y += velocity.0.y * time.delta_secs();
+    if resets.read().count() > 0 {
+        settings.launched = false;
+        plots.clear();
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
-enum Collision {
-    Left,
-    Right,
-    Top,
-    Bottom,
+/// Handles `launch` and `reset` from the console
+fn launch_from_console(mut console_commands: MessageReader<ConsoleCommand>, mut settings: ResMut<ProjectileSettings>) {
+    for command in console_commands.read() {
+        match command.name.as_str() {
+            "launch" => settings.launched = true,
+            "reset" => settings.launched = false,
+            _ => {}
+        }
+    }
 }
 
-fn check_for_collisions(
-    mut projectile_query: Query<(&mut Velocity, &Transform), With<Projectile>>,
-    collider_query: Query<&Transform, (With<Collider>, Without<Projectile>)>,
+/// Spawn or remove the second projectile when the comparison is toggled
+fn sync_comparison_projectile(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    comparison: Res<Comparison<ProjectileSettings>>,
+    scale: Res<WorldScale>,
+    projectiles: Query<(Entity, &ComparisonSide), With<Projectile>>,
 ) {
-    for (mut projectile_velocity, projectile_transform) in &mut projectile_query {
-        for collider_transform in &collider_query {
-            let projectile_center = projectile_transform.translation.truncate();
-            // Circle::default() has radius 0.5, so actual visual radius = 0.5 * scale
-            let projectile_radius = 0.5 * projectile_transform.scale.x;
-            let border_center = collider_transform.translation.truncate();
-            let border_half_size = collider_transform.scale.truncate() / 2.;
-            
-            let collision = projectile_collision(
-                BoundingCircle::new(projectile_center, projectile_radius),
-                Aabb2d::new(border_center, border_half_size),
-            );
-
-            if let Some(collision) = collision {
-                // Reflect the projectile's velocity when it collides
-                let mut reflect_x = false;
-                let mut reflect_y = false;
-
-                // Reflect only if the velocity is in the opposite direction of the collision
-                // This prevents the projectile from getting stuck inside the bar
-                match collision {
-                    Collision::Left => reflect_x = projectile_velocity.0.x > 0.0,
-                    Collision::Right => reflect_x = projectile_velocity.0.x < 0.0,
-                    Collision::Top => reflect_y = projectile_velocity.0.y < 0.0,
-                    Collision::Bottom => reflect_y = projectile_velocity.0.y > 0.0,
-                }
-
-                // Reflect velocity on the x-axis if we hit something on the x-axis
-                if reflect_x {
-                    projectile_velocity.0.x = -projectile_velocity.0.x;
-                }
-
-                // Reflect velocity on the y-axis if we hit something on the y-axis
-                if reflect_y {
-                    projectile_velocity.0.y = -projectile_velocity.0.y;
-                }
-            }
+    let right = projectiles
+        .iter()
+        .find(|(_, side)| **side == ComparisonSide::Right)
+        .map(|(entity, _)| entity);
+    match (comparison.enabled, right) {
+        (true, None) => {
+            commands.spawn(projectile_bundle(ComparisonSide::Right, &scale, &mut meshes, &mut materials));
         }
+        (false, Some(entity)) => commands.entity(entity).despawn(),
+        _ => {}
     }
 }
 
-// Returns `Some` if `projectile` collides with `bounding_box`.
-// The returned `Collision` is the side of `bounding_box` that `projectile` hit.
-fn projectile_collision(projectile: BoundingCircle, bounding_box: Aabb2d) -> Option<Collision> {
-    if !projectile.intersects(&bounding_box) {
-        return None;
+/// Adds the Magnus lift of each projectile's spin to its force
+fn apply_magnus(mut query: Query<(&Magnus, &Drag, &Velocity, &AngularVelocity, &mut Force), With<Projectile>>) {
+    for (magnus, drag, velocity, spin, mut force) in &mut query {
+        force.0 += magnus.force(spin.0, velocity.0 - drag.wind);
     }
+}
 
-    let closest = bounding_box.closest_point(projectile.center());
-    let offset = projectile.center() - closest;
-    let side = if offset.x.abs() > offset.y.abs() {
-        if offset.x < 0. {
-            Collision::Left
-        } else {
-            Collision::Right
-        }
-    } else if offset.y > 0. {
-        Collision::Top
-    } else {
-        Collision::Bottom
-    };
+/// Advances the projectile with the selected integrator, under the acceleration
+/// `apply_forces` found for this step with the drag and Magnus lift re-evaluated at
+/// every stage, the same forces [`predicted_trajectory`] steps
+#[allow(clippy::type_complexity)]
+fn integrate_projectile(
+    mut query: Query<
+        (&mut Transform, &mut Velocity, &Acceleration, &Mass, &Drag, &Magnus, &AngularVelocity),
+        With<Projectile>,
+    >,
+    integrator: Res<Integrator>,
+    time: Res<Time>,
+) {
+    for (mut transform, mut velocity, acceleration, mass, drag, magnus, spin) in &mut query {
+        let state = BodyState::new(transform.translation.truncate(), velocity.0);
+        let stage = stage_acceleration(acceleration.0, 1.0 / mass.0, state, |state| {
+            air_force(drag, magnus, spin.0, state.velocity)
+        });
+        let next = integrator.step(state, time.elapsed_secs(), time.delta_secs(), stage);
+        transform.translation = next.position.extend(transform.translation.z);
+        velocity.0 = next.velocity;
+    }
+}
 
-    Some(side)
+/// Mechanical energy per unit mass of the launched projectile, for the plots and, while
+/// nothing dissipates it, the drift watchdog
+#[allow(clippy::too_many_arguments)]
+fn measure_energy(
+    query: Query<(&Velocity, &Transform, &Launched, &ComparisonSide), With<Projectile>>,
+    settings: Res<ProjectileSettings>,
+    gravity: Res<Gravity>,
+    mut quantities: ResMut<ConservedQuantities>,
+    mut watchdog: ResMut<EnergyWatchdog>,
+    mut metrics: ResMut<ReportMetrics>,
+    mut logger: ResMut<DataLogger>,
+    mut plots: ResMut<PlotPanel>,
+    time: Res<PhysicsTime>,
+) {
+    // The watchdog follows the chapter's own settings, on the left
+    for (velocity, transform, launched, _) in query.iter().filter(|(.., side)| **side == ComparisonSide::Left) {
+        if launched.0 {
+            let kinetic = units::Velocity::<Vec2>::new(velocity.0).length_squared() * 0.5;
+            let potential: units::VelocitySquared = -units::Acceleration::new(settings.gravity(&gravity))
+                * units::Length::new(transform.translation.y);
+            let energy = (kinetic + potential).value;
+            if settings.conserves_energy() {
+                quantities.energy = energy;
+                quantities.momentum = velocity.0;
+            } else {
+                // Drag, wind, lift and lossy bounces change the energy by design; start
+                // from a fresh baseline if the air and bounces are made lossless again
+                watchdog.reset();
+            }
+            metrics.max("max_height", transform.translation.y as f64);
+            logger.log("x", transform.translation.x);
+            logger.log("height", transform.translation.y);
+            logger.log("speed", velocity.0.length());
+            logger.log("energy", energy);
+            let t = time.elapsed_secs();
+            plots.push("Height (m)", "height", t, transform.translation.y);
+            plots.push("Energy per kg (J/kg)", "total", t, energy);
+            plots.push("Phase space (height, vertical velocity)", "ball", transform.translation.y, velocity.0.y);
+        }
+    }
 }
 
-fn despawn_trajectory_markers(mut commands: Commands, query: Query<Entity, With<TrajectoryMarker>>) {
-    for trajectory_entity in query.iter() {
-        commands.entity(trajectory_entity).despawn();
+/// Counts the projectile's bounces off the ground for the run's report
+fn count_bounces(
+    mut collisions: MessageReader<CollisionEvent>,
+    projectiles: Query<(&ComparisonSide, &Mass), With<Projectile>>,
+    solver: Res<ContactSolver>,
+    mut metrics: ResMut<ReportMetrics>,
+) {
+    for collision in collisions.read() {
+        // Only the chapter's own projectile, on the left
+        let Some(mass) = [collision.a, collision.b].into_iter().find_map(|entity| {
+            projectiles
+                .get(entity)
+                .ok()
+                .filter(|(side, _)| **side == ComparisonSide::Left)
+                .map(|(_, mass)| mass.0)
+        }) else {
+            continue;
+        };
+        // Resting on the ground takes a small impulse every step too; a bounce changes the
+        // velocity by more than the solver's resting speed
+        if collision.impulse.length() > mass * solver.resting_speed {
+            let bounces = metrics.get("bounces").unwrap_or(0.0);
+            metrics.set("bounces", bounces + 1.0);
+        }
     }
 }
 
+fn release_trajectory_markers(mut commands: Commands, mut marker_pool: ResMut<EntityPool<TrajectoryMarker>>) {
+    marker_pool.release_all(&mut commands);
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn update_launch(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     settings: Res<ProjectileSettings>,
-    mut projectile_query: Query<(&mut Velocity, &mut Transform, &mut Launched), With<Projectile>>,
+    comparison: Res<Comparison<ProjectileSettings>>,
+    gravity: Res<Gravity>,
+    rate: Res<PhysicsRate>,
+    integrator: Res<Integrator>,
+    scale: Res<WorldScale>,
+    mut projectile_query: Query<
+        (
+            Entity,
+            &mut Velocity,
+            &mut Transform,
+            &mut Launched,
+            &mut PhysicsMaterial,
+            &mut Drag,
+            &mut Magnus,
+            &mut Rotation,
+            &mut AngularVelocity,
+            &mut GravityScale,
+            &mut Trail,
+            &ComparisonSide,
+        ),
+        With<Projectile>,
+    >,
+    mut watchdog: ResMut<EnergyWatchdog>,
+    mut marker_pool: ResMut<EntityPool<TrajectoryMarker>>,
 ) {
-    if let Ok((mut velocity, mut transform, mut launched)) = projectile_query.single_mut() {
-        if !settings.launched {
+    // Both projectiles launch and reset together, so they share one clock
+    let launch = settings.launched;
+    // One wind for both sides, calm while waiting or it would blow the projectile off
+    // the launch point
+    let wind = settings.wind_velocity();
+    for (
+        entity,
+        mut velocity,
+        mut transform,
+        mut launched,
+        mut material,
+        mut drag,
+        mut magnus,
+        mut rotation,
+        mut spin,
+        mut gravity_scale,
+        mut trail,
+        side,
+    ) in &mut projectile_query
+    {
+        let settings = comparison.settings(*side, &settings);
+        *material = PhysicsMaterial::new(settings.restitution, settings.friction);
+        *drag = settings.drag().with_wind(if launch { wind } else { Vec2::ZERO });
+        *magnus = settings.magnus();
+        gravity_scale.0 = settings.gravity_scale;
+        if !launch {
             // Reset to origin
             velocity.0 = Vec2::ZERO;
             transform.translation = Vec3::ZERO;
+            spin.0 = 0.0;
+            rotation.0 = 0.0;
+            transform.rotation = Quat::IDENTITY;
             launched.0 = false;
+            trail.clear();
+            commands.entity(entity).insert(NoGravity);
+            watchdog.reset();
             
             // Show trajectory preview when not launched
-            let current_trajectory = predicted_trajectory(&settings, 10);
+            let current_trajectory = predicted_trajectory(settings, wind, &gravity, &integrator, &rate, 10);
             for position in current_trajectory {
-                commands.spawn((
-                    Mesh2d(meshes.add(Circle::default())),
-                    MeshMaterial2d(materials.add(Color::srgb(0.8, 0.7, 0.8))),
-                    Transform::from_translation(Vec3::new(position.x, position.y, 0.0)).with_scale(Vec3::splat(5.0)),
-                    TrajectoryMarker,
+                let marker = marker_pool.acquire(&mut commands, || {
+                    (
+                        Mesh2d(meshes.add(Circle::default())),
+                        MeshMaterial2d(materials.add(Color::srgb(0.8, 0.7, 0.8))),
+                        TrajectoryMarker,
+                    )
+                });
+                commands.entity(marker).insert((
+                    Transform::from_translation(Vec3::new(position.x, position.y, 0.0)).with_scale(Vec3::splat(scale.pixels(MARKER_DIAMETER))),
+                    *side,
                 ));
             }
         } else if !launched.0 {
             velocity.0 = settings.initial_velocity.0;
+            spin.0 = settings.spin;
             launched.0 = true;
+            commands.entity(entity).remove::<NoGravity>();
+            watchdog.reset();
         }
     }
 }
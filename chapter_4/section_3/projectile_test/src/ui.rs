@@ -1,5 +1,14 @@
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts, EguiPlugin, EguiPrimaryContextPass};
+use rhysics_common::collision::PhysicsMaterial;
+use rhysics_common::comparison::Comparison;
+use rhysics_common::equation::Equation;
+use rhysics_common::forces::Gravity;
+use rhysics_common::integrator::Integrator;
+use rhysics_common::presets::{preset_picker, PresetLibrary};
+use rhysics_common::share::{share_button, SimulationSeed};
+use rhysics_common::timestep::{physics_rate_slider, PhysicsRate};
+use rhysics_common::tutorial::Tutorial;
 use crate::ProjectileSettings;
 
 pub struct UiPlugin;
@@ -12,9 +21,17 @@ impl Plugin for UiPlugin {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn ui_example_system(
     mut contexts: EguiContexts,
-    mut settings: ResMut<ProjectileSettings>
+    mut settings: ResMut<ProjectileSettings>,
+    mut gravity: ResMut<Gravity>,
+    tutorial: Res<Tutorial>,
+    seed: Res<SimulationSeed>,
+    mut comparison: ResMut<Comparison<ProjectileSettings>>,
+    mut rate: ResMut<PhysicsRate>,
+    mut integrator: ResMut<Integrator>,
+    mut presets: ResMut<PresetLibrary<ProjectileSettings>>,
 ) -> Result {
     egui::Window::new("Projectile Options").show(contexts.ctx_mut()?, |ui| {
         ui.heading("Projectile Configuration");
@@ -22,7 +39,8 @@ fn ui_example_system(
         ui.separator();
         
         // Initial velocity components
-        ui.label("Initial Velocity:");
+        let response = ui.label("Initial Velocity:");
+        tutorial.highlight_control(ui, "Initial Velocity", &response);
         ui.horizontal(|ui| {
             ui.label("X: ");
             ui.add(egui::Slider::new(&mut settings.initial_velocity.0.x, -100.0..=100.0)
@@ -36,18 +54,77 @@ fn ui_example_system(
         
         ui.separator();
         
-        // Gravitational constant
+        // Shared gravity; only written back when moved, like the rate below
+        let mut g = gravity.0.y;
         ui.horizontal(|ui| {
             ui.label("Gravity: ");
-            ui.add(egui::Slider::new(&mut settings.gravitational_constant, -300.0..=0.0)
+            let response = ui.add(egui::Slider::new(&mut g, -300.0..=0.0)
                 .text("m/s²"));
+            tutorial.highlight_control(ui, "Gravity", &response);
+        });
+        if g != gravity.0.y {
+            gravity.0.y = g;
+        }
+
+        // Air resistance, ½ρC_dA|v|v against the motion; with air the path falls short of
+        // the parabola and comes down steeper than it went up
+        ui.add(egui::Slider::new(&mut settings.air_density, 0.0..=2.0).text("Air density (kg/m³)"));
+        ui.add(egui::Slider::new(&mut settings.drag_coefficient, 0.0..=1.5).text("Drag coefficient"));
+        ui.add(egui::Slider::new(&mut settings.area, 0.0..=0.2).text("Area (m²)"));
+        // Drag acts on the velocity relative to the air: a tailwind carries the projectile
+        // further, a headwind brings it back, an updraft holds it up
+        ui.add(egui::Slider::new(&mut settings.wind, 0.0..=30.0).text("Wind speed (m/s)"));
+        ui.add(egui::Slider::new(&mut settings.wind_direction, 0.0..=360.0).text("Wind direction (°)"));
+        if settings.wind > 0.0 && settings.air_density == 0.0 {
+            ui.small("No air to carry the wind: raise the air density");
+        }
+        // Magnus lift, across the motion: backspin floats the shot, topspin dips it
+        ui.add(egui::Slider::new(&mut settings.spin, -200.0..=200.0).text("Spin (rad/s)"));
+        if settings.spin != 0.0 && settings.air_density == 0.0 {
+            ui.small("Spin only curves the path through air: raise the air density");
+        }
+
+        // Ground contact; below 1 restitution the bounces lose energy, which the watchdog reports
+        ui.add(egui::Slider::new(&mut settings.restitution, 0.0..=1.0).text("Restitution"));
+        ui.add(egui::Slider::new(&mut settings.friction, 0.0..=1.0).text("Friction"));
+        ui.horizontal(|ui| {
+            ui.label("Material:");
+            for (name, material) in PhysicsMaterial::PRESETS {
+                if ui.button(name).clicked() {
+                    settings.restitution = material.restitution;
+                    settings.friction = material.friction;
+                }
+            }
         });
         
         ui.separator();
 
+        // Physics step rate; only write it back when moved, so the preview isn't rebuilt every frame
+        let mut new_rate = *rate;
+        if physics_rate_slider(ui, &mut new_rate).changed() {
+            *rate = new_rate;
+        }
+
+        // Integration scheme; Euler visibly gains energy, Verlet and RK4 follow the parabola
+        let mut selected = *integrator;
+        egui::ComboBox::from_label("Integrator")
+            .selected_text(selected.name())
+            .show_ui(ui, |ui| {
+                for option in Integrator::ALL {
+                    ui.selectable_value(&mut selected, option, option.name());
+                }
+            });
+        if selected != *integrator {
+            *integrator = selected;
+        }
+
+        ui.separator();
+
         // launch button
         ui.horizontal(|ui| {
-            if ui.button("Launch").clicked() {
+            let response = ui.button("Launch");
+            tutorial.highlight_control(ui, "Launch", &response);
+            if response.clicked() {
                 settings.launched = true;
             }
         });
@@ -59,12 +136,57 @@ fn ui_example_system(
             }
         });
 
+        // Named settings, the chapter's and the reader's own
+        preset_picker(ui, &mut *presets, &mut *settings);
+
+        // Link that reopens the simulation with these settings
+        share_button(ui, &(settings.clone(), *gravity), &seed);
+
+        // Second projectile with its own settings, in a split screen
+        ui.collapsing("Compare", |ui| {
+            ui.checkbox(&mut comparison.enabled, "Side-by-side comparison");
+            ui.add_enabled_ui(comparison.enabled, |ui| {
+                ui.label("Right-hand projectile:");
+                let right = &mut comparison.right;
+                ui.add(egui::Slider::new(&mut right.initial_velocity.0.x, -100.0..=100.0).text("vx (m/s)"));
+                ui.add(egui::Slider::new(&mut right.initial_velocity.0.y, -100.0..=100.0).text("vy (m/s)"));
+                ui.add(egui::Slider::new(&mut right.gravity_scale, 0.0..=3.0).text("× gravity"));
+                ui.add(egui::Slider::new(&mut right.air_density, 0.0..=2.0).text("air density (kg/m³)"));
+                ui.add(egui::Slider::new(&mut right.spin, -200.0..=200.0).text("spin (rad/s)"));
+            });
+        });
+
         // Display current values
         ui.collapsing("Current Values", |ui| {
             ui.label(format!("Velocity: ({:.2}, {:.2}) m/s", 
                 settings.initial_velocity.0.x, 
                 settings.initial_velocity.0.y));
-            ui.label(format!("Gravity: {:.2} m/s²", settings.gravitational_constant));
+            ui.label(format!("Gravity: {:.2} m/s²", gravity.0.y));
+            ui.label(format!("Drag constant ½ρC_dA: {:.4} kg/m", settings.drag().quadratic));
+            ui.label(format!("Magnus constant ½ρAr: {:.5} kg", settings.magnus().0));
+        });
+
+        // Governing equations, with the current values substituted
+        ui.collapsing("Equations", |ui| {
+            let vx = settings.initial_velocity.0.x;
+            let vy = settings.initial_velocity.0.y;
+            let g = gravity.0.y;
+            Equation::new("x(t) = v_{0x} t").show_with_values(ui, &[("v_{0x}", vx)]);
+            ui.separator();
+            Equation::new(r"y(t) = v_{0y} t + \frac{1}{2} g t^2")
+                .show_with_values(ui, &[("v_{0y}", vy), ("g", g)]);
+            // In air there is no closed form; the markers come from stepping this instead
+            ui.separator();
+            Equation::new(r"m \frac{dv}{dt} = m g - \frac{1}{2} \rho C_d A |v| v + \frac{1}{2} \rho A r \omega \times v")
+                .show_with_values(
+                    ui,
+                    &[
+                        (r"\rho", settings.air_density),
+                        ("C_d", settings.drag_coefficient),
+                        ("A", settings.area),
+                        (r"\omega", settings.spin),
+                    ],
+                );
         });
     });
     Ok(())
@@ -0,0 +1,50 @@
+//! The chapter flown without a window, as `--headless <steps>` does
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use rhysics_common::datalog::DataLogger;
+use rhysics_common::report::ReportMetrics;
+use rhysics_common::timestep::PhysicsTime;
+use rhysics_common::watchdog::EnergyWatchdog;
+
+#[test]
+fn launched_projectile_rises_and_comes_down() {
+    let steps = 600;
+    let app = projectile_test::run_headless(steps);
+    let world = app.world();
+
+    // One physics tick per update
+    let tick = world.resource::<Time<Fixed>>().timestep();
+    let elapsed = world.resource::<PhysicsTime>().elapsed();
+    assert!(
+        elapsed >= tick * (steps - 1) && elapsed <= tick * steps,
+        "{elapsed:?} simulated in {steps} steps of {tick:?}"
+    );
+
+    let heights: Vec<f64> = world.resource::<DataLogger>().series("height").map(|(_, height)| height).collect();
+    assert!(heights.len() > 1, "only {} heights logged", heights.len());
+    let max_height = world.resource::<ReportMetrics>().get("max_height").expect("no max_height reported");
+    assert!(max_height > heights[0] + 1.0, "peaked at {max_height} from {}", heights[0]);
+    assert!(
+        heights.last().unwrap() + 1.0 < max_height,
+        "still at {} after {steps} steps, peak {max_height}",
+        heights.last().unwrap()
+    );
+}
+
+#[test]
+fn drag_does_not_trip_the_energy_watchdog() {
+    let mut app = projectile_test::run_headless(0);
+    // Air takes energy away on purpose, well past the watchdog's tolerance
+    let mut settings = app.world_mut().resource_mut::<projectile_test::ProjectileSettings>();
+    settings.air_density = 1.225;
+    settings.drag_coefficient = 0.47;
+    // One physics tick per update, as `run_headless` flies it
+    let tick = app.world().resource::<Time<Fixed>>().timestep();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(tick));
+    for _ in 0..600 {
+        app.update();
+    }
+    let energy: Vec<f64> = app.world().resource::<DataLogger>().series("energy").map(|(_, energy)| energy).collect();
+    assert!(energy.last().unwrap() < &(energy[0] * 0.9), "energy went from {} to {:?}", energy[0], energy.last());
+    assert!(!app.world().resource::<EnergyWatchdog>().is_drifting());
+}
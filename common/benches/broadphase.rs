@@ -0,0 +1,116 @@
+//! Spatial hash vs quadtree neighbor queries
+//!
+//! Run with `cargo bench -p rhysics-common --bench broadphase`. Each case builds the
+//! structure from scratch and then asks for every point's neighbors, as a frame of
+//! boids or collision culling would.
+//!
+//! With evenly spread points and one query radius, the hash usually wins: a cell
+//! lookup is cheaper than walking the tree. With clustered points (a galaxy's dense
+//! core and sparse halo) or query sizes far from the cell size, the quadtree wins,
+//! because it only subdivides where the points are.
+use bevy::math::bounding::Aabb2d;
+use bevy::prelude::*;
+use rhysics_common::quadtree::Quadtree;
+use rhysics_common::spatial::SpatialGrid;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const PARTICLES: usize = 5000;
+const REPEATS: u32 = 5;
+const EXTENT: f32 = 1000.0;
+
+/// Deterministic pseudo-random numbers in -1..1, so runs are comparable
+fn lcg(seed: u64) -> impl FnMut() -> f32 {
+    let mut state = seed;
+    move || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((state >> 40) as f32 / (1u64 << 24) as f32 - 0.5) * 2.0
+    }
+}
+
+fn uniform_points(count: usize, seed: u64) -> Vec<Vec2> {
+    let mut next = lcg(seed);
+    (0..count).map(|_| Vec2::new(next(), next()) * EXTENT).collect()
+}
+
+/// A few tight clusters in an otherwise empty square
+fn clustered_points(count: usize, seed: u64) -> Vec<Vec2> {
+    let mut next = lcg(seed);
+    let centers: Vec<Vec2> = (0..4).map(|_| Vec2::new(next(), next()) * EXTENT * 0.8).collect();
+    (0..count)
+        .map(|i| {
+            // Squaring the offset piles most points near the cluster center
+            let offset = Vec2::new(next(), next());
+            centers[i % centers.len()] + offset * offset.length() * EXTENT * 0.05
+        })
+        .collect()
+}
+
+/// Best of several runs
+fn time(mut f: impl FnMut()) -> Duration {
+    (0..REPEATS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn grid_neighbors(points: &[Vec2], radius: f32, cell_size: f32) -> usize {
+    let mut grid = SpatialGrid::new(cell_size);
+    grid.rebuild(points.iter().map(|&point| (Entity::PLACEHOLDER, point)));
+    points
+        .iter()
+        .map(|&point| grid.query_radius_indices(point, radius).count())
+        .sum()
+}
+
+fn quadtree_neighbors(points: &[Vec2], radius: f32) -> usize {
+    let mut tree = Quadtree::new(Aabb2d::new(Vec2::ZERO, Vec2::splat(EXTENT)));
+    for (index, &point) in points.iter().enumerate() {
+        tree.insert(index, Aabb2d::new(point, Vec2::ZERO));
+    }
+    let radius2 = radius * radius;
+    points
+        .iter()
+        .map(|&point| {
+            let mut count = 0;
+            tree.query_with(Aabb2d::new(point, Vec2::splat(radius)), |_, aabb| {
+                if aabb.min.distance_squared(point) <= radius2 {
+                    count += 1;
+                }
+            });
+            count
+        })
+        .sum()
+}
+
+fn report(name: &str, grid: Duration, quadtree: Duration) {
+    println!(
+        "{name:<28} hash {:>9.3} ms   quadtree {:>9.3} ms   {}",
+        grid.as_secs_f64() * 1000.0,
+        quadtree.as_secs_f64() * 1000.0,
+        if grid <= quadtree { "hash wins" } else { "quadtree wins" }
+    );
+}
+
+fn main() {
+    println!("{PARTICLES} points, best of {REPEATS} runs");
+    let cases = [
+        ("uniform, radius = cell", uniform_points(PARTICLES, 1), 25.0, 25.0),
+        ("uniform, radius = 8 cells", uniform_points(PARTICLES, 1), 200.0, 25.0),
+        ("clustered, radius = cell", clustered_points(PARTICLES, 2), 25.0, 25.0),
+        ("clustered, small radius", clustered_points(PARTICLES, 2), 2.0, 25.0),
+    ];
+    for (name, points, radius, cell_size) in &cases {
+        let grid = time(|| {
+            black_box(grid_neighbors(points, *radius, *cell_size));
+        });
+        let quadtree = time(|| {
+            black_box(quadtree_neighbors(points, *radius));
+        });
+        report(name, grid, quadtree);
+    }
+}
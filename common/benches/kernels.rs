@@ -0,0 +1,99 @@
+//! Scalar vs SIMD timings for the shared kernels
+//!
+//! Run with `cargo bench -p rhysics-common --features simd` to see the speedup,
+//! or without the feature for the scalar baseline only.
+use bevy::prelude::*;
+use rhysics_common::kernels::{scalar, PointSet};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const PARTICLES: usize = 2000;
+const REPEATS: u32 = 5;
+
+/// Deterministic pseudo-random points in a square, so runs are comparable
+fn random_points(count: usize, extent: f32, seed: u64) -> PointSet {
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((state >> 40) as f32 / (1u64 << 24) as f32 - 0.5) * 2.0 * extent
+    };
+    (0..count).map(|_| Vec2::new(next(), next())).collect()
+}
+
+/// Best of several runs
+fn time(mut f: impl FnMut()) -> Duration {
+    (0..REPEATS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn report(name: &str, scalar: Duration, simd: Option<Duration>) {
+    match simd {
+        Some(simd) => println!(
+            "{name:<16} scalar {:>9.3} ms   simd {:>9.3} ms   speedup {:.2}x",
+            scalar.as_secs_f64() * 1000.0,
+            simd.as_secs_f64() * 1000.0,
+            scalar.as_secs_f64() / simd.as_secs_f64()
+        ),
+        None => println!(
+            "{name:<16} scalar {:>9.3} ms   (build with --features simd to compare)",
+            scalar.as_secs_f64() * 1000.0
+        ),
+    }
+}
+
+fn main() {
+    let positions = random_points(PARTICLES, 400.0, 1);
+    let velocities = random_points(PARTICLES, 200.0, 2);
+    let masses = vec![1.0; PARTICLES];
+    println!("{PARTICLES} particles, best of {REPEATS} runs");
+
+    let scalar_steering = time(|| {
+        for position in positions.iter() {
+            black_box(scalar::steering_sums(position, &positions, &velocities, 50.0));
+        }
+    });
+    #[cfg(feature = "simd")]
+    let simd_steering = Some(time(|| {
+        for position in positions.iter() {
+            black_box(rhysics_common::kernels::simd::steering_sums(
+                position,
+                &positions,
+                &velocities,
+                50.0,
+            ));
+        }
+    }));
+    #[cfg(not(feature = "simd"))]
+    let simd_steering = None;
+    report("steering_sums", scalar_steering, simd_steering);
+
+    let scalar_gravity = time(|| {
+        black_box(scalar::pairwise_gravity(&positions, &masses, 1.0, 1.0));
+    });
+    #[cfg(feature = "simd")]
+    let simd_gravity = Some(time(|| {
+        black_box(rhysics_common::kernels::simd::pairwise_gravity(
+            &positions, &masses, 1.0, 1.0,
+        ));
+    }));
+    #[cfg(not(feature = "simd"))]
+    let simd_gravity = None;
+    report("pairwise_gravity", scalar_gravity, simd_gravity);
+
+    let scalar_density = time(|| {
+        black_box(scalar::sph_density(&positions, 1.0, 30.0));
+    });
+    #[cfg(feature = "simd")]
+    let simd_density = Some(time(|| {
+        black_box(rhysics_common::kernels::simd::sph_density(&positions, 1.0, 30.0));
+    }));
+    #[cfg(not(feature = "simd"))]
+    let simd_density = None;
+    report("sph_density", scalar_density, simd_density);
+}
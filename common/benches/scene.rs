@@ -0,0 +1,207 @@
+//! Integrators and neighbor search on the same scenes, for wall time and energy drift
+//!
+//! Run with `cargo bench -p rhysics-common --bench scene`. The first table flies a
+//! swarm of eccentric Kepler orbits with each `Integrator` and reports how long it took
+//! and how far the orbits' energy wandered: explicit Euler spirals out, the symplectic
+//! schemes hold on, and RK4 is the most accurate per step but costs four force
+//! evaluations. The second steps a trapped gas of soft discs, finding touching pairs
+//! with the spatial hash, the quadtree or by checking every pair. All three find the
+//! same pairs, so their drift matches and only the time differs.
+//!
+//! This is what the defaults rest on: semi-implicit Euler for the shared physics,
+//! Verlet or RK4 where a chapter is about accuracy, and the spatial hash for evenly
+//! spread particles.
+use bevy::math::bounding::Aabb2d;
+use bevy::prelude::*;
+use rhysics_common::integrator::{BodyState, Integrator};
+use rhysics_common::quadtree::Quadtree;
+use rhysics_common::spatial::SpatialGrid;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const REPEATS: u32 = 3;
+
+const ORBITS: usize = 500;
+/// Gravitational parameter of the central mass; with a semi-major axis of 1 the period is 2π
+const GM: f32 = 1.0;
+const ECCENTRICITY: f32 = 0.5;
+const ORBIT_DT: f32 = 0.01;
+const ORBIT_PERIODS: f32 = 10.0;
+
+const GAS: usize = 2000;
+const GAS_EXTENT: f32 = 300.0;
+const DISC_DIAMETER: f32 = 10.0;
+const DISC_STIFFNESS: f32 = 100.0;
+/// Squared frequency of the trap holding the gas together
+const TRAP: f32 = 0.01;
+const GAS_DT: f32 = 0.01;
+const GAS_STEPS: usize = 300;
+
+/// Deterministic pseudo-random numbers in -1..1, so runs are comparable
+fn lcg(seed: u64) -> impl FnMut() -> f32 {
+    let mut state = seed;
+    move || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((state >> 40) as f32 / (1u64 << 24) as f32 - 0.5) * 2.0
+    }
+}
+
+/// Best of several runs, with the result of the last
+fn time<R>(mut f: impl FnMut() -> R) -> (Duration, R) {
+    let mut best = Duration::MAX;
+    let mut result = None;
+    for _ in 0..REPEATS {
+        let start = Instant::now();
+        result = Some(black_box(f()));
+        best = best.min(start.elapsed());
+    }
+    (best, result.expect("at least one repeat"))
+}
+
+fn report(name: &str, elapsed: Duration, drift: f64) {
+    println!("{name:<22} {:>9.3} ms   energy drift {:>10.3e}", elapsed.as_secs_f64() * 1000.0, drift);
+}
+
+/// Orbits starting at periapsis, each turned by a random angle
+fn orbits() -> Vec<BodyState> {
+    let mut next = lcg(1);
+    let periapsis = 1.0 - ECCENTRICITY;
+    let speed = (GM * (1.0 + ECCENTRICITY) / periapsis).sqrt();
+    (0..ORBITS)
+        .map(|_| {
+            let turn = Vec2::from_angle(next() * std::f32::consts::PI);
+            BodyState::new(turn.rotate(Vec2::X * periapsis), turn.rotate(Vec2::Y * speed))
+        })
+        .collect()
+}
+
+fn orbit_energy(state: &BodyState) -> f64 {
+    0.5 * state.velocity.length_squared() as f64 - GM as f64 / state.position.length() as f64
+}
+
+/// Mean relative change in orbital energy after flying every orbit with `integrator`
+fn fly_orbits(integrator: Integrator) -> f64 {
+    let steps = (ORBIT_PERIODS * std::f32::consts::TAU / ORBIT_DT) as usize;
+    let gravity = |_: f32, state: BodyState| -GM * state.position / state.position.length().powi(3);
+    let mut total = 0.0;
+    for start in orbits() {
+        let mut state = start;
+        for step in 0..steps {
+            state = integrator.step(state, step as f32 * ORBIT_DT, ORBIT_DT, gravity);
+        }
+        let initial = orbit_energy(&start);
+        total += ((orbit_energy(&state) - initial) / initial).abs();
+    }
+    total / ORBITS as f64
+}
+
+#[derive(Clone, Copy)]
+enum NeighborSearch {
+    SpatialHash,
+    Quadtree,
+    BruteForce,
+}
+
+impl NeighborSearch {
+    fn name(&self) -> &'static str {
+        match self {
+            NeighborSearch::SpatialHash => "spatial hash",
+            NeighborSearch::Quadtree => "quadtree",
+            NeighborSearch::BruteForce => "brute force",
+        }
+    }
+
+    /// Every pair `(i, j)`, `i < j`, closer than a disc diameter
+    fn touching_pairs(&self, positions: &[Vec2]) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        let radius2 = DISC_DIAMETER * DISC_DIAMETER;
+        match self {
+            NeighborSearch::SpatialHash => {
+                let mut grid = SpatialGrid::new(DISC_DIAMETER);
+                grid.rebuild(positions.iter().map(|&position| (Entity::PLACEHOLDER, position)));
+                for (i, &position) in positions.iter().enumerate() {
+                    pairs.extend(grid.query_radius_indices(position, DISC_DIAMETER).filter(|&j| j > i).map(|j| (i, j)));
+                }
+            }
+            NeighborSearch::Quadtree => {
+                let mut tree = Quadtree::new(Aabb2d::new(Vec2::ZERO, Vec2::splat(GAS_EXTENT * 2.0)));
+                for (index, &position) in positions.iter().enumerate() {
+                    tree.insert(index, Aabb2d::new(position, Vec2::ZERO));
+                }
+                for (i, &position) in positions.iter().enumerate() {
+                    tree.query_with(Aabb2d::new(position, Vec2::splat(DISC_DIAMETER)), |j, aabb| {
+                        if j > i && aabb.min.distance_squared(position) <= radius2 {
+                            pairs.push((i, j));
+                        }
+                    });
+                }
+            }
+            NeighborSearch::BruteForce => {
+                for i in 0..positions.len() {
+                    for j in i + 1..positions.len() {
+                        if positions[i].distance_squared(positions[j]) <= radius2 {
+                            pairs.push((i, j));
+                        }
+                    }
+                }
+            }
+        }
+        pairs
+    }
+}
+
+/// Overlap of two discs `distance` apart, zero once they part
+fn overlap(distance: f32) -> f32 {
+    (DISC_DIAMETER - distance).max(0.0)
+}
+
+fn gas_energy(positions: &[Vec2], velocities: &[Vec2], pairs: &[(usize, usize)]) -> f64 {
+    let bodies: f64 = positions
+        .iter()
+        .zip(velocities)
+        .map(|(position, velocity)| 0.5 * (velocity.length_squared() + TRAP * position.length_squared()) as f64)
+        .sum();
+    let contacts: f64 = pairs
+        .iter()
+        .map(|&(i, j)| 0.5 * (DISC_STIFFNESS * overlap(positions[i].distance(positions[j])).powi(2)) as f64)
+        .sum();
+    bodies + contacts
+}
+
+/// Relative change in the gas's energy after stepping it with semi-implicit Euler
+fn step_gas(search: NeighborSearch) -> f64 {
+    let mut next = lcg(2);
+    let mut positions: Vec<Vec2> = (0..GAS).map(|_| Vec2::new(next(), next()) * GAS_EXTENT).collect();
+    let mut velocities: Vec<Vec2> = (0..GAS).map(|_| Vec2::new(next(), next()) * 5.0).collect();
+    let mut pairs = search.touching_pairs(&positions);
+    let initial = gas_energy(&positions, &velocities, &pairs);
+    for _ in 0..GAS_STEPS {
+        let mut accelerations: Vec<Vec2> = positions.iter().map(|&position| -TRAP * position).collect();
+        for &(i, j) in &pairs {
+            let offset = positions[i] - positions[j];
+            let push = offset.normalize_or_zero() * DISC_STIFFNESS * overlap(offset.length());
+            accelerations[i] += push;
+            accelerations[j] -= push;
+        }
+        for ((position, velocity), acceleration) in positions.iter_mut().zip(&mut velocities).zip(accelerations) {
+            *velocity += acceleration * GAS_DT;
+            *position += *velocity * GAS_DT;
+        }
+        pairs = search.touching_pairs(&positions);
+    }
+    ((gas_energy(&positions, &velocities, &pairs) - initial) / initial).abs()
+}
+
+fn main() {
+    println!("{ORBITS} orbits (e = {ECCENTRICITY}) for {ORBIT_PERIODS} periods at dt = {ORBIT_DT}, best of {REPEATS} runs");
+    for integrator in Integrator::ALL {
+        let (elapsed, drift) = time(|| fly_orbits(integrator));
+        report(integrator.name(), elapsed, drift);
+    }
+    println!();
+    println!("{GAS} soft discs for {GAS_STEPS} steps at dt = {GAS_DT}, best of {REPEATS} runs");
+    for search in [NeighborSearch::SpatialHash, NeighborSearch::Quadtree, NeighborSearch::BruteForce] {
+        let (elapsed, drift) = time(|| step_gas(search));
+        report(search.name(), elapsed, drift);
+    }
+}
@@ -0,0 +1,309 @@
+//! Closed-form solutions for checking the numerical simulations
+//!
+//! Each solution gives the exact [`BodyState`] at any time, so chapters can draw the
+//! expected path next to the simulated one, and verification runs can measure how
+//! far an integrator has drifted with [`AnalyticSolution::max_position_error`].
+use bevy::prelude::*;
+use std::f32::consts::TAU;
+
+use crate::integrator::BodyState;
+
+/// A motion with a known state at every time
+pub trait AnalyticSolution {
+    fn state(&self, t: f32) -> BodyState;
+
+    fn position(&self, t: f32) -> Vec2 {
+        self.state(t).position
+    }
+
+    /// Largest distance between simulated `(t, state)` samples and the exact solution
+    fn max_position_error(&self, samples: &[(f32, BodyState)]) -> f32 {
+        samples
+            .iter()
+            .map(|(t, state)| state.position.distance(self.position(*t)))
+            .fold(0.0, f32::max)
+    }
+}
+
+/// Projectile in a vacuum, launched from `origin`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Projectile {
+    pub origin: Vec2,
+    pub velocity: Vec2,
+    /// Vertical acceleration, negative for downward gravity
+    pub gravity: f32,
+}
+
+impl Projectile {
+    pub fn new(velocity: Vec2, gravity: f32) -> Self {
+        Self {
+            origin: Vec2::ZERO,
+            velocity,
+            gravity,
+        }
+    }
+
+    /// Time until it comes back to launch height, if it ever does
+    pub fn flight_time(&self) -> Option<f32> {
+        (self.velocity.y > 0.0 && self.gravity < 0.0).then(|| -2.0 * self.velocity.y / self.gravity)
+    }
+
+    /// Horizontal distance covered by the time it is back at launch height
+    pub fn range(&self) -> Option<f32> {
+        Some(self.velocity.x * self.flight_time()?)
+    }
+
+    /// Height of the peak above the launch point
+    pub fn max_height(&self) -> Option<f32> {
+        (self.velocity.y > 0.0 && self.gravity < 0.0)
+            .then(|| self.velocity.y * self.velocity.y / (-2.0 * self.gravity))
+    }
+}
+
+impl AnalyticSolution for Projectile {
+    fn state(&self, t: f32) -> BodyState {
+        let acceleration = Vec2::new(0.0, self.gravity);
+        BodyState::new(
+            self.origin + self.velocity * t + 0.5 * acceleration * t * t,
+            self.velocity + acceleration * t,
+        )
+    }
+}
+
+/// Simple harmonic motion along `axis` about the origin, x(t) = A cos(ωt + φ)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HarmonicOscillator {
+    pub amplitude: f32,
+    /// Angular frequency ω = √(k/m)
+    pub omega: f32,
+    pub phase: f32,
+    pub axis: Vec2,
+}
+
+impl HarmonicOscillator {
+    /// Oscillation along x started from displacement `x0` with velocity `v0`
+    pub fn from_initial(x0: f32, v0: f32, omega: f32) -> Self {
+        Self {
+            amplitude: (x0 * x0 + (v0 / omega).powi(2)).sqrt(),
+            omega,
+            phase: (-v0 / omega).atan2(x0),
+            axis: Vec2::X,
+        }
+    }
+
+    /// Spring constant `k` and mass `m`, started from displacement `x0` at rest
+    pub fn spring(k: f32, mass: f32, x0: f32) -> Self {
+        Self::from_initial(x0, 0.0, (k / mass).sqrt())
+    }
+
+    pub fn period(&self) -> f32 {
+        TAU / self.omega
+    }
+}
+
+impl AnalyticSolution for HarmonicOscillator {
+    fn state(&self, t: f32) -> BodyState {
+        let angle = self.omega * t + self.phase;
+        BodyState::new(
+            self.axis * self.amplitude * angle.cos(),
+            self.axis * -self.amplitude * self.omega * angle.sin(),
+        )
+    }
+}
+
+/// Damped oscillator x'' + 2γx' + ω₀²x = 0 along x, in all three damping regimes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DampedOscillator {
+    /// Undamped angular frequency ω₀
+    pub omega0: f32,
+    /// Damping rate γ = c / 2m
+    pub gamma: f32,
+    pub x0: f32,
+    pub v0: f32,
+}
+
+/// Which way a [`DampedOscillator`] returns to rest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Damping {
+    Under,
+    Critical,
+    Over,
+}
+
+impl DampedOscillator {
+    pub fn new(omega0: f32, gamma: f32, x0: f32, v0: f32) -> Self {
+        Self { omega0, gamma, x0, v0 }
+    }
+
+    /// Damping ratio ζ = γ / ω₀
+    pub fn damping_ratio(&self) -> f32 {
+        self.gamma / self.omega0
+    }
+
+    pub fn regime(&self) -> Damping {
+        let ratio = self.damping_ratio();
+        if (ratio - 1.0).abs() < 1e-4 {
+            Damping::Critical
+        } else if ratio < 1.0 {
+            Damping::Under
+        } else {
+            Damping::Over
+        }
+    }
+
+    /// Displacement and velocity along x
+    fn solve(&self, t: f32) -> (f32, f32) {
+        let (x0, v0, gamma) = (self.x0, self.v0, self.gamma);
+        let decay = (-gamma * t).exp();
+        match self.regime() {
+            Damping::Under => {
+                let omega = (self.omega0 * self.omega0 - gamma * gamma).sqrt();
+                let b = (v0 + gamma * x0) / omega;
+                let (sin, cos) = (omega * t).sin_cos();
+                let x = decay * (x0 * cos + b * sin);
+                let v = decay * ((b * omega - gamma * x0) * cos - (x0 * omega + gamma * b) * sin);
+                (x, v)
+            }
+            Damping::Critical => {
+                let b = v0 + gamma * x0;
+                let x = decay * (x0 + b * t);
+                let v = decay * (b - gamma * (x0 + b * t));
+                (x, v)
+            }
+            Damping::Over => {
+                let root = (gamma * gamma - self.omega0 * self.omega0).sqrt();
+                let (r1, r2) = (-gamma + root, -gamma - root);
+                let c1 = (v0 - r2 * x0) / (r1 - r2);
+                let c2 = x0 - c1;
+                let (e1, e2) = ((r1 * t).exp(), (r2 * t).exp());
+                (c1 * e1 + c2 * e2, c1 * r1 * e1 + c2 * r2 * e2)
+            }
+        }
+    }
+}
+
+impl AnalyticSolution for DampedOscillator {
+    fn state(&self, t: f32) -> BodyState {
+        let (x, v) = self.solve(t);
+        BodyState::new(Vec2::new(x, 0.0), Vec2::new(v, 0.0))
+    }
+}
+
+/// Bound Kepler orbit around a central mass at the origin
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeplerOrbit {
+    /// Gravitational parameter μ = G M
+    pub mu: f32,
+    pub semi_major_axis: f32,
+    /// Between 0 (circle) and 1 (exclusive)
+    pub eccentricity: f32,
+    /// Angle of the periapsis from the x axis
+    pub periapsis_angle: f32,
+    /// Time of the periapsis passage
+    pub periapsis_time: f32,
+    /// Orbiting clockwise instead of counterclockwise
+    pub clockwise: bool,
+}
+
+impl KeplerOrbit {
+    pub fn new(mu: f32, semi_major_axis: f32, eccentricity: f32) -> Self {
+        Self {
+            mu,
+            semi_major_axis,
+            eccentricity,
+            periapsis_angle: 0.0,
+            periapsis_time: 0.0,
+            clockwise: false,
+        }
+    }
+
+    /// The orbit through `state` at t = 0, if it is bound
+    pub fn from_state(mu: f32, state: BodyState) -> Option<Self> {
+        let (r, v) = (state.position, state.velocity);
+        let distance = r.length();
+        let energy = 0.5 * v.length_squared() - mu / distance;
+        if energy >= 0.0 || distance == 0.0 {
+            return None;
+        }
+        let semi_major_axis = -mu / (2.0 * energy);
+        // Eccentricity vector points at the periapsis
+        let h = r.perp_dot(v);
+        let e_vec = Vec2::new(v.y * h, -v.x * h) / mu - r / distance;
+        let eccentricity = e_vec.length();
+        let periapsis_angle = if eccentricity > 1e-6 { e_vec.to_angle() } else { r.to_angle() };
+
+        let clockwise = h < 0.0;
+
+        // The mean anomaly now gives the time since periapsis
+        let mut true_anomaly = r.to_angle() - periapsis_angle;
+        if clockwise {
+            true_anomaly = -true_anomaly;
+        }
+        let e = eccentricity;
+        let eccentric = 2.0 * ((1.0 - e).sqrt() * (true_anomaly / 2.0).sin())
+            .atan2((1.0 + e).sqrt() * (true_anomaly / 2.0).cos());
+        let mean_anomaly = eccentric - e * eccentric.sin();
+
+        let mut orbit = Self {
+            mu,
+            semi_major_axis,
+            eccentricity,
+            periapsis_angle,
+            periapsis_time: 0.0,
+            clockwise,
+        };
+        orbit.periapsis_time = -mean_anomaly / orbit.mean_motion();
+        Some(orbit)
+    }
+
+    pub fn mean_motion(&self) -> f32 {
+        (self.mu / self.semi_major_axis.powi(3)).sqrt()
+    }
+
+    pub fn period(&self) -> f32 {
+        TAU / self.mean_motion()
+    }
+
+    /// Solve Kepler's equation M = E - e sin E for the eccentric anomaly
+    fn eccentric_anomaly(&self, mean_anomaly: f32) -> f32 {
+        let e = self.eccentricity;
+        let mut anomaly = if e < 0.8 { mean_anomaly } else { std::f32::consts::PI };
+        for _ in 0..50 {
+            let step = (anomaly - e * anomaly.sin() - mean_anomaly) / (1.0 - e * anomaly.cos());
+            anomaly -= step;
+            if step.abs() < 1e-7 {
+                break;
+            }
+        }
+        anomaly
+    }
+}
+
+impl AnalyticSolution for KeplerOrbit {
+    fn state(&self, t: f32) -> BodyState {
+        let (a, e) = (self.semi_major_axis, self.eccentricity);
+        let mean_anomaly = (self.mean_motion() * (t - self.periapsis_time)).rem_euclid(TAU);
+        let anomaly = self.eccentric_anomaly(mean_anomaly);
+        let (sin, cos) = anomaly.sin_cos();
+        let b = a * (1.0 - e * e).sqrt();
+
+        // Perifocal frame, centered on the focus; clockwise orbits are mirrored in it
+        let b = if self.clockwise { -b } else { b };
+        let position = Vec2::new(a * (cos - e), b * sin);
+        let rate = self.mean_motion() / (1.0 - e * cos);
+        let velocity = Vec2::new(-a * sin, b * cos) * rate;
+
+        let rotation = Vec2::from_angle(self.periapsis_angle);
+        BodyState::new(rotation.rotate(position), rotation.rotate(velocity))
+    }
+}
+
+/// N(t) = N₀ e^(-λt)
+pub fn exponential_decay(initial: f32, decay_constant: f32, t: f32) -> f32 {
+    initial * (-decay_constant * t).exp()
+}
+
+/// λ = ln 2 / t½
+pub fn decay_constant(half_life: f32) -> f32 {
+    std::f32::consts::LN_2 / half_life
+}
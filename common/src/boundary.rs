@@ -0,0 +1,276 @@
+//! The walls of the arena, kept at the edges of the window as it is resized
+//!
+//! [`BoundaryPlugin`] keeps the [`Boundary`] resource equal to the visible area in world
+//! units (through [`WorldScale`]), updating it whenever the window is resized or the
+//! scale changes. Entities with a [`BoundaryWall`] are moved and stretched to sit just
+//! outside the matching edge, with their `ColliderShape` (and `Sprite`, if they have
+//! one) resized to match, so a chapter spawns its walls once and never sizes them
+//! itself. Systems that steer away from the edges read them from [`Boundary`].
+//!
+//! What happens to a [`Bounded`] body that crosses an edge is up to the
+//! [`BoundaryMode`] resource: it bounces back in, comes back in on the opposite side,
+//! is despawned, or carries on. The walls only show in `Reflect` mode; chapters that
+//! collide with them themselves skip that in the other modes.
+use std::str::FromStr;
+
+use bevy::prelude::*;
+use bevy::transform::TransformSystems;
+use bevy::window::{PrimaryWindow, WindowResized};
+
+use crate::collision::ColliderShape;
+use crate::world_scale::WorldScale;
+use crate::{Position, Velocity};
+
+/// Fits the [`Boundary`] to the window and places the [`BoundaryWall`]s on it
+pub struct BoundaryPlugin {
+    wall_thickness: f32,
+}
+
+impl Default for BoundaryPlugin {
+    fn default() -> Self {
+        Self { wall_thickness: 10.0 }
+    }
+}
+
+impl BoundaryPlugin {
+    /// How thick the walls are, in world units
+    pub fn with_wall_thickness(mut self, thickness: f32) -> Self {
+        self.wall_thickness = thickness;
+        self
+    }
+}
+
+impl Plugin for BoundaryPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Boundary {
+            rect: Rect::default(),
+            wall_thickness: self.wall_thickness,
+        })
+        .init_resource::<BoundaryMode>()
+        .add_systems(PreStartup, fit_boundary_to_window)
+        .add_systems(PreUpdate, (fit_boundary_to_window, place_walls).chain())
+        .add_systems(PostUpdate, apply_boundary_mode.before(TransformSystems::Propagate));
+    }
+}
+
+/// The arena, centered on the origin, and the thickness of its walls
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct Boundary {
+    pub rect: Rect,
+    pub wall_thickness: f32,
+}
+
+impl Boundary {
+    pub fn size(&self) -> Vec2 {
+        self.rect.size()
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        self.rect.contains(point)
+    }
+
+    /// Distance from `point` to the nearest edge, negative outside
+    pub fn distance_to_edge(&self, point: Vec2) -> f32 {
+        let inside = (point - self.rect.min).min(self.rect.max - point);
+        inside.min_element()
+    }
+}
+
+/// What happens to a [`Bounded`] body that leaves the [`Boundary`]
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundaryMode {
+    /// Bounces back in, the velocity across the edge reversed (a gas in a box)
+    #[default]
+    Reflect,
+    /// Comes back in on the opposite side with the same velocity (a torus)
+    Wrap,
+    /// Is despawned (a detector, or a sink for diffusion)
+    Absorb,
+    /// Carries on outside, with the walls out of the way
+    Open,
+}
+
+impl BoundaryMode {
+    pub const ALL: [BoundaryMode; 4] = [
+        BoundaryMode::Reflect,
+        BoundaryMode::Wrap,
+        BoundaryMode::Absorb,
+        BoundaryMode::Open,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BoundaryMode::Reflect => "reflect",
+            BoundaryMode::Wrap => "wrap",
+            BoundaryMode::Absorb => "absorb",
+            BoundaryMode::Open => "open",
+        }
+    }
+}
+
+impl FromStr for BoundaryMode {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown boundary mode {name}, expected reflect, wrap, absorb or open"))
+    }
+}
+
+/// Marks a body the [`BoundaryMode`] applies to
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub struct Bounded;
+
+/// A wall along one edge of the [`Boundary`], its inner face on the edge
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundaryWall {
+    Left,
+    Right,
+    Bottom,
+    Top,
+}
+
+impl BoundaryWall {
+    pub const ALL: [BoundaryWall; 4] = [
+        BoundaryWall::Left,
+        BoundaryWall::Right,
+        BoundaryWall::Bottom,
+        BoundaryWall::Top,
+    ];
+
+    /// Width and height of the wall; the horizontal walls span the corners too
+    pub fn size(&self, boundary: &Boundary) -> Vec2 {
+        let Vec2 { x: width, y: height } = boundary.size();
+        let thickness = boundary.wall_thickness;
+        match self {
+            BoundaryWall::Left | BoundaryWall::Right => Vec2::new(thickness, height),
+            BoundaryWall::Bottom | BoundaryWall::Top => Vec2::new(width + 2.0 * thickness, thickness),
+        }
+    }
+
+    /// Center of the wall, half a thickness outside its edge
+    pub fn center(&self, boundary: &Boundary) -> Vec2 {
+        let half = boundary.wall_thickness / 2.0;
+        let rect = boundary.rect;
+        match self {
+            BoundaryWall::Left => Vec2::new(rect.min.x - half, rect.center().y),
+            BoundaryWall::Right => Vec2::new(rect.max.x + half, rect.center().y),
+            BoundaryWall::Bottom => Vec2::new(rect.center().x, rect.min.y - half),
+            BoundaryWall::Top => Vec2::new(rect.center().x, rect.max.y + half),
+        }
+    }
+}
+
+/// System to set the [`Boundary`] to the visible area when the window or scale changes
+pub fn fit_boundary_to_window(
+    mut boundary: ResMut<Boundary>,
+    mut resized: MessageReader<WindowResized>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    scale: Option<Res<WorldScale>>,
+) {
+    let resized = resized.read().count() > 0;
+    let scale_changed = scale.as_ref().is_some_and(|scale| scale.is_changed());
+    if !(resized || scale_changed || boundary.rect.is_empty()) {
+        return;
+    }
+    let Ok(window) = window.single() else {
+        return;
+    };
+    let size = scale.as_deref().copied().unwrap_or_default().view_size(window);
+    let rect = Rect::from_center_size(Vec2::ZERO, size);
+    // Only touch the resource on a real change, so walls are placed once per resize
+    if boundary.rect != rect {
+        boundary.rect = rect;
+    }
+}
+
+/// System to move and stretch every [`BoundaryWall`] onto its edge, and show the walls
+/// only in [`BoundaryMode::Reflect`]
+#[allow(clippy::type_complexity)]
+pub fn place_walls(
+    boundary: Res<Boundary>,
+    mode: Res<BoundaryMode>,
+    mut walls: Query<(
+        Ref<BoundaryWall>,
+        &mut Transform,
+        Option<&mut ColliderShape>,
+        Option<&mut Sprite>,
+        Option<&mut Visibility>,
+    )>,
+) {
+    for (wall, mut transform, collider, sprite, visibility) in &mut walls {
+        if !(boundary.is_changed() || mode.is_changed() || wall.is_added()) {
+            continue;
+        }
+        if let Some(mut visibility) = visibility {
+            *visibility = if *mode == BoundaryMode::Reflect {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+        }
+        let size = wall.size(&boundary);
+        transform.translation = wall.center(&boundary).extend(transform.translation.z);
+        if let Some(mut collider) = collider {
+            *collider = ColliderShape::aabb(size / 2.0);
+        }
+        if let Some(mut sprite) = sprite {
+            sprite.custom_size = Some(size);
+        }
+    }
+}
+
+/// System to bounce, wrap or despawn the [`Bounded`] bodies outside the [`Boundary`]
+#[allow(clippy::type_complexity)]
+pub fn apply_boundary_mode(
+    mut commands: Commands,
+    boundary: Res<Boundary>,
+    mode: Res<BoundaryMode>,
+    mut bodies: Query<(Entity, &mut Transform, Option<&mut Position>, Option<&mut Velocity>), With<Bounded>>,
+) {
+    let rect = boundary.rect;
+    if *mode == BoundaryMode::Open || rect.is_empty() {
+        return;
+    }
+    for (entity, mut transform, position, velocity) in &mut bodies {
+        let point = position.as_ref().map_or(transform.translation.truncate(), |position| position.0);
+        if rect.contains(point) {
+            continue;
+        }
+        let moved = match *mode {
+            BoundaryMode::Reflect => {
+                let mut velocity = velocity;
+                // Mirror the overshoot back inside and turn the velocity around on that axis
+                let bounced = reflect(point, rect);
+                for axis in 0..2 {
+                    if bounced[axis] != point[axis] {
+                        if let Some(velocity) = velocity.as_mut() {
+                            let inward = if point[axis] < rect.min[axis] { 1.0 } else { -1.0 };
+                            velocity.0[axis] = velocity.0[axis].abs() * inward;
+                        }
+                    }
+                }
+                bounced
+            }
+            BoundaryMode::Wrap => rect.min + (point - rect.min).rem_euclid(rect.size()),
+            BoundaryMode::Absorb => {
+                commands.entity(entity).despawn();
+                continue;
+            }
+            BoundaryMode::Open => continue,
+        };
+        if let Some(mut position) = position {
+            position.0 = moved;
+        }
+        transform.translation = moved.extend(transform.translation.z);
+    }
+}
+
+/// `point` folded back into `rect` as if its edges were mirrors
+fn reflect(point: Vec2, rect: Rect) -> Vec2 {
+    let reflected = point.clamp(rect.min, rect.max) * 2.0 - point;
+    // A body further out than the whole width just lands on the edge
+    reflected.clamp(rect.min, rect.max)
+}
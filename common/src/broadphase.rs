@@ -0,0 +1,138 @@
+//! Broadphase collision culling
+//!
+//! Finds the pairs of bounding boxes that overlap so the exact (narrowphase) tests
+//! only run on those, instead of every collider against every other.
+//!
+//! [`Broadphase`] picks the method `resolve_collisions` uses. Sweep-and-prune sorts
+//! along x and suits long, flat scenes (a projectile over kilometers of terrain) where
+//! a uniform grid would spread one big box over thousands of cells; the grid suits many
+//! similar small bodies; the quadtree suits bodies clustered in a few places.
+use bevy::math::bounding::{Aabb2d, BoundingVolume, IntersectsVolume};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+
+use crate::parallel;
+use crate::quadtree::Quadtree;
+
+/// How colliders are culled before the exact contact tests; a resource read by
+/// `resolve_collisions`, set with `PhysicsPlugin::with_broadphase`
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub enum Broadphase {
+    /// [`par_sweep_and_prune`]
+    #[default]
+    SweepAndPrune,
+    /// [`grid_pairs`] with square cells this wide (m)
+    Grid { cell_size: f32 },
+    /// [`quadtree_pairs`]
+    Quadtree,
+}
+
+impl Broadphase {
+    /// Pairs of `entries` whose boxes overlap, each once; may reorder `entries`
+    pub fn pairs(&self, entries: &mut [(Entity, Aabb2d)]) -> Vec<(Entity, Entity)> {
+        match *self {
+            Broadphase::SweepAndPrune => par_sweep_and_prune(entries),
+            Broadphase::Grid { cell_size } => grid_pairs(entries, cell_size),
+            Broadphase::Quadtree => quadtree_pairs(entries),
+        }
+    }
+}
+
+/// Below this many boxes per task, spawning tasks costs more than it saves
+const MIN_CHUNK_SIZE: usize = 256;
+
+fn sort_by_left_edge(entries: &mut [(Entity, Aabb2d)]) {
+    entries.sort_unstable_by(|(_, a), (_, b)| a.min.x.total_cmp(&b.min.x));
+}
+
+/// Collect overlaps between `sorted[index]` and the boxes after it
+fn sweep_from(sorted: &[(Entity, Aabb2d)], index: usize, pairs: &mut Vec<(Entity, Entity)>) {
+    let (entity, aabb) = sorted[index];
+    for (other, other_aabb) in &sorted[index + 1..] {
+        // Everything further along starts to the right of this box
+        if other_aabb.min.x > aabb.max.x {
+            break;
+        }
+        if aabb.intersects(other_aabb) {
+            pairs.push((entity, *other));
+        }
+    }
+}
+
+/// Sweep-and-prune: sort the boxes along x, then sweep each one over the boxes
+/// that start before it ends. Reorders `entries`.
+pub fn sweep_and_prune(entries: &mut [(Entity, Aabb2d)]) -> Vec<(Entity, Entity)> {
+    sort_by_left_edge(entries);
+    let mut pairs = Vec::new();
+    for index in 0..entries.len() {
+        sweep_from(entries, index, &mut pairs);
+    }
+    pairs
+}
+
+/// [`sweep_and_prune`] with the sweep split across threads (see [`parallel`]).
+/// Every sweep only reads the sorted list, so chunks are independent.
+pub fn par_sweep_and_prune(entries: &mut [(Entity, Aabb2d)]) -> Vec<(Entity, Entity)> {
+    sort_by_left_edge(entries);
+    let sorted: &[(Entity, Aabb2d)] = entries;
+    parallel::par_chunk_map(sorted, MIN_CHUNK_SIZE, |start, chunk| {
+        let mut pairs = Vec::new();
+        for index in start..start + chunk.len() {
+            sweep_from(sorted, index, &mut pairs);
+        }
+        pairs
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Uniform grid: bin each box into every cell it covers, then test the boxes sharing a
+/// cell. A pair is only reported from the cell holding the corner where their overlap
+/// starts, so pairs sharing several cells come out once.
+pub fn grid_pairs(entries: &[(Entity, Aabb2d)], cell_size: f32) -> Vec<(Entity, Entity)> {
+    let cell_size = cell_size.max(f32::EPSILON);
+    let cell_of = |point: Vec2| (point / cell_size).floor().as_ivec2();
+    let mut cells: HashMap<IVec2, Vec<usize>> = HashMap::new();
+    for (index, (_, aabb)) in entries.iter().enumerate() {
+        let (min, max) = (cell_of(aabb.min), cell_of(aabb.max));
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                cells.entry(IVec2::new(x, y)).or_default().push(index);
+            }
+        }
+    }
+    let mut pairs = Vec::new();
+    for (cell, members) in &cells {
+        for (i, &a) in members.iter().enumerate() {
+            let (entity_a, aabb_a) = entries[a];
+            for &b in &members[i + 1..] {
+                let (entity_b, aabb_b) = entries[b];
+                if aabb_a.intersects(&aabb_b) && cell_of(aabb_a.min.max(aabb_b.min)) == *cell {
+                    pairs.push((entity_a, entity_b));
+                }
+            }
+        }
+    }
+    pairs
+}
+
+/// Quadtree over the boxes' combined bounds, queried with each box in turn
+pub fn quadtree_pairs(entries: &[(Entity, Aabb2d)]) -> Vec<(Entity, Entity)> {
+    let Some(bounds) = entries.iter().map(|(_, aabb)| *aabb).reduce(|a, b| a.merge(&b)) else {
+        return Vec::new();
+    };
+    let mut tree = Quadtree::new(bounds);
+    for (index, (_, aabb)) in entries.iter().enumerate() {
+        tree.insert(index, *aabb);
+    }
+    let mut pairs = Vec::new();
+    for (index, (entity, aabb)) in entries.iter().enumerate() {
+        tree.query_with(*aabb, |other, _| {
+            if other > index {
+                pairs.push((*entity, entries[other].0));
+            }
+        });
+    }
+    pairs
+}
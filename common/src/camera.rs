@@ -0,0 +1,214 @@
+//! Panning and zooming a 2D camera around a world bigger than the window
+//!
+//! Put a [`CameraController`] on a chapter's `Camera2d` and add
+//! [`CameraControllerPlugin`]. Dragging with the right mouse button pans, the scroll
+//! wheel zooms about the point under the cursor, the `horizontal` and `vertical`
+//! [`InputAxes`] (arrow keys, WASD or a gamepad's left stick) nudge the view, `+` and
+//! `-` or the right stick zoom about the middle, `F` (or a [`ZoomToFit`] message) fits every [`FitInView`]
+//! entity on screen and `Home` goes back to the origin at zoom 1. Input that egui is
+//! using is left alone.
+//!
+//! Zoom multiplies the [`WorldScale`]: the screen shows `meters_per_pixel * zoom`
+//! meters per pixel. [`WorldScale::cursor_position`] assumes an unmoved camera, so
+//! chapters with a controller ask [`CameraController::cursor_position`] instead.
+use bevy::input::mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll, MouseScrollUnit};
+use bevy::prelude::*;
+use bevy::transform::TransformSystems;
+use bevy::window::PrimaryWindow;
+use bevy_egui::input::EguiWantsInput;
+
+use crate::input::{InputAxes, InputAxesPlugin};
+use crate::world_scale::{couple_camera_scale, WorldScale};
+
+/// Scroll distance counted as one wheel notch when the device reports pixels
+const PIXELS_PER_LINE: f32 = 100.0;
+
+/// Mouse and keyboard control of every camera with a [`CameraController`]
+pub struct CameraControllerPlugin;
+
+impl Plugin for CameraControllerPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<InputAxesPlugin>() {
+            app.add_plugins(InputAxesPlugin);
+        }
+        app.add_message::<ZoomToFit>()
+            .add_systems(Update, (pan_and_zoom, zoom_to_fit).chain())
+            .add_systems(
+                PostUpdate,
+                apply_zoom.after(couple_camera_scale).before(TransformSystems::Propagate),
+            );
+    }
+}
+
+/// How a camera responds to panning and zooming, and how far it is zoomed
+#[derive(Component, Debug, Clone, Copy)]
+pub struct CameraController {
+    /// Multiplier on the [`WorldScale`]; above 1 shows more of the world
+    pub zoom: f32,
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+    /// Zoom factor per scroll notch or key press
+    pub zoom_step: f32,
+    /// Zoom steps per second with the `zoom` axis fully deflected
+    pub zoom_speed: f32,
+    /// Panning speed at full stick or key (screen pixels per second)
+    pub nudge_speed: f32,
+    /// Space left around the [`FitInView`] entities, as a fraction of their extent
+    pub fit_margin: f32,
+}
+
+impl Default for CameraController {
+    fn default() -> Self {
+        Self {
+            zoom: 1.0,
+            min_zoom: 0.01,
+            max_zoom: 1000.0,
+            zoom_step: 1.1,
+            zoom_speed: 8.0,
+            nudge_speed: 600.0,
+            fit_margin: 0.1,
+        }
+    }
+}
+
+impl CameraController {
+    pub fn with_zoom_limits(mut self, min_zoom: f32, max_zoom: f32) -> Self {
+        self.min_zoom = min_zoom;
+        self.max_zoom = max_zoom;
+        self
+    }
+
+    /// World position under the cursor for the camera at `camera`, if it is in the window
+    pub fn cursor_position(&self, camera: &Transform, scale: &WorldScale, window: &Window) -> Option<Vec2> {
+        scale
+            .cursor_position(window)
+            .map(|offset| camera.translation.truncate() + offset * self.zoom)
+    }
+}
+
+/// Marks an entity that `F` or [`ZoomToFit`] keeps in view
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub struct FitInView;
+
+/// Send to move and zoom the cameras so every [`FitInView`] entity is on screen
+#[derive(Message, Debug, Clone, Copy, Default)]
+pub struct ZoomToFit;
+
+#[allow(clippy::too_many_arguments)]
+fn pan_and_zoom(
+    mut cameras: Query<(&mut CameraController, &mut Transform)>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    scale: Option<Res<WorldScale>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    axes: Res<InputAxes>,
+    motion: Res<AccumulatedMouseMotion>,
+    scroll: Res<AccumulatedMouseScroll>,
+    time: Res<Time<Real>>,
+    egui_input: Option<Res<EguiWantsInput>>,
+) {
+    let Ok(window) = window.single() else {
+        return;
+    };
+    let scale = scale.as_deref().copied().unwrap_or_default();
+    let (pointer_free, keys_free) = egui_input.map_or((true, true), |input| {
+        (!input.wants_any_pointer_input(), !input.wants_any_keyboard_input())
+    });
+
+    // Screen pixels dragged, y up; the world follows the cursor
+    let mut drag = Vec2::ZERO;
+    if pointer_free && buttons.pressed(MouseButton::Right) {
+        drag = Vec2::new(-motion.delta.x, motion.delta.y);
+    }
+    // The axes already ignore keys while egui is typing
+    let nudge = axes.vector("horizontal", "vertical");
+
+    // Zoom steps, out positive; scrolling zooms about the cursor, keys about the middle
+    let mut scroll_steps = 0.0;
+    if pointer_free {
+        scroll_steps = match scroll.unit {
+            MouseScrollUnit::Line => -scroll.delta.y,
+            MouseScrollUnit::Pixel => -scroll.delta.y / PIXELS_PER_LINE,
+        };
+    }
+    let mut key_steps = 0.0;
+    if keys_free {
+        if keys.any_just_pressed([KeyCode::Minus, KeyCode::NumpadSubtract]) {
+            key_steps += 1.0;
+        }
+        if keys.any_just_pressed([KeyCode::Equal, KeyCode::NumpadAdd]) {
+            key_steps -= 1.0;
+        }
+    }
+    let stick_zoom = -axes.get("zoom") * time.delta_secs();
+    let anchor = scale.cursor_position(window).unwrap_or_default();
+
+    for (mut controller, mut transform) in &mut cameras {
+        if keys_free && keys.just_pressed(KeyCode::Home) {
+            controller.zoom = 1.0;
+            transform.translation = Vec3::new(0.0, 0.0, transform.translation.z);
+            continue;
+        }
+        let pan = drag + nudge * controller.nudge_speed * time.delta_secs();
+        if pan != Vec2::ZERO {
+            transform.translation += (pan * scale.meters_per_pixel * controller.zoom).extend(0.0);
+        }
+        let held_steps = key_steps + stick_zoom * controller.zoom_speed;
+        for (steps, anchor) in [(scroll_steps, anchor), (held_steps, Vec2::ZERO)] {
+            if steps == 0.0 {
+                continue;
+            }
+            let zoom = (controller.zoom * controller.zoom_step.powf(steps)).clamp(controller.min_zoom, controller.max_zoom);
+            // Keep the world point at the anchor where it is on screen
+            transform.translation += (anchor * (controller.zoom - zoom)).extend(0.0);
+            controller.zoom = zoom;
+        }
+    }
+}
+
+fn zoom_to_fit(
+    mut requests: MessageReader<ZoomToFit>,
+    keys: Res<ButtonInput<KeyCode>>,
+    egui_input: Option<Res<EguiWantsInput>>,
+    fitted: Query<&GlobalTransform, With<FitInView>>,
+    mut cameras: Query<(&mut CameraController, &mut Transform)>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    scale: Option<Res<WorldScale>>,
+) {
+    let keys_free = egui_input.is_none_or(|input| !input.wants_any_keyboard_input());
+    let requested = requests.read().count() > 0 || (keys_free && keys.just_pressed(KeyCode::KeyF));
+    if !requested {
+        return;
+    }
+    let Ok(window) = window.single() else {
+        return;
+    };
+    let mut points = fitted.iter().map(|transform| transform.translation().truncate());
+    let Some(first) = points.next() else {
+        return;
+    };
+    let bounds = points.fold(Rect::from_corners(first, first), |bounds, point| bounds.union_point(point));
+    let view = scale.as_deref().copied().unwrap_or_default().view_size(window);
+    for (mut controller, mut transform) in &mut cameras {
+        let size = bounds.size() * (1.0 + 2.0 * controller.fit_margin);
+        let zoom = (size / view).max_element();
+        // A single point keeps the current zoom
+        if zoom > 0.0 {
+            controller.zoom = zoom.clamp(controller.min_zoom, controller.max_zoom);
+        }
+        transform.translation = bounds.center().extend(transform.translation.z);
+    }
+}
+
+fn apply_zoom(scale: Option<Res<WorldScale>>, mut cameras: Query<(Ref<CameraController>, &mut Projection)>) {
+    let scale_changed = scale.as_ref().is_some_and(|scale| scale.is_changed());
+    let meters_per_pixel = scale.map_or(1.0, |scale| scale.meters_per_pixel);
+    for (controller, mut projection) in &mut cameras {
+        if !(scale_changed || controller.is_changed()) {
+            continue;
+        }
+        if let Projection::Orthographic(orthographic) = &mut *projection {
+            orthographic.scale = meters_per_pixel * controller.zoom;
+        }
+    }
+}
@@ -0,0 +1,212 @@
+//! Screenshots and short GIF clips of a running simulation, for figures
+//!
+//! [`CapturePlugin`] saves a PNG of the window on F12 and starts or stops a GIF clip
+//! on F11; the Capture window has the same two buttons. Native builds write
+//! `capture-<n>.png` and `capture-<n>.gif` to the working directory, and the web build
+//! downloads them. Clips grab a frame every [`Capture::frame_interval`] seconds, up to
+//! [`Capture::max_frames`], and are encoded when they stop.
+use bevy::prelude::*;
+use bevy::render::view::screenshot::{save_to_disk, Screenshot, ScreenshotCaptured};
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+
+/// Adds the capture keys, the Capture window and the [`Capture`] settings
+pub struct CapturePlugin;
+
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        crate::add_egui(app);
+        app.init_resource::<Capture>()
+            .add_systems(Update, (capture_keys, grab_clip_frames).chain())
+            .add_systems(EguiPrimaryContextPass, capture_panel);
+    }
+}
+
+/// Clip settings and the clip being recorded
+#[derive(Resource, Debug)]
+pub struct Capture {
+    /// Seconds between clip frames
+    pub frame_interval: f32,
+    /// Longest clip, in frames; recording stops by itself after this
+    pub max_frames: usize,
+    /// Number in the next file name
+    counter: u32,
+    clip: Option<Clip>,
+}
+
+impl Default for Capture {
+    fn default() -> Self {
+        Self {
+            // 10 frames per second for 15 seconds
+            frame_interval: 0.1,
+            max_frames: 150,
+            counter: 0,
+            clip: None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Clip {
+    /// RGBA frames, all the size of the first
+    frames: Vec<Vec<u8>>,
+    size: Option<UVec2>,
+    since_last: f32,
+    /// Screenshots asked for but not yet back
+    pending: usize,
+}
+
+impl Capture {
+    pub fn is_recording(&self) -> bool {
+        self.clip.is_some()
+    }
+
+    fn next_name(&mut self, extension: &str) -> String {
+        self.counter += 1;
+        format!("capture-{}.{extension}", self.counter)
+    }
+
+    /// Save a PNG of the primary window
+    pub fn screenshot(&mut self, commands: &mut Commands) {
+        let path = self.next_name("png");
+        log::info!("Saving screenshot {path}");
+        // Bevy downloads the file instead on the web
+        commands.spawn(Screenshot::primary_window()).observe(save_to_disk(path));
+    }
+
+    /// Start a clip, or stop and save the one being recorded
+    pub fn toggle_clip(&mut self) {
+        match self.clip.take() {
+            None => self.clip = Some(Clip::default()),
+            Some(clip) => self.save_clip(clip),
+        }
+    }
+
+    fn save_clip(&mut self, clip: Clip) {
+        let Some(size) = clip.size else {
+            return;
+        };
+        let name = self.next_name("gif");
+        match encode_gif(&clip.frames, size, self.frame_interval) {
+            Ok(bytes) => save_bytes(&name, &bytes, "image/gif"),
+            Err(err) => log::error!("Could not encode {name}: {err}"),
+        }
+    }
+}
+
+fn encode_gif(frames: &[Vec<u8>], size: UVec2, frame_interval: f32) -> Result<Vec<u8>, gif::EncodingError> {
+    let (width, height) = (size.x as u16, size.y as u16);
+    let mut bytes = Vec::new();
+    {
+        let mut encoder = gif::Encoder::new(&mut bytes, width, height, &[])?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+        for rgba in frames {
+            let mut rgba = rgba.clone();
+            let mut frame = gif::Frame::from_rgba_speed(width, height, &mut rgba, 10);
+            // GIF delays are in hundredths of a second
+            frame.delay = (frame_interval * 100.0).round() as u16;
+            encoder.write_frame(&frame)?;
+        }
+    }
+    Ok(bytes)
+}
+
+/// Write the file to the working directory
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn save_bytes(name: &str, bytes: &[u8], _mime: &str) {
+    match std::fs::write(name, bytes) {
+        Ok(()) => log::info!("Saved {name}"),
+        Err(err) => log::error!("Could not save {name}: {err}"),
+    }
+}
+
+/// Hand the file to the browser as a download
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn save_bytes(name: &str, bytes: &[u8], mime: &str) {
+    use wasm_bindgen::JsCast;
+
+    let download = || -> Option<()> {
+        let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+        let options = web_sys::BlobPropertyBag::new();
+        options.set_type(mime);
+        let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options).ok()?;
+        let url = web_sys::Url::create_object_url_with_blob(&blob).ok()?;
+        let document = web_sys::window()?.document()?;
+        let link: web_sys::HtmlAnchorElement = document.create_element("a").ok()?.dyn_into().ok()?;
+        link.set_href(&url);
+        link.set_download(name);
+        link.click();
+        web_sys::Url::revoke_object_url(&url).ok()
+    };
+    if download().is_none() {
+        log::error!("Could not download {name}");
+    }
+}
+
+fn capture_keys(mut commands: Commands, keys: Res<ButtonInput<KeyCode>>, mut capture: ResMut<Capture>) {
+    if keys.just_pressed(KeyCode::F12) {
+        capture.screenshot(&mut commands);
+    }
+    if keys.just_pressed(KeyCode::F11) {
+        capture.toggle_clip();
+    }
+}
+
+/// Ask for a screenshot every frame interval while a clip is recording
+fn grab_clip_frames(mut commands: Commands, mut capture: ResMut<Capture>, time: Res<Time<Real>>) {
+    let (interval, max_frames) = (capture.frame_interval, capture.max_frames);
+    let Some(clip) = capture.clip.as_mut() else {
+        return;
+    };
+    if clip.frames.len() + clip.pending >= max_frames {
+        if clip.pending == 0 {
+            capture.toggle_clip();
+        }
+        return;
+    }
+    clip.since_last += time.delta_secs();
+    if clip.since_last < interval {
+        return;
+    }
+    clip.since_last -= interval;
+    clip.pending += 1;
+    commands.spawn(Screenshot::primary_window()).observe(add_clip_frame);
+}
+
+fn add_clip_frame(captured: On<ScreenshotCaptured>, mut capture: ResMut<Capture>) {
+    let Some(clip) = capture.clip.as_mut() else {
+        return;
+    };
+    clip.pending = clip.pending.saturating_sub(1);
+    let image = &captured.image;
+    let size = image.size();
+    // A resized window would change the frame size mid-clip; keep the first size only
+    if clip.size.is_some_and(|first| first != size) {
+        return;
+    }
+    let Ok(dynamic) = image.clone().try_into_dynamic() else {
+        return;
+    };
+    clip.size = Some(size);
+    clip.frames.push(dynamic.to_rgba8().into_raw());
+}
+
+fn capture_panel(mut contexts: EguiContexts, mut commands: Commands, mut capture: ResMut<Capture>) -> Result {
+    egui::Window::new("Capture")
+        .default_open(false)
+        .anchor(egui::Align2::RIGHT_TOP, [-10.0, 60.0])
+        .resizable(false)
+        .show(contexts.ctx_mut()?, |ui| {
+            if ui.button("📷 Screenshot (F12)").clicked() {
+                capture.screenshot(&mut commands);
+            }
+            let frames = capture.clip.as_ref().map(|clip| clip.frames.len());
+            let label = match frames {
+                Some(frames) => format!("⏹ Stop GIF, {frames} frames (F11)"),
+                None => "⏺ Record GIF (F11)".to_string(),
+            };
+            if ui.button(label).clicked() {
+                capture.toggle_clip();
+            }
+        });
+    Ok(())
+}
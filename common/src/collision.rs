@@ -0,0 +1,936 @@
+//! Collider shapes and narrowphase contact tests
+//!
+//! [`ColliderShape`] gives an entity its collision geometry in world units, separate
+//! from how it is drawn; its position and rotation come from the `Transform`.
+//! [`contact`] tests two shapes and returns where they touch, along which normal and
+//! how deeply they overlap, which is what a collision response needs.
+//! [`find_contacts`] runs the broadphase first and the exact test only on the pairs
+//! it reports, and [`resolve_collisions`] answers the contacts with impulses, using
+//! each body's [`PhysicsMaterial`], and sends a [`CollisionEvent`] for each one so
+//! chapters can keep score, play sounds or count collisions without their own
+//! detection. Fast circles marked [`ContinuousCollision`] are also swept along their
+//! motion each step, so they can't pass through thin walls. [`raycast`] (or the
+//! [`Raycaster`] system parameter) finds the first collider along a ray, for light
+//! rays, lines of sight and picking.
+use bevy::ecs::system::SystemParam;
+use bevy::math::bounding::{Aabb2d, IntersectsVolume};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+
+use crate::broadphase::Broadphase;
+use crate::comparison::ComparisonSide;
+use crate::sleep::Sleeping;
+use crate::{Mass, MomentOfInertia, Position, Velocity};
+
+/// Collision geometry, in the entity's local frame
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component, Default)]
+pub enum ColliderShape {
+    Circle { radius: f32 },
+    /// Box that stays axis-aligned whatever the entity's rotation
+    Aabb { half_size: Vec2 },
+    /// Box that turns with the entity
+    Obb { half_size: Vec2 },
+    /// Segment along the local y axis, rounded by `radius` (like [`Capsule2d`])
+    Capsule { radius: f32, half_length: f32 },
+    /// Line between two local points, with no thickness
+    Segment { a: Vec2, b: Vec2 },
+}
+
+impl Default for ColliderShape {
+    /// The same size as `Circle::default()`
+    fn default() -> Self {
+        Self::Circle { radius: 0.5 }
+    }
+}
+
+impl ColliderShape {
+    pub fn circle(radius: f32) -> Self {
+        Self::Circle { radius }
+    }
+
+    pub fn aabb(half_size: Vec2) -> Self {
+        Self::Aabb { half_size }
+    }
+
+    pub fn obb(half_size: Vec2) -> Self {
+        Self::Obb { half_size }
+    }
+
+    pub fn capsule(radius: f32, half_length: f32) -> Self {
+        Self::Capsule { radius, half_length }
+    }
+
+    pub fn segment(a: Vec2, b: Vec2) -> Self {
+        Self::Segment { a, b }
+    }
+
+    /// The shape placed in the world
+    fn placed(&self, isometry: Isometry2d) -> Placed {
+        match *self {
+            Self::Circle { radius } => Placed::Rounded {
+                a: isometry.translation,
+                b: isometry.translation,
+                radius,
+            },
+            Self::Capsule { radius, half_length } => Placed::Rounded {
+                a: isometry.transform_point(Vec2::new(0.0, -half_length)),
+                b: isometry.transform_point(Vec2::new(0.0, half_length)),
+                radius,
+            },
+            Self::Segment { a, b } => Placed::Rounded {
+                a: isometry.transform_point(a),
+                b: isometry.transform_point(b),
+                radius: 0.0,
+            },
+            Self::Aabb { half_size } => Placed::Box {
+                center: isometry.translation,
+                rotation: Rot2::IDENTITY,
+                half_size,
+            },
+            Self::Obb { half_size } => Placed::Box {
+                center: isometry.translation,
+                rotation: isometry.rotation,
+                half_size,
+            },
+        }
+    }
+
+    /// World-space bounding box, for the broadphase
+    pub fn aabb_at(&self, isometry: Isometry2d) -> Aabb2d {
+        match self.placed(isometry) {
+            Placed::Rounded { a, b, radius } => Aabb2d {
+                min: a.min(b) - radius,
+                max: a.max(b) + radius,
+            },
+            Placed::Box { center, rotation, half_size } => {
+                let extents = (rotation * Vec2::X).abs() * half_size.x + (rotation * Vec2::Y).abs() * half_size.y;
+                Aabb2d::new(center, extents)
+            }
+        }
+    }
+
+    /// Volume (m³) of the solid the shape stands for: a circle is a ball, a capsule a
+    /// 3D capsule and a box a bar as deep as its shorter side. Segments have none.
+    pub fn volume(&self) -> f32 {
+        use std::f32::consts::PI;
+        match *self {
+            ColliderShape::Circle { radius } => 4.0 / 3.0 * PI * radius.powi(3),
+            ColliderShape::Capsule { radius, half_length } => {
+                4.0 / 3.0 * PI * radius.powi(3) + PI * radius * radius * 2.0 * half_length
+            }
+            ColliderShape::Aabb { half_size } | ColliderShape::Obb { half_size } => {
+                8.0 * half_size.x * half_size.y * half_size.min_element()
+            }
+            ColliderShape::Segment { .. } => 0.0,
+        }
+    }
+
+    /// Moment of inertia about the center of a body of `mass` with this shape, spinning
+    /// in the plane
+    pub fn moment_of_inertia(&self, mass: f32) -> MomentOfInertia {
+        match *self {
+            // A solid ball, I = ⅖mr²
+            ColliderShape::Circle { radius } => MomentOfInertia(0.4 * mass * radius * radius),
+            ColliderShape::Capsule { radius, half_length } => {
+                MomentOfInertia::rectangle(mass, Vec2::new(2.0 * radius, 2.0 * (half_length + radius)))
+            }
+            ColliderShape::Aabb { half_size } | ColliderShape::Obb { half_size } => {
+                MomentOfInertia::rectangle(mass, 2.0 * half_size)
+            }
+            ColliderShape::Segment { a, b } => MomentOfInertia::rod(mass, a.distance(b)),
+        }
+    }
+
+    /// Distance from `point` to the surface of the shape, negative inside, for picking
+    pub fn distance_to_point(&self, isometry: Isometry2d, point: Vec2) -> f32 {
+        match self.placed(isometry) {
+            Placed::Rounded { a, b, radius } => {
+                let (closest, _) = closest_points(a, b, point, point);
+                closest.distance(point) - radius
+            }
+            Placed::Box { center, rotation, half_size } => {
+                let local = (rotation.inverse() * (point - center)).abs() - half_size;
+                local.max(Vec2::ZERO).length() + local.max_element().min(0.0)
+            }
+        }
+    }
+}
+
+/// Position and rotation of a collider from a 2D `Transform` (rotation about z)
+pub fn isometry(transform: &Transform) -> Isometry2d {
+    let (angle, _, _) = transform.rotation.to_euler(EulerRot::ZYX);
+    Isometry2d::new(transform.translation.truncate(), Rot2::radians(angle))
+}
+
+/// Where two shapes touch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact {
+    /// Halfway between the two surfaces, in the middle of the overlap
+    pub point: Vec2,
+    /// Unit normal pointing from the first shape to the second
+    pub normal: Vec2,
+    /// How far the shapes overlap along the normal; moving the second shape by
+    /// `normal * depth` separates them
+    pub depth: f32,
+}
+
+impl Contact {
+    /// The same contact seen from the other shape
+    pub fn flipped(self) -> Self {
+        Self {
+            normal: -self.normal,
+            ..self
+        }
+    }
+}
+
+/// Every shape reduces to a segment with a radius (circle, capsule, segment) or a box
+enum Placed {
+    Rounded { a: Vec2, b: Vec2, radius: f32 },
+    Box { center: Vec2, rotation: Rot2, half_size: Vec2 },
+}
+
+/// Test shape `a` at `isometry_a` against shape `b` at `isometry_b`
+pub fn contact(a: &ColliderShape, isometry_a: Isometry2d, b: &ColliderShape, isometry_b: Isometry2d) -> Option<Contact> {
+    match (a.placed(isometry_a), b.placed(isometry_b)) {
+        (Placed::Rounded { a: a0, b: a1, radius: ra }, Placed::Rounded { a: b0, b: b1, radius: rb }) => {
+            rounded_rounded((a0, a1, ra), (b0, b1, rb))
+        }
+        (Placed::Rounded { a, b, radius }, Placed::Box { center, rotation, half_size }) => {
+            rounded_box((a, b, radius), (center, rotation, half_size))
+        }
+        (Placed::Box { center, rotation, half_size }, Placed::Rounded { a, b, radius }) => {
+            rounded_box((a, b, radius), (center, rotation, half_size)).map(Contact::flipped)
+        }
+        (
+            Placed::Box { center: ca, rotation: ra, half_size: ha },
+            Placed::Box { center: cb, rotation: rb, half_size: hb },
+        ) => box_box((ca, ra, ha), (cb, rb, hb)),
+    }
+}
+
+/// Broadphase, then [`contact`] on each overlapping pair of `colliders`. Each result
+/// is `(a, b, contact)` with the normal pointing from `a` to `b`.
+pub fn find_contacts(colliders: &[(Entity, ColliderShape, Isometry2d)]) -> Vec<(Entity, Entity, Contact)> {
+    find_contacts_where(colliders, Broadphase::SweepAndPrune, |_, _| true)
+}
+
+/// [`find_contacts`] with the given `broadphase`, running the narrowphase only on
+/// pairs for which `keep` (given their indices in `colliders`) is true
+pub fn find_contacts_where(
+    colliders: &[(Entity, ColliderShape, Isometry2d)],
+    broadphase: Broadphase,
+    keep: impl Fn(usize, usize) -> bool,
+) -> Vec<(Entity, Entity, Contact)> {
+    let index: HashMap<Entity, usize> = colliders
+        .iter()
+        .enumerate()
+        .map(|(index, (entity, ..))| (*entity, index))
+        .collect();
+    let mut boxes: Vec<(Entity, Aabb2d)> = colliders
+        .iter()
+        .map(|(entity, shape, isometry)| (*entity, shape.aabb_at(*isometry)))
+        .collect();
+    broadphase
+        .pairs(&mut boxes)
+        .into_iter()
+        .filter_map(|(a, b)| {
+            let (index_a, index_b) = (index[&a], index[&b]);
+            if !keep(index_a, index_b) {
+                return None;
+            }
+            let (_, shape_a, isometry_a) = colliders[index_a];
+            let (_, shape_b, isometry_b) = colliders[index_b];
+            contact(&shape_a, isometry_a, &shape_b, isometry_b).map(|contact| (a, b, contact))
+        })
+        .collect()
+}
+
+/// Closest points between segments `p1 q1` and `p2 q2`, either of which may be a
+/// single point (Ericson, Real-Time Collision Detection 5.1.9)
+fn closest_points(p1: Vec2, q1: Vec2, p2: Vec2, q2: Vec2) -> (Vec2, Vec2) {
+    let d1 = q1 - p1;
+    let d2 = q2 - p2;
+    let r = p1 - p2;
+    let a = d1.length_squared();
+    let e = d2.length_squared();
+    let f = d2.dot(r);
+    let (s, t) = if a <= f32::EPSILON && e <= f32::EPSILON {
+        (0.0, 0.0)
+    } else if a <= f32::EPSILON {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = d1.dot(r);
+        if e <= f32::EPSILON {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = d1.dot(d2);
+            let denominator = a * e - b * b;
+            // Parallel segments: any point works, start from p1
+            let s = if denominator > f32::EPSILON {
+                ((b * f - c * e) / denominator).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let t = (b * s + f) / e;
+            if t < 0.0 {
+                ((-c / a).clamp(0.0, 1.0), 0.0)
+            } else if t > 1.0 {
+                (((b - c) / a).clamp(0.0, 1.0), 1.0)
+            } else {
+                (s, t)
+            }
+        }
+    };
+    (p1 + d1 * s, p2 + d2 * t)
+}
+
+/// Two rounded segments touch when their cores are closer than the sum of the radii
+fn rounded_rounded((a0, a1, ra): (Vec2, Vec2, f32), (b0, b1, rb): (Vec2, Vec2, f32)) -> Option<Contact> {
+    let (on_a, on_b) = closest_points(a0, a1, b0, b1);
+    let offset = on_b - on_a;
+    let distance = offset.length();
+    if distance > ra + rb {
+        return None;
+    }
+    // Coincident cores have no direction between them; push across one of the segments
+    let normal = offset
+        .try_normalize()
+        .or((a1 - a0).perp().try_normalize())
+        .or((b1 - b0).perp().try_normalize())
+        .unwrap_or(Vec2::Y);
+    let depth = ra + rb - distance;
+    Some(Contact {
+        point: on_a + normal * (ra - depth / 2.0),
+        normal,
+        depth,
+    })
+}
+
+/// A rounded segment against a box, with the normal pointing into the box
+fn rounded_box((p, q, radius): (Vec2, Vec2, f32), (center, rotation, half_size): (Vec2, Rot2, Vec2)) -> Option<Contact> {
+    // Work in the box's frame, where it is axis-aligned at the origin
+    let local_p = rotation.inverse() * (p - center);
+    let local_q = rotation.inverse() * (q - center);
+
+    // Parts of the core inside the box: an end, or the point nearest the center if
+    // the segment passes through. The deepest one is pushed out of the nearest face.
+    let nearest_center = closest_points(local_p, local_q, Vec2::ZERO, Vec2::ZERO).0;
+    let inside = [local_p, local_q, nearest_center]
+        .into_iter()
+        .map(|point| (point, half_size - point.abs()))
+        .filter(|(_, gap)| gap.x >= 0.0 && gap.y >= 0.0)
+        .max_by(|(_, a), (_, b)| a.min_element().total_cmp(&b.min_element()));
+
+    let (surface, outward, depth) = if let Some((point, gap)) = inside {
+        if gap.x < gap.y {
+            let outward = Vec2::new(point.x.signum(), 0.0);
+            (Vec2::new(outward.x * half_size.x, point.y), outward, gap.x + radius)
+        } else {
+            let outward = Vec2::new(0.0, point.y.signum());
+            (Vec2::new(point.x, outward.y * half_size.y), outward, gap.y + radius)
+        }
+    } else {
+        // The core is outside: find its closest approach to the outline
+        let corners = [
+            -half_size,
+            Vec2::new(half_size.x, -half_size.y),
+            half_size,
+            Vec2::new(-half_size.x, half_size.y),
+        ];
+        let (on_core, on_box) = (0..4)
+            .map(|i| closest_points(local_p, local_q, corners[i], corners[(i + 1) % 4]))
+            .min_by(|(a0, b0), (a1, b1)| a0.distance_squared(*b0).total_cmp(&a1.distance_squared(*b1)))?;
+        let distance = on_core.distance(on_box);
+        if distance > radius {
+            return None;
+        }
+        let outward = (on_core - on_box).try_normalize().unwrap_or(on_box.normalize_or(Vec2::Y));
+        (on_box, outward, radius - distance)
+    };
+
+    Some(Contact {
+        point: center + rotation * (surface - outward * (depth / 2.0)),
+        normal: -(rotation * outward),
+        depth,
+    })
+}
+
+/// Half the width of a box projected onto `axis`
+fn projected_extent(axes: [Vec2; 2], half_size: Vec2, axis: Vec2) -> f32 {
+    axes[0].dot(axis).abs() * half_size.x + axes[1].dot(axis).abs() * half_size.y
+}
+
+/// The corner of a box furthest along `direction`
+fn support(center: Vec2, axes: [Vec2; 2], half_size: Vec2, direction: Vec2) -> Vec2 {
+    center
+        + axes[0] * half_size.x * axes[0].dot(direction).signum()
+        + axes[1] * half_size.y * axes[1].dot(direction).signum()
+}
+
+/// Separating axis test between two boxes; the face axis with the least overlap is the normal
+fn box_box((ca, ra, ha): (Vec2, Rot2, Vec2), (cb, rb, hb): (Vec2, Rot2, Vec2)) -> Option<Contact> {
+    let axes_a = [ra * Vec2::X, ra * Vec2::Y];
+    let axes_b = [rb * Vec2::X, rb * Vec2::Y];
+    let offset = cb - ca;
+
+    // (overlap, normal towards b, whether the axis is one of a's faces)
+    let mut best: Option<(f32, Vec2, bool)> = None;
+    let candidates = axes_a.map(|axis| (axis, true)).into_iter().chain(axes_b.map(|axis| (axis, false)));
+    for (axis, from_a) in candidates {
+        let distance = offset.dot(axis);
+        let overlap = projected_extent(axes_a, ha, axis) + projected_extent(axes_b, hb, axis) - distance.abs();
+        if overlap < 0.0 {
+            return None;
+        }
+        if best.is_none_or(|(least, ..)| overlap < least) {
+            let normal = if distance < 0.0 { -axis } else { axis };
+            best = Some((overlap, normal, from_a));
+        }
+    }
+
+    let (depth, normal, from_a) = best?;
+    // The corner of the other box that reaches deepest past the reference face
+    let point = if from_a {
+        support(cb, axes_b, hb, -normal) + normal * (depth / 2.0)
+    } else {
+        support(ca, axes_a, ha, normal) - normal * (depth / 2.0)
+    };
+    Some(Contact { point, normal, depth })
+}
+
+/// Where a moving circle first touches a shape
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SweepHit {
+    /// Fraction of the motion covered before touching, in 0..=1
+    pub time: f32,
+    /// The touching point on the shape's surface
+    pub point: Vec2,
+    /// Surface normal at `point`, facing the circle
+    pub normal: Vec2,
+}
+
+/// First time a circle of `radius` at `center`, moving by `motion`, touches a circle
+/// of `target_radius` at `target`. Circles already touching aren't reported.
+fn sweep_circle_circle(center: Vec2, radius: f32, motion: Vec2, target: Vec2, target_radius: f32) -> Option<SweepHit> {
+    let reach = radius + target_radius;
+    let offset = center - target;
+    let a = motion.length_squared();
+    let b = offset.dot(motion);
+    let c = offset.length_squared() - reach * reach;
+    if c <= 0.0 || b >= 0.0 || a <= f32::EPSILON {
+        return None;
+    }
+    let discriminant = b * b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let time = (-b - discriminant.sqrt()) / a;
+    (time <= 1.0).then(|| {
+        let normal = (offset + motion * time) / reach;
+        SweepHit { time, point: target + normal * target_radius, normal }
+    })
+}
+
+/// First time a circle of `radius` at `center`, moving by `motion`, touches the segment
+/// from `a` to `b`. Circles already touching aren't reported, so the discrete test
+/// handles them.
+pub fn sweep_circle_segment(center: Vec2, radius: f32, motion: Vec2, a: Vec2, b: Vec2) -> Option<SweepHit> {
+    // Against the two flat sides of the segment's radius-wide band...
+    let side_hit = (b - a).perp().try_normalize().and_then(|perp| {
+        let normal = if (center - a).dot(perp) >= 0.0 { perp } else { -perp };
+        let distance = (center - a).dot(normal);
+        let closing = -motion.dot(normal);
+        if distance <= radius || closing <= 0.0 {
+            return None;
+        }
+        let time = (distance - radius) / closing;
+        let along = (center + motion * time - a).dot(b - a) / (b - a).length_squared();
+        (time <= 1.0 && (0.0..=1.0).contains(&along)).then(|| SweepHit {
+            time,
+            point: a + (b - a) * along,
+            normal,
+        })
+    });
+    // ...or against its rounded ends
+    [side_hit, sweep_circle_circle(center, radius, motion, a, 0.0), sweep_circle_circle(center, radius, motion, b, 0.0)]
+        .into_iter()
+        .flatten()
+        .min_by(|x, y| x.time.total_cmp(&y.time))
+}
+
+/// First time a circle of `radius` at `center`, moving by `motion`, touches `aabb`.
+/// Circles already touching aren't reported.
+pub fn sweep_circle_aabb(center: Vec2, radius: f32, motion: Vec2, aabb: Aabb2d) -> Option<SweepHit> {
+    if aabb.closest_point(center).distance(center) <= radius {
+        return None;
+    }
+    // From outside, the box's radius-wide rounded outline is reached through one of its edges
+    let Aabb2d { min, max } = aabb;
+    let corners = [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)];
+    (0..4)
+        .filter_map(|i| sweep_circle_segment(center, radius, motion, corners[i], corners[(i + 1) % 4]))
+        .min_by(|x, y| x.time.total_cmp(&y.time))
+}
+
+/// First time a circle of `radius` at `center`, moving by `motion`, touches `shape` at `isometry`
+pub fn sweep_circle(center: Vec2, radius: f32, motion: Vec2, shape: &ColliderShape, isometry: Isometry2d) -> Option<SweepHit> {
+    match shape.placed(isometry) {
+        Placed::Rounded { a, b, radius: target_radius } => {
+            // Growing the moving circle by the target's radius leaves a bare segment
+            sweep_circle_segment(center, radius + target_radius, motion, a, b).map(|hit| SweepHit {
+                point: hit.point + hit.normal * target_radius,
+                ..hit
+            })
+        }
+        Placed::Box { center: box_center, rotation, half_size } => {
+            // In the box's own frame it is axis-aligned at the origin
+            let inverse = rotation.inverse();
+            let local = sweep_circle_aabb(
+                inverse * (center - box_center),
+                radius,
+                inverse * motion,
+                Aabb2d::new(Vec2::ZERO, half_size),
+            )?;
+            Some(SweepHit {
+                time: local.time,
+                point: box_center + rotation * local.point,
+                normal: rotation * local.normal,
+            })
+        }
+    }
+}
+
+/// Where a ray first meets a collider
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    pub entity: Entity,
+    pub point: Vec2,
+    /// Surface normal at `point`, facing back along the ray
+    pub normal: Vec2,
+    /// From the ray's origin to `point` (m)
+    pub distance: f32,
+}
+
+/// First of `colliders` hit by the ray from `origin` along `direction` within
+/// `max_distance`. Colliders the origin is inside of aren't hit.
+pub fn raycast(
+    colliders: &[(Entity, ColliderShape, Isometry2d)],
+    origin: Vec2,
+    direction: Vec2,
+    max_distance: f32,
+) -> Option<RayHit> {
+    raycast_where(colliders, origin, direction, max_distance, |_| true)
+}
+
+/// [`raycast`], only against the colliders for which `keep` is true (to skip the
+/// caster's own collider, say)
+pub fn raycast_where(
+    colliders: &[(Entity, ColliderShape, Isometry2d)],
+    origin: Vec2,
+    direction: Vec2,
+    max_distance: f32,
+    keep: impl Fn(Entity) -> bool,
+) -> Option<RayHit> {
+    let motion = direction.try_normalize()? * max_distance;
+    let end = origin + motion;
+    let reach = Aabb2d {
+        min: origin.min(end),
+        max: origin.max(end),
+    };
+    colliders
+        .iter()
+        .filter(|(entity, shape, isometry)| keep(*entity) && shape.aabb_at(*isometry).intersects(&reach))
+        // A ray is a sweep of a circle with no radius
+        .filter_map(|(entity, shape, isometry)| {
+            sweep_circle(origin, 0.0, motion, shape, *isometry).map(|hit| RayHit {
+                entity: *entity,
+                point: hit.point,
+                normal: hit.normal,
+                distance: hit.time * max_distance,
+            })
+        })
+        .min_by(|a, b| a.distance.total_cmp(&b.distance))
+}
+
+/// System parameter for casting rays against every [`ColliderShape`] in the world
+#[derive(SystemParam)]
+pub struct Raycaster<'w, 's> {
+    colliders: Query<'w, 's, (Entity, &'static ColliderShape, &'static Transform, Option<&'static Position>)>,
+}
+
+impl Raycaster<'_, '_> {
+    /// Every collider with its placement, as [`raycast`] takes them
+    pub fn colliders(&self) -> Vec<(Entity, ColliderShape, Isometry2d)> {
+        self.colliders
+            .iter()
+            .map(|(entity, shape, transform, position)| {
+                let mut placement = isometry(transform);
+                if let Some(position) = position {
+                    placement.translation = position.0;
+                }
+                (entity, *shape, placement)
+            })
+            .collect()
+    }
+
+    /// [`raycast`] against every collider
+    pub fn raycast(&self, origin: Vec2, direction: Vec2, max_distance: f32) -> Option<RayHit> {
+        raycast(&self.colliders(), origin, direction, max_distance)
+    }
+
+    /// [`raycast_where`] against every collider
+    pub fn raycast_where(
+        &self,
+        origin: Vec2,
+        direction: Vec2,
+        max_distance: f32,
+        keep: impl Fn(Entity) -> bool,
+    ) -> Option<RayHit> {
+        raycast_where(&self.colliders(), origin, direction, max_distance, keep)
+    }
+}
+
+/// How bouncy, how rough and how dense a collider is
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component, Default)]
+pub struct PhysicsMaterial {
+    /// Fraction of the approach speed kept after a bounce: 0 stops dead, 1 is perfectly elastic
+    pub restitution: f32,
+    /// Coulomb friction coefficient: the tangential impulse is at most this times the normal impulse
+    pub friction: f32,
+    /// Mass per volume (kg/m³), for bodies with [`MassFromMaterial`]
+    pub density: f32,
+}
+
+impl Default for PhysicsMaterial {
+    fn default() -> Self {
+        Self::new(0.5, 0.5)
+    }
+}
+
+impl PhysicsMaterial {
+    pub const RUBBER: Self = Self::with_all(0.8, 0.9, 1100.0);
+    pub const STEEL: Self = Self::with_all(0.6, 0.5, 7850.0);
+    pub const ICE: Self = Self::with_all(0.3, 0.03, 917.0);
+    pub const WOOD: Self = Self::with_all(0.5, 0.4, 700.0);
+    pub const PRESETS: [(&'static str, Self); 4] = [
+        ("Rubber", Self::RUBBER),
+        ("Steel", Self::STEEL),
+        ("Ice", Self::ICE),
+        ("Wood", Self::WOOD),
+    ];
+
+    /// A surface with the density of water
+    pub fn new(restitution: f32, friction: f32) -> Self {
+        Self::with_all(restitution, friction, 1000.0)
+    }
+
+    const fn with_all(restitution: f32, friction: f32, density: f32) -> Self {
+        Self {
+            restitution,
+            friction,
+            density,
+        }
+    }
+
+    pub fn with_density(mut self, density: f32) -> Self {
+        self.density = density;
+        self
+    }
+
+    /// Restitution and friction of two surfaces in contact: the bouncier of the two
+    /// restitutions and the geometric mean of the frictions
+    pub fn combine(&self, other: &Self) -> (f32, f32) {
+        (self.restitution.max(other.restitution), (self.friction * other.friction).sqrt())
+    }
+}
+
+/// Sets a body's [`Mass`] (and [`MomentOfInertia`], if it has one) from its
+/// [`ColliderShape`]'s volume and its [`PhysicsMaterial`]'s density
+#[derive(Component, Default, Debug, Clone, Copy, Reflect)]
+#[reflect(Component, Default)]
+#[require(Mass)]
+pub struct MassFromMaterial;
+
+/// System to recompute the mass of [`MassFromMaterial`] bodies whose shape or material changed
+#[allow(clippy::type_complexity)]
+pub fn compute_material_masses(
+    mut bodies: Query<
+        (&ColliderShape, &PhysicsMaterial, &mut Mass, Option<&mut MomentOfInertia>),
+        (
+            With<MassFromMaterial>,
+            Or<(Changed<ColliderShape>, Changed<PhysicsMaterial>, Added<MassFromMaterial>)>,
+        ),
+    >,
+) {
+    for (shape, material, mut mass, inertia) in &mut bodies {
+        let computed = shape.volume() * material.density;
+        // Massless shapes would get infinite accelerations; leave them as they were
+        if computed <= 0.0 {
+            continue;
+        }
+        mass.0 = computed;
+        if let Some(mut inertia) = inertia {
+            *inertia = shape.moment_of_inertia(computed);
+        }
+    }
+}
+
+/// Tuning for [`resolve_collisions`]
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct ContactSolver {
+    /// Passes over every contact per step; more lets impulses travel through a stack
+    pub iterations: usize,
+    /// Overlap left alone, so resting contacts stay touching instead of jittering (m)
+    pub slop: f32,
+    /// Fraction of the remaining overlap pushed apart each step
+    pub correction: f32,
+    /// Contacts approaching slower than this don't bounce, so resting bodies settle (m/s)
+    pub resting_speed: f32,
+}
+
+impl Default for ContactSolver {
+    fn default() -> Self {
+        Self {
+            iterations: 8,
+            slop: 0.01,
+            correction: 0.8,
+            resting_speed: 0.5,
+        }
+    }
+}
+
+/// Sent by [`resolve_collisions`] for every contact it resolved in a step
+#[derive(Message, Debug, Clone, Copy, PartialEq)]
+pub struct CollisionEvent {
+    pub a: Entity,
+    pub b: Entity,
+    /// Halfway between the two surfaces
+    pub point: Vec2,
+    /// Unit normal from `a` to `b`
+    pub normal: Vec2,
+    /// Overlap before it was pushed apart (m)
+    pub depth: f32,
+    /// Total impulse on `b`, bounce and friction, with its reaction on `a` (N·s); zero
+    /// for separating contacts, and for resting ones just what holds them up
+    pub impulse: Vec2,
+}
+
+/// A collider's state while contacts are being resolved
+struct Body {
+    position: Vec2,
+    velocity: Vec2,
+    /// Zero for static colliders, which nothing can move
+    inverse_mass: f32,
+    material: PhysicsMaterial,
+}
+
+/// System to resolve overlapping colliders with impulses
+///
+/// Bodies with both `Velocity` and `Mass` are moved; any other collider is static, and
+/// so is a [`Sleeping`] body until something hits it hard enough to wake it.
+/// Along the contact normal an impulse stops the approach and adds the bounce given by
+/// the restitution; along the surface, friction removes sliding speed up to μ times
+/// the normal impulse. Remaining overlap is then pushed apart, and a [`CollisionEvent`]
+/// is sent for each contact. Colliders on different [`ComparisonSide`]s belong to
+/// different simulations and never touch.
+#[allow(clippy::type_complexity)]
+pub fn resolve_collisions(
+    mut colliders: Query<(
+        Entity,
+        &ColliderShape,
+        &mut Transform,
+        Option<&mut Position>,
+        Option<&mut Velocity>,
+        Option<&Mass>,
+        Option<&PhysicsMaterial>,
+        Option<&ComparisonSide>,
+        Has<Sleeping>,
+    )>,
+    solver: Res<ContactSolver>,
+    broadphase: Res<Broadphase>,
+    mut events: MessageWriter<CollisionEvent>,
+    mut commands: Commands,
+) {
+    let mut shapes = Vec::new();
+    let mut bodies = Vec::new();
+    let mut sides = Vec::new();
+    let mut asleep = Vec::new();
+    let mut index = HashMap::new();
+    for (entity, shape, transform, position, velocity, mass, material, side, sleeping) in &colliders {
+        let mut placement = isometry(transform);
+        if let Some(position) = position {
+            placement.translation = position.0;
+        }
+        let inverse_mass = match (velocity, mass) {
+            (Some(_), Some(mass)) if mass.0 > 0.0 => 1.0 / mass.0,
+            _ => 0.0,
+        };
+        index.insert(entity, bodies.len());
+        shapes.push((entity, *shape, placement));
+        bodies.push(Body {
+            position: placement.translation,
+            velocity: velocity.map_or(Vec2::ZERO, |velocity| velocity.0),
+            inverse_mass,
+            material: material.copied().unwrap_or_default(),
+        });
+        sides.push(side.copied());
+        asleep.push(sleeping);
+    }
+
+    // Pairs where nothing is awake and free to move have nothing to resolve
+    let awake = |i: usize| bodies[i].inverse_mass > 0.0 && !asleep[i];
+    let contacts: Vec<(usize, usize, Contact)> = find_contacts_where(&shapes, *broadphase, |a, b| awake(a) || awake(b))
+        .into_iter()
+        .map(|(a, b, contact)| (index[&a], index[&b], contact))
+        .filter(|&(a, b, _)| !matches!((sides[a], sides[b]), (Some(side_a), Some(side_b)) if side_a != side_b))
+        .collect();
+
+    // Hits hard enough to bounce wake a sleeper; otherwise it holds still like a wall
+    for &(a, b, Contact { normal, .. }) in &contacts {
+        let approach = (bodies[b].velocity - bodies[a].velocity).dot(normal);
+        for sleeper in [a, b] {
+            if asleep[sleeper] && -approach >= solver.resting_speed {
+                asleep[sleeper] = false;
+                commands.entity(shapes[sleeper].0).try_remove::<Sleeping>();
+            }
+        }
+    }
+    for (body, &asleep) in bodies.iter_mut().zip(&asleep) {
+        if asleep {
+            body.inverse_mass = 0.0;
+        }
+    }
+    let contacts: Vec<(usize, usize, Contact)> = contacts
+        .into_iter()
+        .filter(|&(a, b, _)| bodies[a].inverse_mass + bodies[b].inverse_mass > 0.0)
+        .collect();
+
+    // Impulse given to b at each contact, summed over the iterations
+    let mut impulses = vec![Vec2::ZERO; contacts.len()];
+    for _ in 0..solver.iterations {
+        for (&(a, b, Contact { normal, .. }), total_impulse) in contacts.iter().zip(&mut impulses) {
+            let total_inverse_mass = bodies[a].inverse_mass + bodies[b].inverse_mass;
+            let approach = (bodies[b].velocity - bodies[a].velocity).dot(normal);
+            if approach >= 0.0 {
+                continue;
+            }
+            let (restitution, friction) = bodies[a].material.combine(&bodies[b].material);
+            let restitution = if -approach < solver.resting_speed { 0.0 } else { restitution };
+            let normal_impulse = -(1.0 + restitution) * approach / total_inverse_mass;
+            apply_impulse(&mut bodies, a, b, normal * normal_impulse);
+
+            // Friction opposes the sliding left after the normal impulse
+            let relative = bodies[b].velocity - bodies[a].velocity;
+            let tangent = (relative - normal * relative.dot(normal)).normalize_or_zero();
+            let limit = friction * normal_impulse;
+            let friction_impulse = (-relative.dot(tangent) / total_inverse_mass).clamp(-limit, limit);
+            apply_impulse(&mut bodies, a, b, tangent * friction_impulse);
+            *total_impulse += normal * normal_impulse + tangent * friction_impulse;
+        }
+    }
+
+    events.write_batch(contacts.iter().zip(&impulses).map(|(&(a, b, contact), &impulse)| CollisionEvent {
+        a: shapes[a].0,
+        b: shapes[b].0,
+        point: contact.point,
+        normal: contact.normal,
+        depth: contact.depth,
+        impulse,
+    }));
+
+    for &(a, b, Contact { normal, depth, .. }) in &contacts {
+        let (inverse_mass_a, inverse_mass_b) = (bodies[a].inverse_mass, bodies[b].inverse_mass);
+        let push = normal * ((depth - solver.slop).max(0.0) * solver.correction / (inverse_mass_a + inverse_mass_b));
+        bodies[a].position -= push * inverse_mass_a;
+        bodies[b].position += push * inverse_mass_b;
+    }
+
+    for (entity, _, mut transform, position, velocity, ..) in &mut colliders {
+        let body = &bodies[index[&entity]];
+        if body.inverse_mass == 0.0 {
+            continue;
+        }
+        match position {
+            Some(mut position) => position.0 = body.position,
+            None => transform.translation = body.position.extend(transform.translation.z),
+        }
+        if let Some(mut velocity) = velocity {
+            velocity.0 = body.velocity;
+        }
+    }
+}
+
+/// Apply `impulse` to `b` and its reaction to `a`
+fn apply_impulse(bodies: &mut [Body], a: usize, b: usize, impulse: Vec2) {
+    bodies[a].velocity -= impulse * bodies[a].inverse_mass;
+    bodies[b].velocity += impulse * bodies[b].inverse_mass;
+}
+
+/// Sweep this circle along its motion each step, so it stops at thin static colliders
+/// it would otherwise jump over; meant for a few fast bodies
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub struct ContinuousCollision {
+    /// Position at the start of the current step
+    previous: Option<Vec2>,
+}
+
+/// System to remember where each [`ContinuousCollision`] body starts the step
+pub fn record_swept_positions(
+    mut bodies: Query<(&mut ContinuousCollision, &Transform, Option<&Position>)>,
+) {
+    for (mut swept, transform, position) in &mut bodies {
+        swept.previous = Some(position.map_or(transform.translation.truncate(), |position| position.0));
+    }
+}
+
+/// System to move [`ContinuousCollision`] circles back to where their motion this step
+/// first touched a static collider; [`resolve_collisions`] then answers the contact
+#[allow(clippy::type_complexity)]
+pub fn sweep_continuous_collisions(
+    mut colliders: Query<(
+        Entity,
+        &ColliderShape,
+        &mut Transform,
+        Option<&mut Position>,
+        Option<&ContinuousCollision>,
+        Has<Velocity>,
+        Has<Mass>,
+        Option<&ComparisonSide>,
+    )>,
+    solver: Res<ContactSolver>,
+) {
+    let mut swept = Vec::new();
+    let mut obstacles = Vec::new();
+    for (entity, shape, transform, position, continuous, has_velocity, has_mass, side) in &colliders {
+        let mut placement = isometry(transform);
+        if let Some(position) = position {
+            placement.translation = position.0;
+        }
+        if let (Some(start), ColliderShape::Circle { radius }) = (continuous.and_then(|c| c.previous), shape) {
+            swept.push((entity, start, placement.translation, *radius, side.copied()));
+        } else if !(has_velocity && has_mass) {
+            obstacles.push((*shape, placement, side.copied()));
+        }
+    }
+
+    for (entity, start, end, radius, side) in swept {
+        let motion = end - start;
+        let hit = obstacles
+            .iter()
+            .filter(|(.., other_side)| !matches!((side, other_side), (Some(a), Some(b)) if a != *b))
+            .filter_map(|(shape, placement, _)| sweep_circle(start, radius, motion, shape, *placement))
+            .min_by(|a, b| a.time.total_cmp(&b.time));
+        let (Some(hit), Ok((.., mut transform, position, _, _, _, _))) = (hit, colliders.get_mut(entity)) else {
+            continue;
+        };
+        // Stop just inside the surface, so the discrete test sees the contact
+        let stop = start + motion * hit.time - hit.normal * (solver.slop / 2.0);
+        match position {
+            Some(mut position) => position.0 = stop,
+            None => transform.translation = stop.extend(transform.translation.z),
+        }
+    }
+}
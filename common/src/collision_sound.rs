@@ -0,0 +1,151 @@
+//! Impact sounds for collisions
+//!
+//! [`CollisionSoundPlugin`] plays a short knock for every [`CollisionEvent`] that is a
+//! real hit rather than a body resting on another: one that changes the lighter body's
+//! speed by more than the [`ContactSolver`]'s resting speed. Harder hits are louder and
+//! lower. The knock is synthesized when the plugin is added, so chapters need no audio
+//! files, and [`CollisionSounds`] can swap in a recorded one.
+//!
+//! A flock or a pile of grains collides hundreds of times a second, so at most
+//! `max_per_second` sounds start (with bursts of up to `burst`), and when more hits
+//! arrive than that allows, the hardest ones are heard.
+use bevy::audio::{AudioSource, Volume};
+use bevy::prelude::*;
+
+use crate::collision::{CollisionEvent, ContactSolver};
+use crate::Mass;
+
+/// Sample rate of the synthesized knock (Hz)
+const SAMPLE_RATE: u32 = 44_100;
+
+/// Plays [`CollisionSounds`]' sound for collisions; add it after `DefaultPlugins`, which
+/// brings the audio output
+pub struct CollisionSoundPlugin;
+
+impl Plugin for CollisionSoundPlugin {
+    fn build(&self, app: &mut App) {
+        let knock = app.world_mut().resource_mut::<Assets<AudioSource>>().add(AudioSource {
+            bytes: knock_wav().into(),
+        });
+        app.insert_resource(CollisionSounds::new(knock))
+            .add_systems(Update, play_collision_sounds);
+    }
+}
+
+/// Which sound collisions make, how loud, and how often
+#[derive(Resource, Debug, Clone)]
+pub struct CollisionSounds {
+    pub sound: Handle<AudioSource>,
+    /// Volume of the hardest hits; 0 mutes
+    pub volume: f32,
+    /// Impulse (N·s) at which a hit reaches full volume; softer hits are quieter
+    pub loud_impulse: f32,
+    /// Playback speed of the softest and the hardest hits
+    pub pitch: (f32, f32),
+    /// Sounds started per second on average
+    pub max_per_second: f32,
+    /// Sounds that may start at once after a quiet spell
+    pub burst: f32,
+    /// Sounds that may start now; refills at `max_per_second` up to `burst`
+    budget: f32,
+}
+
+impl CollisionSounds {
+    pub fn new(sound: Handle<AudioSource>) -> Self {
+        Self {
+            sound,
+            volume: 0.5,
+            loud_impulse: 10.0,
+            pitch: (1.4, 0.7),
+            max_per_second: 20.0,
+            burst: 4.0,
+            budget: 4.0,
+        }
+    }
+
+    /// Volume and playback speed for a hit with this impulse
+    pub fn volume_and_pitch(&self, impulse: f32) -> (f32, f32) {
+        // Loudness is heard roughly logarithmically, so the square root spreads soft hits
+        let strength = (impulse / self.loud_impulse).clamp(0.0, 1.0).sqrt();
+        (self.volume * strength, self.pitch.0.lerp(self.pitch.1, strength))
+    }
+}
+
+/// System to start a sound for each hard enough collision, the hardest first, within
+/// the rate limit
+pub fn play_collision_sounds(
+    mut collisions: MessageReader<CollisionEvent>,
+    mut sounds: ResMut<CollisionSounds>,
+    masses: Query<&Mass>,
+    solver: Option<Res<ContactSolver>>,
+    time: Res<Time<Real>>,
+    mut commands: Commands,
+) {
+    sounds.budget = (sounds.budget + sounds.max_per_second * time.delta_secs()).min(sounds.burst);
+    let resting_speed = solver.map_or(0.0, |solver| solver.resting_speed);
+    let mut hits: Vec<f32> = collisions
+        .read()
+        .map(|collision| {
+            // Static colliders have no mass; the lighter moving body feels the hit most
+            let mass = [collision.a, collision.b]
+                .into_iter()
+                .filter_map(|entity| masses.get(entity).ok())
+                .map(|mass| mass.0)
+                .fold(f32::INFINITY, f32::min);
+            (collision.impulse.length(), mass)
+        })
+        .filter(|&(impulse, mass)| impulse > mass * resting_speed)
+        .map(|(impulse, _)| impulse)
+        .collect();
+    if sounds.volume <= 0.0 || sounds.budget < 1.0 {
+        return;
+    }
+    hits.sort_unstable_by(|a, b| b.total_cmp(a));
+    for impulse in hits.into_iter().take(sounds.budget as usize) {
+        let (volume, pitch) = sounds.volume_and_pitch(impulse);
+        commands.spawn((
+            AudioPlayer(sounds.sound.clone()),
+            PlaybackSettings::DESPAWN
+                .with_volume(Volume::Linear(volume))
+                .with_speed(pitch),
+        ));
+        sounds.budget -= 1.0;
+    }
+}
+
+/// A 60 ms knock as a 16-bit mono WAV file: a decaying low tone under a quick burst of
+/// noise, like a ball hitting wood
+fn knock_wav() -> Vec<u8> {
+    let count = SAMPLE_RATE * 60 / 1000;
+    // A fixed linear congruential generator, so the noise is the same every run
+    let mut seed = 0x2545_f491_u32;
+    let samples = (0..count).map(|i| {
+        let t = i as f32 / SAMPLE_RATE as f32;
+        seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        let noise = (seed >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0;
+        let tone = (std::f32::consts::TAU * 180.0 * t).sin() * (-t / 0.02).exp();
+        let click = noise * (-t / 0.004).exp();
+        ((0.7 * tone + 0.3 * click) * i16::MAX as f32) as i16
+    });
+
+    let data_size = count * 2;
+    let mut wav = Vec::with_capacity(44 + data_size as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_size).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    // PCM, one channel
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    // Bytes per frame, bits per sample
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_size.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
@@ -0,0 +1,244 @@
+//! Colormaps, and coloring bodies by a quantity
+//!
+//! [`Colormap`] maps a value in 0..1 to a color, on the CPU with [`Colormap::sample`]
+//! and on the GPU in `shaders/field.wgsl` for [`FieldMaterial`](crate::field::FieldMaterial),
+//! with the same polynomial fits so both agree. Viridis and plasma are perceptually
+//! uniform, inferno suits temperatures, and the cool-warm diverging map suits signed
+//! values like charge.
+//!
+//! A body with a [`ColorBy`] and its own `MeshMaterial2d<ColorMaterial>` is recolored
+//! every frame by [`ColorByPlugin`] from its speed, kinetic energy, [`Charge`] or
+//! [`Temperature`]. Without a fixed range, the range is the spread of that quantity
+//! over every body colored by it, so the colormap always spans what's on screen.
+//! [`ColorBy::with_shades`] instead swaps the body's material for one of a few shared
+//! ones, for crowds too big for a material each.
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+
+use crate::{Charge, Mass, Temperature, Velocity};
+
+/// Recolors every [`ColorBy`] body
+pub struct ColorByPlugin;
+
+impl Plugin for ColorByPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SharedShades>()
+            .add_systems(PostUpdate, color_bodies);
+    }
+}
+
+/// Polynomial fits of the matplotlib maps (Matt Zucker), lowest power first
+#[allow(clippy::excessive_precision)]
+const VIRIDIS: [Vec3; 7] = [
+    Vec3::new(0.2777273, 0.0054073, 0.3340998),
+    Vec3::new(0.1050930, 1.4046135, 1.3845902),
+    Vec3::new(-0.3308618, 0.2148476, 0.0950952),
+    Vec3::new(-4.6342305, -5.7991010, -19.3324410),
+    Vec3::new(6.2282699, 14.1799334, 56.6905526),
+    Vec3::new(4.7763850, -13.7451454, -65.3530326),
+    Vec3::new(-5.4354559, 4.6458526, 26.3124352),
+];
+#[allow(clippy::excessive_precision)]
+const INFERNO: [Vec3; 7] = [
+    Vec3::new(0.0002189, 0.0016510, -0.0194809),
+    Vec3::new(0.1065134, 0.5639564, 3.9327124),
+    Vec3::new(11.6024931, -3.9728540, -15.9423941),
+    Vec3::new(-41.7039961, 17.4363989, 44.3541452),
+    Vec3::new(77.1629357, -33.4023589, -81.8073093),
+    Vec3::new(-71.3194282, 32.6260643, 73.2095199),
+    Vec3::new(25.1311262, -12.2426690, -23.0703250),
+];
+#[allow(clippy::excessive_precision)]
+const PLASMA: [Vec3; 7] = [
+    Vec3::new(0.0587323, 0.0233367, 0.5433402),
+    Vec3::new(2.1765146, 0.2383834, 0.7539605),
+    Vec3::new(-2.6894605, -7.4558511, 3.1107999),
+    Vec3::new(6.1303483, 42.3461881, -28.5188547),
+    Vec3::new(-11.1074362, -82.6663111, 60.1398477),
+    Vec3::new(10.0230656, 71.4136177, -54.0721866),
+    Vec3::new(-3.6587138, -22.9315347, 18.1919078),
+];
+/// Ends and middle of Moreland's cool-warm map
+const COOL: Vec3 = Vec3::new(0.23, 0.30, 0.75);
+const NEUTRAL: Vec3 = Vec3::splat(0.87);
+const WARM: Vec3 = Vec3::new(0.71, 0.02, 0.15);
+
+/// A map from 0..1 to colors; the order matches the shader's indices
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Colormap {
+    #[default]
+    Viridis,
+    /// Black through red to yellow, for temperatures
+    Inferno,
+    /// Cool-warm: blue below the middle of the range and red above, for signed values
+    Diverging,
+    /// Blue through magenta to yellow
+    Plasma,
+}
+
+impl Colormap {
+    pub const ALL: [Colormap; 4] = [Colormap::Viridis, Colormap::Inferno, Colormap::Diverging, Colormap::Plasma];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Colormap::Viridis => "viridis",
+            Colormap::Inferno => "inferno",
+            Colormap::Diverging => "coolwarm",
+            Colormap::Plasma => "plasma",
+        }
+    }
+
+    /// sRGB color for `t`, clamped to 0..1
+    pub fn sample(&self, t: f32) -> Color {
+        let x = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let polynomial = |coefficients: &[Vec3; 7]| coefficients.iter().rev().fold(Vec3::ZERO, |sum, c| sum * x + *c);
+        let rgb = match self {
+            Colormap::Viridis => polynomial(&VIRIDIS),
+            Colormap::Inferno => polynomial(&INFERNO),
+            Colormap::Plasma => polynomial(&PLASMA),
+            Colormap::Diverging if x < 0.5 => COOL.lerp(NEUTRAL, x * 2.0),
+            Colormap::Diverging => NEUTRAL.lerp(WARM, x * 2.0 - 1.0),
+        };
+        let rgb = rgb.clamp(Vec3::ZERO, Vec3::ONE);
+        Color::srgb(rgb.x, rgb.y, rgb.z)
+    }
+
+    /// Color for `value` with `min` and `max` at the ends of the map
+    pub fn sample_range(&self, value: f32, min: f32, max: f32) -> Color {
+        let span = max - min;
+        self.sample(if span.abs() > f32::EPSILON { (value - min) / span } else { 0.5 })
+    }
+}
+
+/// What a [`ColorBy`] body is colored by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorQuantity {
+    /// |v| (m/s)
+    Speed,
+    /// ½mv² (J), with a mass of 1 kg for bodies without [`Mass`]
+    KineticEnergy,
+    /// [`Charge`] (C)
+    Charge,
+    /// [`Temperature`] (K)
+    Temperature,
+}
+
+/// Colors a body's own material by a quantity through a [`Colormap`]
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct ColorBy {
+    pub quantity: ColorQuantity,
+    pub colormap: Colormap,
+    /// Values at the ends of the colormap; `None` spans every body colored by the quantity
+    pub range: Option<(f32, f32)>,
+    /// Steps of the colormap shared between bodies; `None` recolors the body's own material
+    pub shades: Option<u32>,
+}
+
+impl ColorBy {
+    pub fn new(quantity: ColorQuantity) -> Self {
+        Self {
+            quantity,
+            colormap: Colormap::Viridis,
+            range: None,
+            shades: None,
+        }
+    }
+
+    pub fn speed() -> Self {
+        Self::new(ColorQuantity::Speed)
+    }
+
+    pub fn kinetic_energy() -> Self {
+        Self::new(ColorQuantity::KineticEnergy)
+    }
+
+    /// Cool-warm and centered on zero, so positive and negative charges stand apart
+    pub fn charge(max: f32) -> Self {
+        Self::new(ColorQuantity::Charge)
+            .with_colormap(Colormap::Diverging)
+            .with_range(-max, max)
+    }
+
+    pub fn temperature() -> Self {
+        Self::new(ColorQuantity::Temperature).with_colormap(Colormap::Inferno)
+    }
+
+    pub fn with_colormap(mut self, colormap: Colormap) -> Self {
+        self.colormap = colormap;
+        self
+    }
+
+    pub fn with_range(mut self, min: f32, max: f32) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    /// Pick one of `count` shared materials along the colormap instead of recoloring the
+    /// body's own, so any number of bodies adds only `count` materials
+    pub fn with_shades(mut self, count: u32) -> Self {
+        self.shades = Some(count.max(2));
+        self
+    }
+}
+
+/// Materials for [`ColorBy::with_shades`], by colormap, shade count and step
+#[derive(Resource, Default)]
+struct SharedShades(HashMap<(Colormap, u32, u32), Handle<ColorMaterial>>);
+
+/// System to set the color of each [`ColorBy`] body's material from its quantity
+#[allow(clippy::type_complexity)]
+fn color_bodies(
+    mut bodies: Query<(
+        &ColorBy,
+        &mut MeshMaterial2d<ColorMaterial>,
+        Option<&Velocity>,
+        Option<&Mass>,
+        Option<&Charge>,
+        Option<&Temperature>,
+    )>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut shades: ResMut<SharedShades>,
+) {
+    // Spread of each quantity, for the bodies without a fixed range
+    let mut spreads = [(f32::INFINITY, f32::NEG_INFINITY); 4];
+    let values: Vec<Option<f32>> = bodies
+        .iter()
+        .map(|(color_by, _, velocity, mass, charge, temperature)| {
+            let value = match color_by.quantity {
+                ColorQuantity::Speed => velocity.map(|velocity| velocity.0.length()),
+                ColorQuantity::KineticEnergy => velocity
+                    .map(|velocity| 0.5 * mass.map_or(1.0, |mass| mass.0) * velocity.0.length_squared()),
+                ColorQuantity::Charge => charge.map(|charge| charge.0),
+                ColorQuantity::Temperature => temperature.map(|temperature| temperature.0),
+            };
+            if let Some(value) = value {
+                let (min, max) = &mut spreads[color_by.quantity as usize];
+                *min = min.min(value);
+                *max = max.max(value);
+            }
+            value
+        })
+        .collect();
+
+    for ((color_by, mut material, ..), value) in bodies.iter_mut().zip(values) {
+        let Some(value) = value else {
+            continue;
+        };
+        let (min, max) = color_by.range.unwrap_or(spreads[color_by.quantity as usize]);
+        if let Some(count) = color_by.shades {
+            let span = max - min;
+            let t = if span.abs() > f32::EPSILON { ((value - min) / span).clamp(0.0, 1.0) } else { 0.5 };
+            let step = (t * (count - 1) as f32).round() as u32;
+            let shade = shades.0.entry((color_by.colormap, count, step)).or_insert_with(|| {
+                materials.add(color_by.colormap.sample(step as f32 / (count - 1) as f32))
+            });
+            // Only touch the handle when the shade changes, so the renderer isn't told
+            // every body changed every frame
+            if material.0 != *shade {
+                material.0 = shade.clone();
+            }
+        } else if let Some(material) = materials.get_mut(&material.0) {
+            material.color = color_by.colormap.sample_range(value, min, max);
+        }
+    }
+}
@@ -0,0 +1,216 @@
+//! Side-by-side comparison of two parameter sets
+//!
+//! With [`Comparison::enabled`] set, the window is split into a left and a right
+//! viewport. The chapter runs two instances of its physics in the same world, tagging
+//! each instance's entities with a [`ComparisonSide`]: the left instance uses the
+//! chapter's usual settings and the right one [`Comparison::right`]. Both advance on
+//! the same clock, so differences on screen come only from the parameters (drag vs no
+//! drag, Euler vs RK4, ...). Untagged entities, like the ground, appear in both views.
+use std::marker::PhantomData;
+
+use bevy::camera::visibility::RenderLayers;
+use bevy::camera::Viewport;
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, WindowResized};
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+
+/// Adds the split-screen cameras for [`Comparison<S>`]
+pub struct ComparisonPlugin<S: Clone + Send + Sync + 'static>(PhantomData<S>);
+
+impl<S: Clone + Send + Sync + 'static> Default for ComparisonPlugin<S> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<S: Clone + Send + Sync + 'static> Plugin for ComparisonPlugin<S> {
+    fn build(&self, app: &mut App) {
+        crate::add_egui(app);
+        app.add_observer(assign_render_layer)
+            .add_systems(
+                Update,
+                sync_comparison_cameras::<S>
+                    .run_if(resource_changed::<Comparison<S>>.or(on_message::<WindowResized>)),
+            )
+            .add_systems(PostUpdate, follow_main_camera)
+            .add_systems(EguiPrimaryContextPass, comparison_labels::<S>);
+    }
+}
+
+/// Which instance of the simulation an entity belongs to
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ComparisonSide {
+    #[default]
+    Left,
+    Right,
+}
+
+impl ComparisonSide {
+    /// Shared entities stay on layer 0, seen by both viewports
+    fn render_layer(&self) -> usize {
+        match self {
+            ComparisonSide::Left => 1,
+            ComparisonSide::Right => 2,
+        }
+    }
+}
+
+/// The second parameter set and whether the split screen is shown
+#[derive(Resource, Debug, Clone)]
+pub struct Comparison<S> {
+    pub enabled: bool,
+    /// Settings for the [`ComparisonSide::Right`] instance
+    pub right: S,
+    pub left_label: String,
+    pub right_label: String,
+}
+
+impl<S> Comparison<S> {
+    pub fn new(right: S) -> Self {
+        Self {
+            enabled: false,
+            right,
+            left_label: "A".to_string(),
+            right_label: "B".to_string(),
+        }
+    }
+
+    pub fn with_labels(mut self, left: impl Into<String>, right: impl Into<String>) -> Self {
+        self.left_label = left.into();
+        self.right_label = right.into();
+        self
+    }
+
+    /// The settings an entity on `side` should use, given the chapter's own settings
+    pub fn settings<'a>(&'a self, side: ComparisonSide, left: &'a S) -> &'a S {
+        match side {
+            ComparisonSide::Right if self.enabled => &self.right,
+            _ => left,
+        }
+    }
+}
+
+/// One of the two cameras drawing a half of the window
+#[derive(Component)]
+struct ComparisonCamera;
+
+fn assign_render_layer(insert: On<Insert, ComparisonSide>, sides: Query<&ComparisonSide>, mut commands: Commands) {
+    if let Ok(side) = sides.get(insert.entity) {
+        commands
+            .entity(insert.entity)
+            .insert(RenderLayers::layer(side.render_layer()));
+    }
+}
+
+/// The chapter's own camera, which keeps drawing the UI over the split screen
+fn is_main_camera(camera: &Camera, comparison: Option<&ComparisonCamera>) -> bool {
+    camera.is_active && comparison.is_none()
+}
+
+fn sync_comparison_cameras<S: Clone + Send + Sync + 'static>(
+    mut commands: Commands,
+    comparison: Res<Comparison<S>>,
+    mut resized: MessageReader<WindowResized>,
+    mut shown: Local<Option<bool>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    mut cameras: Query<(Entity, &mut Camera, &Transform, Option<&ComparisonCamera>), With<Camera2d>>,
+) {
+    // Settings panels touch the resource every frame, so only rebuild on a real change
+    let resized = resized.read().count() > 0;
+    if *shown == Some(comparison.enabled) && !resized {
+        return;
+    }
+    *shown = Some(comparison.enabled);
+
+    for (entity, _, _, comparison_camera) in &cameras {
+        if comparison_camera.is_some() {
+            commands.entity(entity).despawn();
+        }
+    }
+    let Some((main, mut main_camera, main_transform, _)) = cameras
+        .iter_mut()
+        .find(|(_, camera, _, comparison)| is_main_camera(camera, *comparison))
+    else {
+        return;
+    };
+
+    if !comparison.enabled {
+        main_camera.clear_color = ClearColorConfig::Default;
+        commands.entity(main).insert(RenderLayers::from_layers(&[0, 1]));
+        return;
+    }
+    let Ok(window) = window.single() else {
+        return;
+    };
+
+    // The main camera only draws the UI, after both halves
+    main_camera.clear_color = ClearColorConfig::None;
+    commands.entity(main).insert(RenderLayers::none());
+
+    let size = window.physical_size();
+    let half = UVec2::new(size.x / 2, size.y);
+    for (order, side, x) in [(-2, ComparisonSide::Left, 0), (-1, ComparisonSide::Right, half.x)] {
+        commands.spawn((
+            ComparisonCamera,
+            Camera2d,
+            Camera {
+                order,
+                // Viewports share the target, so only the first camera may clear it
+                clear_color: if order == -2 { ClearColorConfig::Default } else { ClearColorConfig::None },
+                viewport: Some(Viewport {
+                    physical_position: UVec2::new(x, 0),
+                    physical_size: half.max(UVec2::ONE),
+                    ..default()
+                }),
+                ..default()
+            },
+            *main_transform,
+            RenderLayers::from_layers(&[0, side.render_layer()]),
+        ));
+    }
+}
+
+/// Keep both halves looking where the chapter's camera looks, at the same zoom
+#[allow(clippy::type_complexity)]
+fn follow_main_camera(
+    main: Query<(&Camera, &Transform, &Projection), (With<Camera2d>, Without<ComparisonCamera>)>,
+    mut halves: Query<(&mut Transform, &mut Projection), With<ComparisonCamera>>,
+) {
+    let Some((_, main_transform, main_projection)) = main.iter().find(|(camera, ..)| is_main_camera(camera, None))
+    else {
+        return;
+    };
+    for (mut transform, mut projection) in &mut halves {
+        *transform = *main_transform;
+        *projection = main_projection.clone();
+    }
+}
+
+fn comparison_labels<S: Clone + Send + Sync + 'static>(
+    mut contexts: EguiContexts,
+    comparison: Res<Comparison<S>>,
+) -> Result {
+    if !comparison.enabled {
+        return Ok(());
+    }
+    let ctx = contexts.ctx_mut()?;
+    let screen = ctx.content_rect();
+    let painter = ctx.layer_painter(egui::LayerId::background());
+    painter.line_segment(
+        [screen.center_top(), screen.center_bottom()],
+        egui::Stroke::new(2.0, egui::Color32::from_gray(200)),
+    );
+    for (label, x) in [
+        (&comparison.left_label, screen.left() + screen.width() * 0.25),
+        (&comparison.right_label, screen.left() + screen.width() * 0.75),
+    ] {
+        painter.text(
+            egui::pos2(x, screen.top() + 8.0),
+            egui::Align2::CENTER_TOP,
+            label,
+            egui::FontId::proportional(18.0),
+            egui::Color32::WHITE,
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,273 @@
+//! Drop-down developer console for tweaking a running simulation
+//!
+//! The backquote (`~`) key opens a console that takes one command per line. The
+//! console handles `help`, `pause`, `resume`, `seed <n>`, `set <param> <value>` and
+//! `get <param>` itself; parameters are the ones a chapter registers in
+//! [`ConsoleRegistry`]. Any other registered command (`spawn boid 100`) is sent as a
+//! [`ConsoleCommand`] message for the chapter's own systems to act on.
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+
+use crate::share::SimulationSeed;
+use crate::timestep::PhysicsTime;
+
+/// Adds the console panel and command handling
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        crate::add_egui(app);
+        app.init_resource::<Console>()
+            .init_resource::<ConsoleRegistry>()
+            .add_message::<ConsoleCommand>()
+            .add_systems(Update, (toggle_console, run_console_commands).chain())
+            .add_systems(EguiPrimaryContextPass, console_panel);
+    }
+}
+
+/// Reads a parameter's current value
+pub type ParamGetter = Box<dyn Fn(&World) -> f32 + Send + Sync>;
+
+/// Writes a new value into a parameter
+pub type ParamSetter = Box<dyn Fn(&mut World, f32) + Send + Sync>;
+
+/// A named number that `set` and `get` can reach
+pub struct ConsoleParam {
+    pub name: String,
+    pub unit: String,
+    get: ParamGetter,
+    set: ParamSetter,
+}
+
+/// A chapter command, forwarded as a [`ConsoleCommand`]
+pub struct ConsoleCommandInfo {
+    pub name: String,
+    /// Shown by `help`, e.g. "spawn boid <count>"
+    pub usage: String,
+}
+
+/// The commands and parameters a chapter exposes to the console
+#[derive(Resource, Default)]
+pub struct ConsoleRegistry {
+    params: Vec<ConsoleParam>,
+    commands: Vec<ConsoleCommandInfo>,
+}
+
+impl ConsoleRegistry {
+    /// Make a value reachable with `set <name> <value>` and `get <name>`
+    pub fn with_param(
+        mut self,
+        name: impl Into<String>,
+        unit: impl Into<String>,
+        get: impl Fn(&World) -> f32 + Send + Sync + 'static,
+        set: impl Fn(&mut World, f32) + Send + Sync + 'static,
+    ) -> Self {
+        self.params.push(ConsoleParam {
+            name: name.into(),
+            unit: unit.into(),
+            get: Box::new(get),
+            set: Box::new(set),
+        });
+        self
+    }
+
+    /// Accept `name ...` and forward it as a [`ConsoleCommand`]
+    pub fn with_command(mut self, name: impl Into<String>, usage: impl Into<String>) -> Self {
+        self.commands.push(ConsoleCommandInfo {
+            name: name.into(),
+            usage: usage.into(),
+        });
+        self
+    }
+
+    pub fn params(&self) -> &[ConsoleParam] {
+        &self.params
+    }
+
+    pub fn commands(&self) -> &[ConsoleCommandInfo] {
+        &self.commands
+    }
+
+    fn param(&self, name: &str) -> Option<&ConsoleParam> {
+        self.params.iter().find(|param| param.name == name)
+    }
+}
+
+/// A registered chapter command typed into the console
+#[derive(Message, Debug, Clone, PartialEq)]
+pub struct ConsoleCommand {
+    pub name: String,
+    pub args: Vec<String>,
+}
+
+impl ConsoleCommand {
+    /// Parse argument `index`, e.g. the count in `spawn boid 100`
+    pub fn arg<T: std::str::FromStr>(&self, index: usize) -> Option<T> {
+        self.args.get(index)?.parse().ok()
+    }
+}
+
+/// One line of console output
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConsoleLine {
+    Input(String),
+    Output(String),
+    Error(String),
+}
+
+/// Console state: whether it is open, the line being typed and the scrollback
+#[derive(Resource, Default)]
+pub struct Console {
+    pub open: bool,
+    input: String,
+    lines: Vec<ConsoleLine>,
+    /// Submitted lines waiting to run with world access
+    pending: Vec<String>,
+}
+
+impl Console {
+    /// Run a line as if it had been typed, e.g. from a test or a startup script
+    pub fn submit(&mut self, line: impl Into<String>) {
+        self.pending.push(line.into());
+    }
+
+    pub fn lines(&self) -> &[ConsoleLine] {
+        &self.lines
+    }
+}
+
+fn toggle_console(keys: Res<ButtonInput<KeyCode>>, mut console: ResMut<Console>) {
+    if keys.just_pressed(KeyCode::Backquote) {
+        console.open = !console.open;
+    }
+}
+
+fn run_console_commands(world: &mut World) {
+    let pending = std::mem::take(&mut world.resource_mut::<Console>().pending);
+    for line in pending {
+        let output = world.resource_scope(|world, registry: Mut<ConsoleRegistry>| {
+            execute(world, &registry, &line)
+        });
+        let mut console = world.resource_mut::<Console>();
+        console.lines.push(ConsoleLine::Input(line));
+        match output {
+            Ok(text) if text.is_empty() => {}
+            Ok(text) => console.lines.push(ConsoleLine::Output(text)),
+            Err(text) => console.lines.push(ConsoleLine::Error(text)),
+        }
+    }
+}
+
+fn execute(world: &mut World, registry: &ConsoleRegistry, line: &str) -> Result<String, String> {
+    let mut words = line.split_whitespace();
+    let Some(name) = words.next() else {
+        return Ok(String::new());
+    };
+    let args: Vec<String> = words.map(str::to_string).collect();
+    let number = |index: usize| -> Result<f32, String> {
+        let arg = args.get(index).ok_or("missing value")?;
+        arg.parse().map_err(|_| format!("not a number: {arg}"))
+    };
+
+    match name {
+        "help" => Ok(help(registry)),
+        "pause" => {
+            match world.get_resource_mut::<PhysicsTime>() {
+                Some(mut physics_time) => physics_time.pause(),
+                None => world.resource_mut::<Time<Virtual>>().pause(),
+            }
+            Ok("paused".to_string())
+        }
+        "resume" => {
+            match world.get_resource_mut::<PhysicsTime>() {
+                Some(mut physics_time) => physics_time.unpause(),
+                None => world.resource_mut::<Time<Virtual>>().unpause(),
+            }
+            Ok("resumed".to_string())
+        }
+        "seed" => {
+            let Some(mut seed) = world.get_resource_mut::<SimulationSeed>() else {
+                return Err("this simulation has no seed".to_string());
+            };
+            match args.first() {
+                Some(arg) => {
+                    seed.0 = arg.parse().map_err(|_| format!("not a seed: {arg}"))?;
+                    Ok(format!("seed = {}", seed.0))
+                }
+                None => Ok(format!("seed = {}", seed.0)),
+            }
+        }
+        "get" | "set" => {
+            let param_name = args.first().ok_or("missing parameter name")?;
+            let param = registry
+                .param(param_name)
+                .ok_or_else(|| format!("unknown parameter: {param_name}"))?;
+            if name == "set" {
+                (param.set)(world, number(1)?);
+            }
+            Ok(format!("{} = {} {}", param.name, (param.get)(world), param.unit).trim_end().to_string())
+        }
+        _ if registry.commands.iter().any(|command| command.name == name) => {
+            world.write_message(ConsoleCommand {
+                name: name.to_string(),
+                args,
+            });
+            Ok(String::new())
+        }
+        _ => Err(format!("unknown command: {name} (try help)")),
+    }
+}
+
+fn help(registry: &ConsoleRegistry) -> String {
+    let mut text = String::from("help, pause, resume, seed [n], get <param>, set <param> <value>");
+    for command in &registry.commands {
+        text.push('\n');
+        text.push_str(&command.usage);
+    }
+    if !registry.params.is_empty() {
+        let names: Vec<&str> = registry.params.iter().map(|param| param.name.as_str()).collect();
+        text.push_str("\nparams: ");
+        text.push_str(&names.join(", "));
+    }
+    text
+}
+
+fn console_panel(mut contexts: EguiContexts, mut console: ResMut<Console>) -> Result {
+    if !console.open {
+        return Ok(());
+    }
+    egui::TopBottomPanel::top("console").resizable(true).show(contexts.ctx_mut()?, |ui| {
+        egui::ScrollArea::vertical()
+            .max_height(200.0)
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for line in &console.lines {
+                    match line {
+                        ConsoleLine::Input(text) => ui.monospace(format!("> {text}")),
+                        ConsoleLine::Output(text) => ui.monospace(text),
+                        ConsoleLine::Error(text) => {
+                            ui.colored_label(egui::Color32::LIGHT_RED, egui::RichText::new(text).monospace())
+                        }
+                    };
+                }
+            });
+
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut console.input)
+                .font(egui::TextStyle::Monospace)
+                .desired_width(f32::INFINITY),
+        );
+        // The key that opens the console shouldn't end up in the command
+        console.input.retain(|c| c != '`' && c != '~');
+        if response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+            let line = std::mem::take(&mut console.input);
+            if !line.trim().is_empty() {
+                console.submit(line);
+            }
+        }
+        if ui.memory(|memory| memory.focused().is_none()) {
+            response.request_focus();
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,141 @@
+//! Shared bar to pause, step, reset and speed up or slow down a simulation
+//!
+//! [`SimulationControlsPlugin`] shows a small bar at the top of the screen. With the
+//! shared physics, Pause, Step and the speed slider drive its [`PhysicsTime`], so only
+//! the simulation stops or slows down while the UI keeps running; while paused, Step
+//! runs exactly one physics tick. Chapters that step themselves get the same controls
+//! on `Time<Virtual>` instead, which stops `FixedUpdate` and everything driven by it.
+//! Either way slow motion keeps the same physics step size. Reset sends a [`ResetSimulation`]
+//! message, which each chapter answers by putting its own world back to the start.
+//! With the shared physics, Reverse flips the [`TimeDirection`] so the simulation runs
+//! backwards; the button's tooltip says whether the chosen integrator can retrace its
+//! steps exactly.
+use bevy::app::{FixedMain, RunFixedMainLoop, RunFixedMainLoopSystems};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+
+use crate::integrator::Integrator;
+use crate::physics::TimeDirection;
+use crate::timestep::PhysicsTime;
+
+/// Adds the control bar and the [`ResetSimulation`] message
+pub struct SimulationControlsPlugin;
+
+impl Plugin for SimulationControlsPlugin {
+    fn build(&self, app: &mut App) {
+        crate::add_egui(app);
+        app.add_message::<ResetSimulation>()
+            .init_resource::<PendingFixedSteps>()
+            .add_systems(EguiPrimaryContextPass, controls_bar)
+            .add_systems(
+                RunFixedMainLoop,
+                run_pending_fixed_steps.in_set(RunFixedMainLoopSystems::FixedMainLoop),
+            );
+    }
+}
+
+/// Sent when the reader asks to start the simulation over
+#[derive(Message, Debug, Clone, Copy, Default)]
+pub struct ResetSimulation;
+
+/// Fixed steps Step asked for while `Time<Virtual>` is paused
+#[derive(Resource, Debug, Default)]
+struct PendingFixedSteps(u32);
+
+/// Run `FixedMain` once for each pending step, advancing the fixed clock by a timestep
+/// as the fixed loop itself would
+fn run_pending_fixed_steps(world: &mut World) {
+    let steps = std::mem::take(&mut world.resource_mut::<PendingFixedSteps>().0);
+    if steps == 0 {
+        return;
+    }
+    for _ in 0..steps {
+        let timestep = world.resource::<Time<Fixed>>().timestep();
+        world.resource_mut::<Time<Fixed>>().advance_by(timestep);
+        *world.resource_mut::<Time>() = world.resource::<Time<Fixed>>().as_generic();
+        world.run_schedule(FixedMain);
+    }
+    *world.resource_mut::<Time>() = world.resource::<Time<Virtual>>().as_generic();
+}
+
+/// Slowest and fastest simulation speed offered, relative to real time
+const SPEED_RANGE: std::ops::RangeInclusive<f32> = 0.1..=4.0;
+
+fn controls_bar(
+    mut contexts: EguiContexts,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut pending_steps: ResMut<PendingFixedSteps>,
+    mut physics_time: Option<ResMut<PhysicsTime>>,
+    mut resets: MessageWriter<ResetSimulation>,
+    direction: Option<ResMut<TimeDirection>>,
+    integrator: Option<Res<Integrator>>,
+) -> Result {
+    egui::Window::new("Controls")
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 10.0])
+        .title_bar(false)
+        .resizable(false)
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.horizontal(|ui| {
+                let paused = match physics_time.as_deref() {
+                    Some(physics_time) => physics_time.is_paused(),
+                    None => virtual_time.is_paused(),
+                };
+                if ui.button(if paused { "▶ Resume" } else { "⏸ Pause" }).clicked() {
+                    match (physics_time.as_deref_mut(), paused) {
+                        (Some(physics_time), true) => physics_time.unpause(),
+                        (Some(physics_time), false) => physics_time.pause(),
+                        (None, true) => virtual_time.unpause(),
+                        (None, false) => virtual_time.pause(),
+                    }
+                }
+                if ui.add_enabled(paused, egui::Button::new("⏭ Step")).clicked() {
+                    match physics_time.as_deref_mut() {
+                        Some(physics_time) => physics_time.step(),
+                        // Paused virtual time never fills the fixed loop, so run it by hand
+                        None => pending_steps.0 += 1,
+                    }
+                }
+                if ui.button("⟲ Reset").clicked() {
+                    resets.write(ResetSimulation);
+                }
+                if let Some(mut direction) = direction {
+                    let label = match *direction {
+                        TimeDirection::Forward => "⏪ Reverse",
+                        TimeDirection::Backward => "⏩ Forward",
+                    };
+                    let hint = match integrator.as_deref() {
+                        Some(integrator) if integrator.is_time_reversible() => {
+                            format!(
+                                "{} retraces its path exactly under gravity and springs to fixed anchors",
+                                integrator.name()
+                            )
+                        }
+                        Some(integrator) => format!(
+                            "{} only roughly retraces its path; Velocity Verlet retraces it exactly under gravity and springs to fixed anchors",
+                            integrator.name()
+                        ),
+                        None => "Reverse every velocity".to_string(),
+                    };
+                    if ui.button(label).on_hover_text(hint).clicked() {
+                        *direction = direction.reversed();
+                    }
+                }
+                let mut speed = match physics_time.as_deref() {
+                    Some(physics_time) => physics_time.scale(),
+                    None => virtual_time.relative_speed(),
+                };
+                let response = ui.add(
+                    egui::Slider::new(&mut speed, SPEED_RANGE)
+                        .logarithmic(true)
+                        .text("× speed"),
+                );
+                if response.changed() {
+                    match physics_time.as_deref_mut() {
+                        Some(physics_time) => physics_time.set_scale(speed),
+                        None => virtual_time.set_relative_speed(speed),
+                    }
+                }
+            });
+        });
+    Ok(())
+}
@@ -0,0 +1,170 @@
+//! Named time series recorded while a simulation runs, for analysis elsewhere
+//!
+//! Systems push samples into [`DataLogger`] by name (`logger.log("height", y)`), and
+//! [`DataLoggerPlugin`] closes the row at the end of every `FixedUpdate` the physics
+//! ticked in, stamped with the physics clock, so each row holds the values logged
+//! during one step. The Data window pauses or clears
+//! the log and exports it as CSV (a `time` column, then one column per name in the
+//! order they were first logged) to `data-<n>.csv`, or as a download on the web.
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+
+use crate::capture::save_bytes;
+use crate::timestep::{physics_stepped, PhysicsTime};
+
+/// Adds the [`DataLogger`], its row per physics tick and the Data window
+pub struct DataLoggerPlugin;
+
+impl Plugin for DataLoggerPlugin {
+    fn build(&self, app: &mut App) {
+        crate::add_egui(app);
+        app.init_resource::<DataLogger>()
+            .add_systems(FixedLast, finish_row.run_if(physics_stepped))
+            .add_systems(EguiPrimaryContextPass, data_panel);
+    }
+}
+
+/// Samples logged so far, one row per physics tick
+#[derive(Resource, Debug)]
+pub struct DataLogger {
+    /// Whether new rows are kept
+    pub recording: bool,
+    /// Most rows kept; the oldest are dropped first
+    pub max_rows: usize,
+    columns: Vec<String>,
+    rows: std::collections::VecDeque<Row>,
+    /// Values logged since the last row was closed
+    pending: Vec<Option<f64>>,
+    exports: u32,
+}
+
+#[derive(Debug)]
+struct Row {
+    time: f32,
+    values: Vec<Option<f64>>,
+}
+
+impl Default for DataLogger {
+    fn default() -> Self {
+        Self {
+            recording: true,
+            // About half an hour at the default physics rate
+            max_rows: 100_000,
+            columns: Vec::new(),
+            rows: Default::default(),
+            pending: Vec::new(),
+            exports: 0,
+        }
+    }
+}
+
+impl DataLogger {
+    /// Record `value` under `name` for the current step; a later value for the same
+    /// name in the same step replaces it
+    pub fn log(&mut self, name: &str, value: impl Into<f64>) {
+        if !self.recording {
+            return;
+        }
+        let column = match self.columns.iter().position(|column| column == name) {
+            Some(column) => column,
+            None => {
+                self.columns.push(name.to_string());
+                self.columns.len() - 1
+            }
+        };
+        if self.pending.len() <= column {
+            self.pending.resize(column + 1, None);
+        }
+        self.pending[column] = Some(value.into());
+    }
+
+    /// Number of rows kept
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Every kept value of `name`, with the time it was logged at
+    pub fn series(&self, name: &str) -> impl Iterator<Item = (f32, f64)> + '_ {
+        let column = self.columns.iter().position(|column| column == name);
+        self.rows.iter().filter_map(move |row| {
+            let value = (*row.values.get(column?)?)?;
+            Some((row.time, value))
+        })
+    }
+
+    pub fn clear(&mut self) {
+        self.rows.clear();
+    }
+
+    /// The log as CSV; steps where a name wasn't logged leave its cell empty
+    pub fn to_csv(&self) -> String {
+        let mut csv = std::iter::once("time")
+            .chain(self.columns.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(",");
+        csv.push('\n');
+        // Rows where nothing was logged would only be blank lines
+        for row in self.rows.iter().filter(|row| row.values.iter().any(Option::is_some)) {
+            csv.push_str(&row.time.to_string());
+            for column in 0..self.columns.len() {
+                csv.push(',');
+                if let Some(Some(value)) = row.values.get(column) {
+                    csv.push_str(&value.to_string());
+                }
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Save the CSV to `data-<n>.csv` (native) or download it (web)
+    pub fn export(&mut self) {
+        self.exports += 1;
+        save_bytes(&format!("data-{}.csv", self.exports), self.to_csv().as_bytes(), "text/csv");
+    }
+
+    fn finish_row(&mut self, time: f32) {
+        let values = std::mem::take(&mut self.pending);
+        // Steps where nothing was logged leave no row
+        if !self.recording || values.iter().all(Option::is_none) {
+            return;
+        }
+        self.rows.push_back(Row { time, values });
+        while self.rows.len() > self.max_rows.max(1) {
+            self.rows.pop_front();
+        }
+    }
+}
+
+fn finish_row(mut logger: ResMut<DataLogger>, time: Option<Res<PhysicsTime>>, frame: Res<Time>) {
+    let time = time.map_or(frame.elapsed_secs(), |time| time.elapsed_secs());
+    logger.finish_row(time);
+}
+
+fn data_panel(mut contexts: EguiContexts, mut logger: ResMut<DataLogger>) -> Result {
+    egui::Window::new("Data")
+        .default_open(false)
+        .anchor(egui::Align2::RIGHT_TOP, [-10.0, 110.0])
+        .resizable(false)
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.label(format!("{} steps of {}", logger.len(), logger.columns().join(", ")));
+            ui.checkbox(&mut logger.recording, "Recording");
+            ui.horizontal(|ui| {
+                if ui.add_enabled(!logger.is_empty(), egui::Button::new("Export CSV")).clicked() {
+                    logger.export();
+                }
+                if ui.button("Clear").clicked() {
+                    logger.clear();
+                }
+            });
+        });
+    Ok(())
+}
@@ -0,0 +1,146 @@
+//! Toggleable overlay with frame rate, body count and where the time goes
+//!
+//! [`DiagnosticsOverlayPlugin`] shows the frame rate and frame time, the number of
+//! entities and of simulated bodies (anything with a `Velocity`), and the average
+//! wall-clock time of named spans of systems. The physics step is always timed; a
+//! chapter times its own systems by chaining [`begin_span`] and [`end_span`] around
+//! them. F3 shows and hides the overlay.
+use bevy::diagnostic::{
+    DiagnosticPath, DiagnosticsStore, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin,
+};
+use bevy::platform::time::Instant;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+
+use crate::physics::run_physics_step;
+use crate::Velocity;
+
+/// Adds the overlay, the frame time and entity count diagnostics it reads, and a
+/// "physics" span around the physics step
+pub struct DiagnosticsOverlayPlugin;
+
+impl Plugin for DiagnosticsOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        crate::add_egui(app);
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin::default());
+        }
+        if !app.is_plugin_added::<EntityCountDiagnosticsPlugin>() {
+            app.add_plugins(EntityCountDiagnosticsPlugin::default());
+        }
+        app.init_resource::<DiagnosticsOverlay>()
+            .init_resource::<SpanTimings>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    begin_span("physics").before(run_physics_step),
+                    end_span("physics").after(run_physics_step),
+                ),
+            )
+            .add_systems(Update, toggle_overlay)
+            .add_systems(EguiPrimaryContextPass, overlay_panel);
+    }
+}
+
+/// Whether the overlay is showing
+#[derive(Resource, Debug, Default)]
+pub struct DiagnosticsOverlay {
+    pub visible: bool,
+}
+
+/// How much of the latest sample goes into each span's running average
+const SMOOTHING: f32 = 0.1;
+
+/// Wall-clock time of named spans of systems, smoothed over recent runs
+#[derive(Resource, Debug, Default)]
+pub struct SpanTimings {
+    spans: Vec<Span>,
+}
+
+#[derive(Debug)]
+struct Span {
+    name: &'static str,
+    started: Option<Instant>,
+    /// Running average (ms)
+    average: f32,
+}
+
+impl SpanTimings {
+    fn span(&mut self, name: &'static str) -> &mut Span {
+        let index = match self.spans.iter().position(|span| span.name == name) {
+            Some(index) => index,
+            None => {
+                self.spans.push(Span { name, started: None, average: 0.0 });
+                self.spans.len() - 1
+            }
+        };
+        &mut self.spans[index]
+    }
+
+    /// Average time of `name` (ms), if it has run
+    pub fn average_ms(&self, name: &str) -> Option<f32> {
+        self.spans.iter().find(|span| span.name == name).map(|span| span.average)
+    }
+
+    /// Every span with its average time (ms), in the order they first ran
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, f32)> + '_ {
+        self.spans.iter().map(|span| (span.name, span.average))
+    }
+}
+
+/// System that starts timing `name`; chain it before the systems to time
+pub fn begin_span(name: &'static str) -> impl FnMut(ResMut<SpanTimings>) {
+    move |mut timings: ResMut<SpanTimings>| {
+        timings.span(name).started = Some(Instant::now());
+    }
+}
+
+/// System that stops timing `name` and folds the time into its average
+pub fn end_span(name: &'static str) -> impl FnMut(ResMut<SpanTimings>) {
+    move |mut timings: ResMut<SpanTimings>| {
+        let span = timings.span(name);
+        if let Some(started) = span.started.take() {
+            let elapsed = started.elapsed().as_secs_f32() * 1000.0;
+            span.average += (elapsed - span.average) * SMOOTHING;
+        }
+    }
+}
+
+fn toggle_overlay(keys: Res<ButtonInput<KeyCode>>, mut overlay: ResMut<DiagnosticsOverlay>) {
+    if keys.just_pressed(KeyCode::F3) {
+        overlay.visible = !overlay.visible;
+    }
+}
+
+fn overlay_panel(
+    mut contexts: EguiContexts,
+    overlay: Res<DiagnosticsOverlay>,
+    diagnostics: Res<DiagnosticsStore>,
+    timings: Res<SpanTimings>,
+    bodies: Query<(), With<Velocity>>,
+) -> Result {
+    if !overlay.visible {
+        return Ok(());
+    }
+    let smoothed = |path: &DiagnosticPath| diagnostics.get(path).and_then(|diagnostic| diagnostic.smoothed());
+    egui::Window::new("Diagnostics")
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+        .resizable(false)
+        .show(contexts.ctx_mut()?, |ui| {
+            if let (Some(fps), Some(frame_time)) = (
+                smoothed(&FrameTimeDiagnosticsPlugin::FPS),
+                smoothed(&FrameTimeDiagnosticsPlugin::FRAME_TIME),
+            ) {
+                ui.label(format!("{fps:.0} FPS ({frame_time:.2} ms/frame)"));
+            }
+            if let Some(entities) = smoothed(&EntityCountDiagnosticsPlugin::ENTITY_COUNT) {
+                ui.label(format!("Entities: {entities:.0}"));
+            }
+            ui.label(format!("Bodies: {}", bodies.iter().len()));
+            ui.separator();
+            for (name, average) in timings.iter() {
+                ui.label(format!("{name}: {average:.3} ms"));
+            }
+        });
+    Ok(())
+}
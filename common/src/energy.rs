@@ -0,0 +1,164 @@
+//! Kinetic and potential energy totals over every body
+//!
+//! [`EnergyDiagnosticsPlugin`] adds up [`EnergyTotals`] after each physics tick: the
+//! translational and rotational kinetic energy of every body with a `Mass` (and
+//! `MomentOfInertia`), the gravitational potential energy of those the shared `Gravity`
+//! acts on (zero at the origin), and the elastic energy stored in every `Spring`. A
+//! total that creeps up or down in a closed system is the integrator's error, which
+//! makes it the quickest way to compare schemes and step sizes. `with_readout` shows
+//! the totals on screen.
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+
+use crate::forces::{Gravity, GravityScale, NoGravity, Spring};
+use crate::physics::run_physics_step;
+use crate::timestep::physics_stepped;
+use crate::{AngularVelocity, Mass, MomentOfInertia, Position, Velocity};
+
+/// Sums [`EnergyTotals`] after every `FixedUpdate` the physics ticked in
+#[derive(Default)]
+pub struct EnergyDiagnosticsPlugin {
+    readout: bool,
+}
+
+impl EnergyDiagnosticsPlugin {
+    /// Also show the totals in a corner of the screen
+    pub fn with_readout(mut self) -> Self {
+        self.readout = true;
+        self
+    }
+}
+
+impl Plugin for EnergyDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EnergyTotals>()
+            .add_systems(FixedUpdate, sum_energy.after(run_physics_step).run_if(physics_stepped));
+        if self.readout {
+            crate::add_egui(app);
+            app.add_systems(EguiPrimaryContextPass, energy_readout);
+        }
+    }
+}
+
+/// Energy of every body at the end of the last step (J)
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq)]
+pub struct EnergyTotals {
+    /// ½mv² summed over bodies with a `Mass` and `Velocity`
+    pub kinetic: f32,
+    /// ½Iω² summed over bodies with a `MomentOfInertia` and `AngularVelocity`
+    pub rotational: f32,
+    /// −m·g·x summed over bodies the shared `Gravity` acts on
+    pub gravitational: f32,
+    /// ½k·(length − rest_length)² summed over `Spring`s
+    pub elastic: f32,
+    /// Total energy at the first step, to measure drift against
+    pub initial: Option<f32>,
+}
+
+impl EnergyTotals {
+    pub fn kinetic_total(&self) -> f32 {
+        self.kinetic + self.rotational
+    }
+
+    pub fn potential_total(&self) -> f32 {
+        self.gravitational + self.elastic
+    }
+
+    pub fn total(&self) -> f32 {
+        self.kinetic_total() + self.potential_total()
+    }
+
+    /// Change in total energy since the first step, relative to it
+    pub fn drift(&self) -> f32 {
+        self.initial
+            .map_or(0.0, |initial| (self.total() - initial) / initial.abs().max(f32::EPSILON))
+    }
+
+    /// Measure drift from the next step, e.g. after a reset
+    pub fn reset(&mut self) {
+        self.initial = None;
+    }
+}
+
+/// Where a body is, from its `Position` or else its `Transform`
+fn position_of(position: Option<&Position>, transform: Option<&Transform>) -> Option<Vec2> {
+    position
+        .map(|position| position.0)
+        .or_else(|| transform.map(|transform| transform.translation.truncate()))
+}
+
+/// System to add up the energy of every body into [`EnergyTotals`]
+#[allow(clippy::type_complexity)]
+pub fn sum_energy(
+    bodies: Query<(
+        &Mass,
+        Option<&Velocity>,
+        Option<&Position>,
+        Option<&Transform>,
+        Option<&GravityScale>,
+        Has<NoGravity>,
+    )>,
+    spinning: Query<(&MomentOfInertia, &AngularVelocity)>,
+    springs: Query<(Entity, &Spring)>,
+    ends: Query<(Option<&Position>, Option<&Transform>)>,
+    gravity: Option<Res<Gravity>>,
+    mut totals: ResMut<EnergyTotals>,
+) {
+    let gravity = gravity.map_or(Vec2::ZERO, |gravity| gravity.0);
+    let mut kinetic = 0.0;
+    let mut gravitational = 0.0;
+    for (mass, velocity, position, transform, scale, no_gravity) in &bodies {
+        if let Some(velocity) = velocity {
+            kinetic += 0.5 * mass.0 * velocity.0.length_squared();
+        }
+        if no_gravity {
+            continue;
+        }
+        if let Some(position) = position_of(position, transform) {
+            let g = gravity * scale.map_or(1.0, |scale| scale.0);
+            gravitational -= mass.0 * g.dot(position);
+        }
+    }
+    let rotational = spinning
+        .iter()
+        .map(|(inertia, angular_velocity)| 0.5 * inertia.0 * angular_velocity.0 * angular_velocity.0)
+        .sum();
+    let elastic = springs
+        .iter()
+        .filter_map(|(entity, spring)| {
+            let (position, transform) = ends.get(entity).ok()?;
+            let (other_position, other_transform) = ends.get(spring.other).ok()?;
+            let length = position_of(position, transform)?.distance(position_of(other_position, other_transform)?);
+            Some(spring.potential_energy(length))
+        })
+        .sum();
+
+    totals.kinetic = kinetic;
+    totals.rotational = rotational;
+    totals.gravitational = gravitational;
+    totals.elastic = elastic;
+    if totals.initial.is_none() {
+        totals.initial = Some(totals.total());
+    }
+}
+
+fn energy_readout(mut contexts: EguiContexts, totals: Res<EnergyTotals>) -> Result {
+    egui::Window::new("Energy")
+        .anchor(egui::Align2::LEFT_BOTTOM, [10.0, -10.0])
+        .collapsible(true)
+        .resizable(false)
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.label(format!("Kinetic: {:.3} J", totals.kinetic));
+            if totals.rotational != 0.0 {
+                ui.label(format!("Rotational: {:.3} J", totals.rotational));
+            }
+            ui.label(format!("Gravitational: {:.3} J", totals.gravitational));
+            if totals.elastic != 0.0 {
+                ui.label(format!("Elastic: {:.3} J", totals.elastic));
+            }
+            ui.separator();
+            ui.label(format!("Total: {:.3} J", totals.total()));
+            ui.label(format!("Drift: {:+.3}%", totals.drift() * 100.0));
+        });
+    Ok(())
+}
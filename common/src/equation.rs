@@ -0,0 +1,316 @@
+//! A small LaTeX-like equation renderer for egui panels
+//!
+//! Supports `^` superscripts, `_` subscripts (single characters or `{groups}`),
+//! `\frac{a}{b}`, `\sqrt{x}` and the usual Greek letters and operators, e.g.
+//! `y(t) = v_{0y} t + \frac{1}{2} g t^2`.
+use bevy_egui::egui;
+use egui::text::LayoutJob;
+use egui::{Align, FontId, TextFormat};
+
+const BASE_SIZE: f32 = 16.0;
+const SCRIPT_SCALE: f32 = 0.7;
+const FRACTION_SCALE: f32 = 0.85;
+
+/// A parsed equation ready to be drawn into an egui `Ui`
+#[derive(Debug, Clone)]
+pub struct Equation {
+    source: String,
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    /// Variables, drawn in italics
+    Symbol(String),
+    /// Numbers, operators and punctuation
+    Text(String),
+    Superscript(Vec<Node>),
+    Subscript(Vec<Node>),
+    Fraction(Vec<Node>, Vec<Node>),
+    Sqrt(Vec<Node>),
+}
+
+impl Equation {
+    pub fn new(source: impl Into<String>) -> Self {
+        let source = source.into();
+        let chars: Vec<char> = source.chars().collect();
+        let mut parser = Parser { chars: &chars, pos: 0 };
+        let nodes = parser.parse_until(None);
+        Self { source, nodes }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Draw the equation
+    pub fn show(&self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 0.0;
+            render_row(ui, &self.nodes, BASE_SIZE);
+        });
+    }
+
+    /// The same equation with symbols replaced by their current values,
+    /// e.g. `[("g", -9.81)]` turns `g t^2` into `(-9.81) t^2`
+    pub fn substitute(&self, values: &[(&str, f32)]) -> Equation {
+        // Replace longer symbols first so `v_{0y}` wins over `v`
+        let mut values = values.to_vec();
+        values.sort_by_key(|(symbol, _)| std::cmp::Reverse(symbol.len()));
+
+        let mut result = String::new();
+        let mut rest = self.source.as_str();
+        let mut previous: Option<char> = None;
+        'outer: while let Some(next) = rest.chars().next() {
+            for (symbol, value) in &values {
+                if symbol.is_empty() || !rest.starts_with(symbol) {
+                    continue;
+                }
+                let after = rest[symbol.len()..].chars().next();
+                let starts_word = !previous.is_some_and(|c| c.is_alphabetic() || c == '\\');
+                let ends_word = !after.is_some_and(|c| c.is_alphabetic() || c == '_');
+                if starts_word && ends_word {
+                    result.push_str(&format!("({value:.2})"));
+                    rest = &rest[symbol.len()..];
+                    previous = Some(')');
+                    continue 'outer;
+                }
+            }
+            result.push(next);
+            rest = &rest[next.len_utf8()..];
+            previous = Some(next);
+        }
+        Equation::new(result)
+    }
+
+    /// Draw the symbolic equation followed by the version with values substituted
+    pub fn show_with_values(&self, ui: &mut egui::Ui, values: &[(&str, f32)]) {
+        self.show(ui);
+        self.substitute(values).show(ui);
+    }
+}
+
+struct Parser<'a> {
+    chars: &'a [char],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn parse_until(&mut self, end: Option<char>) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            if Some(c) == end {
+                self.pos += 1;
+                break;
+            }
+            self.pos += 1;
+            match c {
+                '^' => nodes.push(Node::Superscript(self.parse_argument())),
+                '_' => nodes.push(Node::Subscript(self.parse_argument())),
+                '{' => nodes.extend(self.parse_until(Some('}'))),
+                '\\' => nodes.push(self.parse_command()),
+                '*' => nodes.push(Node::Text("·".to_string())),
+                c if c.is_alphabetic() => nodes.push(Node::Symbol(c.to_string())),
+                c => nodes.push(Node::Text(c.to_string())),
+            }
+        }
+        nodes
+    }
+
+    /// A `{group}`, a `\command` or a single character
+    fn parse_argument(&mut self) -> Vec<Node> {
+        match self.peek() {
+            Some('{') => {
+                self.pos += 1;
+                self.parse_until(Some('}'))
+            }
+            Some('\\') => {
+                self.pos += 1;
+                vec![self.parse_command()]
+            }
+            Some(c) => {
+                self.pos += 1;
+                if c.is_alphabetic() {
+                    vec![Node::Symbol(c.to_string())]
+                } else {
+                    vec![Node::Text(c.to_string())]
+                }
+            }
+            None => Vec::new(),
+        }
+    }
+
+    fn parse_command(&mut self) -> Node {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+            self.pos += 1;
+        }
+        let name: String = self.chars[start..self.pos].iter().collect();
+        match name.as_str() {
+            "frac" => {
+                let numerator = self.parse_argument();
+                let denominator = self.parse_argument();
+                Node::Fraction(numerator, denominator)
+            }
+            "sqrt" => Node::Sqrt(self.parse_argument()),
+            // An escaped character such as `\{` or `\ `
+            "" => Node::Text(self.parse_argument().iter().map(flatten).collect()),
+            _ => match command_symbol(&name) {
+                Some(symbol) if symbol.chars().all(char::is_alphabetic) => {
+                    Node::Symbol(symbol.to_string())
+                }
+                Some(symbol) => Node::Text(symbol.to_string()),
+                None => Node::Text(format!("\\{name}")),
+            },
+        }
+    }
+}
+
+fn command_symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" => "θ",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "pi" => "π",
+        "rho" => "ρ",
+        "sigma" => "σ",
+        "tau" => "τ",
+        "phi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Sigma" => "Σ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        "hbar" => "ħ",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "cdot" => "·",
+        "times" => "×",
+        "pm" => "±",
+        "approx" => "≈",
+        "le" => "≤",
+        "ge" => "≥",
+        "ne" => "≠",
+        "infty" => "∞",
+        "int" => "∫",
+        "sum" => "∑",
+        "to" => "→",
+        "propto" => "∝",
+        "vec" => "→",
+        _ => return None,
+    })
+}
+
+/// Inline text for nodes that can't be laid out in two dimensions (inside scripts)
+fn flatten(node: &Node) -> String {
+    match node {
+        Node::Symbol(text) | Node::Text(text) => text.clone(),
+        Node::Superscript(inner) => format!("^{}", inner.iter().map(flatten).collect::<String>()),
+        Node::Subscript(inner) => format!("_{}", inner.iter().map(flatten).collect::<String>()),
+        Node::Fraction(numerator, denominator) => format!(
+            "{}/{}",
+            numerator.iter().map(flatten).collect::<String>(),
+            denominator.iter().map(flatten).collect::<String>()
+        ),
+        Node::Sqrt(inner) => format!("√({})", inner.iter().map(flatten).collect::<String>()),
+    }
+}
+
+fn text_format(ui: &egui::Ui, size: f32, italics: bool, valign: Align) -> TextFormat {
+    TextFormat {
+        font_id: FontId::proportional(size),
+        color: ui.visuals().text_color(),
+        italics,
+        valign,
+        ..Default::default()
+    }
+}
+
+/// Append nodes to a single line of text, with scripts raised or lowered
+fn append_inline(ui: &egui::Ui, job: &mut LayoutJob, nodes: &[Node], size: f32, valign: Align) {
+    for node in nodes {
+        match node {
+            Node::Symbol(text) => job.append(text, 0.0, text_format(ui, size, true, valign)),
+            Node::Text(text) => job.append(text, 0.0, text_format(ui, size, false, valign)),
+            Node::Superscript(inner) => {
+                append_inline(ui, job, inner, size * SCRIPT_SCALE, Align::TOP)
+            }
+            Node::Subscript(inner) => {
+                append_inline(ui, job, inner, size * SCRIPT_SCALE, Align::BOTTOM)
+            }
+            Node::Fraction(..) | Node::Sqrt(_) => {
+                job.append(&flatten(node), 0.0, text_format(ui, size, false, valign))
+            }
+        }
+    }
+}
+
+fn flush(ui: &mut egui::Ui, job: &mut LayoutJob) {
+    if !job.text.is_empty() {
+        ui.label(std::mem::take(job));
+    }
+}
+
+/// Lay out a row of nodes, stacking fractions vertically
+fn render_row(ui: &mut egui::Ui, nodes: &[Node], size: f32) {
+    let mut job = LayoutJob::default();
+    for node in nodes {
+        match node {
+            Node::Fraction(numerator, denominator) => {
+                flush(ui, &mut job);
+                render_fraction(ui, numerator, denominator, size * FRACTION_SCALE);
+            }
+            Node::Sqrt(inner) => {
+                job.append("√", 0.0, text_format(ui, size, false, Align::Center));
+                flush(ui, &mut job);
+                let rect = ui
+                    .horizontal(|ui| render_row(ui, inner, size))
+                    .response
+                    .rect;
+                // Overline covering the radicand
+                ui.painter().hline(
+                    rect.x_range(),
+                    rect.top() + 1.0,
+                    egui::Stroke::new(1.0, ui.visuals().text_color()),
+                );
+            }
+            _ => append_inline(ui, &mut job, std::slice::from_ref(node), size, Align::Center),
+        }
+    }
+    flush(ui, &mut job);
+}
+
+fn render_fraction(ui: &mut egui::Ui, numerator: &[Node], denominator: &[Node], size: f32) {
+    ui.vertical(|ui| {
+        ui.spacing_mut().item_spacing.y = 0.0;
+        let top = ui.horizontal(|ui| render_row(ui, numerator, size)).response.rect;
+        let (bar, _) = ui.allocate_exact_size(egui::vec2(1.0, 4.0), egui::Sense::hover());
+        let bottom = ui.horizontal(|ui| render_row(ui, denominator, size)).response.rect;
+        let width = top.width().max(bottom.width());
+        ui.painter().hline(
+            top.left()..=top.left() + width,
+            bar.center().y,
+            egui::Stroke::new(1.0, ui.visuals().text_color()),
+        );
+    });
+}
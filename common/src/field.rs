@@ -0,0 +1,196 @@
+//! GPU-colored scalar, vector and complex fields
+//!
+//! Grid chapters (heat, pressure, wavefunctions) keep their values in a [`FieldGrid`]
+//! and draw it on one quad with a [`FieldMaterial`], instead of spawning a sprite per
+//! cell. The grid is uploaded as a two-channel float texture whenever it is written,
+//! and `shaders/field.wgsl` interpolates it and maps it through a colormap.
+//!
+//! With the `hot-reload` feature, native builds watch the shader source, so edits to
+//! the coloring show up in the running simulation.
+use bevy::asset::{embedded_asset, RenderAssetUsages};
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssets;
+use bevy::render::render_resource::{
+    AsBindGroup, AsBindGroupShaderType, Extent3d, ShaderType, TextureDimension, TextureFormat,
+};
+use bevy::render::texture::GpuImage;
+use bevy::shader::ShaderRef;
+use bevy::sprite_render::{Material2d, Material2dPlugin};
+
+pub use crate::colormap::Colormap;
+
+const SHADER_PATH: &str = "embedded://rhysics_common/shaders/field.wgsl";
+
+/// Registers [`FieldMaterial`] and its shader
+pub struct FieldMaterialPlugin;
+
+impl Plugin for FieldMaterialPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "shaders/field.wgsl");
+        app.add_plugins(Material2dPlugin::<FieldMaterial>::default());
+    }
+}
+
+/// How the two channels of each cell are read
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FieldMode {
+    /// One value per cell, in the first channel
+    #[default]
+    Scalar,
+    /// Colored by the length of the (x, y) vector
+    Vector,
+    /// (re, im); brightness from the modulus, hue from the phase
+    Complex,
+}
+
+/// Uniform matching `FieldSettings` in the shader
+#[derive(ShaderType, Debug, Clone, Copy)]
+pub struct FieldSettings {
+    min: f32,
+    max: f32,
+    mode: u32,
+    colormap: u32,
+}
+
+/// Colors the field texture of a quad
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+#[uniform(0, FieldSettings)]
+pub struct FieldMaterial {
+    /// Written by [`FieldGrid::image`]
+    #[texture(1, sample_type = "float", filterable = false)]
+    pub field: Handle<Image>,
+    pub mode: FieldMode,
+    pub colormap: Colormap,
+    /// Values mapped to the ends of the colormap
+    pub range: (f32, f32),
+}
+
+impl FieldMaterial {
+    pub fn new(field: Handle<Image>) -> Self {
+        Self {
+            field,
+            mode: FieldMode::Scalar,
+            colormap: Colormap::Viridis,
+            range: (0.0, 1.0),
+        }
+    }
+
+    pub fn with_mode(mut self, mode: FieldMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_colormap(mut self, colormap: Colormap) -> Self {
+        self.colormap = colormap;
+        self
+    }
+
+    pub fn with_range(mut self, min: f32, max: f32) -> Self {
+        self.range = (min, max);
+        self
+    }
+}
+
+impl AsBindGroupShaderType<FieldSettings> for FieldMaterial {
+    fn as_bind_group_shader_type(&self, _images: &RenderAssets<GpuImage>) -> FieldSettings {
+        FieldSettings {
+            min: self.range.0,
+            max: self.range.1,
+            mode: self.mode as u32,
+            colormap: self.colormap as u32,
+        }
+    }
+}
+
+impl Material2d for FieldMaterial {
+    fn fragment_shader() -> ShaderRef {
+        SHADER_PATH.into()
+    }
+}
+
+/// A `width` × `height` grid of two-channel cells, row 0 at the bottom
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldGrid {
+    pub width: u32,
+    pub height: u32,
+    pub cells: Vec<Vec2>,
+}
+
+impl FieldGrid {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Vec2::ZERO; (width * height) as usize],
+        }
+    }
+
+    pub fn index(&self, x: u32, y: u32) -> usize {
+        (y * self.width + x) as usize
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Vec2 {
+        self.cells[self.index(x, y)]
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, value: Vec2) {
+        let index = self.index(x, y);
+        self.cells[index] = value;
+    }
+
+    /// Set a scalar field, one value per cell in row order
+    pub fn set_scalars(&mut self, values: &[f32]) {
+        for (cell, value) in self.cells.iter_mut().zip(values) {
+            *cell = Vec2::new(*value, 0.0);
+        }
+    }
+
+    /// Texture holding the grid, for [`FieldMaterial::field`]
+    pub fn image(&self) -> Image {
+        let mut image = Image::new_fill(
+            Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0; 8],
+            TextureFormat::Rg32Float,
+            RenderAssetUsages::RENDER_WORLD | RenderAssetUsages::MAIN_WORLD,
+        );
+        self.write_to(&mut image);
+        image
+    }
+
+    /// Copy the cells into an image made by [`FieldGrid::image`], which re-uploads it
+    pub fn write_to(&self, image: &mut Image) {
+        let data = image.data.get_or_insert_default();
+        data.clear();
+        for cell in &self.cells {
+            data.extend_from_slice(&cell.x.to_le_bytes());
+            data.extend_from_slice(&cell.y.to_le_bytes());
+        }
+    }
+
+    /// Smallest and largest value, or modulus for vector and complex fields
+    pub fn range(&self, mode: FieldMode) -> (f32, f32) {
+        let value = |cell: &Vec2| match mode {
+            FieldMode::Scalar => cell.x,
+            FieldMode::Vector | FieldMode::Complex => cell.length(),
+        };
+        self.cells.iter().map(value).fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), v| {
+            (min.min(v), max.max(v))
+        })
+    }
+}
+
+/// A quad of `size` world units drawing `material`, e.g. the visible area from
+/// [`WorldScale::view_size`](crate::world_scale::WorldScale::view_size) for a
+/// full-screen field
+pub fn field_quad(
+    size: Vec2,
+    meshes: &mut Assets<Mesh>,
+    material: Handle<FieldMaterial>,
+) -> impl Bundle {
+    (Mesh2d(meshes.add(Rectangle::from_size(size))), MeshMaterial2d(material))
+}
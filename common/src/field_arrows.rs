@@ -0,0 +1,156 @@
+//! Arrows showing a vector field across the screen
+//!
+//! [`FieldArrowsPlugin<F>`] samples every `F: ForceField` entity on a grid over what
+//! each 2D camera sees and draws an arrow gizmo at each grid point, giving the force on
+//! a 1 kg body at rest there. Fields that aren't `ForceField`s (an electric field from
+//! charges, a fluid's velocity grid) are drawn with [`draw_vector_field`] from a
+//! chapter's own system, with any sampling closure.
+//!
+//! [`FieldArrows`] sets how dense the grid is and how long the arrows are, both in
+//! screen pixels so the picture reads the same at any zoom; the Field Arrows window
+//! has sliders for them. The grid is fixed in the world, so it doesn't shimmer while
+//! the camera pans.
+use std::marker::PhantomData;
+
+use bevy::gizmos::config::GizmoConfigStore;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+
+use crate::force_field::{FieldRegion, ForceField};
+
+/// Draws the arrows of every `F` field, and adds the [`FieldArrows`] settings and window
+pub struct FieldArrowsPlugin<F: ForceField>(PhantomData<F>);
+
+impl<F: ForceField> Default for FieldArrowsPlugin<F> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<F: ForceField> Plugin for FieldArrowsPlugin<F> {
+    fn build(&self, app: &mut App) {
+        // Several fields share one set of settings and one window
+        if !app.world().contains_resource::<FieldArrows>() {
+            crate::add_egui(app);
+            app.init_resource::<FieldArrows>()
+                .add_systems(EguiPrimaryContextPass, field_arrows_window);
+        }
+        app.add_systems(
+            PostUpdate,
+            draw_field_arrows::<F>.run_if(resource_exists::<GizmoConfigStore>),
+        );
+    }
+}
+
+/// How the field arrows are laid out and drawn
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct FieldArrows {
+    pub visible: bool,
+    /// Distance between neighboring arrows (logical pixels)
+    pub spacing: f32,
+    /// Arrow length per unit of field (logical pixels)
+    pub scale: f32,
+    /// Longest arrow drawn, so arrows near a singularity don't cover the screen (logical pixels)
+    pub max_length: f32,
+    pub color: Color,
+}
+
+impl Default for FieldArrows {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            spacing: 40.0,
+            scale: 10.0,
+            max_length: 36.0,
+            color: Color::srgba(0.5, 0.8, 1.0, 0.6),
+        }
+    }
+}
+
+/// Area seen by a 2D camera in world units, and the world length of a logical pixel
+pub fn visible_area(camera: &Camera, transform: &GlobalTransform) -> Option<(Rect, f32)> {
+    let viewport = camera.logical_viewport_rect()?;
+    let corner_a = camera.viewport_to_world_2d(transform, Vec2::ZERO).ok()?;
+    let corner_b = camera.viewport_to_world_2d(transform, viewport.size()).ok()?;
+    let area = Rect::from_corners(corner_a, corner_b);
+    Some((area, area.width() / viewport.width()))
+}
+
+/// Draw an arrow of `sample(point)` at each point of a grid over `area`, laid out and
+/// sized by `arrows` with `meters_per_pixel` world units to a logical pixel
+pub fn draw_vector_field(
+    gizmos: &mut Gizmos,
+    area: Rect,
+    meters_per_pixel: f32,
+    arrows: &FieldArrows,
+    mut sample: impl FnMut(Vec2) -> Vec2,
+) {
+    let step = arrows.spacing * meters_per_pixel;
+    if !step.is_finite() || step <= 0.0 || area.is_empty() {
+        return;
+    }
+    // Grid points at the cell centers of a grid anchored at the origin
+    let first = ((area.min / step).floor() + 0.5) * step;
+    let count = ((area.max - first) / step).ceil().as_uvec2() + UVec2::ONE;
+    let max_length = arrows.max_length * meters_per_pixel;
+    for row in 0..count.y {
+        for column in 0..count.x {
+            let point = first + UVec2::new(column, row).as_vec2() * step;
+            let arrow = (sample(point) * arrows.scale * meters_per_pixel).clamp_length_max(max_length);
+            // Arrows under a pixel long would only be arrowheads
+            if arrow.length_squared() < meters_per_pixel * meters_per_pixel {
+                continue;
+            }
+            // Centered on the grid point, like a wind barb
+            gizmos.arrow_2d(point - arrow / 2.0, point + arrow / 2.0, arrows.color);
+        }
+    }
+}
+
+/// System to draw the force every `F` field puts on a 1 kg body at rest
+pub fn draw_field_arrows<F: ForceField>(
+    mut gizmos: Gizmos,
+    arrows: Res<FieldArrows>,
+    fields: Query<(&F, &GlobalTransform, Option<&FieldRegion>)>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    time: Res<Time>,
+) {
+    if !arrows.visible || fields.is_empty() {
+        return;
+    }
+    let t = time.elapsed_secs();
+    for (camera, camera_transform) in &cameras {
+        if !camera.is_active {
+            continue;
+        }
+        let Some((area, meters_per_pixel)) = visible_area(camera, camera_transform) else {
+            continue;
+        };
+        draw_vector_field(&mut gizmos, area, meters_per_pixel, &arrows, |point| {
+            fields
+                .iter()
+                .map(|(field, center, region)| {
+                    let offset = point - center.translation().truncate();
+                    if region.is_some_and(|region| !region.contains(offset)) {
+                        return Vec2::ZERO;
+                    }
+                    field.force(offset, Vec2::ZERO, 1.0, t)
+                })
+                .sum()
+        });
+    }
+}
+
+fn field_arrows_window(mut contexts: EguiContexts, mut arrows: ResMut<FieldArrows>) -> Result {
+    egui::Window::new("Field Arrows")
+        .default_open(false)
+        .anchor(egui::Align2::LEFT_BOTTOM, [10.0, -10.0])
+        .resizable(false)
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.checkbox(&mut arrows.visible, "Show field");
+            ui.add(egui::Slider::new(&mut arrows.spacing, 10.0..=200.0).text("Spacing (px)"));
+            ui.add(egui::Slider::new(&mut arrows.scale, 0.1..=100.0).logarithmic(true).text("Scale (px per unit)"));
+            ui.add(egui::Slider::new(&mut arrows.max_length, 5.0..=200.0).text("Longest arrow (px)"));
+        });
+    Ok(())
+}
@@ -0,0 +1,232 @@
+//! Force fields acting on every body in a region
+//!
+//! A field is a component implementing [`ForceField`] on its own entity, centered on
+//! that entity's `Transform` and limited to its [`FieldRegion`] if it has one.
+//! [`ForceFieldPlugin<F>`] adds each body's share of every `F` field to its `Force`
+//! in `PhysicsSet::Forces` of the physics step; [`ForceFieldsPlugin`] does so for the built-in
+//! [`UniformWind`], [`PointAttractor`], [`Vortex`] and [`Turbulence`]. A simulation
+//! registers its own field by implementing the trait and adding the plugin for it.
+use bevy::prelude::*;
+use std::f32::consts::TAU;
+use std::marker::PhantomData;
+
+use crate::physics::{PhysicsSet, PhysicsStep};
+use crate::{Force, Mass, Position, Velocity};
+
+/// Something that pushes bodies around depending on where they are
+pub trait ForceField: Component {
+    /// Force on a body of `mass` at `offset` from the field's center, moving at
+    /// `velocity`, `time` seconds into the simulation
+    fn force(&self, offset: Vec2, velocity: Vec2, mass: f32, time: f32) -> Vec2;
+}
+
+/// Applies every `F` field to the bodies in its region
+pub struct ForceFieldPlugin<F: ForceField>(PhantomData<F>);
+
+impl<F: ForceField> Default for ForceFieldPlugin<F> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<F: ForceField> Plugin for ForceFieldPlugin<F> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PhysicsStep, apply_force_field::<F>.in_set(PhysicsSet::Forces));
+    }
+}
+
+/// Adds [`ForceFieldPlugin`] for each of the built-in fields
+pub struct ForceFieldsPlugin;
+
+impl Plugin for ForceFieldsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            ForceFieldPlugin::<UniformWind>::default(),
+            ForceFieldPlugin::<PointAttractor>::default(),
+            ForceFieldPlugin::<Vortex>::default(),
+            ForceFieldPlugin::<Turbulence>::default(),
+        ));
+    }
+}
+
+/// Where around its center a field acts; fields without one act everywhere
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub enum FieldRegion {
+    Circle { radius: f32 },
+    Rectangle { half_size: Vec2 },
+}
+
+impl FieldRegion {
+    pub fn contains(&self, offset: Vec2) -> bool {
+        match self {
+            FieldRegion::Circle { radius } => offset.length_squared() <= radius * radius,
+            FieldRegion::Rectangle { half_size } => offset.abs().cmple(*half_size).all(),
+        }
+    }
+}
+
+/// System to add the force of every `F` field to the bodies inside it
+#[allow(clippy::type_complexity)]
+pub fn apply_force_field<F: ForceField>(
+    fields: Query<(&F, &Transform, Option<&FieldRegion>)>,
+    mut bodies: Query<(&Transform, Option<&Position>, Option<&Velocity>, &Mass, &mut Force)>,
+    time: Res<Time>,
+) {
+    let t = time.elapsed_secs();
+    for (field, center, region) in &fields {
+        let center = center.translation.truncate();
+        for (transform, position, velocity, mass, mut force) in &mut bodies {
+            let position = position.map_or(transform.translation.truncate(), |position| position.0);
+            let offset = position - center;
+            if region.is_some_and(|region| !region.contains(offset)) {
+                continue;
+            }
+            let velocity = velocity.map_or(Vec2::ZERO, |velocity| velocity.0);
+            force.0 += field.force(offset, velocity, mass.0, t);
+        }
+    }
+}
+
+/// Moving air: drags bodies towards the wind's velocity, F = k·(wind − v)
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct UniformWind {
+    /// Air velocity (m/s)
+    pub velocity: Vec2,
+    /// k (N·s/m)
+    pub coefficient: f32,
+}
+
+impl UniformWind {
+    pub fn new(velocity: Vec2, coefficient: f32) -> Self {
+        Self { velocity, coefficient }
+    }
+}
+
+impl ForceField for UniformWind {
+    fn force(&self, _offset: Vec2, velocity: Vec2, _mass: f32, _time: f32) -> Vec2 {
+        self.coefficient * (self.velocity - velocity)
+    }
+}
+
+/// Inverse-square pull towards the center, F = m·strength / r², softened near it;
+/// a negative strength repels
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct PointAttractor {
+    /// Acceleration at 1 m (m³/s²), like G·M
+    pub strength: f32,
+    /// Distance below which the pull stops growing, so bodies passing through the
+    /// center aren't flung away (m)
+    pub softening: f32,
+}
+
+impl PointAttractor {
+    pub fn new(strength: f32) -> Self {
+        Self { strength, softening: 0.1 }
+    }
+
+    pub fn with_softening(mut self, softening: f32) -> Self {
+        self.softening = softening;
+        self
+    }
+}
+
+impl ForceField for PointAttractor {
+    fn force(&self, offset: Vec2, _velocity: Vec2, mass: f32, _time: f32) -> Vec2 {
+        let distance_squared = offset.length_squared() + self.softening * self.softening;
+        -offset.normalize_or_zero() * (mass * self.strength / distance_squared)
+    }
+}
+
+/// Swirl around the center, counterclockwise for positive strength, fading outside `radius`
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Vortex {
+    /// Tangential acceleration at the core's edge (m/s²)
+    pub strength: f32,
+    /// Size of the core; inside it the swirl grows with distance like a solid
+    /// rotation, outside it falls off as 1/r (m)
+    pub radius: f32,
+}
+
+impl Vortex {
+    pub fn new(strength: f32, radius: f32) -> Self {
+        Self { strength, radius }
+    }
+}
+
+impl ForceField for Vortex {
+    fn force(&self, offset: Vec2, _velocity: Vec2, mass: f32, _time: f32) -> Vec2 {
+        let distance = offset.length();
+        if distance <= f32::EPSILON {
+            return Vec2::ZERO;
+        }
+        let profile = if distance < self.radius {
+            distance / self.radius
+        } else {
+            self.radius / distance
+        };
+        offset.perp() / distance * (mass * self.strength * profile)
+    }
+}
+
+/// Gusty force from drifting Perlin noise, different in every place and moment
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Turbulence {
+    /// Largest acceleration (m/s²)
+    pub strength: f32,
+    /// Size of a gust (m)
+    pub scale: f32,
+    /// How fast the pattern changes (gusts per second)
+    pub speed: f32,
+    pub seed: u32,
+}
+
+impl Turbulence {
+    pub fn new(strength: f32, scale: f32) -> Self {
+        Self { strength, scale, speed: 1.0, seed: 0 }
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+impl ForceField for Turbulence {
+    fn force(&self, offset: Vec2, _velocity: Vec2, mass: f32, time: f32) -> Vec2 {
+        let point = offset / self.scale + Vec2::splat(time * self.speed);
+        // Independent noise for each component
+        let noise = Vec2::new(perlin(point, self.seed), perlin(point, self.seed.wrapping_add(1)));
+        noise * (mass * self.strength)
+    }
+}
+
+/// Unit gradient for a lattice point
+fn lattice_gradient(cell: IVec2, seed: u32) -> Vec2 {
+    let mut hash = (cell.x as u32).wrapping_mul(0x27d4_eb2d)
+        ^ (cell.y as u32).wrapping_mul(0x1656_67b1)
+        ^ seed.wrapping_mul(0x9e37_79b9);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x2c1b_3c6d);
+    hash ^= hash >> 12;
+    Vec2::from_angle(hash as f32 / u32::MAX as f32 * TAU)
+}
+
+/// 2D Perlin gradient noise, roughly in -1..1
+fn perlin(point: Vec2, seed: u32) -> f32 {
+    let floor = point.floor();
+    let local = point - floor;
+    let cell = floor.as_ivec2();
+    // Quintic fade, so the noise is smooth across cell edges
+    let fade = local * local * local * (local * (local * 6.0 - 15.0) + 10.0);
+    let corner = |corner: IVec2| lattice_gradient(cell + corner, seed).dot(local - corner.as_vec2());
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+    let bottom = lerp(corner(IVec2::ZERO), corner(IVec2::X), fade.x);
+    let top = lerp(corner(IVec2::Y), corner(IVec2::ONE), fade.x);
+    // Gradient noise peaks at about ±0.7 in 2D
+    lerp(bottom, top, fade.y) * std::f32::consts::SQRT_2
+}
@@ -0,0 +1,206 @@
+//! Shared force sources
+//!
+//! Each system here adds to the `Force` of the bodies it acts on, in
+//! `PhysicsSet::Forces`, before `apply_forces` turns the total into acceleration.
+use bevy::prelude::*;
+
+use crate::share::{ShareParams, ShareSettings};
+use crate::{constants, Force, Mass, Position, Velocity};
+
+/// A damped Hooke's law spring from this body to `other`
+///
+/// Pulls both ends towards `rest_length` apart with force k·(length − rest_length),
+/// and damps their relative motion along the spring with force c·(closing speed).
+/// `other` is pulled too if it has a `Force`; without one it acts as a fixed anchor.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct Spring {
+    pub other: Entity,
+    /// Length at which the spring neither pushes nor pulls (m)
+    pub rest_length: f32,
+    /// k (N/m)
+    pub stiffness: f32,
+    /// c (N·s/m)
+    pub damping: f32,
+}
+
+impl Spring {
+    pub fn new(other: Entity, rest_length: f32, stiffness: f32) -> Self {
+        Self {
+            other,
+            rest_length,
+            stiffness,
+            damping: 0.0,
+        }
+    }
+
+    pub fn with_damping(mut self, damping: f32) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    /// Elastic potential energy ½k(length − rest_length)² stored at `length`
+    pub fn potential_energy(&self, length: f32) -> f32 {
+        0.5 * self.stiffness * (length - self.rest_length).powi(2)
+    }
+
+    /// Force on this end, at `offset` from the other end and moving at `relative_velocity` to it
+    pub fn force(&self, offset: Vec2, relative_velocity: Vec2) -> Vec2 {
+        let Some(direction) = offset.try_normalize() else {
+            return Vec2::ZERO;
+        };
+        let stretch = offset.length() - self.rest_length;
+        let separating_speed = relative_velocity.dot(direction);
+        -(self.stiffness * stretch + self.damping * separating_speed) * direction
+    }
+}
+
+/// System to add every [`Spring`]'s force to both of its ends
+pub fn apply_springs(
+    springs: Query<(Entity, &Spring)>,
+    bodies: Query<(&Position, Option<&Velocity>)>,
+    mut forces: Query<&mut Force>,
+) {
+    let velocity_of = |velocity: Option<&Velocity>| velocity.map_or(Vec2::ZERO, |velocity| velocity.0);
+    for (entity, spring) in &springs {
+        let (Ok((position, velocity)), Ok((other_position, other_velocity))) =
+            (bodies.get(entity), bodies.get(spring.other))
+        else {
+            continue;
+        };
+        let force = spring.force(
+            position.0 - other_position.0,
+            velocity_of(velocity) - velocity_of(other_velocity),
+        );
+        if let Ok(mut own) = forces.get_mut(entity) {
+            own.0 += force;
+        }
+        if let Ok(mut other) = forces.get_mut(spring.other) {
+            other.0 -= force;
+        }
+    }
+}
+
+/// Air or fluid resistance, −b·u − c·|u|·u for the velocity u relative to the air
+///
+/// The linear term dominates for small, slow bodies (Stokes drag in a viscous fluid),
+/// the quadratic term for large, fast ones (a ball through air). In still air u is the
+/// body's velocity; in a wind a body at rest is pushed along, and one flying into the
+/// wind is slowed harder.
+#[derive(Component, Default, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component, Default)]
+pub struct Drag {
+    /// b (N·s/m)
+    pub linear: f32,
+    /// c (N·s²/m²)
+    pub quadratic: f32,
+    /// Velocity of the air the body moves through (m/s)
+    pub wind: Vec2,
+}
+
+impl Drag {
+    pub fn new(linear: f32, quadratic: f32) -> Self {
+        Self {
+            linear,
+            quadratic,
+            wind: Vec2::ZERO,
+        }
+    }
+
+    pub fn linear(linear: f32) -> Self {
+        Self::new(linear, 0.0)
+    }
+
+    pub fn quadratic(quadratic: f32) -> Self {
+        Self::new(0.0, quadratic)
+    }
+
+    /// The same drag in air moving at `wind`
+    pub fn with_wind(mut self, wind: Vec2) -> Self {
+        self.wind = wind;
+        self
+    }
+
+    /// Force on a body moving at `velocity`
+    pub fn force(&self, velocity: Vec2) -> Vec2 {
+        let relative = velocity - self.wind;
+        -(self.linear + self.quadratic * relative.length()) * relative
+    }
+
+    /// Speed at which drag balances a constant force of `weight` (N), e.g. m·g for a
+    /// falling body
+    pub fn terminal_speed(&self, weight: f32) -> f32 {
+        let weight = weight.abs();
+        if self.quadratic > 0.0 {
+            // c v² + b v − W = 0
+            let b = self.linear;
+            (-b + (b * b + 4.0 * self.quadratic * weight).sqrt()) / (2.0 * self.quadratic)
+        } else if self.linear > 0.0 {
+            weight / self.linear
+        } else {
+            f32::INFINITY
+        }
+    }
+}
+
+/// System to add each body's [`Drag`] to its net force
+pub fn apply_drag(mut bodies: Query<(&Drag, &Velocity, &mut Force)>) {
+    for (drag, velocity, mut force) in &mut bodies {
+        force.0 += drag.force(velocity.0);
+    }
+}
+
+/// Uniform gravitational acceleration (m/s²) pulling on every body with a `Mass`
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Resource, Default)]
+pub struct Gravity(pub Vec2);
+
+impl Default for Gravity {
+    /// Earth's surface gravity, pointing down
+    fn default() -> Self {
+        Self(Vec2::new(0.0, -constants::GRAVITY))
+    }
+}
+
+impl ShareSettings for Gravity {
+    fn write_params(&self, params: &mut ShareParams) {
+        params.set("g", self.0.y);
+        if self.0.x != 0.0 {
+            params.set("gx", self.0.x);
+        }
+    }
+
+    fn read_params(&mut self, params: &ShareParams) {
+        // `gravity` is the longhand for links written by hand
+        params.read_into("gravity", &mut self.0.y);
+        params.read_into("g", &mut self.0.y);
+        params.read_into("gx", &mut self.0.x);
+    }
+}
+
+/// Exempt a body from [`Gravity`], e.g. while it waits to be launched
+#[derive(Component, Default, Debug, Clone, Copy, Reflect)]
+#[reflect(Component, Default)]
+pub struct NoGravity;
+
+/// Multiply [`Gravity`] for one body, e.g. to compare a body under the Moon's pull
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component, Default)]
+pub struct GravityScale(pub f32);
+
+impl Default for GravityScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// System to add each body's weight, m·g, to its net force
+pub fn apply_gravity(
+    mut bodies: Query<(&Mass, Option<&GravityScale>, &mut Force), Without<NoGravity>>,
+    gravity: Res<Gravity>,
+) {
+    for (mass, scale, mut force) in &mut bodies {
+        let scale = scale.map_or(1.0, |scale| scale.0);
+        force.0 += gravity.0 * mass.0 * scale;
+    }
+}
@@ -0,0 +1,423 @@
+//! WebGPU compute backend for the particle kernels
+//!
+//! With the `gpu-compute` feature, [`GpuComputePlugin`] runs one of the [`kernels`]
+//! loops as a compute shader instead: the simulation uploads its particle state into a
+//! storage buffer with [`GpuParticles::upload`], a compute pass runs the
+//! [`GpuKernel`] over it, and the results are read back into [`GpuParticles`] a frame
+//! or two later. Each upload gets a generation that comes back with its results, so a
+//! simulation can tell which particles they belong to. Until results for the current
+//! particle count are back, or on adapters without compute shaders (WebGL2), the
+//! accessors return `None` and the simulation falls back to the CPU kernels.
+//!
+//! [`kernels`]: crate::kernels
+use bevy::asset::embedded_asset;
+use bevy::prelude::*;
+use bevy::render::extract_resource::{ExtractResource, ExtractResourcePlugin};
+use bevy::render::gpu_readback::{Readback, ReadbackComplete};
+use bevy::render::render_asset::RenderAssets;
+use bevy::render::render_graph::{self, RenderGraph, RenderLabel};
+use bevy::render::render_resource::binding_types::{storage_buffer, storage_buffer_read_only, uniform_buffer};
+use bevy::render::render_resource::*;
+use bevy::render::renderer::{RenderAdapter, RenderContext, RenderDevice, RenderQueue};
+use bevy::render::storage::{GpuShaderStorageBuffer, ShaderStorageBuffer};
+use bevy::render::{Render, RenderApp, RenderSystems};
+
+use crate::kernels::SteeringSums;
+
+const SHADER_PATH: &str = "embedded://rhysics_common/shaders/particles.wgsl";
+/// Matches `@workgroup_size` in the shader
+const WORKGROUP_SIZE: u32 = 64;
+
+/// Runs `kernel` on the GPU when the adapter supports compute shaders
+pub struct GpuComputePlugin {
+    pub kernel: GpuKernel,
+}
+
+impl GpuComputePlugin {
+    pub fn new(kernel: GpuKernel) -> Self {
+        Self { kernel }
+    }
+}
+
+impl Plugin for GpuComputePlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "shaders/particles.wgsl");
+        app.init_resource::<ComputeBackend>()
+            .add_plugins(ExtractResourcePlugin::<ExtractedParticles>::default())
+            .add_systems(Startup, spawn_readback.run_if(resource_exists::<GpuParticles>));
+    }
+
+    // The render device only exists once the renderer is up, so decide here
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        if !supports_compute(render_app.world()) {
+            log::info!("Compute shaders unavailable, particle kernels stay on the CPU");
+            return;
+        }
+
+        let shader = app.world().resource::<AssetServer>().load(SHADER_PATH);
+        let render_app = app.sub_app_mut(RenderApp);
+        let pipelines = ParticlePipelines::new(render_app.world(), shader);
+        render_app
+            .insert_resource(pipelines)
+            .add_systems(Render, prepare_bind_group.in_set(RenderSystems::PrepareBindGroups));
+        let mut graph = render_app.world_mut().resource_mut::<RenderGraph>();
+        graph.add_node(ParticleComputeLabel, ParticleComputeNode);
+        graph.add_node_edge(ParticleComputeLabel, bevy::render::graph::CameraDriverLabel);
+
+        let mut buffers = app.world_mut().resource_mut::<Assets<ShaderStorageBuffer>>();
+        let particles = GpuParticles::new(self.kernel, &mut buffers);
+        app.insert_resource(particles).insert_resource(ComputeBackend::Gpu);
+    }
+}
+
+fn supports_compute(render_world: &World) -> bool {
+    let adapter = render_world.resource::<RenderAdapter>();
+    let device = render_world.resource::<RenderDevice>();
+    adapter.get_downlevel_capabilities().flags.contains(DownlevelFlags::COMPUTE_SHADERS)
+        && device.limits().max_storage_buffers_per_shader_stage >= 2
+}
+
+/// Where the particle kernels run, decided once the renderer starts
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ComputeBackend {
+    #[default]
+    Cpu,
+    Gpu,
+}
+
+/// Which of the [`kernels`](crate::kernels) loops the compute pass runs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GpuKernel {
+    /// Boid neighbor sums, as in `kernels::steering_sums`
+    Steering { radius: f32 },
+    /// Softened gravity, as in `kernels::pairwise_gravity`, followed by a
+    /// semi-implicit Euler step of `dt` (zero only computes accelerations)
+    Gravity { g: f32, softening: f32, dt: f32 },
+    /// Poly6 density, as in `kernels::sph_density`
+    SphDensity { mass: f32, h: f32 },
+}
+
+impl GpuKernel {
+    fn args(&self) -> Vec3 {
+        match *self {
+            GpuKernel::Steering { radius } => Vec3::new(radius, 0.0, 0.0),
+            GpuKernel::Gravity { g, softening, dt } => Vec3::new(g, softening, dt),
+            GpuKernel::SphDensity { mass, h } => Vec3::new(mass, h, 0.0),
+        }
+    }
+}
+
+/// One particle in the input storage buffer
+#[derive(ShaderType, Debug, Default, Clone, Copy)]
+struct GpuParticle {
+    position: Vec2,
+    velocity: Vec2,
+    mass: f32,
+}
+
+/// Two vectors per particle; their meaning depends on the kernel (see the shader)
+#[derive(ShaderType, Debug, Default, Clone, Copy)]
+struct GpuOutput {
+    a: Vec4,
+    b: Vec4,
+}
+
+#[derive(ShaderType)]
+struct KernelParams {
+    count: u32,
+    generation: u32,
+    args: Vec3,
+}
+
+/// Results of one compute pass
+#[derive(Debug, Clone, PartialEq)]
+pub struct GpuResults<T> {
+    /// What [`GpuParticles::upload`] returned for the particle state they came from
+    pub generation: u32,
+    /// One per uploaded particle, in upload order
+    pub values: Vec<T>,
+}
+
+/// Particle state on the GPU and the latest results read back from it
+#[derive(Resource, Debug)]
+pub struct GpuParticles {
+    /// Can be changed between frames, e.g. when a slider moves
+    pub kernel: GpuKernel,
+    count: u32,
+    /// Of the latest upload
+    generation: u32,
+    input: Handle<ShaderStorageBuffer>,
+    output: Handle<ShaderStorageBuffer>,
+    results: Vec<GpuOutput>,
+    /// Of the upload `results` came from
+    results_generation: u32,
+}
+
+impl GpuParticles {
+    fn new(kernel: GpuKernel, buffers: &mut Assets<ShaderStorageBuffer>) -> Self {
+        let input = buffers.add(ShaderStorageBuffer::from(vec![GpuParticle::default()]));
+        let mut output = ShaderStorageBuffer::from(vec![GpuOutput::default()]);
+        output.buffer_description.usage |= BufferUsages::COPY_SRC;
+        Self {
+            kernel,
+            count: 0,
+            generation: 0,
+            input,
+            output: buffers.add(output),
+            results: Vec::new(),
+            results_generation: 0,
+        }
+    }
+
+    /// Replace the particle state the next compute pass reads and return its
+    /// generation, which its [`GpuResults`] carry. `masses` is only used by
+    /// [`GpuKernel::Gravity`]; missing masses count as one.
+    pub fn upload(
+        &mut self,
+        buffers: &mut Assets<ShaderStorageBuffer>,
+        positions: &[Vec2],
+        velocities: &[Vec2],
+        masses: &[f32],
+    ) -> u32 {
+        let particles: Vec<GpuParticle> = positions
+            .iter()
+            .zip(velocities)
+            .enumerate()
+            .map(|(index, (position, velocity))| GpuParticle {
+                position: *position,
+                velocity: *velocity,
+                mass: masses.get(index).copied().unwrap_or(1.0),
+            })
+            .collect();
+        let count = particles.len() as u32;
+        // Storage buffers can't be empty
+        if let Some(input) = buffers.get_mut(&self.input) {
+            input.set_data(if particles.is_empty() { vec![GpuParticle::default()] } else { particles });
+        }
+        // One more output than particles, where the pass stamps the generation
+        if count != self.count {
+            if let Some(output) = buffers.get_mut(&self.output) {
+                output.set_data(vec![GpuOutput::default(); count as usize + 1]);
+            }
+            self.count = count;
+        }
+        self.generation = self.generation.wrapping_add(1);
+        self.generation
+    }
+
+    /// Latest results, if they were computed for the current particle count
+    fn current<T>(&self, value: impl Fn(&GpuOutput) -> T) -> Option<GpuResults<T>> {
+        (self.count > 0 && self.results.len() == self.count as usize).then(|| GpuResults {
+            generation: self.results_generation,
+            values: self.results.iter().map(value).collect(),
+        })
+    }
+
+    /// Results of [`GpuKernel::Steering`]
+    pub fn steering_sums(&self) -> Option<GpuResults<SteeringSums>> {
+        let GpuKernel::Steering { .. } = self.kernel else {
+            return None;
+        };
+        self.current(|output| SteeringSums {
+            alignment: output.a.xy(),
+            cohesion: output.a.zw(),
+            separation: output.b.xy(),
+            count: output.b.z as u32,
+        })
+    }
+
+    /// Accelerations from [`GpuKernel::Gravity`]
+    pub fn accelerations(&self) -> Option<GpuResults<Vec2>> {
+        let GpuKernel::Gravity { .. } = self.kernel else {
+            return None;
+        };
+        self.current(|output| output.a.xy())
+    }
+
+    /// Positions and velocities after the [`GpuKernel::Gravity`] step
+    pub fn integrated(&self) -> Option<GpuResults<(Vec2, Vec2)>> {
+        let GpuKernel::Gravity { .. } = self.kernel else {
+            return None;
+        };
+        self.current(|output| (output.b.xy(), output.b.zw()))
+    }
+
+    /// Densities from [`GpuKernel::SphDensity`]
+    pub fn densities(&self) -> Option<GpuResults<f32>> {
+        let GpuKernel::SphDensity { .. } = self.kernel else {
+            return None;
+        };
+        self.current(|output| output.a.x)
+    }
+}
+
+/// Reads the output buffer back after every compute pass
+fn spawn_readback(mut commands: Commands, particles: Res<GpuParticles>) {
+    commands
+        .spawn(Readback::buffer(particles.output.clone()))
+        .observe(|event: On<ReadbackComplete>, mut particles: ResMut<GpuParticles>| {
+            let mut results: Vec<GpuOutput> = event.to_shader_type();
+            // The last output is the generation stamp, not a particle
+            if let Some(stamp) = results.pop() {
+                particles.results_generation = stamp.a.x.to_bits();
+                particles.results = results;
+            }
+        });
+}
+
+/// What the render world needs from [`GpuParticles`] each frame
+#[derive(Resource)]
+struct ExtractedParticles {
+    kernel: GpuKernel,
+    count: u32,
+    generation: u32,
+    input: Handle<ShaderStorageBuffer>,
+    output: Handle<ShaderStorageBuffer>,
+}
+
+impl ExtractResource for ExtractedParticles {
+    type Source = GpuParticles;
+
+    fn extract_resource(source: &GpuParticles) -> Self {
+        Self {
+            kernel: source.kernel,
+            count: source.count,
+            generation: source.generation,
+            input: source.input.clone(),
+            output: source.output.clone(),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct ParticlePipelines {
+    layout: BindGroupLayout,
+    steering: CachedComputePipelineId,
+    gravity: CachedComputePipelineId,
+    sph_density: CachedComputePipelineId,
+}
+
+impl ParticlePipelines {
+    fn new(render_world: &World, shader: Handle<Shader>) -> Self {
+        let layout = render_world.resource::<RenderDevice>().create_bind_group_layout(
+            "particle_kernels",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    uniform_buffer::<KernelParams>(false),
+                    storage_buffer_read_only::<Vec<GpuParticle>>(false),
+                    storage_buffer::<Vec<GpuOutput>>(false),
+                ),
+            ),
+        );
+        let pipeline_cache = render_world.resource::<PipelineCache>();
+        let queue = |entry_point: &'static str| {
+            pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+                label: Some(entry_point.into()),
+                layout: vec![layout.clone()],
+                shader: shader.clone(),
+                entry_point: Some(entry_point.into()),
+                ..default()
+            })
+        };
+        Self {
+            steering: queue("steering"),
+            gravity: queue("gravity"),
+            sph_density: queue("sph_density"),
+            layout,
+        }
+    }
+
+    fn get(&self, kernel: GpuKernel) -> CachedComputePipelineId {
+        match kernel {
+            GpuKernel::Steering { .. } => self.steering,
+            GpuKernel::Gravity { .. } => self.gravity,
+            GpuKernel::SphDensity { .. } => self.sph_density,
+        }
+    }
+}
+
+/// Everything the compute node needs for this frame's dispatch
+#[derive(Resource)]
+struct ParticleBindGroup {
+    bind_group: BindGroup,
+    pipeline: CachedComputePipelineId,
+    workgroups: u32,
+}
+
+// The storage buffers are recreated whenever the simulation uploads, so rebuild every frame
+fn prepare_bind_group(
+    mut commands: Commands,
+    particles: Option<Res<ExtractedParticles>>,
+    pipelines: Res<ParticlePipelines>,
+    buffers: Res<RenderAssets<GpuShaderStorageBuffer>>,
+    device: Res<RenderDevice>,
+    queue: Res<RenderQueue>,
+) {
+    commands.remove_resource::<ParticleBindGroup>();
+    let Some(particles) = particles.filter(|particles| particles.count > 0) else {
+        return;
+    };
+    let (Some(input), Some(output)) = (buffers.get(&particles.input), buffers.get(&particles.output)) else {
+        return;
+    };
+
+    let mut params = UniformBuffer::from(KernelParams {
+        count: particles.count,
+        generation: particles.generation,
+        args: particles.kernel.args(),
+    });
+    params.write_buffer(&device, &queue);
+    let Some(params) = params.binding() else {
+        return;
+    };
+    let bind_group = device.create_bind_group(
+        "particle_kernels",
+        &pipelines.layout,
+        &BindGroupEntries::sequential((
+            params,
+            input.buffer.as_entire_buffer_binding(),
+            output.buffer.as_entire_buffer_binding(),
+        )),
+    );
+    commands.insert_resource(ParticleBindGroup {
+        bind_group,
+        pipeline: pipelines.get(particles.kernel),
+        workgroups: particles.count.div_ceil(WORKGROUP_SIZE),
+    });
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+struct ParticleComputeLabel;
+
+struct ParticleComputeNode;
+
+impl render_graph::Node for ParticleComputeNode {
+    fn run(
+        &self,
+        _graph: &mut render_graph::RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), render_graph::NodeRunError> {
+        let Some(dispatch) = world.get_resource::<ParticleBindGroup>() else {
+            return Ok(());
+        };
+        // Nothing to do until the shader has compiled; the CPU kernels cover the gap
+        let Some(pipeline) = world.resource::<PipelineCache>().get_compute_pipeline(dispatch.pipeline) else {
+            return Ok(());
+        };
+        let mut pass = render_context
+            .command_encoder()
+            .begin_compute_pass(&ComputePassDescriptor {
+                label: Some("particle_kernels"),
+                ..default()
+            });
+        pass.set_pipeline(pipeline);
+        pass.set_bind_group(0, &dispatch.bind_group, &[]);
+        pass.dispatch_workgroups(dispatch.workgroups, 1, 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+//! Running a simulation without a window or renderer, for data and physics checks
+//!
+//! [`HeadlessPlugin`] stands in for `DefaultPlugins`: it adds `MinimalPlugins` with
+//! transforms, input, assets for the meshes and materials chapters spawn, and a primary
+//! `Window` that is never opened, so systems that size things from the window still
+//! see one. Time advances by exactly one physics tick per update, so [`run_steps`]
+//! takes as long as the physics does and gives the same result on every machine. The
+//! egui windows of the common plugins are left out. A chapter exposes this as
+//! `run_headless(steps)` next to `run()`; trials that only need the physics systems can
+//! start from the barer [`headless_app`](crate::montecarlo::headless_app) instead.
+use bevy::app::{AppExit, PluginsState};
+use bevy::asset::AssetPlugin;
+use bevy::input::InputPlugin;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy::window::{ExitCondition, WindowPlugin};
+
+/// Everything a chapter's simulation needs, without a window, renderer or UI
+pub struct HeadlessPlugin {
+    /// Size of the window the simulation believes it has
+    pub window_size: UVec2,
+}
+
+impl Default for HeadlessPlugin {
+    fn default() -> Self {
+        Self {
+            // The native window size
+            window_size: UVec2::new(800, 600),
+        }
+    }
+}
+
+impl HeadlessPlugin {
+    pub fn with_window_size(mut self, width: u32, height: u32) -> Self {
+        self.window_size = UVec2::new(width, height);
+        self
+    }
+}
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            InputPlugin,
+            AssetPlugin::default(),
+            WindowPlugin {
+                primary_window: Some(Window {
+                    title: "headless".to_string(),
+                    resolution: (self.window_size.x, self.window_size.y).into(),
+                    ..default()
+                }),
+                exit_condition: ExitCondition::DontExit,
+                close_when_requested: false,
+                ..default()
+            },
+        ))
+        .init_asset::<Mesh>()
+        .init_asset::<ColorMaterial>()
+        .init_asset::<Image>();
+    }
+}
+
+/// Whether the app runs without a renderer, so plugins leave out their windows
+pub fn is_headless(app: &App) -> bool {
+    app.is_plugin_added::<HeadlessPlugin>()
+}
+
+/// Finish building `app`, update it `steps` times, one physics tick each, then send
+/// `AppExit` so plugins that write files on exit do so
+pub fn run_steps(app: &mut App, steps: u32) {
+    while app.plugins_state() == PluginsState::Adding {
+        bevy::tasks::tick_global_task_pools_on_main_thread();
+    }
+    app.finish();
+    app.cleanup();
+    for _ in 0..steps {
+        // Follow the fixed timestep, which the chapter may change as it runs
+        let timestep = app.world().resource::<Time<Fixed>>().timestep();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(timestep));
+        app.update();
+    }
+    app.world_mut().write_message(AppExit::Success);
+    app.update();
+}
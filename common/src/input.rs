@@ -0,0 +1,144 @@
+//! Named input axes fed by the keyboard and gamepads
+//!
+//! Controls that steer something continuously (panning the camera, a player-driven
+//! body) read an axis from [`InputAxes`] instead of checking keys: `horizontal`,
+//! `vertical` and `zoom` by default, each in -1..1. [`InputBindings`] says which keys,
+//! gamepad stick and gamepad buttons drive each axis, and chapters can rebind them or
+//! add their own axes. Every connected gamepad counts; keys are ignored while egui is
+//! typing.
+use bevy::input::InputSystems;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy_egui::input::EguiWantsInput;
+
+/// Keeps [`InputAxes`] in step with [`InputBindings`]
+pub struct InputAxesPlugin;
+
+impl Plugin for InputAxesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputBindings>()
+            .init_resource::<InputAxes>()
+            .add_systems(PreUpdate, update_input_axes.after(InputSystems));
+    }
+}
+
+/// What drives one axis; every source adds up and the sum is clamped to -1..1
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AxisBinding {
+    pub negative_keys: Vec<KeyCode>,
+    pub positive_keys: Vec<KeyCode>,
+    pub stick: Option<GamepadAxis>,
+    pub negative_button: Option<GamepadButton>,
+    pub positive_button: Option<GamepadButton>,
+}
+
+impl AxisBinding {
+    pub fn keys(negative: impl IntoIterator<Item = KeyCode>, positive: impl IntoIterator<Item = KeyCode>) -> Self {
+        Self {
+            negative_keys: negative.into_iter().collect(),
+            positive_keys: positive.into_iter().collect(),
+            ..default()
+        }
+    }
+
+    pub fn with_stick(mut self, stick: GamepadAxis) -> Self {
+        self.stick = Some(stick);
+        self
+    }
+
+    pub fn with_buttons(mut self, negative: GamepadButton, positive: GamepadButton) -> Self {
+        self.negative_button = Some(negative);
+        self.positive_button = Some(positive);
+        self
+    }
+}
+
+/// Which inputs drive each named axis
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct InputBindings {
+    pub axes: HashMap<String, AxisBinding>,
+    /// Stick deflections smaller than this read as 0, so a worn stick doesn't drift
+    pub dead_zone: f32,
+}
+
+impl Default for InputBindings {
+    /// Arrow keys, WASD, the left stick and the d-pad move; the right stick zooms
+    fn default() -> Self {
+        Self {
+            axes: HashMap::new(),
+            dead_zone: 0.15,
+        }
+        .with_axis(
+            "horizontal",
+            AxisBinding::keys([KeyCode::ArrowLeft, KeyCode::KeyA], [KeyCode::ArrowRight, KeyCode::KeyD])
+                .with_stick(GamepadAxis::LeftStickX)
+                .with_buttons(GamepadButton::DPadLeft, GamepadButton::DPadRight),
+        )
+        .with_axis(
+            "vertical",
+            AxisBinding::keys([KeyCode::ArrowDown, KeyCode::KeyS], [KeyCode::ArrowUp, KeyCode::KeyW])
+                .with_stick(GamepadAxis::LeftStickY)
+                .with_buttons(GamepadButton::DPadDown, GamepadButton::DPadUp),
+        )
+        // Positive zooms in; the keyboard's `+` and `-` are single steps handled by the camera
+        .with_axis("zoom", AxisBinding::default().with_stick(GamepadAxis::RightStickY))
+    }
+}
+
+impl InputBindings {
+    /// Bind `name`, replacing what it was bound to
+    pub fn with_axis(mut self, name: &str, binding: AxisBinding) -> Self {
+        self.axes.insert(name.to_string(), binding);
+        self
+    }
+}
+
+/// Current value of every bound axis, in -1..1
+#[derive(Resource, Debug, Clone, Default)]
+pub struct InputAxes {
+    values: HashMap<String, f32>,
+}
+
+impl InputAxes {
+    /// 0 for axes that aren't bound
+    pub fn get(&self, name: &str) -> f32 {
+        self.values.get(name).copied().unwrap_or(0.0)
+    }
+
+    /// Two axes as a vector no longer than 1, so diagonals aren't faster
+    pub fn vector(&self, x: &str, y: &str) -> Vec2 {
+        Vec2::new(self.get(x), self.get(y)).clamp_length_max(1.0)
+    }
+}
+
+/// System to read every binding's keys and gamepads into [`InputAxes`]
+pub fn update_input_axes(
+    bindings: Res<InputBindings>,
+    mut axes: ResMut<InputAxes>,
+    keys: Option<Res<ButtonInput<KeyCode>>>,
+    gamepads: Query<&Gamepad>,
+    egui_input: Option<Res<EguiWantsInput>>,
+) {
+    let keys = keys.filter(|_| egui_input.is_none_or(|input| !input.wants_any_keyboard_input()));
+    axes.values.clear();
+    for (name, binding) in &bindings.axes {
+        let mut value = 0.0;
+        if let Some(keys) = keys.as_deref() {
+            value += keys.any_pressed(binding.positive_keys.iter().copied()) as i32 as f32;
+            value -= keys.any_pressed(binding.negative_keys.iter().copied()) as i32 as f32;
+        }
+        for gamepad in &gamepads {
+            let stick = binding.stick.and_then(|stick| gamepad.get(stick)).unwrap_or(0.0);
+            if stick.abs() > bindings.dead_zone {
+                value += stick;
+            }
+            if binding.positive_button.is_some_and(|button| gamepad.pressed(button)) {
+                value += 1.0;
+            }
+            if binding.negative_button.is_some_and(|button| gamepad.pressed(button)) {
+                value -= 1.0;
+            }
+        }
+        axes.values.insert(name.clone(), value.clamp(-1.0, 1.0));
+    }
+}
@@ -0,0 +1,60 @@
+//! Live entity and component editor
+//!
+//! With the `inspector` feature, [`InspectorPlugin`] registers the common components
+//! for reflection and adds a world inspector window (toggled with F2), where any
+//! entity's `Position`, `Velocity`, `Mass` and so on can be read and edited while the
+//! simulation runs: a single boid sped up, a projectile's mass doubled mid-flight.
+use bevy::input::common_conditions::input_toggle_active;
+use bevy::prelude::*;
+use bevy_inspector_egui::quick::WorldInspectorPlugin;
+
+use crate::collision::{ColliderShape, MassFromMaterial, PhysicsMaterial};
+use crate::forces::{Drag, Gravity, GravityScale, NoGravity, Spring};
+use crate::sleep::{NeverSleep, Sleeping};
+use crate::three_d::{Acceleration3d, AngularVelocity3d, Force3d, Position3d, Velocity3d};
+use crate::{
+    Acceleration, AngularAcceleration, AngularVelocity, Charge, Force, Mass, MomentOfInertia, Position, Rotation,
+    Temperature, Torque, Velocity,
+};
+
+/// Adds the world inspector window, hidden until F2 is pressed
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        crate::add_egui(app);
+        register_common_types(app);
+        app.add_plugins(WorldInspectorPlugin::new().run_if(input_toggle_active(false, KeyCode::F2)));
+    }
+}
+
+/// Register the common components and resources, so reflection-based tools can see them
+pub fn register_common_types(app: &mut App) {
+    app.register_type::<Position>()
+        .register_type::<Velocity>()
+        .register_type::<Acceleration>()
+        .register_type::<Mass>()
+        .register_type::<Force>()
+        .register_type::<Rotation>()
+        .register_type::<AngularVelocity>()
+        .register_type::<AngularAcceleration>()
+        .register_type::<MomentOfInertia>()
+        .register_type::<Torque>()
+        .register_type::<Charge>()
+        .register_type::<Temperature>()
+        .register_type::<Position3d>()
+        .register_type::<Velocity3d>()
+        .register_type::<Acceleration3d>()
+        .register_type::<Force3d>()
+        .register_type::<AngularVelocity3d>()
+        .register_type::<Spring>()
+        .register_type::<Drag>()
+        .register_type::<NoGravity>()
+        .register_type::<GravityScale>()
+        .register_type::<Gravity>()
+        .register_type::<ColliderShape>()
+        .register_type::<PhysicsMaterial>()
+        .register_type::<MassFromMaterial>()
+        .register_type::<Sleeping>()
+        .register_type::<NeverSleep>();
+}
@@ -0,0 +1,231 @@
+//! Numerical integrators for the equations of motion
+use bevy::prelude::*;
+use rhysics_math::ode::Rk45;
+use std::ops::{Add, Mul, Sub};
+
+/// A vector the integrators can advance: `Vec2` for the shared physics, `Vec3` for the
+/// 3D bodies, or a plain `f32`
+pub trait StateVector: Copy + Add<Output = Self> + Sub<Output = Self> + Mul<f32, Output = Self> {}
+
+impl<V: Copy + Add<Output = V> + Sub<Output = V> + Mul<f32, Output = V>> StateVector for V {}
+
+/// Position and velocity of a body, the state advanced by the integrators
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct BodyState<V = Vec2> {
+    pub position: V,
+    pub velocity: V,
+}
+
+impl<V> BodyState<V> {
+    pub fn new(position: V, velocity: V) -> Self {
+        Self { position, velocity }
+    }
+}
+
+impl BodyState {
+    /// `[x, y, vx, vy]`, the layout `rhysics_math::ode` steps
+    fn to_array(self) -> [f64; 4] {
+        [self.position.x, self.position.y, self.velocity.x, self.velocity.y].map(f64::from)
+    }
+
+    fn from_slice(state: &[f64]) -> Self {
+        Self::new(
+            Vec2::new(state[0] as f32, state[1] as f32),
+            Vec2::new(state[2] as f32, state[3] as f32),
+        )
+    }
+}
+
+impl<V: StateVector> Add for BodyState<V> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.position + other.position, self.velocity + other.velocity)
+    }
+}
+
+impl<V: StateVector> Sub for BodyState<V> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.position - other.position, self.velocity - other.velocity)
+    }
+}
+
+impl<V: StateVector> Mul<f32> for BodyState<V> {
+    type Output = Self;
+
+    fn mul(self, factor: f32) -> Self {
+        Self::new(self.position * factor, self.velocity * factor)
+    }
+}
+
+/// Time derivative of a state: (velocity, acceleration)
+fn derivative<V: StateVector>(
+    state: BodyState<V>,
+    t: f32,
+    acceleration: &impl Fn(f32, BodyState<V>) -> V,
+) -> BodyState<V> {
+    BodyState::new(state.velocity, acceleration(t, state))
+}
+
+/// Acceleration for every stage of a step: `held`, the step's accumulated force over the
+/// mass, with the body's own state-dependent forces `local` re-evaluated at each
+/// stage's state instead of held at their value for `start`
+pub fn stage_acceleration<V: StateVector>(
+    held: V,
+    inverse_mass: f32,
+    start: BodyState<V>,
+    local: impl Fn(BodyState<V>) -> V,
+) -> impl Fn(f32, BodyState<V>) -> V {
+    let at_start = local(start);
+    move |_, state| held + (local(state) - at_start) * inverse_mass
+}
+
+/// Fixed-step integration scheme, switchable at runtime to compare accuracy and energy behavior
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Integrator {
+    /// x += v dt, then v += a dt. Gains energy in orbits and oscillators.
+    Euler,
+    /// v += a dt, then x += v dt with the new velocity. Symplectic: energy oscillates
+    /// instead of drifting.
+    #[default]
+    SemiImplicitEuler,
+    /// Second order and symplectic; the acceleration is re-evaluated at the new position
+    VelocityVerlet,
+    /// Classic fourth-order Runge-Kutta
+    Rk4,
+}
+
+impl Integrator {
+    pub const ALL: [Integrator; 4] = [
+        Integrator::Euler,
+        Integrator::SemiImplicitEuler,
+        Integrator::VelocityVerlet,
+        Integrator::Rk4,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Integrator::Euler => "Explicit Euler",
+            Integrator::SemiImplicitEuler => "Semi-implicit Euler",
+            Integrator::VelocityVerlet => "Velocity Verlet",
+            Integrator::Rk4 => "RK4",
+        }
+    }
+
+    /// Whether reversing every velocity and stepping on retraces the path exactly (up to
+    /// rounding) under gravity and springs to fixed anchors, the position-dependent
+    /// forces `integrate_bodies` re-evaluates within a step; see `physics::TimeDirection`
+    pub fn is_time_reversible(&self) -> bool {
+        matches!(self, Integrator::VelocityVerlet)
+    }
+
+    /// Advance `state` from `t` by `dt`, with `acceleration(t, state)`
+    pub fn step<V: StateVector>(
+        &self,
+        state: BodyState<V>,
+        t: f32,
+        dt: f32,
+        acceleration: impl Fn(f32, BodyState<V>) -> V,
+    ) -> BodyState<V> {
+        let BodyState { position, velocity } = state;
+        match self {
+            Integrator::Euler => {
+                let a = acceleration(t, state);
+                BodyState::new(position + velocity * dt, velocity + a * dt)
+            }
+            Integrator::SemiImplicitEuler => {
+                let velocity = velocity + acceleration(t, state) * dt;
+                BodyState::new(position + velocity * dt, velocity)
+            }
+            Integrator::VelocityVerlet => {
+                let a0 = acceleration(t, state);
+                let position = position + velocity * dt + a0 * (0.5 * dt * dt);
+                // Velocity-dependent forces see a first-order guess of the new velocity
+                let a1 = acceleration(t + dt, BodyState::new(position, velocity + a0 * dt));
+                BodyState::new(position, velocity + (a0 + a1) * (0.5 * dt))
+            }
+            Integrator::Rk4 => {
+                let k1 = derivative(state, t, &acceleration);
+                let k2 = derivative(state + k1 * (dt / 2.0), t + dt / 2.0, &acceleration);
+                let k3 = derivative(state + k2 * (dt / 2.0), t + dt / 2.0, &acceleration);
+                let k4 = derivative(state + k3 * dt, t + dt, &acceleration);
+                state + (k1 + k2 * 2.0 + k3 * 2.0 + k4) * (dt / 6.0)
+            }
+        }
+    }
+}
+
+/// Adaptive RK45 stepping with error control, by [`rhysics_math::ode::Rk45`]
+///
+/// Each call to [`AdaptiveStep::advance`] covers a whole frame or fixed step, taking
+/// as many internal steps as the tolerance requires: tiny ones during a close
+/// encounter, large ones while nothing much is happening.
+#[derive(Component, Resource, Debug, Clone, Copy)]
+pub struct AdaptiveStep {
+    /// Largest allowed local error per internal step, absolute for components below 1
+    /// and relative above
+    pub tolerance: f32,
+    pub min_dt: f32,
+    pub max_dt: f32,
+    /// Step size carried over to the next call
+    dt: f32,
+}
+
+impl Default for AdaptiveStep {
+    fn default() -> Self {
+        Self::new(1e-4, 1e-5, 0.05)
+    }
+}
+
+impl AdaptiveStep {
+    pub fn new(tolerance: f32, min_dt: f32, max_dt: f32) -> Self {
+        Self {
+            tolerance,
+            min_dt,
+            max_dt,
+            dt: max_dt,
+        }
+    }
+
+    /// The step size the next internal step will try
+    pub fn dt(&self) -> f32 {
+        self.dt
+    }
+
+    /// Advance `state` from `t` by `duration` seconds, backwards in time if it is negative
+    pub fn advance(
+        &mut self,
+        state: BodyState,
+        t: f32,
+        duration: f32,
+        acceleration: impl Fn(f32, BodyState) -> Vec2,
+    ) -> BodyState {
+        let rk45 = Rk45::new(self.tolerance.into()).with_step_limits(self.min_dt.into(), self.max_dt.into());
+        let mut rates = |t: f64, state: &[f64], rate: &mut [f64]| {
+            let acceleration = acceleration(t as f32, BodyState::from_slice(state));
+            rate.copy_from_slice(&[state[2], state[3], acceleration.x.into(), acceleration.y.into()]);
+        };
+        let direction = f64::from(duration.signum());
+        let end = f64::from(t) + f64::from(duration);
+        let mut t = f64::from(t);
+        let mut state = state.to_array().to_vec();
+        while (end - t) * direction > 0.0 {
+            // Step sizes are kept positive; `direction` gives the sign
+            let dt = f64::from(self.dt.clamp(self.min_dt, self.max_dt)).min((end - t) * direction);
+            let attempt = rk45.attempt(&mut rates, t, &state, dt * direction);
+            // At the smallest step allowed the error is accepted whatever it is
+            if attempt.accepted || dt <= f64::from(self.min_dt) {
+                state = attempt.state;
+                t += dt * direction;
+                // Don't let a short final step shrink the next call's step
+                if dt < f64::from(self.dt) {
+                    continue;
+                }
+            }
+            self.dt = attempt.next_step as f32;
+        }
+        BodyState::from_slice(&state)
+    }
+}
@@ -0,0 +1,93 @@
+//! Smooths rendering of entities that are moved in `FixedUpdate`
+//!
+//! Physics advances in fixed steps while frames are drawn at the display rate, so
+//! without interpolation a body visibly jumps once per step at low fixed rates.
+//! Entities with [`TransformInterpolation`] are drawn between their previous and
+//! current physics states, blended by how far the clock has run into the next step.
+use bevy::prelude::*;
+use bevy::transform::TransformSystems;
+
+/// Blends the rendered `Transform` of [`TransformInterpolation`] entities between fixed updates
+pub struct InterpolationPlugin;
+
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedFirst, restore_physics_transforms)
+            .add_systems(FixedLast, record_physics_transforms)
+            .add_systems(
+                PostUpdate,
+                interpolate_transforms.before(TransformSystems::Propagate),
+            );
+    }
+}
+
+/// Opt an entity into transform interpolation
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub struct TransformInterpolation {
+    previous: Option<(Vec3, Quat)>,
+    current: Option<(Vec3, Quat)>,
+    /// The blended transform written last frame, used to detect teleports
+    rendered: Option<(Vec3, Quat)>,
+}
+
+impl TransformInterpolation {
+    /// Was the transform moved by something other than the interpolation (a reset, a drag)?
+    fn moved_externally(&self, transform: &Transform) -> bool {
+        self.rendered
+            .is_some_and(|rendered| rendered != (transform.translation, transform.rotation))
+    }
+
+    /// Take the transform as-is, with nothing to blend from
+    fn snap(&mut self, transform: &Transform) {
+        let state = Some((transform.translation, transform.rotation));
+        self.previous = state;
+        self.current = state;
+        self.rendered = None;
+    }
+}
+
+/// Put the true physics state back before the fixed step runs
+fn restore_physics_transforms(mut query: Query<(&mut Transform, &mut TransformInterpolation)>) {
+    for (mut transform, mut interpolation) in &mut query {
+        if interpolation.moved_externally(&transform) {
+            interpolation.snap(&transform);
+        } else if let Some((translation, rotation)) = interpolation.current {
+            transform.translation = translation;
+            transform.rotation = rotation;
+        }
+        interpolation.previous = interpolation.current;
+        interpolation.rendered = interpolation.current;
+    }
+}
+
+/// Remember the state produced by the fixed step
+fn record_physics_transforms(mut query: Query<(&Transform, &mut TransformInterpolation)>) {
+    for (transform, mut interpolation) in &mut query {
+        interpolation.current = Some((transform.translation, transform.rotation));
+        if interpolation.previous.is_none() {
+            interpolation.previous = interpolation.current;
+        }
+        interpolation.rendered = interpolation.current;
+    }
+}
+
+fn interpolate_transforms(
+    mut query: Query<(&mut Transform, &mut TransformInterpolation)>,
+    fixed_time: Res<Time<Fixed>>,
+) {
+    let alpha = fixed_time.overstep_fraction();
+    for (mut transform, mut interpolation) in &mut query {
+        if interpolation.moved_externally(&transform) {
+            interpolation.snap(&transform);
+            continue;
+        }
+        let (Some((from_translation, from_rotation)), Some((to_translation, to_rotation))) =
+            (interpolation.previous, interpolation.current)
+        else {
+            continue;
+        };
+        transform.translation = from_translation.lerp(to_translation, alpha);
+        transform.rotation = from_rotation.slerp(to_rotation, alpha);
+        interpolation.rendered = Some((transform.translation, transform.rotation));
+    }
+}
@@ -0,0 +1,266 @@
+//! Hot inner loops shared by the particle chapters
+//!
+//! The scalar versions are always available. Building with the `simd` feature
+//! switches the top-level functions to eight-wide SIMD versions; see
+//! `benches/kernels.rs` for the comparison.
+use bevy::prelude::*;
+
+/// Width of the SIMD lanes, and the padding granularity of [`PointSet`]
+const LANES: usize = 8;
+
+/// Points stored as separate x and y arrays, padded with NaN to a whole number of
+/// SIMD lanes so the vector loops can load them directly. Build one per frame and
+/// share it between every query.
+#[derive(Debug, Default, Clone)]
+pub struct PointSet {
+    xs: Vec<f32>,
+    ys: Vec<f32>,
+    len: usize,
+}
+
+impl PointSet {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<Vec2> {
+        (index < self.len).then(|| Vec2::new(self.xs[index], self.ys[index]))
+    }
+
+    /// The points without padding
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = Vec2> + '_ {
+        self.xs[..self.len]
+            .iter()
+            .zip(&self.ys[..self.len])
+            .map(|(x, y)| Vec2::new(*x, *y))
+    }
+}
+
+impl FromIterator<Vec2> for PointSet {
+    fn from_iter<I: IntoIterator<Item = Vec2>>(points: I) -> Self {
+        let (mut xs, mut ys): (Vec<f32>, Vec<f32>) = points.into_iter().map(|point| (point.x, point.y)).unzip();
+        let len = xs.len();
+        let padded = len.next_multiple_of(LANES);
+        xs.resize(padded, f32::NAN);
+        ys.resize(padded, f32::NAN);
+        Self { xs, ys, len }
+    }
+}
+
+impl From<&[Vec2]> for PointSet {
+    fn from(points: &[Vec2]) -> Self {
+        points.iter().copied().collect()
+    }
+}
+
+/// Neighbor sums for the three classic boid rules
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SteeringSums {
+    /// Sum of neighbor velocities
+    pub alignment: Vec2,
+    /// Sum of neighbor positions
+    pub cohesion: Vec2,
+    /// Sum of offsets away from neighbors, weighted by inverse square distance
+    pub separation: Vec2,
+    pub count: u32,
+}
+
+pub use scalar::steering_sums_of;
+#[cfg(not(feature = "simd"))]
+pub use scalar::{pairwise_gravity, sph_density, steering_sums};
+#[cfg(feature = "simd")]
+pub use simd::{pairwise_gravity, sph_density, steering_sums};
+
+/// Normalization of the 2D poly6 smoothing kernel
+fn poly6_factor(h: f32) -> f32 {
+    4.0 / (std::f32::consts::PI * h.powi(8))
+}
+
+pub mod scalar {
+    use super::*;
+
+    /// Sum the boid rule contributions of every neighbor within `radius` of `position`.
+    /// The boid itself (at distance zero) is skipped.
+    pub fn steering_sums(position: Vec2, positions: &PointSet, velocities: &PointSet, radius: f32) -> SteeringSums {
+        steering_sums_of(position, positions.iter().zip(velocities.iter()), radius)
+    }
+
+    /// [`steering_sums`] over candidate `(position, velocity)` pairs, e.g. the
+    /// neighbors a [`SpatialGrid`](crate::spatial::SpatialGrid) query found
+    pub fn steering_sums_of(
+        position: Vec2,
+        candidates: impl IntoIterator<Item = (Vec2, Vec2)>,
+        radius: f32,
+    ) -> SteeringSums {
+        let mut sums = SteeringSums::default();
+        for (other_pos, other_vel) in candidates {
+            let diff = other_pos - position;
+            let dist = diff.length();
+            if dist < radius && dist > 0.0 {
+                sums.alignment += other_vel;
+                sums.cohesion += other_pos;
+                sums.separation -= diff / (dist * dist);
+                sums.count += 1;
+            }
+        }
+        sums
+    }
+
+    /// Softened Newtonian gravity: the acceleration of every body due to all the others
+    pub fn pairwise_gravity(positions: &PointSet, masses: &[f32], g: f32, softening: f32) -> Vec<Vec2> {
+        let softening2 = softening * softening;
+        positions
+            .iter()
+            .map(|position| {
+                let mut acceleration = Vec2::ZERO;
+                for (other, mass) in positions.iter().zip(masses) {
+                    let diff = other - position;
+                    let d2 = diff.length_squared();
+                    if d2 > 0.0 {
+                        let r2 = d2 + softening2;
+                        acceleration += diff * (g * mass / (r2 * r2.sqrt()));
+                    }
+                }
+                acceleration
+            })
+            .collect()
+    }
+
+    /// SPH density at every particle using the poly6 kernel with smoothing length `h`
+    pub fn sph_density(positions: &PointSet, mass: f32, h: f32) -> Vec<f32> {
+        let h2 = h * h;
+        let factor = mass * poly6_factor(h);
+        positions
+            .iter()
+            .map(|position| {
+                let mut density = 0.0;
+                for other in positions.iter() {
+                    let r2 = position.distance_squared(other);
+                    if r2 < h2 {
+                        density += (h2 - r2).powi(3);
+                    }
+                }
+                density * factor
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "simd")]
+pub mod simd {
+    use super::*;
+    use wide::{f32x8, CmpGt, CmpLt};
+
+    fn lanes(chunk: &[f32]) -> f32x8 {
+        let lanes: [f32; LANES] = chunk.try_into().expect("PointSet is padded to whole lanes");
+        f32x8::from(lanes)
+    }
+
+    /// Eight masses starting at `start`, padded with zero past the end
+    fn load_masses(masses: &[f32], start: usize) -> f32x8 {
+        match masses.get(start..start + LANES) {
+            Some(chunk) => lanes(chunk),
+            None => {
+                let mut padded = [0.0; LANES];
+                for (lane, mass) in masses[start.min(masses.len())..].iter().enumerate() {
+                    padded[lane] = *mass;
+                }
+                f32x8::from(padded)
+            }
+        }
+    }
+
+    /// SIMD version of [`scalar::steering_sums`]
+    pub fn steering_sums(position: Vec2, positions: &PointSet, velocities: &PointSet, radius: f32) -> SteeringSums {
+        let (px, py) = (f32x8::splat(position.x), f32x8::splat(position.y));
+        let radius2 = f32x8::splat(radius * radius);
+        let zero = f32x8::ZERO;
+        let mut alignment = (zero, zero);
+        let mut cohesion = (zero, zero);
+        let mut separation = (zero, zero);
+        let mut count = zero;
+
+        let others = positions.xs.chunks_exact(LANES).zip(positions.ys.chunks_exact(LANES));
+        let other_velocities = velocities.xs.chunks_exact(LANES).zip(velocities.ys.chunks_exact(LANES));
+        for ((ox, oy), (vx, vy)) in others.zip(other_velocities) {
+            let (ox, oy, vx, vy) = (lanes(ox), lanes(oy), lanes(vx), lanes(vy));
+            let (dx, dy) = (ox - px, oy - py);
+            let d2 = dx * dx + dy * dy;
+            // NaN padding fails both comparisons, so padding lanes are masked out
+            let mask = d2.cmp_lt(radius2) & d2.cmp_gt(zero);
+            // Avoid dividing by zero in masked-out lanes
+            let safe_d2 = mask.blend(d2, f32x8::ONE);
+
+            alignment.0 += mask.blend(vx, zero);
+            alignment.1 += mask.blend(vy, zero);
+            cohesion.0 += mask.blend(ox, zero);
+            cohesion.1 += mask.blend(oy, zero);
+            separation.0 -= mask.blend(dx / safe_d2, zero);
+            separation.1 -= mask.blend(dy / safe_d2, zero);
+            count += mask.blend(f32x8::ONE, zero);
+        }
+
+        SteeringSums {
+            alignment: Vec2::new(alignment.0.reduce_add(), alignment.1.reduce_add()),
+            cohesion: Vec2::new(cohesion.0.reduce_add(), cohesion.1.reduce_add()),
+            separation: Vec2::new(separation.0.reduce_add(), separation.1.reduce_add()),
+            count: count.reduce_add() as u32,
+        }
+    }
+
+    /// SIMD version of [`scalar::pairwise_gravity`]
+    pub fn pairwise_gravity(positions: &PointSet, masses: &[f32], g: f32, softening: f32) -> Vec<Vec2> {
+        let softening2 = f32x8::splat(softening * softening);
+        let g = f32x8::splat(g);
+        let zero = f32x8::ZERO;
+        let masses: Vec<f32x8> = (0..positions.xs.len())
+            .step_by(LANES)
+            .map(|start| load_masses(masses, start))
+            .collect();
+        positions
+            .iter()
+            .map(|position| {
+                let (px, py) = (f32x8::splat(position.x), f32x8::splat(position.y));
+                let mut ax = zero;
+                let mut ay = zero;
+                let others = positions.xs.chunks_exact(LANES).zip(positions.ys.chunks_exact(LANES));
+                for ((ox, oy), mass) in others.zip(&masses) {
+                    let (dx, dy) = (lanes(ox) - px, lanes(oy) - py);
+                    let d2 = dx * dx + dy * dy;
+                    let r2 = d2 + softening2;
+                    let strength = g * *mass / (r2 * r2.sqrt());
+                    // Skips the body itself and the NaN padding
+                    let mask = d2.cmp_gt(zero);
+                    ax += mask.blend(dx * strength, zero);
+                    ay += mask.blend(dy * strength, zero);
+                }
+                Vec2::new(ax.reduce_add(), ay.reduce_add())
+            })
+            .collect()
+    }
+
+    /// SIMD version of [`scalar::sph_density`]
+    pub fn sph_density(positions: &PointSet, mass: f32, h: f32) -> Vec<f32> {
+        let h2 = f32x8::splat(h * h);
+        let factor = mass * poly6_factor(h);
+        positions
+            .iter()
+            .map(|position| {
+                let (px, py) = (f32x8::splat(position.x), f32x8::splat(position.y));
+                let mut density = f32x8::ZERO;
+                let others = positions.xs.chunks_exact(LANES).zip(positions.ys.chunks_exact(LANES));
+                for (ox, oy) in others {
+                    let (dx, dy) = (lanes(ox) - px, lanes(oy) - py);
+                    let r2 = dx * dx + dy * dy;
+                    let w = h2 - r2;
+                    density += r2.cmp_lt(h2).blend(w * w * w, f32x8::ZERO);
+                }
+                density.reduce_add() * factor
+            })
+            .collect()
+    }
+}
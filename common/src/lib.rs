@@ -1,23 +1,182 @@
 /// Common utilities and components for all physics simulations
+use bevy::platform::collections::HashMap;
 use bevy::prelude::*;
 
+pub mod analytic;
+pub mod boundary;
+pub mod broadphase;
+pub mod camera;
+pub mod capture;
+pub mod collision;
+pub mod collision_sound;
+pub mod colormap;
+pub mod comparison;
+pub mod console;
+pub mod controls;
+pub mod datalog;
+pub mod diagnostics;
+pub mod energy;
+pub mod equation;
+pub mod field;
+pub mod field_arrows;
+pub mod force_field;
+pub mod forces;
+#[cfg(feature = "gpu-compute")]
+pub mod gpu;
+pub mod headless;
+pub mod input;
+#[cfg(feature = "inspector")]
+pub mod inspector;
+pub mod integrator;
+pub mod interpolation;
+pub mod kernels;
+pub mod montecarlo;
+pub mod parallel;
+pub mod particles;
+pub mod physics;
+pub mod pick;
+pub mod plot;
+pub mod pointer;
+pub mod pool;
+pub mod presets;
+pub mod quadtree;
+pub mod quiz;
+pub mod replay;
+pub mod report;
+pub mod save;
+pub mod share;
+pub mod sleep;
+pub mod spatial;
+pub mod spawn_layout;
+pub mod three_d;
+pub mod timestep;
+pub mod trail;
+pub mod tutorial;
+pub mod units;
+pub mod watchdog;
+pub mod world_scale;
+pub mod xpbd;
+
+/// Add egui unless the simulation's own UI already did
+pub(crate) fn add_egui(app: &mut App) {
+    // Windows added without egui are never drawn, which is what a headless run wants
+    if !headless::is_headless(app) && !app.is_plugin_added::<bevy_egui::EguiPlugin>() {
+        app.add_plugins(bevy_egui::EguiPlugin::default());
+    }
+}
+
 /// Common camera setup for 2D simulations
 pub fn spawn_camera(mut commands: Commands) {
     commands.spawn(Camera2d);
 }
 
 /// Common component for positioning entities
-#[derive(Component, Default, Debug, Clone, Copy)]
+#[derive(Component, Default, Debug, Clone, Copy, Reflect)]
+#[reflect(Component, Default)]
 pub struct Position(pub Vec2);
 
 /// Common component for velocity
-#[derive(Component, Default, Debug, Clone, Copy)]
+#[derive(Component, Default, Debug, Clone, Copy, Reflect)]
+#[reflect(Component, Default)]
 pub struct Velocity(pub Vec2);
 
 /// Common component for acceleration
-#[derive(Component, Default, Debug, Clone, Copy)]
+#[derive(Component, Default, Debug, Clone, Copy, Reflect)]
+#[reflect(Component, Default)]
 pub struct Acceleration(pub Vec2);
 
+/// Inertial mass (kg); bodies with a mass are moved by the [`Force`]s applied to them
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component, Default)]
+#[require(Force, Acceleration)]
+pub struct Mass(pub f32);
+
+impl Default for Mass {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Net force (N) accumulated over a step; force sources add to it and
+/// [`apply_forces`] turns it into acceleration and clears it
+#[derive(Component, Default, Debug, Clone, Copy, Reflect)]
+#[reflect(Component, Default)]
+pub struct Force(pub Vec2);
+
+/// Angle of a body about the z axis (radians, counterclockwise), the rotational
+/// counterpart of [`Position`]
+#[derive(Component, Default, Debug, Clone, Copy, Reflect)]
+#[reflect(Component, Default)]
+pub struct Rotation(pub f32);
+
+/// Spin (rad/s, counterclockwise)
+#[derive(Component, Default, Debug, Clone, Copy, Reflect)]
+#[reflect(Component, Default)]
+pub struct AngularVelocity(pub f32);
+
+/// Rate of change of [`AngularVelocity`] (rad/s²)
+#[derive(Component, Default, Debug, Clone, Copy, Reflect)]
+#[reflect(Component, Default)]
+pub struct AngularAcceleration(pub f32);
+
+/// Moment of inertia about the center (kg·m²), the rotational counterpart of [`Mass`];
+/// bodies with one are spun by the [`Torque`]s applied to them
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component, Default)]
+#[require(Torque, AngularAcceleration)]
+pub struct MomentOfInertia(pub f32);
+
+impl Default for MomentOfInertia {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+impl MomentOfInertia {
+    /// Solid disk, I = ½mr²
+    pub fn disk(mass: f32, radius: f32) -> Self {
+        Self(0.5 * mass * radius * radius)
+    }
+
+    /// Thin ring, I = mr²
+    pub fn ring(mass: f32, radius: f32) -> Self {
+        Self(mass * radius * radius)
+    }
+
+    /// Solid rectangle, I = m(w² + h²)/12
+    pub fn rectangle(mass: f32, size: Vec2) -> Self {
+        Self(mass * size.length_squared() / 12.0)
+    }
+
+    /// Thin rod about its middle, I = mL²/12
+    pub fn rod(mass: f32, length: f32) -> Self {
+        Self(mass * length * length / 12.0)
+    }
+}
+
+/// Net torque (N·m, counterclockwise) accumulated over a step; force sources add to it
+/// and [`apply_torques`] turns it into angular acceleration and clears it
+#[derive(Component, Default, Debug, Clone, Copy, Reflect)]
+#[reflect(Component, Default)]
+pub struct Torque(pub f32);
+
+impl Torque {
+    /// Torque of `force` applied at `lever_arm` from the center, τ = r × F
+    pub fn of(lever_arm: Vec2, force: Vec2) -> f32 {
+        lever_arm.perp_dot(force)
+    }
+}
+
+/// Electric charge (C)
+#[derive(Component, Default, Debug, Clone, Copy, Reflect)]
+#[reflect(Component, Default)]
+pub struct Charge(pub f32);
+
+/// Temperature of a body or parcel (K), for chapters that model heat
+#[derive(Component, Default, Debug, Clone, Copy, Reflect)]
+#[reflect(Component, Default)]
+pub struct Temperature(pub f32);
+
 /// Common physics constants
 pub mod constants {
     /// Gravitational acceleration (m/s²)
@@ -67,17 +226,129 @@ pub fn project_positions(mut positionables: Query<(&mut Transform, &Position)>)
     }
 }
 
-/// System to apply velocity to position
-pub fn apply_velocity(mut entities: Query<(&mut Position, &Velocity)>) {
+/// System to turn [`Rotation`] components into Transform.rotation about z
+pub fn project_rotations(mut rotatables: Query<(&mut Transform, &Rotation)>) {
+    for (mut transform, rotation) in &mut rotatables {
+        transform.rotation = Quat::from_rotation_z(rotation.0);
+    }
+}
+
+/// System to advance bodies with a position by the selected [`Integrator`]. Each body's
+/// own [`Drag`](forces::Drag) and [`Spring`](forces::Spring)s are re-evaluated at every
+/// stage of the integrator, with the spring's other end where it started the step;
+/// the rest of the step's accumulated force is held constant.
+#[allow(clippy::type_complexity)]
+pub fn integrate_bodies(
+    mut bodies: ParamSet<(
+        Query<
+            (Entity, &mut Position, &mut Velocity, Option<&Acceleration>, Option<&Mass>, Option<&forces::Drag>),
+            Without<sleep::Sleeping>,
+        >,
+        Query<(&Position, Option<&Velocity>)>,
+    )>,
+    springs: Query<(Entity, &forces::Spring)>,
+    integrator: Res<integrator::Integrator>,
+    time: Res<Time>,
+) {
+    // Every spring on each body, with where its other end starts the step
+    let mut attached: HashMap<Entity, Vec<(forces::Spring, integrator::BodyState)>> = HashMap::new();
+    {
+        let ends = bodies.p1();
+        let start_of = |entity| {
+            ends.get(entity).ok().map(|(position, velocity)| {
+                integrator::BodyState::new(position.0, velocity.map_or(Vec2::ZERO, |velocity| velocity.0))
+            })
+        };
+        for (entity, spring) in &springs {
+            if let (Some(start), Some(other)) = (start_of(entity), start_of(spring.other)) {
+                attached.entry(entity).or_default().push((*spring, other));
+                attached.entry(spring.other).or_default().push((*spring, start));
+            }
+        }
+    }
+
+    let t = time.elapsed_secs();
+    let mut movers = bodies.p0();
+    for (entity, mut position, mut velocity, acceleration, mass, drag) in &mut movers {
+        let held = acceleration.map_or(Vec2::ZERO, |acceleration| acceleration.0);
+        let start = integrator::BodyState::new(position.0, velocity.0);
+        let springs = attached.get(&entity).map_or(&[][..], Vec::as_slice);
+        // A spring's force on either end depends only on the offset and relative velocity
+        let local = |state: integrator::BodyState| {
+            drag.map_or(Vec2::ZERO, |drag| drag.force(state.velocity))
+                + springs
+                    .iter()
+                    .map(|(spring, other)| {
+                        spring.force(state.position - other.position, state.velocity - other.velocity)
+                    })
+                    .sum::<Vec2>()
+        };
+        // Forces only move bodies with a mass
+        let next = match mass {
+            Some(mass) => integrator.step(
+                start,
+                t,
+                time.delta_secs(),
+                integrator::stage_acceleration(held, 1.0 / mass.0, start, local),
+            ),
+            None => integrator.step(start, t, time.delta_secs(), |_, _| held),
+        };
+        position.0 = next.position;
+        velocity.0 = next.velocity;
+    }
+}
+
+/// System to apply velocity to position over the step
+pub fn apply_velocity(mut entities: Query<(&mut Position, &Velocity), Without<sleep::Sleeping>>, time: Res<Time>) {
     for (mut position, velocity) in &mut entities {
-        position.0 += velocity.0;
+        position.0 += velocity.0 * time.delta_secs();
+    }
+}
+
+/// System to set acceleration from the accumulated force, a = F / m, and reset the force
+pub fn apply_forces(mut entities: Query<(&mut Force, &Mass, &mut Acceleration)>) {
+    for (mut force, mass, mut acceleration) in &mut entities {
+        acceleration.0 = force.0 / mass.0;
+        force.0 = Vec2::ZERO;
     }
 }
 
-/// System to apply acceleration to velocity
-pub fn apply_acceleration(mut entities: Query<(&mut Velocity, &Acceleration)>) {
+/// System to apply acceleration to velocity over the step
+pub fn apply_acceleration(
+    mut entities: Query<(&mut Velocity, &Acceleration), Without<sleep::Sleeping>>,
+    time: Res<Time>,
+) {
     for (mut velocity, acceleration) in &mut entities {
-        velocity.0 += acceleration.0;
+        velocity.0 += acceleration.0 * time.delta_secs();
     }
 }
 
+/// System to set angular acceleration from the accumulated torque, α = τ / I, and reset the torque
+pub fn apply_torques(mut entities: Query<(&mut Torque, &MomentOfInertia, &mut AngularAcceleration)>) {
+    for (mut torque, inertia, mut angular_acceleration) in &mut entities {
+        angular_acceleration.0 = torque.0 / inertia.0;
+        torque.0 = 0.0;
+    }
+}
+
+/// System to advance rotating bodies by the selected [`Integrator`], holding each body's
+/// angular acceleration constant over the step: no shared torque depends on the body's
+/// angle or spin, so there is nothing to re-evaluate between stages
+pub fn integrate_rotations(
+    mut entities: Query<
+        (&mut Rotation, &mut AngularVelocity, Option<&AngularAcceleration>),
+        Without<sleep::Sleeping>,
+    >,
+    integrator: Res<integrator::Integrator>,
+    time: Res<Time>,
+) {
+    let t = time.elapsed_secs();
+    for (mut rotation, mut angular_velocity, angular_acceleration) in &mut entities {
+        let alpha = angular_acceleration.map_or(0.0, |angular_acceleration| angular_acceleration.0);
+        // The integrators step 2D states; the angle rides along the x axis
+        let state = integrator::BodyState::new(Vec2::new(rotation.0, 0.0), Vec2::new(angular_velocity.0, 0.0));
+        let next = integrator.step(state, t, time.delta_secs(), |_, _| Vec2::new(alpha, 0.0));
+        rotation.0 = next.position.x;
+        angular_velocity.0 = next.velocity.x;
+    }
+}
@@ -0,0 +1,239 @@
+//! Monte Carlo batches: many headless runs with randomized inputs, aggregated
+//!
+//! [`MonteCarlo::run`] calls a trial function once per run, in parallel (see
+//! [`parallel`](crate::parallel)). Each [`Trial`] gets its own seed and random number
+//! generator for drawing parameters, usually builds a [`headless_app`] with the
+//! chapter's physics systems, steps it, and records the quantities of interest. The
+//! [`BatchResults`] then give the distribution of every recorded metric: mean,
+//! standard deviation, range and a histogram. Chapters built on randomness
+//! (percolation, radioactive decay, random walks) report these distributions
+//! instead of a single run.
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::parallel;
+use crate::share::SimulationSeed;
+
+/// Configuration of a batch
+#[derive(Debug, Clone, Copy)]
+pub struct MonteCarlo {
+    pub runs: usize,
+    /// Seed the per-trial seeds are derived from, so a whole batch is reproducible
+    pub seed: u64,
+    /// Number of histogram bins per metric
+    pub bins: usize,
+}
+
+impl MonteCarlo {
+    pub fn new(runs: usize) -> Self {
+        Self {
+            runs,
+            seed: 0,
+            bins: 20,
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn with_bins(mut self, bins: usize) -> Self {
+        self.bins = bins;
+        self
+    }
+
+    /// Run `trial` once per run and collect what each one records
+    pub fn run<F>(&self, trial: F) -> BatchResults
+    where
+        F: Fn(&mut Trial) + Send + Sync,
+    {
+        let indices: Vec<usize> = (0..self.runs).collect();
+        let trials = parallel::par_map(&indices, 1, |&index| {
+            let seed = trial_seed(self.seed, index);
+            let mut current = Trial {
+                index,
+                seed,
+                rng: StdRng::seed_from_u64(seed),
+                metrics: BTreeMap::new(),
+            };
+            trial(&mut current);
+            TrialResult {
+                index,
+                seed,
+                metrics: current.metrics,
+            }
+        });
+        BatchResults {
+            trials,
+            bins: self.bins.max(1),
+        }
+    }
+}
+
+/// SplitMix64 step, so neighboring trial indices get unrelated seeds
+fn trial_seed(batch_seed: u64, index: usize) -> u64 {
+    let mut z = batch_seed.wrapping_add((index as u64 + 1).wrapping_mul(0x9e3779b97f4a7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// One run of a batch: its seed, a generator for drawing parameters, and its results
+pub struct Trial {
+    pub index: usize,
+    pub seed: u64,
+    pub rng: StdRng,
+    metrics: BTreeMap<String, f64>,
+}
+
+impl Trial {
+    /// A parameter drawn uniformly from `range`
+    pub fn uniform(&mut self, range: std::ops::Range<f32>) -> f32 {
+        self.rng.random_range(range)
+    }
+
+    /// Record a result of this run
+    pub fn record(&mut self, name: impl Into<String>, value: f64) {
+        self.metrics.insert(name.into(), value);
+    }
+}
+
+/// An app with no window or renderer whose clock advances by exactly `dt` per
+/// update, with one fixed step per update, so a trial is deterministic for its seed
+pub fn headless_app(seed: u64, dt: Duration) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(dt))
+        .insert_resource(Time::<Fixed>::from_duration(dt))
+        .insert_resource(SimulationSeed(seed));
+    app
+}
+
+/// What one trial recorded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrialResult {
+    pub index: usize,
+    pub seed: u64,
+    pub metrics: BTreeMap<String, f64>,
+}
+
+/// Every trial of a batch, with per-metric statistics
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchResults {
+    pub trials: Vec<TrialResult>,
+    bins: usize,
+}
+
+impl BatchResults {
+    /// Names of every metric recorded by at least one trial
+    pub fn metrics(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .trials
+            .iter()
+            .flat_map(|trial| trial.metrics.keys().map(String::as_str))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// Values of `metric` from the trials that recorded it
+    pub fn values(&self, metric: &str) -> Vec<f64> {
+        self.trials
+            .iter()
+            .filter_map(|trial| trial.metrics.get(metric).copied())
+            .collect()
+    }
+
+    pub fn statistics(&self, metric: &str) -> Option<Statistics> {
+        Statistics::from_values(&self.values(metric), self.bins)
+    }
+
+    /// Statistics of every metric, e.g. for writing a summary as JSON
+    pub fn summary(&self) -> BTreeMap<String, Statistics> {
+        self.metrics()
+            .into_iter()
+            .filter_map(|metric| Some((metric.to_string(), self.statistics(metric)?)))
+            .collect()
+    }
+}
+
+/// Distribution of one metric over a batch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Statistics {
+    pub count: usize,
+    pub mean: f64,
+    /// Sample standard deviation (zero for a single value)
+    pub stddev: f64,
+    pub min: f64,
+    pub max: f64,
+    pub histogram: Histogram,
+}
+
+impl Statistics {
+    pub fn from_values(values: &[f64], bins: usize) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        let count = values.len();
+        let mean = values.iter().sum::<f64>() / count as f64;
+        let variance = if count > 1 {
+            values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / (count - 1) as f64
+        } else {
+            0.0
+        };
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        Some(Self {
+            count,
+            mean,
+            stddev: variance.sqrt(),
+            min,
+            max,
+            histogram: Histogram::new(values, min, max, bins),
+        })
+    }
+
+    /// Standard error of the mean
+    pub fn standard_error(&self) -> f64 {
+        self.stddev / (self.count as f64).sqrt()
+    }
+}
+
+/// Counts of values in equal-width bins between `min` and `max`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Histogram {
+    pub min: f64,
+    pub max: f64,
+    pub counts: Vec<usize>,
+}
+
+impl Histogram {
+    fn new(values: &[f64], min: f64, max: f64, bins: usize) -> Self {
+        let mut counts = vec![0; bins.max(1)];
+        let width = (max - min) / counts.len() as f64;
+        for value in values {
+            let bin = if width > 0.0 { ((value - min) / width) as usize } else { 0 };
+            // The maximum itself falls in the last bin
+            let last = counts.len() - 1;
+            counts[bin.min(last)] += 1;
+        }
+        Self { min, max, counts }
+    }
+
+    pub fn bin_width(&self) -> f64 {
+        (self.max - self.min) / self.counts.len() as f64
+    }
+
+    /// Center of bin `index`, for plotting
+    pub fn bin_center(&self, index: usize) -> f64 {
+        self.min + (index as f64 + 0.5) * self.bin_width()
+    }
+}
@@ -0,0 +1,77 @@
+//! Data-parallel loops that also run concurrently in the browser
+//!
+//! Natively the work is split over Bevy's compute task pool. Bevy's task pools are
+//! single-threaded on wasm, so builds with the `wasm-threads` feature hand the chunks
+//! to a rayon pool of web workers instead. The page starts that pool by awaiting the
+//! exported `initThreads` after `init()`; until it is ready the chunks simply run one
+//! after another on the main thread.
+use bevy::tasks::{ComputeTaskPool, ParallelSlice, TaskPool};
+
+/// Number of threads the parallel loops can currently use
+pub fn thread_count() -> usize {
+    #[cfg(all(target_arch = "wasm32", feature = "wasm-threads"))]
+    if web::ready() {
+        return rayon::current_num_threads();
+    }
+    ComputeTaskPool::get_or_init(TaskPool::default).thread_num().max(1)
+}
+
+/// Split `items` into one chunk per thread (but no smaller than `min_chunk_size`) and
+/// map the chunks in parallel. `f` gets the index of the chunk's first item and the
+/// chunk; the results come back in chunk order.
+pub fn par_chunk_map<T, R, F>(items: &[T], min_chunk_size: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send + 'static,
+    F: Fn(usize, &[T]) -> R + Send + Sync,
+{
+    let chunk_size = (items.len() / thread_count()).max(min_chunk_size).max(1);
+
+    #[cfg(all(target_arch = "wasm32", feature = "wasm-threads"))]
+    if web::ready() {
+        use rayon::prelude::*;
+        return items
+            .par_chunks(chunk_size)
+            .enumerate()
+            .map(|(chunk_index, chunk)| f(chunk_index * chunk_size, chunk))
+            .collect();
+    }
+
+    let task_pool = ComputeTaskPool::get_or_init(TaskPool::default);
+    items.par_chunk_map(task_pool, chunk_size, |chunk_index, chunk| f(chunk_index * chunk_size, chunk))
+}
+
+/// Map every item in parallel, keeping the order
+pub fn par_map<T, R, F>(items: &[T], min_chunk_size: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send + 'static,
+    F: Fn(&T) -> R + Send + Sync,
+{
+    par_chunk_map(items, min_chunk_size, |_, chunk| chunk.iter().map(&f).collect::<Vec<_>>())
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm-threads"))]
+mod web {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use wasm_bindgen::prelude::*;
+
+    static READY: AtomicBool = AtomicBool::new(false);
+
+    pub(super) fn ready() -> bool {
+        READY.load(Ordering::Acquire)
+    }
+
+    /// Start `threads` web workers for the parallel loops. Exported to JavaScript as
+    /// `initThreads`; it only works on a cross-origin isolated page, since the
+    /// workers share the wasm memory through a `SharedArrayBuffer`.
+    #[wasm_bindgen(js_name = initThreads)]
+    pub async fn init_threads(threads: usize) -> Result<(), JsValue> {
+        wasm_bindgen_futures::JsFuture::from(wasm_bindgen_rayon::init_thread_pool(threads)).await?;
+        READY.store(true, Ordering::Release);
+        Ok(())
+    }
+}
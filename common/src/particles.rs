@@ -0,0 +1,175 @@
+//! Batched rendering for very large particle counts
+//!
+//! Spawning tens of thousands of entities that each carry their own `Mesh2d` means
+//! one draw and one set of render-world bookkeeping per particle. Entities with a
+//! [`BatchedParticle`] instead have no mesh of their own: every frame their
+//! transforms and colors are written into the vertex buffer of a single shared mesh,
+//! which is drawn in one call.
+//!
+//! This is batching on the CPU, not GPU instancing: the corners of every particle are
+//! transformed and uploaded each frame, so the cost still grows with the particle
+//! count and the corners per shape. Only the index buffer is kept between frames,
+//! until the number of particles or the shape changes.
+use bevy::asset::RenderAssetUsages;
+use bevy::camera::visibility::NoFrustumCulling;
+use bevy::mesh::{Indices, PrimitiveTopology};
+use bevy::prelude::*;
+
+/// Draws every [`BatchedParticle`] as part of one batched mesh
+pub struct ParticleRendererPlugin;
+
+impl Plugin for ParticleRendererPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ParticleRenderer>()
+            .add_systems(Startup, spawn_particle_batch)
+            .add_systems(PostUpdate, update_particle_batch);
+    }
+}
+
+/// Outline used for every particle in the batch
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ParticleShape {
+    Square,
+    /// Points along the local x axis, so rotation shows heading
+    Triangle,
+    /// Cheap stand-in for a circle
+    #[default]
+    Hexagon,
+}
+
+impl ParticleShape {
+    /// Corners of the outline for a particle of unit diameter, as a triangle fan
+    fn corners(&self) -> Vec<Vec2> {
+        match self {
+            ParticleShape::Square => vec![
+                Vec2::new(-0.5, -0.5),
+                Vec2::new(0.5, -0.5),
+                Vec2::new(0.5, 0.5),
+                Vec2::new(-0.5, 0.5),
+            ],
+            ParticleShape::Triangle => vec![
+                Vec2::new(0.5, 0.0),
+                Vec2::new(-0.5, 0.3),
+                Vec2::new(-0.5, -0.3),
+            ],
+            ParticleShape::Hexagon => (0..6)
+                .map(|i| Vec2::from_angle(i as f32 * std::f32::consts::TAU / 6.0) * 0.5)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct ParticleRenderer {
+    pub shape: ParticleShape,
+}
+
+/// A particle drawn through the shared batch instead of its own mesh,
+/// placed, rotated and scaled by the entity's `Transform`
+#[derive(Component, Debug, Clone, Copy)]
+#[require(Transform)]
+pub struct BatchedParticle {
+    pub color: Color,
+    /// Diameter in world units
+    pub size: f32,
+}
+
+impl Default for BatchedParticle {
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            size: 1.0,
+        }
+    }
+}
+
+/// The single entity holding the batched mesh
+#[derive(Component)]
+struct ParticleBatch;
+
+fn spawn_particle_batch(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let mut mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    );
+    write_batch(&mut mesh, Vec::new(), Vec::new(), Some(Vec::new()));
+    commands.spawn((
+        ParticleBatch,
+        Mesh2d(meshes.add(mesh)),
+        // Vertex colors are multiplied by the material color, so keep it white
+        MeshMaterial2d(materials.add(Color::WHITE)),
+        Transform::default(),
+        // The bounds change every frame, so never cull the batch
+        NoFrustumCulling,
+    ));
+}
+
+fn update_particle_batch(
+    particles: Query<(&Transform, &BatchedParticle)>,
+    batch: Query<&Mesh2d, With<ParticleBatch>>,
+    renderer: Res<ParticleRenderer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    // Particle count and shape the mesh's indices were built for
+    mut indexed: Local<Option<(usize, ParticleShape)>>,
+) {
+    let Ok(mesh2d) = batch.single() else {
+        return;
+    };
+    let Some(mesh) = meshes.get_mut(&mesh2d.0) else {
+        return;
+    };
+
+    let corners = renderer.shape.corners();
+    let count = particles.iter().len();
+    let mut positions: Vec<[f32; 3]> = Vec::with_capacity(count * corners.len());
+    let mut colors: Vec<[f32; 4]> = Vec::with_capacity(count * corners.len());
+    for (transform, particle) in &particles {
+        let color = particle.color.to_linear().to_f32_array();
+        for corner in &corners {
+            let local = (*corner * particle.size).extend(0.0);
+            positions.push(transform.transform_point(local).to_array());
+            colors.push(color);
+        }
+    }
+
+    let indices = (*indexed != Some((count, renderer.shape))).then(|| {
+        *indexed = Some((count, renderer.shape));
+        fan_indices(count, corners.len() as u32)
+    });
+    write_batch(mesh, positions, colors, indices);
+}
+
+/// A triangle fan around the first corner of each of `count` particles
+fn fan_indices(count: usize, corners: u32) -> Vec<u32> {
+    let mut indices = Vec::with_capacity(count * (corners as usize - 2) * 3);
+    for base in (0..count as u32).map(|particle| particle * corners) {
+        for i in 1..corners - 1 {
+            indices.extend_from_slice(&[base, base + i, base + i + 1]);
+        }
+    }
+    indices
+}
+
+/// Replace the batch's vertices, and its indices when given
+fn write_batch(
+    mesh: &mut Mesh,
+    mut positions: Vec<[f32; 3]>,
+    mut colors: Vec<[f32; 4]>,
+    mut indices: Option<Vec<u32>>,
+) {
+    // Keep a degenerate triangle in an empty batch so the GPU buffers are never zero-sized
+    if positions.is_empty() {
+        positions = vec![[0.0; 3]; 3];
+        colors = vec![[0.0; 4]; 3];
+        indices = indices.map(|_| vec![0, 1, 2]);
+    }
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    if let Some(indices) = indices {
+        mesh.insert_indices(Indices::U32(indices));
+    }
+}
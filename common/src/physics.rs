@@ -0,0 +1,336 @@
+//! One plugin for the shared motion systems
+//!
+//! [`PhysicsPlugin`] runs [`compute_material_masses`] and the shared force sources ([`apply_gravity`], [`apply_springs`],
+//! [`apply_drag`]), then
+//! [`apply_forces`] and [`integrate_bodies`] with the selected [`Integrator`] (and
+//! [`apply_torques`] and [`integrate_rotations`] for spinning bodies), then (if
+//! asked) [`solve_constraints`] and [`resolve_collisions`], then [`project_positions`]
+//! and [`project_rotations`] (and [`update_sleep`] if asked),
+//! in the [`PhysicsStep`] schedule. That schedule runs [`Substeps`] times per tick of
+//! `FixedUpdate` (or the schedule given to `in_schedule`), each time with its share of
+//! the tick in `Time`. Chapters put their own physics systems in the same [`PhysicsSet`]s
+//! of [`PhysicsStep`] (force sources first, then integration, then constraints and
+//! collisions, then syncing transforms) so every simulation steps in the same order.
+//! Pausing and slow motion act on the [`PhysicsTime`] clock, not on frame time.
+//!
+//! Flipping the [`TimeDirection`] runs the simulation backwards: every velocity and spin
+//! is reversed before the next tick, which is what running the equations with negative
+//! dt amounts to. With a time-reversible integrator, bodies under gravity and springs
+//! to fixed anchors retrace their paths back to the start; springs between two moving
+//! bodies only nearly do, since [`integrate_bodies`] holds the other end still within
+//! a step, and drag, collisions with restitution below 1 and mixing gases don't, which
+//! is the point.
+use bevy::ecs::intern::Interned;
+use bevy::ecs::schedule::ScheduleLabel;
+use bevy::prelude::*;
+
+use crate::broadphase::Broadphase;
+use crate::collision::{
+    compute_material_masses, record_swept_positions, resolve_collisions, sweep_continuous_collisions, CollisionEvent,
+    ContactSolver,
+};
+use crate::sleep::{update_sleep, SleepSettings};
+use crate::forces::{apply_drag, apply_gravity, apply_springs, Gravity};
+use crate::xpbd::{record_constrained_positions, solve_constraints, ConstrainedPositions, ConstraintSolver};
+use crate::timestep::{PhysicsRate, PhysicsRatePlugin, PhysicsTime, Substeps};
+use crate::integrator::Integrator;
+use crate::three_d::{AngularVelocity3d, Velocity3d};
+use crate::{
+    apply_acceleration, apply_forces, apply_torques, apply_velocity, integrate_bodies, integrate_rotations,
+    project_positions, project_rotations, AngularVelocity, Velocity,
+};
+
+/// The stages of a physics step, run in this order
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PhysicsSet {
+    /// Add to each body's `Force` (gravity, drag, springs, ...) and `Torque`
+    Forces,
+    /// Turn forces into accelerations, then advance velocities, then positions
+    Integrate,
+    /// Project positions onto the XPBD constraints
+    Constrain,
+    /// Find overlapping colliders and push them apart
+    Collide,
+    /// Copy physics state to what gets drawn
+    Sync,
+}
+
+/// One (sub)step of the shared physics, holding the [`PhysicsSet`]s
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PhysicsStep;
+
+/// Adds the shared motion systems in [`PhysicsSet`] order
+pub struct PhysicsPlugin {
+    schedule: Interned<dyn ScheduleLabel>,
+    timestep_hz: Option<f64>,
+    substeps: u32,
+    integrator: Integrator,
+    acceleration: bool,
+    velocity: bool,
+    rotation: bool,
+    projection: bool,
+    collisions: bool,
+    constraints: bool,
+    sleeping: bool,
+    broadphase: Broadphase,
+}
+
+impl Default for PhysicsPlugin {
+    fn default() -> Self {
+        Self {
+            schedule: FixedUpdate.intern(),
+            timestep_hz: None,
+            substeps: 1,
+            integrator: Integrator::default(),
+            acceleration: true,
+            velocity: true,
+            rotation: true,
+            projection: true,
+            collisions: false,
+            constraints: false,
+            sleeping: false,
+            broadphase: Broadphase::default(),
+        }
+    }
+}
+
+impl PhysicsPlugin {
+    /// Step the physics from `schedule` instead of `FixedUpdate`
+    pub fn in_schedule(mut self, schedule: impl ScheduleLabel) -> Self {
+        self.schedule = schedule.intern();
+        self
+    }
+
+    /// Start at `hz` fixed steps per second (see [`PhysicsRate`])
+    pub fn with_timestep_hz(mut self, hz: f64) -> Self {
+        self.timestep_hz = Some(hz);
+        self
+    }
+
+    /// Start with each tick split into `substeps` steps (see [`Substeps`]); the resource
+    /// can be changed at runtime
+    pub fn with_substeps(mut self, substeps: u32) -> Self {
+        self.substeps = substeps.max(1);
+        self
+    }
+
+    /// Start with `integrator` instead of semi-implicit Euler; the [`Integrator`]
+    /// resource can be changed at runtime
+    pub fn with_integrator(mut self, integrator: Integrator) -> Self {
+        self.integrator = integrator;
+        self
+    }
+
+    /// Don't add `Acceleration` to `Velocity`, e.g. when the chapter integrates forces itself
+    pub fn without_acceleration(mut self) -> Self {
+        self.acceleration = false;
+        self
+    }
+
+    /// Don't move `Position` by `Velocity`
+    pub fn without_velocity(mut self) -> Self {
+        self.velocity = false;
+        self
+    }
+
+    /// Don't spin bodies by `Torque` and `AngularVelocity`
+    pub fn without_rotation(mut self) -> Self {
+        self.rotation = false;
+        self
+    }
+
+    /// Resolve contacts between `ColliderShape`s with impulses (see [`resolve_collisions`]),
+    /// sweeping `ContinuousCollision` bodies first; insert a [`ContactSolver`] to tune it
+    pub fn with_collisions(mut self) -> Self {
+        self.collisions = true;
+        self
+    }
+
+    /// Cull collision pairs with `broadphase` instead of sweep-and-prune; the
+    /// [`Broadphase`] resource can be changed at runtime
+    pub fn with_broadphase(mut self, broadphase: Broadphase) -> Self {
+        self.broadphase = broadphase;
+        self
+    }
+
+    /// Solve the `xpbd` distance, pin and angle constraints after integrating; insert a
+    /// [`ConstraintSolver`] to set the iteration count
+    pub fn with_constraints(mut self) -> Self {
+        self.constraints = true;
+        self
+    }
+
+    /// Put bodies that rest for a while to sleep (see [`update_sleep`]); insert a
+    /// [`SleepSettings`] to set the thresholds
+    pub fn with_sleeping(mut self) -> Self {
+        self.sleeping = true;
+        self
+    }
+
+    /// Don't copy `Position` and `Rotation` into `Transform`
+    pub fn without_projection(mut self) -> Self {
+        self.projection = false;
+        self
+    }
+}
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_schedule(PhysicsStep)
+            .insert_resource(Substeps(self.substeps))
+            .init_resource::<TimeDirection>()
+            .init_resource::<PhysicsTime>()
+            .add_systems(self.schedule, (reverse_velocities, run_physics_step).chain());
+        app.configure_sets(
+            PhysicsStep,
+            (
+                PhysicsSet::Forces,
+                PhysicsSet::Integrate,
+                PhysicsSet::Constrain,
+                PhysicsSet::Collide,
+                PhysicsSet::Sync,
+            )
+                .chain(),
+        );
+        app.add_systems(
+            PhysicsStep,
+            (
+                (compute_material_masses, (apply_gravity, apply_springs, apply_drag))
+                    .chain()
+                    .in_set(PhysicsSet::Forces),
+                apply_forces.in_set(PhysicsSet::Integrate),
+            ),
+        );
+        app.insert_resource(self.integrator);
+        app.init_resource::<Gravity>();
+        match (self.acceleration, self.velocity) {
+            (true, true) => {
+                app.add_systems(
+                    PhysicsStep,
+                    integrate_bodies.in_set(PhysicsSet::Integrate).after(apply_forces),
+                );
+            }
+            // Only one half of the step: no scheme to choose
+            (true, false) => {
+                app.add_systems(
+                    PhysicsStep,
+                    apply_acceleration.in_set(PhysicsSet::Integrate).after(apply_forces),
+                );
+            }
+            (false, true) => {
+                app.add_systems(PhysicsStep, apply_velocity.in_set(PhysicsSet::Integrate));
+            }
+            (false, false) => {}
+        }
+        if self.rotation {
+            app.add_systems(
+                PhysicsStep,
+                (apply_torques, integrate_rotations).chain().in_set(PhysicsSet::Integrate),
+            );
+        }
+        if self.constraints {
+            app.init_resource::<ConstraintSolver>()
+                .init_resource::<ConstrainedPositions>()
+                .add_systems(
+                    PhysicsStep,
+                    (
+                        record_constrained_positions.in_set(PhysicsSet::Forces),
+                        solve_constraints.in_set(PhysicsSet::Constrain),
+                    ),
+                );
+        }
+        if self.collisions {
+            app.init_resource::<ContactSolver>()
+                .insert_resource(self.broadphase)
+                .add_message::<CollisionEvent>();
+            app.add_systems(
+                PhysicsStep,
+                (
+                    record_swept_positions.in_set(PhysicsSet::Forces),
+                    (sweep_continuous_collisions, resolve_collisions)
+                        .chain()
+                        .in_set(PhysicsSet::Collide),
+                ),
+            );
+        }
+        if self.sleeping {
+            app.init_resource::<SleepSettings>()
+                .add_systems(PhysicsStep, update_sleep.in_set(PhysicsSet::Sync));
+        }
+        if self.projection {
+            app.add_systems(PhysicsStep, (project_positions, project_rotations).in_set(PhysicsSet::Sync));
+        }
+        if let Some(hz) = self.timestep_hz {
+            if !app.is_plugin_added::<PhysicsRatePlugin>() {
+                app.add_plugins(PhysicsRatePlugin);
+            }
+            app.insert_resource(PhysicsRate::new(hz));
+        }
+    }
+}
+
+/// Which way the simulation is running
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TimeDirection {
+    #[default]
+    Forward,
+    Backward,
+}
+
+impl TimeDirection {
+    pub fn reversed(self) -> Self {
+        match self {
+            TimeDirection::Forward => TimeDirection::Backward,
+            TimeDirection::Backward => TimeDirection::Forward,
+        }
+    }
+}
+
+/// System to reverse every velocity and spin when the [`TimeDirection`] differs from
+/// the one it last ran with; setting it to the same value again leaves them alone
+pub fn reverse_velocities(
+    direction: Res<TimeDirection>,
+    mut applied: Local<Option<TimeDirection>>,
+    mut velocities: Query<&mut Velocity>,
+    mut spins: Query<&mut AngularVelocity>,
+    mut velocities_3d: Query<&mut Velocity3d>,
+    mut spins_3d: Query<&mut AngularVelocity3d>,
+) {
+    // The first direction seen is the one the bodies were set up for
+    let previous = applied.replace(*direction).unwrap_or(*direction);
+    if previous == *direction {
+        return;
+    }
+    velocities.iter_mut().for_each(|mut velocity| velocity.0 = -velocity.0);
+    spins.iter_mut().for_each(|mut spin| spin.0 = -spin.0);
+    velocities_3d.iter_mut().for_each(|mut velocity| velocity.0 = -velocity.0);
+    spins_3d.iter_mut().for_each(|mut spin| spin.0 = -spin.0);
+}
+
+/// Exclusive system to run [`PhysicsStep`] [`Substeps`] times per physics tick,
+/// splitting the tick's `Time` between them. [`PhysicsTime`] decides how many ticks
+/// this `FixedUpdate` gets (none while paused, several when sped up), and its clock is
+/// the `Time` the steps see.
+pub fn run_physics_step(world: &mut World) {
+    let substeps = world.get_resource::<Substeps>().map_or(1, |substeps| substeps.0.max(1));
+    let frame = *world.resource::<Time>();
+    let (ticks, start) = match world.get_resource_mut::<PhysicsTime>() {
+        Some(mut physics_time) => (physics_time.take_ticks(), physics_time.elapsed()),
+        None => (1, frame.elapsed().saturating_sub(frame.delta())),
+    };
+    let step = frame.delta() / substeps;
+    let mut time = Time::<()>::default();
+    time.advance_to(start);
+    for _ in 0..ticks {
+        for _ in 0..substeps {
+            time.advance_by(step);
+            *world.resource_mut::<Time>() = time;
+            world.run_schedule(PhysicsStep);
+        }
+        if let Some(mut physics_time) = world.get_resource_mut::<PhysicsTime>() {
+            physics_time.advance_by(frame.delta());
+        }
+    }
+    // Back to the frame's own clock, whatever rounding did to the substeps
+    *world.resource_mut::<Time>() = frame;
+}
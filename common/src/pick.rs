@@ -0,0 +1,230 @@
+//! Grabbing bodies with the mouse or a finger and dragging them around
+//!
+//! Give a body a [`Draggable`] and a `ColliderShape` and add [`PickDragPlugin`]. Pressing
+//! the left mouse button or touching the screen on (or within [`PickDrag::tolerance`]
+//! pixels of) the collider grabs it at that point, and it follows the [`Pointer`] until
+//! the button or finger is let go: a
+//! [`DragMode::Teleport`] body is moved with the cursor and thrown with its velocity on
+//! release, while a [`DragMode::Spring`] body is pulled towards it by a damped spring
+//! in `PhysicsSet::Forces`, so gravity, collisions and constraints still act on it.
+//! Grabbing, moving and releasing each send a [`DragEvent`]. Clicks that egui is using
+//! are left alone.
+use bevy::prelude::*;
+use bevy_egui::input::EguiWantsInput;
+
+use crate::collision::{isometry, ColliderShape};
+use crate::physics::{PhysicsSet, PhysicsStep};
+use crate::pointer::{Pointer, PointerPlugin};
+use crate::{Force, Mass, Position, Velocity};
+
+/// Lets the mouse grab and drag every [`Draggable`] body
+pub struct PickDragPlugin;
+
+impl Plugin for PickDragPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<PointerPlugin>() {
+            app.add_plugins(PointerPlugin);
+        }
+        app.init_resource::<PickDrag>()
+            .add_message::<DragEvent>()
+            .add_systems(Update, pick_and_drag)
+            .add_systems(
+                PhysicsStep,
+                (
+                    pull_dragged.in_set(PhysicsSet::Forces),
+                    hold_dragged.in_set(PhysicsSet::Constrain),
+                ),
+            );
+    }
+}
+
+/// How close a click has to be to a body to grab it
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct PickDrag {
+    /// Distance from a collider's surface that still grabs it (logical pixels)
+    pub tolerance: f32,
+}
+
+impl Default for PickDrag {
+    fn default() -> Self {
+        Self { tolerance: 6.0 }
+    }
+}
+
+/// How a grabbed body follows the cursor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DragMode {
+    /// Moved with the cursor, and let go with the cursor's velocity
+    Teleport,
+    /// Pulled towards the cursor, a = stiffness·stretch − damping·v
+    Spring {
+        /// Acceleration per meter of stretch (1/s²)
+        stiffness: f32,
+        /// Velocity damping (1/s)
+        damping: f32,
+    },
+}
+
+/// Marks a body the mouse can grab, and how it is dragged
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Draggable {
+    pub mode: DragMode,
+}
+
+impl Default for Draggable {
+    fn default() -> Self {
+        Self::teleport()
+    }
+}
+
+impl Draggable {
+    pub fn teleport() -> Self {
+        Self { mode: DragMode::Teleport }
+    }
+
+    pub fn spring(stiffness: f32, damping: f32) -> Self {
+        Self {
+            mode: DragMode::Spring { stiffness, damping },
+        }
+    }
+}
+
+/// On a body while it is held: where it was grabbed and where the cursor is
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Dragged {
+    /// Grabbed point relative to the body's center
+    pub grab_offset: Vec2,
+    /// World position of the cursor
+    pub target: Vec2,
+    /// Velocity of the cursor, smoothed over a few frames
+    pub velocity: Vec2,
+}
+
+/// Sent when a body is grabbed, dragged or let go, with the cursor's world position
+#[derive(Message, Debug, Clone, Copy, PartialEq)]
+pub enum DragEvent {
+    Grabbed { entity: Entity, point: Vec2 },
+    Moved { entity: Entity, point: Vec2 },
+    Released { entity: Entity, point: Vec2, velocity: Vec2 },
+}
+
+/// Position of the window point `cursor` in the world, through the camera whose
+/// viewport it is over, and the meters per pixel there
+fn cursor_world_position(cursor: Vec2, cameras: &Query<(&Camera, &GlobalTransform), With<Camera2d>>) -> Option<(Vec2, f32)> {
+    cameras.iter().filter(|(camera, _)| camera.is_active).find_map(|(camera, transform)| {
+        let viewport = camera.logical_viewport_rect()?;
+        if !viewport.contains(cursor) {
+            return None;
+        }
+        let point = camera.viewport_to_world_2d(transform, cursor - viewport.min).ok()?;
+        let next_pixel = camera.viewport_to_world_2d(transform, cursor - viewport.min + Vec2::X).ok()?;
+        Some((point, point.distance(next_pixel)))
+    })
+}
+
+/// System to grab a [`Draggable`] body under the cursor, move its target while the
+/// button is held and let it go on release
+#[allow(clippy::too_many_arguments)]
+fn pick_and_drag(
+    mut commands: Commands,
+    pick: Res<PickDrag>,
+    pointer: Res<Pointer>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    draggables: Query<(Entity, &ColliderShape, &Transform, Option<&Position>), With<Draggable>>,
+    mut dragged: Query<(Entity, &mut Dragged)>,
+    mut events: MessageWriter<DragEvent>,
+    time: Res<Time<Real>>,
+    egui_input: Option<Res<EguiWantsInput>>,
+) {
+    let cursor = pointer.position.and_then(|position| cursor_world_position(position, &cameras));
+
+    if pointer.just_released {
+        for (entity, held) in &dragged {
+            commands.entity(entity).try_remove::<Dragged>();
+            events.write(DragEvent::Released {
+                entity,
+                point: held.target,
+                velocity: held.velocity,
+            });
+        }
+        return;
+    }
+
+    if let Some((point, _)) = cursor.filter(|_| pointer.pressed) {
+        let dt = time.delta_secs();
+        for (entity, mut held) in &mut dragged {
+            if dt <= 0.0 {
+                continue;
+            }
+            // Smooth the cursor's velocity, so the throw doesn't depend on the last frame alone
+            let velocity = (point - held.target) / dt;
+            held.velocity = held.velocity.lerp(velocity, 0.5);
+            if point != held.target {
+                held.target = point;
+                events.write(DragEvent::Moved { entity, point });
+            }
+        }
+    }
+
+    let pointer_free = egui_input.is_none_or(|input| !input.wants_any_pointer_input());
+    if !(pointer_free && pointer.just_pressed) {
+        return;
+    }
+    let Some((point, meters_per_pixel)) = cursor else {
+        return;
+    };
+    let tolerance = pick.tolerance * meters_per_pixel;
+    // The closest body within reach, so small bodies in front of big ones can be grabbed
+    let picked = draggables
+        .iter()
+        .map(|(entity, shape, transform, position)| {
+            let mut isometry = isometry(transform);
+            if let Some(position) = position {
+                isometry.translation = position.0;
+            }
+            (entity, isometry.translation, shape.distance_to_point(isometry, point))
+        })
+        .filter(|(_, _, distance)| *distance <= tolerance)
+        .min_by(|a, b| a.2.total_cmp(&b.2));
+    if let Some((entity, center, _)) = picked {
+        commands.entity(entity).insert(Dragged {
+            grab_offset: point - center,
+            target: point,
+            velocity: Vec2::ZERO,
+        });
+        events.write(DragEvent::Grabbed { entity, point });
+    }
+}
+
+/// System to pull every held [`DragMode::Spring`] body towards the cursor
+#[allow(clippy::type_complexity)]
+fn pull_dragged(mut bodies: Query<(&Draggable, &Dragged, &Transform, Option<&Position>, &Velocity, &Mass, &mut Force)>) {
+    for (draggable, held, transform, position, velocity, mass, mut force) in &mut bodies {
+        let DragMode::Spring { stiffness, damping } = draggable.mode else {
+            continue;
+        };
+        let center = position.map_or(transform.translation.truncate(), |position| position.0);
+        let stretch = held.target - (center + held.grab_offset);
+        force.0 += mass.0 * (stiffness * stretch - damping * velocity.0);
+    }
+}
+
+/// System to put every held [`DragMode::Teleport`] body under the cursor, moving at its speed
+#[allow(clippy::type_complexity)]
+fn hold_dragged(
+    mut bodies: Query<(&Draggable, &Dragged, &mut Transform, Option<&mut Position>, Option<&mut Velocity>)>,
+) {
+    for (draggable, held, mut transform, position, velocity) in &mut bodies {
+        if draggable.mode != DragMode::Teleport {
+            continue;
+        }
+        let center = held.target - held.grab_offset;
+        match position {
+            Some(mut position) => position.0 = center,
+            None => transform.translation = center.extend(transform.translation.z),
+        }
+        if let Some(mut velocity) = velocity {
+            velocity.0 = held.velocity;
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Live line plots of whatever a simulation wants to watch
+//!
+//! A chapter pushes points into named series of named plots on [`PlotPanel`]:
+//! `panel.push("Height", "left", t, y)` for a quantity against time, or
+//! `panel.push("Phase space", "x", x, v)` for one quantity against another.
+//! [`PlotPanelPlugin`] draws every plot, one below the other, in the Plots window,
+//! with a legend when a plot has more than one series. Each series keeps its latest
+//! [`PlotPanel::max_points`] points.
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+use egui_plot::{Legend, Line, Plot, PlotPoints};
+
+/// Adds the [`PlotPanel`] and the Plots window
+pub struct PlotPanelPlugin;
+
+impl Plugin for PlotPanelPlugin {
+    fn build(&self, app: &mut App) {
+        crate::add_egui(app);
+        app.init_resource::<PlotPanel>()
+            .add_systems(EguiPrimaryContextPass, plot_window);
+    }
+}
+
+/// The plots on show, in the order they were first pushed to
+#[derive(Resource, Debug)]
+pub struct PlotPanel {
+    /// Points kept per series; the oldest are dropped first
+    pub max_points: usize,
+    /// Height of each plot in the window (logical pixels)
+    pub plot_height: f32,
+    plots: Vec<PlotData>,
+}
+
+#[derive(Debug)]
+struct PlotData {
+    title: String,
+    series: Vec<Series>,
+}
+
+#[derive(Debug)]
+struct Series {
+    name: String,
+    points: VecDeque<[f64; 2]>,
+}
+
+impl Default for PlotPanel {
+    fn default() -> Self {
+        Self {
+            // Half a minute of one point per physics tick
+            max_points: 2000,
+            plot_height: 140.0,
+            plots: Vec::new(),
+        }
+    }
+}
+
+impl PlotPanel {
+    /// Add the point `(x, y)` to `series` of `plot`, making either if it's new
+    pub fn push(&mut self, plot: &str, series: &str, x: impl Into<f64>, y: impl Into<f64>) {
+        let max_points = self.max_points.max(1);
+        let series = self.series_mut(plot, series);
+        series.points.push_back([x.into(), y.into()]);
+        while series.points.len() > max_points {
+            series.points.pop_front();
+        }
+    }
+
+    /// Forget the points of every series of `plot`
+    pub fn clear_plot(&mut self, plot: &str) {
+        for data in self.plots.iter_mut().filter(|data| data.title == plot) {
+            data.series.iter_mut().for_each(|series| series.points.clear());
+        }
+    }
+
+    /// Forget every point, keeping the plots in place
+    pub fn clear(&mut self) {
+        for data in &mut self.plots {
+            data.series.iter_mut().for_each(|series| series.points.clear());
+        }
+    }
+
+    fn series_mut(&mut self, plot: &str, series: &str) -> &mut Series {
+        let plot = match self.plots.iter().position(|data| data.title == plot) {
+            Some(index) => &mut self.plots[index],
+            None => {
+                self.plots.push(PlotData { title: plot.to_string(), series: Vec::new() });
+                self.plots.last_mut().expect("just pushed")
+            }
+        };
+        match plot.series.iter().position(|data| data.name == series) {
+            Some(index) => &mut plot.series[index],
+            None => {
+                plot.series.push(Series { name: series.to_string(), points: VecDeque::new() });
+                plot.series.last_mut().expect("just pushed")
+            }
+        }
+    }
+}
+
+fn plot_window(mut contexts: EguiContexts, mut panel: ResMut<PlotPanel>) -> Result {
+    if panel.plots.is_empty() {
+        return Ok(());
+    }
+    egui::Window::new("Plots")
+        .default_open(false)
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -60.0])
+        .default_width(320.0)
+        .show(contexts.ctx_mut()?, |ui| {
+            let height = panel.plot_height;
+            for data in &panel.plots {
+                ui.label(&data.title);
+                let mut plot = Plot::new(&data.title).height(height);
+                if data.series.len() > 1 {
+                    plot = plot.legend(Legend::default());
+                }
+                plot.show(ui, |plot_ui| {
+                    for series in &data.series {
+                        let points: PlotPoints = series.points.iter().copied().collect();
+                        plot_ui.line(Line::new(series.name.as_str(), points));
+                    }
+                });
+            }
+            if ui.button("Clear").clicked() {
+                panel.clear();
+            }
+        });
+    Ok(())
+}
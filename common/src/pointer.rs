@@ -0,0 +1,88 @@
+//! One pointer for mouse and touch
+//!
+//! [`PointerPlugin`] keeps the [`Pointer`] resource up to date from whichever input
+//! was used last: the left mouse button and cursor, or the first finger on a touch
+//! screen. A tap presses and releases it like a click, and a finger dragged across
+//! the screen moves it like the cursor with the button held, so systems that read
+//! [`Pointer`] instead of the mouse work the same on tablets. The one difference is
+//! that a finger has no position while it isn't touching, where the cursor hovers.
+use bevy::input::touch::Touches;
+use bevy::input::InputSystems;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+/// Keeps [`Pointer`] in step with the mouse and touch screen
+pub struct PointerPlugin;
+
+impl Plugin for PointerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Pointer>()
+            .add_systems(PreUpdate, update_pointer.after(InputSystems));
+    }
+}
+
+/// Where the pointer is
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PointerSource {
+    #[default]
+    Mouse,
+    Touch,
+}
+
+/// The mouse cursor and left button, or the first finger on a touch screen
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub struct Pointer {
+    /// Window position (logical pixels from the top left), like `Window::cursor_position`
+    pub position: Option<Vec2>,
+    pub pressed: bool,
+    pub just_pressed: bool,
+    pub just_released: bool,
+    pub source: PointerSource,
+}
+
+impl Pointer {
+    /// Position while pressed: a held button or a finger on the screen
+    pub fn pressed_position(&self) -> Option<Vec2> {
+        self.position.filter(|_| self.pressed)
+    }
+}
+
+/// System to set [`Pointer`] from this frame's touches, or from the mouse when no
+/// finger is down or lifting
+pub fn update_pointer(
+    mut pointer: ResMut<Pointer>,
+    buttons: Option<Res<ButtonInput<MouseButton>>>,
+    touches: Option<Res<Touches>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    let touch = touches.as_deref().and_then(|touches| {
+        // The first finger to land leads; others (a pinch) don't move the pointer
+        let finger = touches
+            .iter()
+            .min_by_key(|touch| touch.id())
+            .or_else(|| touches.iter_just_released().min_by_key(|touch| touch.id()))?;
+        Some(Pointer {
+            position: Some(finger.position()),
+            pressed: touches.get_pressed(finger.id()).is_some(),
+            just_pressed: touches.just_pressed(finger.id()),
+            just_released: touches.just_released(finger.id()),
+            source: PointerSource::Touch,
+        })
+    });
+    *pointer = touch.unwrap_or_else(|| {
+        let (pressed, just_pressed, just_released) = buttons.as_deref().map_or((false, false, false), |buttons| {
+            (
+                buttons.pressed(MouseButton::Left),
+                buttons.just_pressed(MouseButton::Left),
+                buttons.just_released(MouseButton::Left),
+            )
+        });
+        Pointer {
+            position: window.single().ok().and_then(Window::cursor_position),
+            pressed,
+            just_pressed,
+            just_released,
+            source: PointerSource::Mouse,
+        }
+    });
+}
@@ -0,0 +1,70 @@
+//! Entity pooling for simulations that spawn and remove many short-lived entities
+//!
+//! Despawning and respawning markers, wavefronts or particle bursts on every settings
+//! change causes frame hitches and (when each spawn adds a mesh or material) leaks
+//! assets. An [`EntityPool`] hides released entities instead and hands them back out
+//! on the next [`EntityPool::acquire`].
+use bevy::prelude::*;
+use std::marker::PhantomData;
+
+/// Recycled entities of one kind, keyed by a marker component `M`
+#[derive(Resource)]
+pub struct EntityPool<M: Component> {
+    free: Vec<Entity>,
+    active: Vec<Entity>,
+    _marker: PhantomData<M>,
+}
+
+impl<M: Component> Default for EntityPool<M> {
+    fn default() -> Self {
+        Self {
+            free: Vec::new(),
+            active: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<M: Component> EntityPool<M> {
+    /// Take an entity from the pool and make it visible again, or spawn a new one
+    /// with `spawn` if the pool is empty. Reused entities keep their components,
+    /// so callers should insert whatever changes (usually the `Transform`).
+    pub fn acquire<B: Bundle>(&mut self, commands: &mut Commands, spawn: impl FnOnce() -> B) -> Entity {
+        let entity = match self.free.pop() {
+            Some(entity) => {
+                commands.entity(entity).try_insert(Visibility::Inherited);
+                entity
+            }
+            None => commands.spawn((spawn(), Visibility::Inherited)).id(),
+        };
+        self.active.push(entity);
+        entity
+    }
+
+    /// Hide an entity and keep it for reuse
+    pub fn release(&mut self, commands: &mut Commands, entity: Entity) {
+        if let Some(index) = self.active.iter().position(|active| *active == entity) {
+            self.active.swap_remove(index);
+            commands.entity(entity).try_insert(Visibility::Hidden);
+            self.free.push(entity);
+        }
+    }
+
+    /// Hide every entity handed out by the pool
+    pub fn release_all(&mut self, commands: &mut Commands) {
+        for entity in self.active.drain(..) {
+            commands.entity(entity).try_insert(Visibility::Hidden);
+            self.free.push(entity);
+        }
+    }
+
+    /// Entities currently in use
+    pub fn active(&self) -> &[Entity] {
+        &self.active
+    }
+
+    /// Number of hidden entities waiting to be reused
+    pub fn free_count(&self) -> usize {
+        self.free.len()
+    }
+}
@@ -0,0 +1,202 @@
+//! Named parameter sets for a chapter's settings, kept between runs
+//!
+//! A preset is a chapter's [`ShareSettings`] written out as link parameters
+//! (`vx=30&vy=30&drag=0.01`), so anything that can be shared can be saved too. A
+//! chapter ships its own presets with [`PresetPlugin::with_preset`]; the reader's
+//! are saved as RON to `presets/<simulation>.ron` on native, or to `localStorage` on
+//! the web, and loaded again on the next start. [`preset_picker`] is the dropdown
+//! and save box for a chapter's settings window.
+use std::marker::PhantomData;
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::share::{ShareParams, ShareSettings};
+
+/// Keeps a [`PresetLibrary`] for `S`, with the chapter's presets and the reader's saved ones
+pub struct PresetPlugin<S: ShareSettings + Resource> {
+    simulation: String,
+    shipped: Vec<Preset>,
+    marker: PhantomData<S>,
+}
+
+impl<S: ShareSettings + Resource> PresetPlugin<S> {
+    /// `simulation` names the file (or storage key) the reader's presets are kept in
+    pub fn new(simulation: impl Into<String>) -> Self {
+        Self {
+            simulation: simulation.into(),
+            shipped: Vec::new(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Ship `settings` as a preset called `name`, listed before the reader's own
+    pub fn with_preset(mut self, name: impl Into<String>, settings: &S) -> Self {
+        self.shipped.push(Preset::new(name, settings));
+        self
+    }
+}
+
+impl<S: ShareSettings + Resource> Plugin for PresetPlugin<S> {
+    fn build(&self, app: &mut App) {
+        let mut library = PresetLibrary::<S> {
+            simulation: self.simulation.clone(),
+            shipped: self.shipped.clone(),
+            saved: Vec::new(),
+            name: String::new(),
+            marker: PhantomData,
+        };
+        library.saved = library.read_saved();
+        app.insert_resource(library);
+    }
+}
+
+/// One named set of parameters
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Preset {
+    pub name: String,
+    /// The settings as link parameters, e.g. `vx=30&vy=30`
+    pub params: String,
+}
+
+impl Preset {
+    pub fn new<S: ShareSettings>(name: impl Into<String>, settings: &S) -> Self {
+        let mut params = ShareParams::default();
+        settings.write_params(&mut params);
+        Self {
+            name: name.into(),
+            params: params.to_query(),
+        }
+    }
+
+    /// Overwrite the parameters of `settings` that this preset has
+    pub fn apply<S: ShareSettings>(&self, settings: &mut S) {
+        settings.read_params(&ShareParams::parse(&self.params));
+    }
+}
+
+/// The presets offered for `S`
+#[derive(Resource)]
+pub struct PresetLibrary<S: ShareSettings + Resource> {
+    simulation: String,
+    shipped: Vec<Preset>,
+    saved: Vec<Preset>,
+    /// Name typed into the save box
+    name: String,
+    marker: PhantomData<S>,
+}
+
+impl<S: ShareSettings + Resource> PresetLibrary<S> {
+    /// The chapter's presets, then the reader's
+    pub fn presets(&self) -> impl Iterator<Item = &Preset> {
+        self.shipped.iter().chain(&self.saved)
+    }
+
+    /// Save `settings` as `name`, replacing a saved preset of the same name, and write
+    /// the reader's presets out
+    pub fn save(&mut self, name: impl Into<String>, settings: &S) {
+        let preset = Preset::new(name, settings);
+        match self.saved.iter_mut().find(|saved| saved.name == preset.name) {
+            Some(saved) => *saved = preset,
+            None => self.saved.push(preset),
+        }
+        self.write_saved();
+    }
+
+    /// Forget the reader's preset called `name`
+    pub fn delete(&mut self, name: &str) {
+        self.saved.retain(|saved| saved.name != name);
+        self.write_saved();
+    }
+
+    fn is_saved(&self, name: &str) -> bool {
+        self.saved.iter().any(|saved| saved.name == name)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn path(&self) -> PathBuf {
+        PathBuf::from("presets").join(format!("{}.ron", self.simulation))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn storage_key(&self) -> String {
+        format!("rhysics-presets-{}", self.simulation)
+    }
+
+    fn read_saved(&self) -> Vec<Preset> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let text = std::fs::read_to_string(self.path()).ok();
+        #[cfg(target_arch = "wasm32")]
+        let text = local_storage().and_then(|storage| storage.get_item(&self.storage_key()).ok().flatten());
+        let Some(text) = text else {
+            return Vec::new();
+        };
+        ron::from_str(&text).unwrap_or_else(|err| {
+            log::warn!("Ignoring unreadable presets for {}: {err}", self.simulation);
+            Vec::new()
+        })
+    }
+
+    fn write_saved(&self) {
+        let text = ron::ser::to_string_pretty(&self.saved, ron::ser::PrettyConfig::default())
+            .expect("presets only hold strings");
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let path = self.path();
+            let written = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|()| std::fs::write(&path, text));
+            if let Err(err) = written {
+                log::error!("Could not save presets to {}: {err}", path.display());
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        if local_storage().and_then(|storage| storage.set_item(&self.storage_key(), &text).ok()).is_none() {
+            log::error!("Could not save presets to localStorage");
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+/// Dropdown to load a preset into `settings`, with a box to save the current settings
+/// under a name and a button to delete the reader's presets
+pub fn preset_picker<S: ShareSettings + Resource>(
+    ui: &mut egui::Ui,
+    library: &mut PresetLibrary<S>,
+    settings: &mut S,
+) {
+    let mut chosen = None;
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_label("Preset")
+            .selected_text("Load…")
+            .show_ui(ui, |ui| {
+                for preset in library.presets() {
+                    if ui.selectable_label(false, &preset.name).clicked() {
+                        chosen = Some(preset.clone());
+                    }
+                }
+            });
+        let name = library.name.trim().to_string();
+        if library.is_saved(&name) && ui.button("Delete").clicked() {
+            library.delete(&name);
+        }
+    });
+    if let Some(preset) = chosen {
+        preset.apply(settings);
+        library.name = preset.name;
+    }
+    ui.horizontal(|ui| {
+        ui.add(egui::TextEdit::singleline(&mut library.name).hint_text("name").desired_width(120.0));
+        let name = library.name.trim().to_string();
+        if ui.add_enabled(!name.is_empty(), egui::Button::new("Save preset")).clicked() {
+            library.save(name, settings);
+        }
+    });
+}
@@ -0,0 +1,242 @@
+//! Checkpoint quizzes that check a student's answer against the running simulation
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+
+/// Adds the quiz panel, answer checking and the [`QuizAnswered`] message
+pub struct QuizPlugin;
+
+impl Plugin for QuizPlugin {
+    fn build(&self, app: &mut App) {
+        crate::add_egui(app);
+        app.init_resource::<Quiz>()
+            .add_message::<QuizAnswered>()
+            .add_systems(Update, check_quiz_answers)
+            .add_systems(EguiPrimaryContextPass, quiz_panel);
+    }
+}
+
+/// Reads a value out of the simulation, e.g. the measured range of a projectile
+pub type Measurement = Box<dyn Fn(&World) -> Option<f32> + Send + Sync>;
+
+/// Checks whether the simulation has been put into the requested state
+pub type Check = Box<dyn Fn(&World) -> bool + Send + Sync>;
+
+/// How a question is answered and checked
+pub enum Answer {
+    /// A number typed by the student, compared with a known value
+    Numeric { expected: f32, tolerance: f32 },
+    /// A number typed by the student, compared with a value measured from the simulation
+    Measured { measure: Measurement, tolerance: f32 },
+    /// The student sets the simulation up and presses "Check"
+    Interactive(Check),
+}
+
+/// A single checkpoint question
+pub struct Question {
+    pub prompt: String,
+    pub unit: String,
+    pub hint: Option<String>,
+    pub answer: Answer,
+    pub attempts: u32,
+    pub correct: bool,
+}
+
+impl Question {
+    fn new(prompt: impl Into<String>, answer: Answer) -> Self {
+        Self {
+            prompt: prompt.into(),
+            unit: String::new(),
+            hint: None,
+            answer,
+            attempts: 0,
+            correct: false,
+        }
+    }
+
+    /// A question with a fixed numeric answer
+    pub fn numeric(prompt: impl Into<String>, expected: f32, tolerance: f32) -> Self {
+        Self::new(prompt, Answer::Numeric { expected, tolerance })
+    }
+
+    /// A question whose numeric answer is measured from the simulation when checked
+    pub fn measured(
+        prompt: impl Into<String>,
+        tolerance: f32,
+        measure: impl Fn(&World) -> Option<f32> + Send + Sync + 'static,
+    ) -> Self {
+        Self::new(
+            prompt,
+            Answer::Measured {
+                measure: Box::new(measure),
+                tolerance,
+            },
+        )
+    }
+
+    /// A question answered by configuring the simulation
+    pub fn interactive(
+        prompt: impl Into<String>,
+        check: impl Fn(&World) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self::new(prompt, Answer::Interactive(Box::new(check)))
+    }
+
+    pub fn with_unit(mut self, unit: impl Into<String>) -> Self {
+        self.unit = unit.into();
+        self
+    }
+
+    /// Shown after the first wrong attempt
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    fn takes_number(&self) -> bool {
+        !matches!(self.answer, Answer::Interactive(_))
+    }
+}
+
+/// Sent whenever a question is checked
+#[derive(Message, Debug, Clone, Copy)]
+pub struct QuizAnswered {
+    pub question: usize,
+    pub correct: bool,
+}
+
+/// The questions registered by a chapter and the student's progress through them
+#[derive(Resource, Default)]
+pub struct Quiz {
+    questions: Vec<Question>,
+    current: usize,
+    input: String,
+    feedback: Option<String>,
+    submission: Option<Submission>,
+}
+
+/// An answer waiting to be checked against the world
+struct Submission {
+    question: usize,
+    value: Option<f32>,
+}
+
+impl Quiz {
+    pub fn new(questions: Vec<Question>) -> Self {
+        Self {
+            questions,
+            ..default()
+        }
+    }
+
+    pub fn questions(&self) -> &[Question] {
+        &self.questions
+    }
+
+    /// Number of questions answered correctly
+    pub fn score(&self) -> usize {
+        self.questions.iter().filter(|question| question.correct).count()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.questions.iter().all(|question| question.correct)
+    }
+
+    fn select(&mut self, index: usize) {
+        self.current = index.min(self.questions.len().saturating_sub(1));
+        self.input.clear();
+        self.feedback = None;
+    }
+}
+
+fn within(value: f32, expected: f32, tolerance: f32) -> bool {
+    (value - expected).abs() <= tolerance
+}
+
+/// Check the submitted answer, which may need to read arbitrary simulation state
+fn check_quiz_answers(world: &mut World) {
+    let result = world.resource_scope(|world, mut quiz: Mut<Quiz>| {
+        let submission = quiz.submission.take()?;
+        let question = quiz.questions.get(submission.question)?;
+        let (correct, feedback) = match (&question.answer, submission.value) {
+            (Answer::Numeric { expected, tolerance }, Some(value)) => {
+                let correct = within(value, *expected, *tolerance);
+                (correct, format!("Expected {expected:.2} {}", question.unit))
+            }
+            (Answer::Measured { measure, tolerance }, Some(value)) => match measure(world) {
+                Some(measured) => {
+                    let correct = within(value, measured, *tolerance);
+                    (correct, format!("The simulation measured {measured:.2} {}", question.unit))
+                }
+                None => (false, "Run the simulation first, then check again".to_string()),
+            },
+            (Answer::Interactive(check), _) => (check(world), String::new()),
+            (_, None) => (false, "Enter a number".to_string()),
+        };
+
+        let question = &mut quiz.questions[submission.question];
+        question.attempts += 1;
+        question.correct |= correct;
+        let feedback = match (correct, question.hint.as_ref()) {
+            (true, _) => format!("Correct! {feedback}"),
+            (false, Some(hint)) => format!("Not quite. Hint: {hint}"),
+            (false, None) => "Not quite, try again".to_string(),
+        };
+        quiz.feedback = Some(feedback);
+        Some(QuizAnswered {
+            question: submission.question,
+            correct,
+        })
+    });
+
+    if let Some(answered) = result {
+        world.write_message(answered);
+    }
+}
+
+fn quiz_panel(mut contexts: EguiContexts, mut quiz: ResMut<Quiz>) -> Result {
+    if quiz.questions.is_empty() {
+        return Ok(());
+    }
+    let ctx = contexts.ctx_mut()?;
+    let quiz = &mut *quiz;
+
+    egui::Window::new("Checkpoint")
+        .default_open(false)
+        .show(ctx, |ui| {
+            ui.label(format!("Progress: {} / {}", quiz.score(), quiz.questions.len()));
+            ui.horizontal(|ui| {
+                for index in 0..quiz.questions.len() {
+                    let label = if quiz.questions[index].correct {
+                        format!("{} ✔", index + 1)
+                    } else {
+                        format!("{}", index + 1)
+                    };
+                    if ui.selectable_label(quiz.current == index, label).clicked() {
+                        quiz.select(index);
+                    }
+                }
+            });
+            ui.separator();
+
+            let question = &quiz.questions[quiz.current];
+            ui.label(&question.prompt);
+            let takes_number = question.takes_number();
+            let unit = question.unit.clone();
+            ui.horizontal(|ui| {
+                if takes_number {
+                    ui.text_edit_singleline(&mut quiz.input);
+                    ui.label(unit);
+                }
+                if ui.button("Check").clicked() {
+                    quiz.submission = Some(Submission {
+                        question: quiz.current,
+                        value: quiz.input.trim().parse().ok(),
+                    });
+                }
+            });
+            if let Some(feedback) = &quiz.feedback {
+                ui.label(feedback);
+            }
+        });
+    Ok(())
+}
@@ -0,0 +1,354 @@
+//! Recording of the last few seconds of a simulation, to scrub through and replay
+//!
+//! [`ReplayPlugin`] snapshots the `Transform`, `Position` and `Velocity` of every
+//! [`Recorded`] entity after each physics tick into a ring buffer of
+//! [`Recorder::capacity`] frames. Other components are recorded by adding
+//! [`RecordComponentPlugin<C>`] for them. The Replay window pauses the simulation and
+//! plays the recording forwards or backwards, or scrubs to a frame; resuming carries on
+//! simulating from the frame on screen and forgets the frames after it. Export writes
+//! the recording as CSV, to [`Recorder::export_path`] on native and to the log on the web.
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::path::PathBuf;
+
+use bevy::ecs::component::Mutable;
+use bevy::prelude::*;
+use bevy::transform::TransformSystems;
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+
+use crate::physics::run_physics_step;
+use crate::timestep::physics_stepped;
+use crate::{Position, Velocity};
+
+/// Adds the [`Recorder`], its recording and playback systems, and the Replay window
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        crate::add_egui(app);
+        app.init_resource::<Recorder>()
+            .add_systems(FixedUpdate, record_frame.after(run_physics_step).run_if(physics_stepped))
+            .add_systems(PostUpdate, play_back.before(TransformSystems::Propagate))
+            .add_systems(EguiPrimaryContextPass, replay_panel);
+    }
+}
+
+/// Also records and replays `C` on [`Recorded`] entities; needs [`ReplayPlugin`]
+pub struct RecordComponentPlugin<C: Component + Clone>(PhantomData<C>);
+
+impl<C: Component + Clone> Default for RecordComponentPlugin<C> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<C: Component<Mutability = Mutable> + Clone> Plugin for RecordComponentPlugin<C> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ComponentTrack<C>>()
+            .add_systems(FixedUpdate, record_component::<C>.after(record_frame).run_if(physics_stepped))
+            .add_systems(PostUpdate, restore_component::<C>.after(play_back));
+    }
+}
+
+/// Marks an entity to be recorded
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub struct Recorded;
+
+/// State of one entity in one frame
+#[derive(Debug, Clone, Copy)]
+pub struct RecordedBody {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub position: Option<Vec2>,
+    pub velocity: Option<Vec2>,
+}
+
+#[derive(Debug)]
+struct Frame {
+    /// Simulated time at the end of the tick (s)
+    time: f32,
+    bodies: Vec<(Entity, RecordedBody)>,
+}
+
+/// Where playback is, while the simulation is paused for it
+#[derive(Debug, Clone, Copy)]
+pub struct Playback {
+    /// Frame on screen, counted from the oldest kept frame; fractional while playing
+    pub cursor: f32,
+    /// Playback speed relative to real time; negative plays backwards, 0 holds the frame
+    pub speed: f32,
+}
+
+/// The recorded frames and whether they are being played back
+#[derive(Resource, Debug)]
+pub struct Recorder {
+    /// Most frames kept; the oldest are dropped first
+    pub capacity: usize,
+    /// Where Export writes the CSV on native
+    pub export_path: PathBuf,
+    frames: VecDeque<Frame>,
+    /// Number of frames dropped from the front so far, so tracks can line up with `frames`
+    first: u64,
+    playback: Option<Playback>,
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self {
+            // Ten seconds at the default physics rate
+            capacity: 640,
+            export_path: PathBuf::from("replay.csv"),
+            frames: VecDeque::new(),
+            first: 0,
+            playback: None,
+        }
+    }
+}
+
+impl Recorder {
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Simulated time between the oldest and newest frame (s)
+    pub fn duration(&self) -> f32 {
+        match (self.frames.front(), self.frames.back()) {
+            (Some(first), Some(last)) => last.time - first.time,
+            _ => 0.0,
+        }
+    }
+
+    pub fn playback(&self) -> Option<Playback> {
+        self.playback
+    }
+
+    pub fn is_playing_back(&self) -> bool {
+        self.playback.is_some()
+    }
+
+    /// Stop recording and hold the latest frame; pause the simulation alongside
+    pub fn start_playback(&mut self) {
+        if !self.frames.is_empty() {
+            self.playback = Some(Playback {
+                cursor: (self.frames.len() - 1) as f32,
+                speed: 0.0,
+            });
+        }
+    }
+
+    /// Go back to recording from the frame on screen, forgetting the frames after it
+    pub fn stop_playback(&mut self) {
+        if let Some(playback) = self.playback.take() {
+            self.frames.truncate(playback.cursor.round() as usize + 1);
+        }
+    }
+
+    /// Index of the frame on screen, counted from the first frame ever recorded
+    fn current_frame(&self) -> Option<u64> {
+        self.playback.map(|playback| self.first + playback.cursor.round() as u64)
+    }
+
+    /// Range of frame indices kept, counted from the first frame ever recorded
+    fn kept(&self) -> std::ops::Range<u64> {
+        self.first..self.first + self.frames.len() as u64
+    }
+
+    /// The recording as CSV, one row per entity per frame
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("frame,time,entity,x,y,vx,vy\n");
+        for (index, frame) in self.frames.iter().enumerate() {
+            for (entity, body) in &frame.bodies {
+                let position = body.position.unwrap_or(body.translation.truncate());
+                let velocity = body.velocity.unwrap_or_default();
+                csv.push_str(&format!(
+                    "{},{},{},{},{},{},{}\n",
+                    self.first + index as u64,
+                    frame.time,
+                    entity.to_bits(),
+                    position.x,
+                    position.y,
+                    velocity.x,
+                    velocity.y,
+                ));
+            }
+        }
+        csv
+    }
+
+    /// Write the CSV to `export_path` (native) or the log (web)
+    pub fn export(&self) {
+        let csv = self.to_csv();
+        #[cfg(not(target_arch = "wasm32"))]
+        match std::fs::write(&self.export_path, &csv) {
+            Ok(()) => log::info!("Wrote {} frames to {}", self.frames.len(), self.export_path.display()),
+            Err(err) => log::error!("Could not write replay to {}: {err}", self.export_path.display()),
+        }
+        #[cfg(target_arch = "wasm32")]
+        log::info!("Replay:\n{csv}");
+    }
+}
+
+/// Recorded values of one component, frame by frame alongside the [`Recorder`]
+#[derive(Resource)]
+pub struct ComponentTrack<C: Component + Clone> {
+    frames: VecDeque<(u64, Vec<(Entity, C)>)>,
+}
+
+impl<C: Component + Clone> Default for ComponentTrack<C> {
+    fn default() -> Self {
+        Self { frames: VecDeque::new() }
+    }
+}
+
+/// System to snapshot every [`Recorded`] entity, unless playing back
+#[allow(clippy::type_complexity)]
+pub fn record_frame(
+    mut recorder: ResMut<Recorder>,
+    bodies: Query<(Entity, &Transform, Option<&Position>, Option<&Velocity>), With<Recorded>>,
+    time: Res<Time>,
+) {
+    if recorder.is_playing_back() {
+        return;
+    }
+    let bodies = bodies
+        .iter()
+        .map(|(entity, transform, position, velocity)| {
+            let body = RecordedBody {
+                translation: transform.translation,
+                rotation: transform.rotation,
+                position: position.map(|position| position.0),
+                velocity: velocity.map(|velocity| velocity.0),
+            };
+            (entity, body)
+        })
+        .collect();
+    recorder.frames.push_back(Frame { time: time.elapsed_secs(), bodies });
+    while recorder.frames.len() > recorder.capacity.max(1) {
+        recorder.frames.pop_front();
+        recorder.first += 1;
+    }
+}
+
+/// System to move the playback cursor and put the frame under it back on screen
+pub fn play_back(
+    mut recorder: ResMut<Recorder>,
+    mut bodies: Query<(&mut Transform, Option<&mut Position>, Option<&mut Velocity>)>,
+    real_time: Res<Time<Real>>,
+    fixed_time: Res<Time<Fixed>>,
+) {
+    let last = recorder.frames.len().saturating_sub(1) as f32;
+    let Some(playback) = recorder.playback.as_mut() else {
+        return;
+    };
+    // One frame per fixed step at speed 1
+    let frames_per_second = 1.0 / fixed_time.timestep().as_secs_f32();
+    playback.cursor = (playback.cursor + playback.speed * frames_per_second * real_time.delta_secs()).clamp(0.0, last);
+    let index = playback.cursor.round() as usize;
+    let Some(frame) = recorder.frames.get(index) else {
+        return;
+    };
+    for (entity, body) in &frame.bodies {
+        let Ok((mut transform, position, velocity)) = bodies.get_mut(*entity) else {
+            continue;
+        };
+        transform.translation = body.translation;
+        transform.rotation = body.rotation;
+        if let (Some(mut position), Some(recorded)) = (position, body.position) {
+            position.0 = recorded;
+        }
+        if let (Some(mut velocity), Some(recorded)) = (velocity, body.velocity) {
+            velocity.0 = recorded;
+        }
+    }
+}
+
+fn record_component<C: Component + Clone>(
+    recorder: Res<Recorder>,
+    mut track: ResMut<ComponentTrack<C>>,
+    components: Query<(Entity, &C), With<Recorded>>,
+) {
+    let kept = recorder.kept();
+    // Forget frames the recorder dropped, or cut off when resuming from an earlier frame
+    track.frames.retain(|(index, _)| kept.contains(index));
+    if recorder.is_playing_back() || kept.is_empty() {
+        return;
+    }
+    let latest = kept.end - 1;
+    if track.frames.back().is_some_and(|(index, _)| *index == latest) {
+        return;
+    }
+    let values = components.iter().map(|(entity, value)| (entity, value.clone())).collect();
+    track.frames.push_back((latest, values));
+}
+
+fn restore_component<C: Component<Mutability = Mutable> + Clone>(
+    recorder: Res<Recorder>,
+    track: Res<ComponentTrack<C>>,
+    mut components: Query<&mut C>,
+) {
+    let Some(current) = recorder.current_frame() else {
+        return;
+    };
+    let Some((_, values)) = track.frames.iter().find(|(index, _)| *index == current) else {
+        return;
+    };
+    for (entity, value) in values {
+        if let Ok(mut component) = components.get_mut(*entity) {
+            *component = value.clone();
+        }
+    }
+}
+
+fn replay_panel(
+    mut contexts: EguiContexts,
+    mut recorder: ResMut<Recorder>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+) -> Result {
+    egui::Window::new("Replay")
+        .default_open(false)
+        .anchor(egui::Align2::LEFT_TOP, [10.0, 60.0])
+        .resizable(false)
+        .show(contexts.ctx_mut()?, |ui| {
+            match recorder.playback() {
+                None => {
+                    ui.label(format!("Recording: {} frames ({:.1} s)", recorder.len(), recorder.duration()));
+                    if ui.add_enabled(!recorder.is_empty(), egui::Button::new("⏸ Replay")).clicked() {
+                        recorder.start_playback();
+                        virtual_time.pause();
+                    }
+                }
+                Some(mut playback) => {
+                    let last = recorder.len().saturating_sub(1) as f32;
+                    let mut frame = playback.cursor.round();
+                    if ui.add(egui::Slider::new(&mut frame, 0.0..=last).integer().text("frame")).changed() {
+                        playback.cursor = frame;
+                        playback.speed = 0.0;
+                    }
+                    ui.horizontal(|ui| {
+                        if ui.button("◀ Back").clicked() {
+                            playback.speed = -1.0;
+                        }
+                        if ui.button("⏸ Hold").clicked() {
+                            playback.speed = 0.0;
+                        }
+                        if ui.button("▶ Play").clicked() {
+                            playback.speed = 1.0;
+                        }
+                    });
+                    recorder.playback = Some(playback);
+                    if ui.button("⏺ Resume from here").clicked() {
+                        recorder.stop_playback();
+                        virtual_time.unpause();
+                    }
+                }
+            }
+            if ui.add_enabled(!recorder.is_empty(), egui::Button::new("Export CSV")).clicked() {
+                recorder.export();
+            }
+        });
+    Ok(())
+}
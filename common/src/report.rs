@@ -0,0 +1,175 @@
+//! Self-describing summaries of simulation runs
+//!
+//! [`SimReportPlugin`] counts fixed steps and wall time from startup. When the app
+//! exits it builds a [`SimReport`] with the seed, a hash of the chapter's
+//! [`ShareSettings`], the step count, the timings and whatever the chapter recorded in
+//! [`ReportMetrics`], and writes it as JSON. Parameter sweeps and CI runs can then be
+//! told apart and reproduced from their output alone.
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use std::path::PathBuf;
+
+use bevy::platform::time::Instant;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::forces::Gravity;
+use crate::share::{ShareParams, ShareSettings, SimulationSeed};
+use crate::watchdog::ConservedQuantities;
+
+/// Tracks the run and writes a [`SimReport`] on exit
+pub struct SimReportPlugin<S: ShareSettings + Resource> {
+    name: String,
+    path: Option<PathBuf>,
+    _settings: PhantomData<S>,
+}
+
+impl<S: ShareSettings + Resource> SimReportPlugin<S> {
+    /// Report for the simulation called `name`, logged on exit
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            path: None,
+            _settings: PhantomData,
+        }
+    }
+
+    /// Also write the JSON to `path` (native only). Without this, the
+    /// `RHYSICS_REPORT` environment variable can name the file instead.
+    pub fn write_to(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+}
+
+impl<S: ShareSettings + Resource> Plugin for SimReportPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ReportRun {
+            name: self.name.clone(),
+            path: self.path.clone().or_else(|| std::env::var_os("RHYSICS_REPORT").map(PathBuf::from)),
+            started: Instant::now(),
+            steps: 0,
+        })
+        .init_resource::<ReportMetrics>()
+        .add_systems(FixedLast, count_step)
+        .add_systems(Last, write_report_on_exit::<S>.run_if(on_message::<AppExit>));
+    }
+}
+
+/// Summary of one run, serializable to JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimReport {
+    pub simulation: String,
+    pub seed: Option<u64>,
+    /// The parameters as they would appear in a share link, without the seed
+    pub parameters: String,
+    /// FNV-1a hash of `parameters`, for grouping runs with the same configuration
+    /// across seeds
+    pub parameter_hash: String,
+    pub steps: u64,
+    /// Virtual time simulated, in seconds
+    pub simulated_time: f64,
+    pub wall_time: f64,
+    pub metrics: BTreeMap<String, f64>,
+}
+
+impl SimReport {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("reports only hold plain values")
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Key physical quantities a chapter wants in its report (range, peak height, ...)
+#[derive(Resource, Debug, Default, Clone)]
+pub struct ReportMetrics(BTreeMap<String, f64>);
+
+impl ReportMetrics {
+    pub fn set(&mut self, name: impl Into<String>, value: f64) {
+        self.0.insert(name.into(), value);
+    }
+
+    /// Keep the largest value seen, e.g. for a peak height
+    pub fn max(&mut self, name: impl Into<String>, value: f64) {
+        let entry = self.0.entry(name.into()).or_insert(value);
+        *entry = entry.max(value);
+    }
+
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.0.get(name).copied()
+    }
+}
+
+/// The report built when the app exited
+#[derive(Resource, Debug, Clone)]
+pub struct LastReport(pub SimReport);
+
+#[derive(Resource)]
+struct ReportRun {
+    name: String,
+    path: Option<PathBuf>,
+    started: Instant,
+    steps: u64,
+}
+
+fn count_step(mut run: ResMut<ReportRun>) {
+    run.steps += 1;
+}
+
+/// 64-bit FNV-1a, stable across platforms and Rust versions unlike `DefaultHasher`
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn write_report_on_exit<S: ShareSettings + Resource>(
+    mut commands: Commands,
+    run: Res<ReportRun>,
+    settings: Option<Res<S>>,
+    gravity: Option<Res<Gravity>>,
+    seed: Option<Res<SimulationSeed>>,
+    metrics: Res<ReportMetrics>,
+    conserved: Option<Res<ConservedQuantities>>,
+    time: Res<Time<Virtual>>,
+) {
+    let mut params = ShareParams::default();
+    if let Some(settings) = &settings {
+        settings.write_params(&mut params);
+    }
+    if let Some(gravity) = &gravity {
+        gravity.write_params(&mut params);
+    }
+    let parameters = params.to_query();
+
+    let mut all_metrics = metrics.0.clone();
+    if let Some(conserved) = conserved {
+        all_metrics.entry("energy".to_string()).or_insert(conserved.energy as f64);
+        all_metrics.entry("momentum".to_string()).or_insert(conserved.momentum.length() as f64);
+    }
+
+    let report = SimReport {
+        simulation: run.name.clone(),
+        seed: seed.map(|seed| seed.0),
+        parameter_hash: format!("{:016x}", fnv1a(&parameters)),
+        parameters,
+        steps: run.steps,
+        simulated_time: time.elapsed_secs_f64(),
+        wall_time: run.started.elapsed().as_secs_f64(),
+        metrics: all_metrics,
+    };
+    let json = report.to_json();
+    log::info!("Simulation report:\n{json}");
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(path) = &run.path {
+        if let Err(err) = std::fs::write(path, &json) {
+            log::error!("Could not write report to {}: {err}", path.display());
+        }
+    }
+    commands.insert_resource(LastReport(report));
+}
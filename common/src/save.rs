@@ -0,0 +1,210 @@
+//! Saving a simulation's state to disk and picking it up again later
+//!
+//! [`save_state`] writes the physics components of every [`Saved`] entity, and the
+//! shared `Gravity`, as RON to [`SaveFile::path`] (or to the page's `localStorage`
+//! under [`SaveFile::key`] on the web). [`load_state`] puts them back. Entities are
+//! matched by the order they were spawned in, so a chapter that spawns the same bodies
+//! on every start (as seeded ones do) can be closed and resumed where it was left.
+//! [`SaveStatePlugin`] binds them to F5 and F9.
+use std::path::PathBuf;
+
+use bevy::input::common_conditions::input_just_pressed;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::forces::Gravity;
+use crate::{AngularVelocity, Mass, Position, Rotation, Velocity};
+
+/// F5 saves and F9 loads the state of the [`Saved`] entities
+pub struct SaveStatePlugin;
+
+impl Plugin for SaveStatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SaveFile>().add_systems(
+            Update,
+            (
+                save_state.run_if(input_just_pressed(KeyCode::F5)),
+                load_state.run_if(input_just_pressed(KeyCode::F9)),
+            ),
+        );
+    }
+}
+
+/// Marks an entity whose physics state is saved and loaded
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub struct Saved;
+
+/// Where the state goes
+#[derive(Resource, Debug, Clone)]
+pub struct SaveFile {
+    /// File on native
+    pub path: PathBuf,
+    /// `localStorage` key on the web
+    pub key: String,
+}
+
+impl Default for SaveFile {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("state.ron"),
+            key: "rhysics-state".to_string(),
+        }
+    }
+}
+
+/// State of one saved entity; components it doesn't have stay `None`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SavedBody {
+    pub translation: [f32; 3],
+    /// Angle of the `Transform` about z (radians)
+    pub angle: f32,
+    pub position: Option<[f32; 2]>,
+    pub velocity: Option<[f32; 2]>,
+    pub mass: Option<f32>,
+    pub rotation: Option<f32>,
+    pub angular_velocity: Option<f32>,
+}
+
+/// Everything [`save_state`] writes
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SavedState {
+    pub gravity: Option<[f32; 2]>,
+    /// In spawn order
+    pub bodies: Vec<SavedBody>,
+}
+
+impl SavedState {
+    pub fn to_ron(&self) -> String {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .expect("saved state only holds plain values")
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(text)
+    }
+}
+
+/// The [`Saved`] entities' state, in spawn order
+pub fn capture_state(world: &mut World) -> SavedState {
+    let gravity = world.get_resource::<Gravity>().map(|gravity| gravity.0.to_array());
+    let mut query = world.query_filtered::<(
+        Entity,
+        &Transform,
+        Option<&Position>,
+        Option<&Velocity>,
+        Option<&Mass>,
+        Option<&Rotation>,
+        Option<&AngularVelocity>,
+    ), With<Saved>>();
+    let mut bodies: Vec<_> = query.iter(world).collect();
+    bodies.sort_by_key(|(entity, ..)| *entity);
+    let bodies = bodies
+        .into_iter()
+        .map(|(_, transform, position, velocity, mass, rotation, angular_velocity)| SavedBody {
+            translation: transform.translation.to_array(),
+            angle: transform.rotation.to_euler(EulerRot::ZYX).0,
+            position: position.map(|position| position.0.to_array()),
+            velocity: velocity.map(|velocity| velocity.0.to_array()),
+            mass: mass.map(|mass| mass.0),
+            rotation: rotation.map(|rotation| rotation.0),
+            angular_velocity: angular_velocity.map(|angular_velocity| angular_velocity.0),
+        })
+        .collect();
+    SavedState { gravity, bodies }
+}
+
+/// Put `state` back onto the [`Saved`] entities, matching them by spawn order
+pub fn restore_state(world: &mut World, state: &SavedState) {
+    if let (Some(gravity), Some(mut current)) = (state.gravity, world.get_resource_mut::<Gravity>()) {
+        current.0 = Vec2::from_array(gravity);
+    }
+    let mut saved = world.query_filtered::<Entity, With<Saved>>();
+    let mut entities: Vec<Entity> = saved.iter(world).collect();
+    entities.sort();
+    if entities.len() != state.bodies.len() {
+        log::warn!(
+            "Saved state has {} bodies but the simulation has {}; restoring the first {}",
+            state.bodies.len(),
+            entities.len(),
+            entities.len().min(state.bodies.len())
+        );
+    }
+    let mut query = world.query::<(
+        &mut Transform,
+        Option<&mut Position>,
+        Option<&mut Velocity>,
+        Option<&mut Mass>,
+        Option<&mut Rotation>,
+        Option<&mut AngularVelocity>,
+    )>();
+    for (entity, body) in entities.into_iter().zip(&state.bodies) {
+        let Ok((mut transform, position, velocity, mass, rotation, angular_velocity)) = query.get_mut(world, entity)
+        else {
+            continue;
+        };
+        transform.translation = Vec3::from_array(body.translation);
+        transform.rotation = Quat::from_rotation_z(body.angle);
+        if let (Some(mut position), Some(saved)) = (position, body.position) {
+            position.0 = Vec2::from_array(saved);
+        }
+        if let (Some(mut velocity), Some(saved)) = (velocity, body.velocity) {
+            velocity.0 = Vec2::from_array(saved);
+        }
+        if let (Some(mut mass), Some(saved)) = (mass, body.mass) {
+            mass.0 = saved;
+        }
+        if let (Some(mut rotation), Some(saved)) = (rotation, body.rotation) {
+            rotation.0 = saved;
+        }
+        if let (Some(mut angular_velocity), Some(saved)) = (angular_velocity, body.angular_velocity) {
+            angular_velocity.0 = saved;
+        }
+    }
+}
+
+/// Exclusive system to write the state to the [`SaveFile`]
+pub fn save_state(world: &mut World) {
+    let file = world.get_resource::<SaveFile>().cloned().unwrap_or_default();
+    let state = capture_state(world);
+    let text = state.to_ron();
+    #[cfg(not(target_arch = "wasm32"))]
+    match std::fs::write(&file.path, text) {
+        Ok(()) => log::info!("Saved {} bodies to {}", state.bodies.len(), file.path.display()),
+        Err(err) => log::error!("Could not save state to {}: {err}", file.path.display()),
+    }
+    #[cfg(target_arch = "wasm32")]
+    match local_storage().map(|storage| storage.set_item(&file.key, &text)) {
+        Some(Ok(())) => log::info!("Saved {} bodies to localStorage", state.bodies.len()),
+        _ => log::error!("Could not save state to localStorage"),
+    }
+}
+
+/// Exclusive system to restore the state from the [`SaveFile`], if there is one
+pub fn load_state(world: &mut World) {
+    let file = world.get_resource::<SaveFile>().cloned().unwrap_or_default();
+    #[cfg(not(target_arch = "wasm32"))]
+    let text = match std::fs::read_to_string(&file.path) {
+        Ok(text) => text,
+        Err(err) => {
+            log::warn!("No saved state at {}: {err}", file.path.display());
+            return;
+        }
+    };
+    #[cfg(target_arch = "wasm32")]
+    let Some(text) = local_storage().and_then(|storage| storage.get_item(&file.key).ok().flatten()) else {
+        log::warn!("No saved state in localStorage");
+        return;
+    };
+    match SavedState::from_ron(&text) {
+        Ok(state) => {
+            restore_state(world, &state);
+            log::info!("Loaded {} bodies", state.bodies.len());
+        }
+        Err(err) => log::error!("Could not read saved state: {err}"),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
@@ -0,0 +1,104 @@
+// Colors a grid of values stored in an RG float texture, for `field::FieldMaterial`.
+// The texture is read with textureLoad and blended by hand, since float textures
+// aren't filterable everywhere (WebGL2).
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+struct FieldSettings {
+    min: f32,
+    max: f32,
+    // 0 scalar, 1 vector magnitude, 2 complex (hue = phase)
+    mode: u32,
+    // 0 viridis, 1 inferno, 2 diverging, 3 plasma, as in `colormap::Colormap`
+    colormap: u32,
+}
+
+@group(2) @binding(0) var<uniform> settings: FieldSettings;
+@group(2) @binding(1) var field: texture_2d<f32>;
+
+const VIRIDIS_0 = vec3<f32>(0.2777273, 0.0054073, 0.3340998);
+const VIRIDIS_1 = vec3<f32>(0.1050930, 1.4046135, 1.3845902);
+const VIRIDIS_2 = vec3<f32>(-0.3308618, 0.2148476, 0.0950952);
+const VIRIDIS_3 = vec3<f32>(-4.6342305, -5.7991010, -19.3324410);
+const VIRIDIS_4 = vec3<f32>(6.2282699, 14.1799334, 56.6905526);
+const VIRIDIS_5 = vec3<f32>(4.7763850, -13.7451454, -65.3530326);
+const VIRIDIS_6 = vec3<f32>(-5.4354559, 4.6458526, 26.3124352);
+
+const INFERNO_0 = vec3<f32>(0.0002189, 0.0016510, -0.0194809);
+const INFERNO_1 = vec3<f32>(0.1065134, 0.5639564, 3.9327124);
+const INFERNO_2 = vec3<f32>(11.6024931, -3.9728540, -15.9423941);
+const INFERNO_3 = vec3<f32>(-41.7039961, 17.4363989, 44.3541452);
+const INFERNO_4 = vec3<f32>(77.1629357, -33.4023589, -81.8073093);
+const INFERNO_5 = vec3<f32>(-71.3194282, 32.6260643, 73.2095199);
+const INFERNO_6 = vec3<f32>(25.1311262, -12.2426690, -23.0703250);
+
+const PLASMA_0 = vec3<f32>(0.0587323, 0.0233367, 0.5433402);
+const PLASMA_1 = vec3<f32>(2.1765146, 0.2383834, 0.7539605);
+const PLASMA_2 = vec3<f32>(-2.6894605, -7.4558511, 3.1107999);
+const PLASMA_3 = vec3<f32>(6.1303483, 42.3461881, -28.5188547);
+const PLASMA_4 = vec3<f32>(-11.1074362, -82.6663111, 60.1398477);
+const PLASMA_5 = vec3<f32>(10.0230656, 71.4136177, -54.0721866);
+const PLASMA_6 = vec3<f32>(-3.6587138, -22.9315347, 18.1919078);
+
+// Bilinear sample, with row 0 of the grid at the bottom of the quad
+fn sample_field(uv: vec2<f32>) -> vec2<f32> {
+    let size = vec2<f32>(textureDimensions(field));
+    let p = clamp(vec2(uv.x, 1.0 - uv.y) * size - 0.5, vec2(0.0), size - 1.0);
+    let i = vec2<i32>(floor(p));
+    let f = p - floor(p);
+    let last = vec2<i32>(size) - 1;
+    let a = textureLoad(field, i, 0).rg;
+    let b = textureLoad(field, min(i + vec2(1, 0), last), 0).rg;
+    let c = textureLoad(field, min(i + vec2(0, 1), last), 0).rg;
+    let d = textureLoad(field, min(i + vec2(1, 1), last), 0).rg;
+    return mix(mix(a, b, f.x), mix(c, d, f.x), f.y);
+}
+
+// sRGB colormap value for t in [0, 1]
+fn colormap(t: f32) -> vec3<f32> {
+    let x = clamp(t, 0.0, 1.0);
+    switch settings.colormap {
+        case 1u: {
+            return INFERNO_0 + x * (INFERNO_1 + x * (INFERNO_2 + x * (INFERNO_3 + x * (INFERNO_4 + x * (INFERNO_5 + x * INFERNO_6)))));
+        }
+        case 2u: {
+            // Blue below the middle of the range, red above, white at it
+            let blue = vec3(0.23, 0.30, 0.75);
+            let red = vec3(0.71, 0.02, 0.15);
+            if x < 0.5 {
+                return mix(blue, vec3(0.87), x * 2.0);
+            }
+            return mix(vec3(0.87), red, x * 2.0 - 1.0);
+        }
+        case 3u: {
+            return PLASMA_0 + x * (PLASMA_1 + x * (PLASMA_2 + x * (PLASMA_3 + x * (PLASMA_4 + x * (PLASMA_5 + x * PLASMA_6)))));
+        }
+        default: {
+            return VIRIDIS_0 + x * (VIRIDIS_1 + x * (VIRIDIS_2 + x * (VIRIDIS_3 + x * (VIRIDIS_4 + x * (VIRIDIS_5 + x * VIRIDIS_6)))));
+        }
+    }
+}
+
+fn hue(angle: f32) -> vec3<f32> {
+    let h = angle / 6.2831853 + 1.0;
+    return clamp(abs(fract(h + vec3(0.0, 2.0 / 3.0, 1.0 / 3.0)) * 6.0 - 3.0) - 1.0, vec3(0.0), vec3(1.0));
+}
+
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+    let value = sample_field(mesh.uv);
+    let span = max(settings.max - settings.min, 1e-6);
+    var srgb: vec3<f32>;
+    switch settings.mode {
+        case 1u: {
+            srgb = colormap((length(value) - settings.min) / span);
+        }
+        case 2u: {
+            let magnitude = clamp((length(value) - settings.min) / span, 0.0, 1.0);
+            srgb = hue(atan2(value.y, value.x)) * magnitude;
+        }
+        default: {
+            srgb = colormap((value.x - settings.min) / span);
+        }
+    }
+    return vec4(pow(max(srgb, vec3(0.0)), vec3(2.2)), 1.0);
+}
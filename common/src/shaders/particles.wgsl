@@ -0,0 +1,115 @@
+// Compute versions of the `kernels` loops, one invocation per particle (see gpu.rs)
+
+struct Particle {
+    position: vec2<f32>,
+    velocity: vec2<f32>,
+    mass: f32,
+}
+
+struct Output {
+    a: vec4<f32>,
+    b: vec4<f32>,
+}
+
+struct Params {
+    count: u32,
+    // Of the upload the particles came from
+    generation: u32,
+    // Kernel-specific arguments, in the order of the `GpuKernel` fields
+    args: vec3<f32>,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> particles: array<Particle>;
+@group(0) @binding(2) var<storage, read_write> outputs: array<Output>;
+
+const PI: f32 = 3.14159265358979;
+
+// The first invocation stamps the generation into the extra output after the last
+// particle's, bit for bit, so the readback says which upload it belongs to
+fn stamp_generation(i: u32) {
+    if i == 0u {
+        outputs[params.count] = Output(vec4<f32>(bitcast<f32>(params.generation), 0.0, 0.0, 0.0), vec4<f32>(0.0));
+    }
+}
+
+// a = (alignment, cohesion), b = (separation, count, 0)
+@compute @workgroup_size(64)
+fn steering(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    stamp_generation(i);
+    if i >= params.count {
+        return;
+    }
+    let radius = params.args.x;
+    let position = particles[i].position;
+    var alignment = vec2<f32>(0.0);
+    var cohesion = vec2<f32>(0.0);
+    var separation = vec2<f32>(0.0);
+    var count = 0.0;
+    for (var j = 0u; j < params.count; j++) {
+        let other = particles[j];
+        let diff = other.position - position;
+        let dist = length(diff);
+        if dist < radius && dist > 0.0 {
+            alignment += other.velocity;
+            cohesion += other.position;
+            separation -= diff / (dist * dist);
+            count += 1.0;
+        }
+    }
+    outputs[i] = Output(vec4<f32>(alignment, cohesion), vec4<f32>(separation, count, 0.0));
+}
+
+// a = (acceleration, 0, 0), b = (position, velocity) after a semi-implicit Euler step of dt
+@compute @workgroup_size(64)
+fn gravity(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    stamp_generation(i);
+    if i >= params.count {
+        return;
+    }
+    let g = params.args.x;
+    let softening2 = params.args.y * params.args.y;
+    let dt = params.args.z;
+    let body = particles[i];
+    var acceleration = vec2<f32>(0.0);
+    for (var j = 0u; j < params.count; j++) {
+        let other = particles[j];
+        let diff = other.position - body.position;
+        let d2 = dot(diff, diff);
+        if d2 > 0.0 {
+            let r2 = d2 + softening2;
+            acceleration += diff * (g * other.mass / (r2 * sqrt(r2)));
+        }
+    }
+    let velocity = body.velocity + acceleration * dt;
+    let position = body.position + velocity * dt;
+    outputs[i] = Output(vec4<f32>(acceleration, 0.0, 0.0), vec4<f32>(position, velocity));
+}
+
+// a = (density, 0, 0, 0)
+@compute @workgroup_size(64)
+fn sph_density(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    stamp_generation(i);
+    if i >= params.count {
+        return;
+    }
+    let mass = params.args.x;
+    let h = params.args.y;
+    let h2 = h * h;
+    let position = particles[i].position;
+    var density = 0.0;
+    for (var j = 0u; j < params.count; j++) {
+        let diff = particles[j].position - position;
+        let r2 = dot(diff, diff);
+        if r2 < h2 {
+            let w = h2 - r2;
+            density += w * w * w;
+        }
+    }
+    // 2D poly6 normalization, as in `kernels::poly6_factor`
+    let factor = mass * 4.0 / (PI * pow(h, 8.0));
+    outputs[i] = Output(vec4<f32>(density * factor, 0.0, 0.0, 0.0), vec4<f32>(0.0));
+}
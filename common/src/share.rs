@@ -0,0 +1,211 @@
+//! Shareable links that reproduce a configured simulation
+//!
+//! A chapter implements [`ShareSettings`] for its settings resource and adds
+//! [`SharePlugin`]. [`share_button`] copies a link with those settings and the
+//! [`SimulationSeed`] in its query string (`?vx=30&vy=30&g=-9.81&seed=42`); when the
+//! WASM build is opened from such a link, the values are applied before `Startup`
+//! so the simulation starts exactly as it was shared.
+//!
+//! Every parameter of the page, including ones no settings resource reads
+//! (`?count=500`), is also kept in the [`LaunchConfig`] resource for `Startup`
+//! systems, so the book can link to a pre-configured state. Native builds take the
+//! same query string from `--params "seed=42&count=500"`.
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+/// Applies the page's link parameters to `S` and the [`SimulationSeed`]
+pub struct SharePlugin<S: ShareSettings + Resource>(PhantomData<S>);
+
+impl<S: ShareSettings + Resource> Default for SharePlugin<S> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<S: ShareSettings + Resource> Plugin for SharePlugin<S> {
+    fn build(&self, app: &mut App) {
+        if !app.world().contains_resource::<LaunchConfig>() {
+            app.insert_resource(LaunchConfig(page_params().unwrap_or_default()));
+        }
+        app.init_resource::<SimulationSeed>()
+            .add_systems(PreStartup, apply_page_params::<S>);
+    }
+}
+
+/// Settings that can be written into and restored from a link
+pub trait ShareSettings {
+    fn write_params(&self, params: &mut ShareParams);
+    /// Keys missing from `params` or failing to parse should keep their current value
+    fn read_params(&mut self, params: &ShareParams);
+}
+
+/// For chapters where the seed is the only setting
+#[derive(Resource, Default)]
+pub struct NoSettings;
+
+impl ShareSettings for NoSettings {
+    fn write_params(&self, _params: &mut ShareParams) {}
+    fn read_params(&mut self, _params: &ShareParams) {}
+}
+
+/// Two settings resources in one link, e.g. a chapter's settings and the shared `Gravity`
+impl<A: ShareSettings, B: ShareSettings> ShareSettings for (A, B) {
+    fn write_params(&self, params: &mut ShareParams) {
+        self.0.write_params(params);
+        self.1.write_params(params);
+    }
+
+    fn read_params(&mut self, params: &ShareParams) {
+        self.0.read_params(params);
+        self.1.read_params(params);
+    }
+}
+
+/// Seed for everything random in a simulation, so a shared link spawns the same world
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimulationSeed(pub u64);
+
+impl Default for SimulationSeed {
+    fn default() -> Self {
+        Self(getrandom::u64().unwrap_or_default())
+    }
+}
+
+/// Parameters the simulation was opened with: the page's query string on the web, or
+/// `--params` natively; inserted by [`SharePlugin`]
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct LaunchConfig(pub ShareParams);
+
+impl LaunchConfig {
+    pub fn get<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.0.get(key)
+    }
+
+    /// Overwrite `target` if `key` was given and parses
+    pub fn read_into<T: FromStr>(&self, key: &str, target: &mut T) {
+        self.0.read_into(key, target);
+    }
+}
+
+/// Ordered key-value pairs of a link's query string. Values are written with
+/// `Display`, which gives the shortest string that parses back to the same float.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ShareParams {
+    pairs: Vec<(String, String)>,
+}
+
+impl ShareParams {
+    /// Parse `a=1&b=2`, with or without a leading `?` or `#`
+    pub fn parse(query: &str) -> Self {
+        let query = query.trim_start_matches(['?', '#']);
+        let pairs = query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        Self { pairs }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    pub fn set(&mut self, key: &str, value: impl Display) {
+        let value = value.to_string();
+        match self.pairs.iter_mut().find(|(existing, _)| existing == key) {
+            Some(pair) => pair.1 = value,
+            None => self.pairs.push((key.to_string(), value)),
+        }
+    }
+
+    pub fn get<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.pairs
+            .iter()
+            .find(|(existing, _)| existing == key)
+            .and_then(|(_, value)| value.parse().ok())
+    }
+
+    /// Overwrite `target` if `key` is present and parses
+    pub fn read_into<T: FromStr>(&self, key: &str, target: &mut T) {
+        if let Some(value) = self.get(key) {
+            *target = value;
+        }
+    }
+
+    pub fn to_query(&self) -> String {
+        self.pairs
+            .iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+}
+
+/// The parameters for `settings` and `seed`
+pub fn share_params<S: ShareSettings>(settings: &S, seed: &SimulationSeed) -> ShareParams {
+    let mut params = ShareParams::default();
+    settings.write_params(&mut params);
+    params.set("seed", seed.0);
+    params
+}
+
+/// Full link to this page with `settings` and `seed`; just the query string on native
+pub fn share_link<S: ShareSettings>(settings: &S, seed: &SimulationSeed) -> String {
+    format!("{}?{}", page_base_url(), share_params(settings, seed).to_query())
+}
+
+/// A "Copy share link" button for a chapter's settings window
+pub fn share_button<S: ShareSettings>(ui: &mut egui::Ui, settings: &S, seed: &SimulationSeed) {
+    let link = share_link(settings, seed);
+    if ui.button("Copy share link").on_hover_text(&link).clicked() {
+        ui.ctx().copy_text(link);
+    }
+}
+
+fn apply_page_params<S: ShareSettings + Resource>(
+    launch: Res<LaunchConfig>,
+    mut settings: ResMut<S>,
+    mut seed: ResMut<SimulationSeed>,
+) {
+    if launch.0.is_empty() {
+        return;
+    }
+    settings.read_params(&launch.0);
+    launch.read_into("seed", &mut seed.0);
+}
+
+#[cfg(target_arch = "wasm32")]
+fn page_params() -> Option<ShareParams> {
+    let location = web_sys::window()?.location();
+    // Accept parameters after a `#` too, for hosts that rewrite query strings
+    let search = location.search().ok().filter(|search| search.len() > 1);
+    let params = ShareParams::parse(&search.or_else(|| location.hash().ok())?);
+    (!params.is_empty()).then_some(params)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn page_params() -> Option<ShareParams> {
+    let args: Vec<String> = std::env::args().collect();
+    let index = args.iter().position(|arg| arg == "--params")?;
+    let params = ShareParams::parse(args.get(index + 1)?);
+    (!params.is_empty()).then_some(params)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn page_base_url() -> String {
+    web_sys::window()
+        .and_then(|window| {
+            let location = window.location();
+            Some(location.origin().ok()? + &location.pathname().ok()?)
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn page_base_url() -> String {
+    String::new()
+}
@@ -0,0 +1,93 @@
+//! Putting resting bodies to sleep
+//!
+//! A body whose speed and spin stay below [`SleepSettings`]' thresholds for
+//! `steps` physics steps in a row gets [`Sleeping`]: its velocity is zeroed, the
+//! integrators skip it, and [`resolve_collisions`](crate::collision::resolve_collisions)
+//! treats it as static and skips pairs of bodies that are all asleep or static. Big
+//! stacks and piles of grains then cost almost nothing once they settle.
+//!
+//! A sleeper wakes when something hits it hard enough to bounce, when it is dragged,
+//! or when a system gives it a velocity above the threshold. Knocking a body out from
+//! under a sleeping one doesn't wake it; remove [`Sleeping`] by hand for that, or put
+//! [`NeverSleep`] on bodies that must keep moving.
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+
+use crate::pick::Dragged;
+use crate::{AngularVelocity, Mass, Velocity};
+
+/// On a body that is resting and skipped by the integrators and collisions
+#[derive(Component, Default, Debug, Clone, Copy, Reflect)]
+#[reflect(Component, Default)]
+pub struct Sleeping;
+
+/// Keeps a body awake however slowly it moves
+#[derive(Component, Default, Debug, Clone, Copy, Reflect)]
+#[reflect(Component, Default)]
+pub struct NeverSleep;
+
+/// When bodies fall asleep
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct SleepSettings {
+    /// Bodies slower than this count as resting (m/s)
+    pub speed: f32,
+    /// ... and spinning slower than this (rad/s)
+    pub angular_speed: f32,
+    /// Steps in a row a body must rest before it sleeps
+    pub steps: u32,
+}
+
+impl Default for SleepSettings {
+    fn default() -> Self {
+        Self {
+            speed: 0.05,
+            angular_speed: 0.05,
+            steps: 60,
+        }
+    }
+}
+
+/// System to put bodies that have rested long enough to sleep, and wake sleepers that
+/// were dragged or set moving
+#[allow(clippy::type_complexity)]
+pub fn update_sleep(
+    mut bodies: Query<
+        (
+            Entity,
+            &mut Velocity,
+            Option<&mut AngularVelocity>,
+            Has<Sleeping>,
+            Has<Dragged>,
+        ),
+        (With<Mass>, Without<NeverSleep>),
+    >,
+    settings: Res<SleepSettings>,
+    mut resting_steps: Local<HashMap<Entity, u32>>,
+    mut commands: Commands,
+) {
+    let mut previous = std::mem::take(&mut *resting_steps);
+    for (entity, mut velocity, spin, sleeping, dragged) in &mut bodies {
+        let spin_speed = spin.as_ref().map_or(0.0, |spin| spin.0.abs());
+        let resting = velocity.0.length() < settings.speed && spin_speed < settings.angular_speed;
+        if sleeping {
+            if dragged || !resting {
+                commands.entity(entity).try_remove::<Sleeping>();
+            }
+            continue;
+        }
+        let steps = if resting && !dragged {
+            previous.remove(&entity).unwrap_or(0) + 1
+        } else {
+            0
+        };
+        if steps >= settings.steps {
+            velocity.0 = Vec2::ZERO;
+            if let Some(mut spin) = spin {
+                spin.0 = 0.0;
+            }
+            commands.entity(entity).try_insert(Sleeping);
+        } else if steps > 0 {
+            resting_steps.insert(entity, steps);
+        }
+    }
+}
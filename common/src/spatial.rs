@@ -0,0 +1,181 @@
+//! Uniform spatial hash for fast neighbor queries
+//!
+//! [`SpatialGrid`] buckets points into square cells, so finding everything within
+//! a radius only looks at the few cells the radius overlaps instead of every point.
+//! With [`SpatialGridPlugin`], the grid resource is rebuilt every frame from the
+//! `Position` (or, failing that, the `Transform`) of entities marked
+//! [`SpatialIndexed`].
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+
+use crate::Position;
+
+/// Rebuilds the [`SpatialGrid`] resource in `PreUpdate`
+pub struct SpatialGridPlugin {
+    pub cell_size: f32,
+}
+
+impl SpatialGridPlugin {
+    /// A cell size near the usual query radius works best
+    pub fn new(cell_size: f32) -> Self {
+        Self { cell_size }
+    }
+}
+
+impl Plugin for SpatialGridPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SpatialGrid::new(self.cell_size))
+            .add_systems(PreUpdate, rebuild_spatial_grid);
+    }
+}
+
+/// Include this entity in the [`SpatialGrid`]
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub struct SpatialIndexed;
+
+/// Points bucketed by cell. Indices returned by the queries refer to
+/// [`SpatialGrid::entries`], in the order the points were inserted.
+#[derive(Resource, Debug, Clone)]
+pub struct SpatialGrid {
+    cell_size: f32,
+    entries: Vec<(Entity, Vec2)>,
+    cells: HashMap<IVec2, Vec<u32>>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            entries: Vec::new(),
+            cells: HashMap::default(),
+        }
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    fn cell(&self, position: Vec2) -> IVec2 {
+        (position / self.cell_size).floor().as_ivec2()
+    }
+
+    /// Replace the contents with `points`
+    ///
+    /// Cells that stay occupied keep their buckets' capacity, so rebuilding every frame
+    /// for tens of thousands of points barely allocates.
+    pub fn rebuild(&mut self, points: impl IntoIterator<Item = (Entity, Vec2)>) {
+        self.entries.clear();
+        for bucket in self.cells.values_mut() {
+            bucket.clear();
+        }
+        for (entity, position) in points {
+            let index = self.entries.len() as u32;
+            let cell = self.cell(position);
+            self.entries.push((entity, position));
+            self.cells.entry(cell).or_default().push(index);
+        }
+        self.cells.retain(|_, bucket| !bucket.is_empty());
+    }
+
+    /// Every point, in insertion order
+    pub fn entries(&self) -> &[(Entity, Vec2)] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Indices of the points within `radius` of `position`, including any at `position` itself
+    pub fn query_radius_indices(&self, position: Vec2, radius: f32) -> impl Iterator<Item = usize> + '_ {
+        let min = self.cell(position - radius);
+        let max = self.cell(position + radius);
+        let radius2 = radius * radius;
+        (min.y..=max.y)
+            .flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .map(|&index| index as usize)
+            .filter(move |&index| self.entries[index].1.distance_squared(position) <= radius2)
+    }
+
+    /// [`SpatialGrid::query_radius_indices`] in a world that wraps around at the edges of
+    /// `period` (a torus), or doesn't with `None`. Each index comes with the point's
+    /// position moved by whole periods to the copy near `position`, so points just across
+    /// an edge count as neighbors. `radius` should be under half the period.
+    pub fn query_radius_wrapped(
+        &self,
+        position: Vec2,
+        radius: f32,
+        period: Option<Rect>,
+    ) -> impl Iterator<Item = (usize, Vec2)> + '_ {
+        let size = period.map(|period| period.size());
+        let reach = period.map(|period| period.inflate(radius));
+        (-1..=1)
+            .flat_map(|x| (-1..=1).map(move |y| IVec2::new(x, y)))
+            .filter_map(move |copy| {
+                if copy == IVec2::ZERO {
+                    return Some(Vec2::ZERO);
+                }
+                // Only the copies of the world whose edge is within reach
+                let shift = copy.as_vec2() * size?;
+                reach?.contains(position - shift).then_some(shift)
+            })
+            .flat_map(move |shift| {
+                self.query_radius_indices(position - shift, radius)
+                    .map(move |index| (index, self.entries[index].1 + shift))
+            })
+    }
+
+    /// The `k` points nearest `position` that `accept` lets through, nearest first, with
+    /// positions on the torus `period` as in [`SpatialGrid::query_radius_wrapped`]. The
+    /// search starts a cell wide and doubles until it has `k` or reaches `max_radius`,
+    /// so points in a crowd look at few cells and lone ones still find distant company.
+    pub fn query_nearest_wrapped(
+        &self,
+        position: Vec2,
+        k: usize,
+        max_radius: f32,
+        period: Option<Rect>,
+        accept: impl Fn(usize, Vec2) -> bool,
+    ) -> Vec<(usize, Vec2)> {
+        let mut radius = self.cell_size.min(max_radius);
+        loop {
+            let mut found: Vec<(usize, Vec2)> = self
+                .query_radius_wrapped(position, radius, period)
+                .filter(|&(index, point)| accept(index, point))
+                .collect();
+            // Everything within `radius` is in, so with `k` of them the nearest `k` are too
+            if found.len() >= k || radius >= max_radius {
+                found.sort_by(|(_, a), (_, b)| a.distance_squared(position).total_cmp(&b.distance_squared(position)));
+                found.truncate(k);
+                return found;
+            }
+            radius = (radius * 2.0).min(max_radius);
+        }
+    }
+
+    /// Entities and positions within `radius` of `position`
+    pub fn query_radius(&self, position: Vec2, radius: f32) -> impl Iterator<Item = (Entity, Vec2)> + '_ {
+        self.query_radius_indices(position, radius).map(|index| self.entries[index])
+    }
+}
+
+/// Refill the grid from the [`SpatialIndexed`] entities; add it to another schedule
+/// too if the grid must follow positions between fixed steps
+#[allow(clippy::type_complexity)]
+pub fn rebuild_spatial_grid(
+    mut grid: ResMut<SpatialGrid>,
+    entities: Query<(Entity, Option<&Position>, Option<&Transform>), With<SpatialIndexed>>,
+) {
+    grid.rebuild(entities.iter().filter_map(|(entity, position, transform)| {
+        let position = position
+            .map(|position| position.0)
+            .or(transform.map(|transform| transform.translation.truncate()))?;
+        Some((entity, position))
+    }));
+}
@@ -0,0 +1,194 @@
+//! Where to put N new bodies: uniformly, on a grid, in a Gaussian cluster or spread
+//! out by a Poisson-disk
+//!
+//! [`SpawnLayout::positions`] places `count` points inside a [`SpawnRegion`] (a
+//! rectangle or a circle) and leaves spawning to the chapter, which adds its own
+//! components at each point. All randomness comes from the `rng` passed in, so seeding
+//! it from `SimulationSeed` spawns the same world every time. [`SpawnRegion::sample`]
+//! draws a single uniform point, which is also handy for random velocities.
+use bevy::prelude::*;
+use rand::Rng;
+use std::f32::consts::TAU;
+
+/// Random draws before giving up: on a Gaussian point landing inside, or on a Poisson-disk
+/// point finding room next to an existing one
+const ATTEMPTS: usize = 30;
+
+/// Area new bodies are placed in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpawnRegion {
+    Rect(Rect),
+    Circle { center: Vec2, radius: f32 },
+}
+
+impl SpawnRegion {
+    /// Rectangle of `size` centered on the origin, e.g. the visible area
+    pub fn centered(size: Vec2) -> Self {
+        Self::Rect(Rect::from_center_size(Vec2::ZERO, size))
+    }
+
+    pub fn circle(center: Vec2, radius: f32) -> Self {
+        Self::Circle { center, radius }
+    }
+
+    pub fn center(&self) -> Vec2 {
+        match *self {
+            SpawnRegion::Rect(rect) => rect.center(),
+            SpawnRegion::Circle { center, .. } => center,
+        }
+    }
+
+    pub fn bounds(&self) -> Rect {
+        match *self {
+            SpawnRegion::Rect(rect) => rect,
+            SpawnRegion::Circle { center, radius } => Rect::from_center_half_size(center, Vec2::splat(radius)),
+        }
+    }
+
+    pub fn area(&self) -> f32 {
+        match *self {
+            SpawnRegion::Rect(rect) => rect.width() * rect.height(),
+            SpawnRegion::Circle { radius, .. } => std::f32::consts::PI * radius * radius,
+        }
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        match *self {
+            SpawnRegion::Rect(rect) => rect.contains(point),
+            SpawnRegion::Circle { center, radius } => center.distance_squared(point) <= radius * radius,
+        }
+    }
+
+    /// A uniformly random point inside the region
+    pub fn sample(&self, rng: &mut impl Rng) -> Vec2 {
+        match *self {
+            SpawnRegion::Rect(rect) => Vec2::new(
+                rect.min.x + rng.random::<f32>() * rect.width(),
+                rect.min.y + rng.random::<f32>() * rect.height(),
+            ),
+            SpawnRegion::Circle { center, radius } => {
+                // The square root keeps the density even out to the rim
+                let distance = radius * rng.random::<f32>().sqrt();
+                center + Vec2::from_angle(rng.random::<f32>() * TAU) * distance
+            }
+        }
+    }
+}
+
+/// How points are spread over a [`SpawnRegion`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpawnLayout {
+    /// Independent uniform points (an ideal gas)
+    Uniform,
+    /// Rows and columns as square as the region allows (a crystal, a cloth)
+    Grid,
+    /// Clustered about the region's center with standard deviation `spread`, redrawn if
+    /// outside (a galaxy, a drop of dye)
+    Gaussian { spread: f32 },
+    /// Random but never closer than `min_distance` (a liquid, a forest); fewer than
+    /// `count` points come back if no more fit
+    PoissonDisk { min_distance: f32 },
+}
+
+impl SpawnLayout {
+    /// `count` points inside `region` laid out this way
+    pub fn positions(&self, region: SpawnRegion, count: usize, rng: &mut impl Rng) -> Vec<Vec2> {
+        match *self {
+            SpawnLayout::Uniform => (0..count).map(|_| region.sample(rng)).collect(),
+            SpawnLayout::Grid => grid(region, count),
+            SpawnLayout::Gaussian { spread } => (0..count).map(|_| gaussian(region, spread, rng)).collect(),
+            SpawnLayout::PoissonDisk { min_distance } => poisson_disk(region, min_distance, count, rng),
+        }
+    }
+}
+
+/// Cell centers of the coarsest square grid with at least `count` cells inside the region
+fn grid(region: SpawnRegion, count: usize) -> Vec<Vec2> {
+    if count == 0 {
+        return Vec::new();
+    }
+    let bounds = region.bounds();
+    // Start from the spacing that would fit exactly, and shrink it until enough cells land inside
+    let mut spacing = (region.area() / count as f32).sqrt();
+    loop {
+        let cells = (bounds.size() / spacing).floor().max(Vec2::ONE).as_uvec2();
+        let used = cells.as_vec2() * spacing;
+        let first = bounds.center() - used / 2.0 + spacing / 2.0;
+        let points: Vec<Vec2> = (0..cells.y)
+            .flat_map(|row| (0..cells.x).map(move |column| first + UVec2::new(column, row).as_vec2() * spacing))
+            .filter(|point| region.contains(*point))
+            .take(count)
+            .collect();
+        if points.len() == count || spacing <= f32::EPSILON {
+            return points;
+        }
+        spacing *= 0.95;
+    }
+}
+
+/// A normally distributed point about the region's center (Box-Muller), kept inside
+fn gaussian(region: SpawnRegion, spread: f32, rng: &mut impl Rng) -> Vec2 {
+    for _ in 0..ATTEMPTS {
+        let radius = (-2.0 * (1.0 - rng.random::<f32>()).ln()).sqrt() * spread;
+        let point = region.center() + Vec2::from_angle(rng.random::<f32>() * TAU) * radius;
+        if region.contains(point) {
+            return point;
+        }
+    }
+    // Far tails of a wide spread in a small region
+    region.sample(rng)
+}
+
+/// Bridson's fast Poisson-disk sampling, stopping at `count` points
+fn poisson_disk(region: SpawnRegion, min_distance: f32, count: usize, rng: &mut impl Rng) -> Vec<Vec2> {
+    if count == 0 || min_distance <= 0.0 {
+        return (0..count).map(|_| region.sample(rng)).collect();
+    }
+    let bounds = region.bounds();
+    // Cells small enough to hold at most one point each
+    let cell_size = min_distance / std::f32::consts::SQRT_2;
+    let cells = (bounds.size() / cell_size).ceil().max(Vec2::ONE).as_uvec2();
+    let mut grid: Vec<Option<usize>> = vec![None; (cells.x * cells.y) as usize];
+    let cell_of = |point: Vec2| ((point - bounds.min) / cell_size).as_uvec2().min(cells - UVec2::ONE);
+
+    let mut points = vec![region.sample(rng)];
+    let cell = cell_of(points[0]);
+    grid[(cell.y * cells.x + cell.x) as usize] = Some(0);
+    let mut active = vec![0];
+    while points.len() < count && !active.is_empty() {
+        let slot = rng.random_range(0..active.len());
+        let origin = points[active[slot]];
+        let candidate = (0..ATTEMPTS).find_map(|_| {
+            let distance = min_distance * (1.0 + rng.random::<f32>());
+            let point = origin + Vec2::from_angle(rng.random::<f32>() * TAU) * distance;
+            if !region.contains(point) {
+                return None;
+            }
+            let cell = cell_of(point).as_ivec2();
+            let too_close = (-2..=2).any(|dy| {
+                (-2..=2).any(|dx| {
+                    let neighbor = cell + IVec2::new(dx, dy);
+                    if neighbor.cmplt(IVec2::ZERO).any() || neighbor.cmpge(cells.as_ivec2()).any() {
+                        return false;
+                    }
+                    grid[(neighbor.y as u32 * cells.x + neighbor.x as u32) as usize]
+                        .is_some_and(|other| points[other].distance_squared(point) < min_distance * min_distance)
+                })
+            });
+            (!too_close).then_some(point)
+        });
+        match candidate {
+            Some(point) => {
+                let cell = cell_of(point);
+                grid[(cell.y * cells.x + cell.x) as usize] = Some(points.len());
+                active.push(points.len());
+                points.push(point);
+            }
+            // Nothing fits around this point any more
+            None => {
+                active.swap_remove(slot);
+            }
+        }
+    }
+    points
+}
@@ -0,0 +1,167 @@
+//! 3D counterparts of the shared bodies, for gyroscopes, 3D orbits and 3D flocks
+//!
+//! [`Position3d`], [`Velocity3d`], [`Acceleration3d`] and [`Force3d`] mirror the 2D
+//! components, and [`AngularVelocity3d`] spins a body's `Transform` about an axis.
+//! [`Mass`] and `GravityScale`/`NoGravity` are shared with 2D. [`Physics3dPlugin`] runs
+//! their gravity, force, integration and projection systems in the same [`PhysicsSet`]s
+//! of the [`PhysicsStep`] schedule as the 2D ones, through the same [`Integrator`], so
+//! substeps, the rate slider and the integrator picker work unchanged.
+//!
+//! [`spawn_camera_3d`] is the 3D camera to go with them. Lit meshes need the `pbr`
+//! feature, which turns on Bevy's PBR renderer and adds a light to that camera.
+use bevy::prelude::*;
+
+use crate::forces::{GravityScale, NoGravity};
+use crate::integrator::{BodyState, Integrator};
+use crate::physics::{PhysicsPlugin, PhysicsSet, PhysicsStep};
+use crate::Mass;
+
+/// Runs the 3D force, integration and projection systems in [`PhysicsStep`], adding
+/// the shared [`PhysicsPlugin`] if the chapter hasn't
+pub struct Physics3dPlugin {
+    gravity: bool,
+}
+
+impl Default for Physics3dPlugin {
+    fn default() -> Self {
+        Self { gravity: true }
+    }
+}
+
+impl Physics3dPlugin {
+    pub fn without_gravity(mut self) -> Self {
+        self.gravity = false;
+        self
+    }
+}
+
+impl Plugin for Physics3dPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<PhysicsPlugin>() {
+            app.add_plugins(PhysicsPlugin::default());
+        }
+        app.init_resource::<Gravity3d>().add_systems(
+            PhysicsStep,
+            (
+                (apply_forces_3d, integrate_bodies_3d, integrate_spins)
+                    .chain()
+                    .in_set(PhysicsSet::Integrate),
+                project_positions_3d.in_set(PhysicsSet::Sync),
+            ),
+        );
+        if self.gravity {
+            app.add_systems(PhysicsStep, apply_gravity_3d.in_set(PhysicsSet::Forces));
+        }
+    }
+}
+
+/// Position of a 3D body (m)
+#[derive(Component, Default, Debug, Clone, Copy, Reflect)]
+#[reflect(Component, Default)]
+pub struct Position3d(pub Vec3);
+
+/// Velocity of a 3D body (m/s); bodies with one are moved by the 3D systems
+#[derive(Component, Default, Debug, Clone, Copy, Reflect)]
+#[reflect(Component, Default)]
+#[require(Force3d, Acceleration3d)]
+pub struct Velocity3d(pub Vec3);
+
+/// Acceleration of a 3D body (m/s²)
+#[derive(Component, Default, Debug, Clone, Copy, Reflect)]
+#[reflect(Component, Default)]
+pub struct Acceleration3d(pub Vec3);
+
+/// Net force (N) on a 3D body accumulated over a step; [`apply_forces_3d`] turns it
+/// into acceleration and clears it
+#[derive(Component, Default, Debug, Clone, Copy, Reflect)]
+#[reflect(Component, Default)]
+pub struct Force3d(pub Vec3);
+
+impl Force3d {
+    /// Torque of `force` applied at `lever_arm` from the center, τ = r × F
+    pub fn torque(lever_arm: Vec3, force: Vec3) -> Vec3 {
+        lever_arm.cross(force)
+    }
+}
+
+/// Spin about an axis (rad/s), its direction the axis and its length the rate
+#[derive(Component, Default, Debug, Clone, Copy, Reflect)]
+#[reflect(Component, Default)]
+pub struct AngularVelocity3d(pub Vec3);
+
+/// Uniform gravitational acceleration for the 3D bodies (m/s²), down the y axis by default
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct Gravity3d(pub Vec3);
+
+impl Default for Gravity3d {
+    fn default() -> Self {
+        Self(Vec3::NEG_Y * crate::constants::GRAVITY)
+    }
+}
+
+/// System to add the weight of every 3D body to its force
+pub fn apply_gravity_3d(
+    mut bodies: Query<(&Mass, Option<&GravityScale>, &mut Force3d), Without<NoGravity>>,
+    gravity: Res<Gravity3d>,
+) {
+    for (mass, scale, mut force) in &mut bodies {
+        let scale = scale.map_or(1.0, |scale| scale.0);
+        force.0 += gravity.0 * mass.0 * scale;
+    }
+}
+
+/// System to set acceleration from the accumulated force, a = F / m, and reset the force
+pub fn apply_forces_3d(mut bodies: Query<(&mut Force3d, &Mass, &mut Acceleration3d)>) {
+    for (mut force, mass, mut acceleration) in &mut bodies {
+        acceleration.0 = force.0 / mass.0;
+        force.0 = Vec3::ZERO;
+    }
+}
+
+/// System to advance 3D bodies over the step with the chosen [`Integrator`], holding the
+/// acceleration constant: gravity, the only shared 3D force, doesn't depend on the state
+pub fn integrate_bodies_3d(
+    mut bodies: Query<(&mut Position3d, &mut Velocity3d, &Acceleration3d)>,
+    integrator: Res<Integrator>,
+    time: Res<Time>,
+) {
+    let t = time.elapsed_secs();
+    for (mut position, mut velocity, acceleration) in &mut bodies {
+        let a = acceleration.0;
+        let next = integrator.step(BodyState::new(position.0, velocity.0), t, time.delta_secs(), |_, _| a);
+        position.0 = next.position;
+        velocity.0 = next.velocity;
+    }
+}
+
+/// System to turn each spinning body's `Transform` by its [`AngularVelocity3d`] over the step
+pub fn integrate_spins(mut bodies: Query<(&mut Transform, &AngularVelocity3d)>, time: Res<Time>) {
+    for (mut transform, angular_velocity) in &mut bodies {
+        let turn = Quat::from_scaled_axis(angular_velocity.0 * time.delta_secs());
+        transform.rotation = (turn * transform.rotation).normalize();
+    }
+}
+
+/// System to copy [`Position3d`] into Transform.translation
+pub fn project_positions_3d(mut bodies: Query<(&mut Transform, &Position3d)>) {
+    for (mut transform, position) in &mut bodies {
+        transform.translation = position.0;
+    }
+}
+
+/// Perspective camera 20 m back along z looking at the origin, lit from over its
+/// shoulder when the `pbr` feature is on
+pub fn spawn_camera_3d(mut commands: Commands) {
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 0.0, 20.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+    #[cfg(feature = "pbr")]
+    commands.spawn((
+        DirectionalLight {
+            shadows_enabled: true,
+            ..default()
+        },
+        Transform::from_xyz(5.0, 10.0, 20.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+}
@@ -0,0 +1,206 @@
+//! Physics rate, adjustable while the simulation runs
+//!
+//! Physics runs in `FixedUpdate` at [`PhysicsRate`], independently of how often frames
+//! are drawn. [`physics_rate_slider`] lets the reader change the rate from a chapter's
+//! panel, to see how the step size affects accuracy and stability. Code that steps or
+//! samples the physics on its own (trajectory predictions, recorders) should use
+//! [`PhysicsRate::dt`] so it agrees with the running simulation.
+//!
+//! [`Substeps`] splits each of those steps into smaller ones without changing the
+//! fixed rate, for stiff springs and fast bodies that a whole step would blow up.
+//!
+//! [`PhysicsTime`] is the shared physics' own clock. Pausing it or scaling it stops or
+//! slows only the physics: frames, egui and UI animations keep real time. Slow motion
+//! runs fewer ticks and fast motion more, so the step size never changes.
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+/// Keeps `Time<Fixed>` at the chosen [`PhysicsRate`]
+pub struct PhysicsRatePlugin;
+
+impl Plugin for PhysicsRatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PhysicsRate>()
+            .add_systems(PreStartup, apply_physics_rate)
+            .add_systems(First, apply_physics_rate.run_if(resource_changed::<PhysicsRate>));
+    }
+}
+
+/// Physics steps per second
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct PhysicsRate {
+    pub hz: f64,
+}
+
+impl PhysicsRate {
+    /// Rates offered by [`physics_rate_slider`]
+    pub const RANGE: RangeInclusive<f64> = 30.0..=960.0;
+
+    pub fn new(hz: f64) -> Self {
+        Self {
+            hz: hz.clamp(*Self::RANGE.start(), *Self::RANGE.end()),
+        }
+    }
+
+    /// Seconds per physics step
+    pub fn dt(&self) -> f32 {
+        (1.0 / self.hz) as f32
+    }
+
+    /// Number of whole steps in `seconds` of simulated time
+    pub fn steps_in(&self, seconds: f32) -> usize {
+        (seconds as f64 * self.hz).round() as usize
+    }
+}
+
+impl Default for PhysicsRate {
+    /// Bevy's default fixed rate
+    fn default() -> Self {
+        Self { hz: 64.0 }
+    }
+}
+
+/// Integration steps per physics tick; the shared physics runs this many times per
+/// `FixedUpdate` with the tick's time split evenly between them
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Substeps(pub u32);
+
+impl Default for Substeps {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+impl Substeps {
+    /// Seconds per substep at `rate`
+    pub fn dt(&self, rate: &PhysicsRate) -> f32 {
+        rate.dt() / self.0.max(1) as f32
+    }
+}
+
+/// Clock of the shared physics, separate from the frame clock; inserted by `PhysicsPlugin`
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct PhysicsTime {
+    elapsed: Duration,
+    scale: f32,
+    paused: bool,
+    /// Ticks asked for with [`PhysicsTime::step`] while paused
+    queued_steps: u32,
+    /// Fraction of a tick carried over by the scale
+    carry: f32,
+    /// Ticks run in the latest `FixedUpdate`
+    last_ticks: u32,
+}
+
+impl Default for PhysicsTime {
+    fn default() -> Self {
+        Self {
+            elapsed: Duration::ZERO,
+            scale: 1.0,
+            paused: false,
+            queued_steps: 0,
+            carry: 0.0,
+            last_ticks: 0,
+        }
+    }
+}
+
+impl PhysicsTime {
+    /// Simulated time so far
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn elapsed_secs(&self) -> f32 {
+        self.elapsed.as_secs_f32()
+    }
+
+    /// Physics ticks per `FixedUpdate`, on average; below 1 is slow motion
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale.max(0.0);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn unpause(&mut self) {
+        self.paused = false;
+        self.queued_steps = 0;
+    }
+
+    /// Run exactly one tick at the next `FixedUpdate`, while paused
+    pub fn step(&mut self) {
+        if self.paused {
+            self.queued_steps += 1;
+        }
+    }
+
+    /// Whether the physics ticked in the latest `FixedUpdate`
+    pub fn stepped(&self) -> bool {
+        self.last_ticks > 0
+    }
+
+    /// `seconds` of frame time turned into simulated time: scaled, and none while paused
+    pub fn scaled(&self, seconds: f32) -> f32 {
+        if self.paused {
+            0.0
+        } else {
+            seconds * self.scale
+        }
+    }
+
+    /// Ticks to run in this `FixedUpdate`
+    pub(crate) fn take_ticks(&mut self) -> u32 {
+        let ticks = if self.paused {
+            std::mem::take(&mut self.queued_steps)
+        } else {
+            self.carry += self.scale;
+            let ticks = self.carry.floor();
+            self.carry -= ticks;
+            ticks as u32
+        };
+        self.last_ticks = ticks;
+        ticks
+    }
+
+    pub(crate) fn advance_by(&mut self, delta: Duration) {
+        self.elapsed += delta;
+    }
+}
+
+/// Run condition: the shared physics ticked in this `FixedUpdate` (always true
+/// without a [`PhysicsTime`]), for systems that record or measure each step
+pub fn physics_stepped(time: Option<Res<PhysicsTime>>) -> bool {
+    time.is_none_or(|time| time.stepped())
+}
+
+fn apply_physics_rate(rate: Res<PhysicsRate>, mut time: ResMut<Time<Fixed>>) {
+    time.set_timestep_hz(rate.hz);
+}
+
+/// Logarithmic slider for the physics rate, with the step size alongside
+pub fn physics_rate_slider(ui: &mut egui::Ui, rate: &mut PhysicsRate) -> egui::Response {
+    ui.horizontal(|ui| {
+        let response = ui.add(
+            egui::Slider::new(&mut rate.hz, PhysicsRate::RANGE)
+                .logarithmic(true)
+                .integer()
+                .text("physics Hz"),
+        );
+        ui.label(format!("dt = {:.2} ms", rate.dt() * 1000.0));
+        response
+    })
+    .inner
+}
@@ -0,0 +1,124 @@
+//! Fading trails of where bodies have been
+//!
+//! Give a body a [`Trail`] and add [`TrailPlugin`]: after transforms are propagated each
+//! frame the body's position is recorded whenever it has moved [`Trail::spacing`] from
+//! the last recorded point, keeping the newest [`Trail::max_points`], and the points are
+//! drawn as one gizmo polyline that fades out towards the oldest. Nothing is spawned
+//! per point, so long orbits and thousands of trails stay cheap. Trails are still
+//! recorded without a renderer (headless runs), just not drawn.
+use std::collections::VecDeque;
+
+use bevy::gizmos::config::GizmoConfigStore;
+use bevy::prelude::*;
+use bevy::transform::TransformSystems;
+
+/// Records and draws every [`Trail`]
+pub struct TrailPlugin;
+
+impl Plugin for TrailPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            (
+                record_trails,
+                draw_trails.run_if(resource_exists::<GizmoConfigStore>),
+            )
+                .chain()
+                .after(TransformSystems::Propagate),
+        );
+    }
+}
+
+/// Past positions of a body, drawn as a polyline fading from `color` to transparent
+#[derive(Component, Debug, Clone)]
+pub struct Trail {
+    /// Most points kept; the oldest are dropped first
+    pub max_points: usize,
+    /// Distance the body moves before another point is recorded, in world units
+    pub spacing: f32,
+    pub color: Color,
+    /// A jump further than this, like wrapping round to the other edge, starts the trail
+    /// over instead of drawing a line across the screen
+    pub break_distance: Option<f32>,
+    points: VecDeque<Vec2>,
+}
+
+impl Default for Trail {
+    fn default() -> Self {
+        Self::new(200, 0.1)
+    }
+}
+
+impl Trail {
+    pub fn new(max_points: usize, spacing: f32) -> Self {
+        Self {
+            max_points,
+            spacing,
+            color: Color::WHITE,
+            break_distance: None,
+            points: VecDeque::with_capacity(max_points),
+        }
+    }
+
+    pub fn with_color(mut self, color: impl Into<Color>) -> Self {
+        self.color = color.into();
+        self
+    }
+
+    pub fn with_break_distance(mut self, distance: f32) -> Self {
+        self.break_distance = Some(distance);
+        self
+    }
+
+    /// Recorded points, oldest first
+    pub fn points(&self) -> impl Iterator<Item = Vec2> + '_ {
+        self.points.iter().copied()
+    }
+
+    /// Forget the recorded path, e.g. when the body is reset
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    /// Record `position` if it is far enough from the last point
+    pub fn push(&mut self, position: Vec2) {
+        if let (Some(last), Some(limit)) = (self.points.back(), self.break_distance) {
+            if last.distance_squared(position) > limit * limit {
+                self.points.clear();
+            }
+        }
+        let far_enough = self
+            .points
+            .back()
+            .is_none_or(|last| last.distance_squared(position) >= self.spacing * self.spacing);
+        if !far_enough {
+            return;
+        }
+        self.points.push_back(position);
+        while self.points.len() > self.max_points {
+            self.points.pop_front();
+        }
+    }
+}
+
+/// System to add each body's current position to its [`Trail`]
+pub fn record_trails(mut trails: Query<(&mut Trail, &GlobalTransform)>) {
+    for (mut trail, transform) in &mut trails {
+        trail.push(transform.translation().truncate());
+    }
+}
+
+/// System to draw each [`Trail`] from transparent at its oldest point to its color at the body
+pub fn draw_trails(mut gizmos: Gizmos, trails: Query<&Trail>) {
+    for trail in &trails {
+        let count = trail.points.len();
+        if count < 2 {
+            continue;
+        }
+        let alpha = trail.color.alpha();
+        gizmos.linestrip_gradient_2d(trail.points().enumerate().map(|(index, point)| {
+            let fade = (index + 1) as f32 / count as f32;
+            (point, trail.color.with_alpha(alpha * fade))
+        }));
+    }
+}
@@ -0,0 +1,243 @@
+//! Guided tutorial overlays that turn a simulation into an interactive walkthrough
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+
+/// Adds the tutorial overlay and the system that advances it
+pub struct TutorialPlugin;
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        crate::add_egui(app);
+        app.init_resource::<Tutorial>()
+            .add_systems(Update, (toggle_tutorial, advance_tutorial).chain())
+            .add_systems(EguiPrimaryContextPass, tutorial_overlay);
+    }
+}
+
+/// Condition checked against the world to decide whether a step is complete
+pub type StepCondition = Box<dyn Fn(&World) -> bool + Send + Sync>;
+
+/// What a tutorial step points the student at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Highlight {
+    /// A named UI control, see [`Tutorial::highlight_control`]
+    Control(&'static str),
+    /// An entity in the world, circled on screen
+    Entity(Entity),
+}
+
+/// How a tutorial step moves on to the next one
+pub enum Advance {
+    /// The student presses "Next"
+    Manual,
+    /// The step completes as soon as the condition holds
+    When(StepCondition),
+}
+
+/// A single step of a tutorial
+pub struct TutorialStep {
+    pub title: String,
+    pub text: String,
+    pub highlight: Option<Highlight>,
+    pub advance: Advance,
+}
+
+impl TutorialStep {
+    pub fn new(title: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            text: text.into(),
+            highlight: None,
+            advance: Advance::Manual,
+        }
+    }
+
+    /// Highlight a named UI control while this step is shown
+    pub fn highlight_control(mut self, name: &'static str) -> Self {
+        self.highlight = Some(Highlight::Control(name));
+        self
+    }
+
+    /// Circle an entity while this step is shown
+    pub fn highlight_entity(mut self, entity: Entity) -> Self {
+        self.highlight = Some(Highlight::Entity(entity));
+        self
+    }
+
+    /// Wait for a condition (e.g. "the projectile was launched") instead of a button press
+    pub fn wait_until(mut self, condition: impl Fn(&World) -> bool + Send + Sync + 'static) -> Self {
+        self.advance = Advance::When(Box::new(condition));
+        self
+    }
+}
+
+/// The sequence of steps registered by a chapter and the student's progress through it
+#[derive(Resource, Default)]
+pub struct Tutorial {
+    steps: Vec<TutorialStep>,
+    current: usize,
+    active: bool,
+}
+
+impl Tutorial {
+    /// Create a tutorial that starts active
+    pub fn new(steps: Vec<TutorialStep>) -> Self {
+        Self {
+            active: !steps.is_empty(),
+            steps,
+            current: 0,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active && self.current < self.steps.len()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current >= self.steps.len()
+    }
+
+    pub fn current_step(&self) -> Option<&TutorialStep> {
+        if self.active {
+            self.steps.get(self.current)
+        } else {
+            None
+        }
+    }
+
+    /// Start the tutorial again from the first step
+    pub fn restart(&mut self) {
+        self.current = 0;
+        self.active = !self.steps.is_empty();
+    }
+
+    pub fn next(&mut self) {
+        self.current = (self.current + 1).min(self.steps.len());
+    }
+
+    pub fn back(&mut self) {
+        self.current = self.current.saturating_sub(1);
+    }
+
+    /// Is the named control highlighted by the current step?
+    pub fn is_highlighted(&self, name: &str) -> bool {
+        matches!(
+            self.current_step().and_then(|step| step.highlight),
+            Some(Highlight::Control(control)) if control == name
+        )
+    }
+
+    /// Draw a highlight frame around a control if the current step points at it.
+    /// Call this from a simulation's egui code with the control's response.
+    pub fn highlight_control(&self, ui: &egui::Ui, name: &str, response: &egui::Response) {
+        if self.is_highlighted(name) {
+            ui.painter().rect_stroke(
+                response.rect.expand(3.0),
+                4.0,
+                egui::Stroke::new(2.0, HIGHLIGHT_COLOR),
+                egui::StrokeKind::Outside,
+            );
+        }
+    }
+}
+
+const HIGHLIGHT_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 200, 0);
+
+/// F1 shows or hides the tutorial
+fn toggle_tutorial(keys: Res<ButtonInput<KeyCode>>, mut tutorial: ResMut<Tutorial>) {
+    if keys.just_pressed(KeyCode::F1) {
+        if tutorial.is_finished() {
+            tutorial.restart();
+        } else {
+            tutorial.active = !tutorial.active;
+        }
+    }
+}
+
+/// Move to the next step once the current step's condition holds
+fn advance_tutorial(world: &mut World) {
+    world.resource_scope(|world, mut tutorial: Mut<Tutorial>| {
+        let complete = match tutorial.current_step().map(|step| &step.advance) {
+            Some(Advance::When(condition)) => condition(world),
+            _ => false,
+        };
+        if complete {
+            tutorial.next();
+        }
+    });
+}
+
+fn tutorial_overlay(
+    mut contexts: EguiContexts,
+    mut tutorial: ResMut<Tutorial>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    transform_query: Query<&GlobalTransform>,
+) -> Result {
+    if !tutorial.is_active() {
+        return Ok(());
+    }
+    let ctx = contexts.ctx_mut()?;
+    let step_count = tutorial.steps.len();
+    let current = tutorial.current;
+    let Some(step) = tutorial.current_step() else {
+        return Ok(());
+    };
+
+    // Circle the highlighted entity in screen space
+    if let Some(Highlight::Entity(entity)) = step.highlight {
+        if let (Ok((camera, camera_transform)), Ok(target)) =
+            (camera_query.single(), transform_query.get(entity))
+        {
+            if let Ok(screen_pos) = camera.world_to_viewport(camera_transform, target.translation()) {
+                let painter = ctx.layer_painter(egui::LayerId::new(
+                    egui::Order::Foreground,
+                    egui::Id::new("tutorial_highlight"),
+                ));
+                painter.circle_stroke(
+                    egui::pos2(screen_pos.x, screen_pos.y),
+                    24.0,
+                    egui::Stroke::new(3.0, HIGHLIGHT_COLOR),
+                );
+            }
+        }
+    }
+
+    let title = step.title.clone();
+    let text = step.text.clone();
+    let manual = matches!(step.advance, Advance::Manual);
+    let mut next = false;
+    let mut back = false;
+    let mut skip = false;
+
+    egui::Window::new("Tutorial")
+        .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -20.0])
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(format!("Step {} of {}", current + 1, step_count));
+            ui.heading(title);
+            ui.label(text);
+            ui.separator();
+            ui.horizontal(|ui| {
+                back = ui.add_enabled(current > 0, egui::Button::new("Back")).clicked();
+                if manual {
+                    next = ui.button("Next").clicked();
+                } else {
+                    ui.label("Waiting for you to try it...");
+                }
+                skip = ui.button("Skip tutorial").clicked();
+            });
+            ui.small("Press F1 to show or hide the tutorial");
+        });
+
+    if next {
+        tutorial.next();
+    }
+    if back {
+        tutorial.back();
+    }
+    if skip {
+        tutorial.active = false;
+    }
+    Ok(())
+}
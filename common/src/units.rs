@@ -0,0 +1,271 @@
+//! Physical quantities with their dimensions checked at compile time
+//!
+//! A [`Quantity`] is a plain `f32` (or `Vec2` for vector quantities) tagged with a
+//! dimension from [`dim`]. Adding or subtracting quantities only compiles when the
+//! dimensions match, and multiplying or dividing them gives the dimension physics
+//! says it should: a velocity times a time is a length, a length divided by a time is
+//! a velocity, and adding a velocity to an acceleration doesn't build. Values are in
+//! SI units. The wrapper has no runtime cost; take `.value` out at the edges, where
+//! the numbers go into components.
+//!
+//! Only the products the chapters need are defined. A new formula that needs another
+//! one adds a line to the `products!` list below.
+use bevy::prelude::*;
+use std::marker::PhantomData;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+/// Dimension markers
+pub mod dim {
+    macro_rules! dimensions {
+        ($($(#[$doc:meta])* $name:ident),* $(,)?) => {
+            $(
+                $(#[$doc])*
+                #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+                pub struct $name;
+            )*
+        };
+    }
+
+    dimensions! {
+        /// m
+        Length,
+        /// m²
+        Area,
+        /// s
+        Time,
+        /// kg
+        Mass,
+        /// m/s
+        Velocity,
+        /// m²/s², e.g. energy per unit mass
+        VelocitySquared,
+        /// m/s²
+        Acceleration,
+        /// N = kg m/s²
+        Force,
+        /// kg m/s
+        Momentum,
+        /// J = N m
+        Energy,
+        /// N/m
+        SpringConstant,
+    }
+}
+
+/// A value of dimension `D`, a scalar by default or a `Vec2`
+#[derive(Debug)]
+pub struct Quantity<D, V = f32> {
+    pub value: V,
+    _dimension: PhantomData<D>,
+}
+
+impl<D, V> Quantity<D, V> {
+    pub const fn new(value: V) -> Self {
+        Self {
+            value,
+            _dimension: PhantomData,
+        }
+    }
+}
+
+impl<D> Quantity<D, Vec2> {
+    pub fn x(&self) -> Quantity<D> {
+        Quantity::new(self.value.x)
+    }
+
+    pub fn y(&self) -> Quantity<D> {
+        Quantity::new(self.value.y)
+    }
+
+    /// Magnitude of the vector, in the same dimension
+    pub fn length(&self) -> Quantity<D> {
+        Quantity::new(self.value.length())
+    }
+}
+
+pub type Length<V = f32> = Quantity<dim::Length, V>;
+pub type Area = Quantity<dim::Area>;
+pub type Time = Quantity<dim::Time>;
+pub type Mass = Quantity<dim::Mass>;
+pub type Velocity<V = f32> = Quantity<dim::Velocity, V>;
+pub type VelocitySquared = Quantity<dim::VelocitySquared>;
+pub type Acceleration<V = f32> = Quantity<dim::Acceleration, V>;
+pub type Force<V = f32> = Quantity<dim::Force, V>;
+pub type Momentum<V = f32> = Quantity<dim::Momentum, V>;
+pub type Energy = Quantity<dim::Energy>;
+pub type SpringConstant = Quantity<dim::SpringConstant>;
+
+// Unit names for the same quantities, for code that reads better with them
+pub type Meters<V = f32> = Length<V>;
+pub type Seconds = Time;
+pub type Kilograms = Mass;
+pub type MetersPerSecond<V = f32> = Velocity<V>;
+pub type Newtons<V = f32> = Force<V>;
+pub type Joules = Energy;
+
+// Manual impls, since derives would require `D` itself to be Clone, PartialEq, ...
+impl<D, V: Clone> Clone for Quantity<D, V> {
+    fn clone(&self) -> Self {
+        Self::new(self.value.clone())
+    }
+}
+
+impl<D, V: Copy> Copy for Quantity<D, V> {}
+
+impl<D, V: PartialEq> PartialEq for Quantity<D, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<D> PartialOrd for Quantity<D> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<D, V: Default> Default for Quantity<D, V> {
+    fn default() -> Self {
+        Self::new(V::default())
+    }
+}
+
+impl<D, V: Add<Output = V>> Add for Quantity<D, V> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.value + other.value)
+    }
+}
+
+impl<D, V: AddAssign> AddAssign for Quantity<D, V> {
+    fn add_assign(&mut self, other: Self) {
+        self.value += other.value;
+    }
+}
+
+impl<D, V: Sub<Output = V>> Sub for Quantity<D, V> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.value - other.value)
+    }
+}
+
+impl<D, V: SubAssign> SubAssign for Quantity<D, V> {
+    fn sub_assign(&mut self, other: Self) {
+        self.value -= other.value;
+    }
+}
+
+impl<D, V: Neg<Output = V>> Neg for Quantity<D, V> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.value)
+    }
+}
+
+/// Scaling by a pure number keeps the dimension
+impl<D, V: Mul<f32, Output = V>> Mul<f32> for Quantity<D, V> {
+    type Output = Self;
+
+    fn mul(self, factor: f32) -> Self {
+        Self::new(self.value * factor)
+    }
+}
+
+impl<D, V: Div<f32, Output = V>> Div<f32> for Quantity<D, V> {
+    type Output = Self;
+
+    fn div(self, divisor: f32) -> Self {
+        Self::new(self.value / divisor)
+    }
+}
+
+/// `A * B = C`, plus `B * A = C`, `C / B = A` and `C / A = B`. The left operand
+/// may be a vector; the right one is a scalar.
+macro_rules! products {
+    ($($a:ident * $b:ident = $c:ident;)*) => {
+        $(
+            impl<V: Mul<f32, Output = V>> Mul<Quantity<dim::$b>> for Quantity<dim::$a, V> {
+                type Output = Quantity<dim::$c, V>;
+
+                fn mul(self, other: Quantity<dim::$b>) -> Self::Output {
+                    Quantity::new(self.value * other.value)
+                }
+            }
+
+            impl<V: Mul<f32, Output = V>> Mul<Quantity<dim::$a>> for Quantity<dim::$b, V> {
+                type Output = Quantity<dim::$c, V>;
+
+                fn mul(self, other: Quantity<dim::$a>) -> Self::Output {
+                    Quantity::new(self.value * other.value)
+                }
+            }
+
+            impl<V: Div<f32, Output = V>> Div<Quantity<dim::$b>> for Quantity<dim::$c, V> {
+                type Output = Quantity<dim::$a, V>;
+
+                fn div(self, other: Quantity<dim::$b>) -> Self::Output {
+                    Quantity::new(self.value / other.value)
+                }
+            }
+
+            impl<V: Div<f32, Output = V>> Div<Quantity<dim::$a>> for Quantity<dim::$c, V> {
+                type Output = Quantity<dim::$b, V>;
+
+                fn div(self, other: Quantity<dim::$a>) -> Self::Output {
+                    Quantity::new(self.value / other.value)
+                }
+            }
+        )*
+    };
+}
+
+/// `A * A = C` and `C / A = A`
+macro_rules! squares {
+    ($($a:ident * $a2:ident = $c:ident;)*) => {
+        $(
+            impl Mul<Quantity<dim::$a>> for Quantity<dim::$a> {
+                type Output = Quantity<dim::$c>;
+
+                fn mul(self, other: Quantity<dim::$a>) -> Self::Output {
+                    Quantity::new(self.value * other.value)
+                }
+            }
+
+            impl Div<Quantity<dim::$a>> for Quantity<dim::$c> {
+                type Output = Quantity<dim::$a>;
+
+                fn div(self, other: Quantity<dim::$a>) -> Self::Output {
+                    Quantity::new(self.value / other.value)
+                }
+            }
+        )*
+    };
+}
+
+products! {
+    Velocity * Time = Length;
+    Acceleration * Time = Velocity;
+    Acceleration * Length = VelocitySquared;
+    Mass * Acceleration = Force;
+    Mass * Velocity = Momentum;
+    Mass * VelocitySquared = Energy;
+    Force * Time = Momentum;
+    Force * Length = Energy;
+    SpringConstant * Length = Force;
+}
+
+squares! {
+    Length * Length = Area;
+    Velocity * Velocity = VelocitySquared;
+}
+
+impl Velocity<Vec2> {
+    /// v·v, e.g. for kinetic energy
+    pub fn length_squared(&self) -> VelocitySquared {
+        Quantity::new(self.value.length_squared())
+    }
+}
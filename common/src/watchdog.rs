@@ -0,0 +1,183 @@
+//! Watches the energy and momentum of closed systems for numerical drift
+//!
+//! Simulations write their totals into [`ConservedQuantities`] each step. The first
+//! sample after [`EnergyWatchdog::reset`] becomes the baseline, and a [`DriftWarning`]
+//! is sent (and shown on screen) when a total wanders further than the tolerance.
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+
+/// Adds drift monitoring for [`ConservedQuantities`] and the on-screen warning
+pub struct EnergyWatchdogPlugin;
+
+impl Plugin for EnergyWatchdogPlugin {
+    fn build(&self, app: &mut App) {
+        crate::add_egui(app);
+        app.init_resource::<EnergyWatchdog>()
+            .init_resource::<ConservedQuantities>()
+            .add_message::<DriftWarning>()
+            .add_systems(
+                FixedLast,
+                check_drift.run_if(resource_changed::<ConservedQuantities>),
+            )
+            .add_systems(EguiPrimaryContextPass, drift_warning_panel);
+    }
+}
+
+/// Totals for the current step, written by the simulation
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq)]
+pub struct ConservedQuantities {
+    pub energy: f32,
+    pub momentum: Vec2,
+}
+
+/// Which total drifted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriftQuantity {
+    Energy,
+    Momentum,
+}
+
+/// Sent once when a conserved quantity drifts past its tolerance
+#[derive(Message, Debug, Clone, Copy)]
+pub struct DriftWarning {
+    pub quantity: DriftQuantity,
+    /// Drift relative to the baseline magnitude
+    pub relative_drift: f32,
+}
+
+#[derive(Resource, Debug)]
+pub struct EnergyWatchdog {
+    /// Largest allowed relative change in total energy
+    pub energy_tolerance: f32,
+    /// Largest allowed relative change in total momentum
+    pub momentum_tolerance: f32,
+    /// Momentum is only conserved without external forces such as gravity
+    pub watch_momentum: bool,
+    baseline: Option<ConservedQuantities>,
+    energy_drift: f32,
+    momentum_drift: f32,
+    warned: Vec<DriftQuantity>,
+}
+
+impl Default for EnergyWatchdog {
+    fn default() -> Self {
+        Self {
+            energy_tolerance: 0.01,
+            momentum_tolerance: 0.01,
+            watch_momentum: true,
+            baseline: None,
+            energy_drift: 0.0,
+            momentum_drift: 0.0,
+            warned: Vec::new(),
+        }
+    }
+}
+
+impl EnergyWatchdog {
+    /// A watchdog for systems under external forces, where only energy is conserved
+    pub fn energy_only() -> Self {
+        Self {
+            watch_momentum: false,
+            ..default()
+        }
+    }
+
+    /// Take the next sample as the new baseline, e.g. after a launch or reset
+    pub fn reset(&mut self) {
+        self.baseline = None;
+        self.energy_drift = 0.0;
+        self.momentum_drift = 0.0;
+        self.warned.clear();
+    }
+
+    /// Relative energy change since the baseline
+    pub fn energy_drift(&self) -> f32 {
+        self.energy_drift
+    }
+
+    /// Relative momentum change since the baseline
+    pub fn momentum_drift(&self) -> f32 {
+        self.momentum_drift
+    }
+
+    pub fn is_drifting(&self) -> bool {
+        !self.warned.is_empty()
+    }
+}
+
+/// Relative change, falling back to the absolute change for near-zero baselines
+fn relative_change(baseline: f32, current: f32) -> f32 {
+    (current - baseline).abs() / baseline.abs().max(1.0)
+}
+
+fn check_drift(
+    quantities: Res<ConservedQuantities>,
+    mut watchdog: ResMut<EnergyWatchdog>,
+    mut warnings: MessageWriter<DriftWarning>,
+) {
+    let Some(baseline) = watchdog.baseline else {
+        watchdog.baseline = Some(*quantities);
+        return;
+    };
+    watchdog.energy_drift = relative_change(baseline.energy, quantities.energy);
+    watchdog.momentum_drift = (quantities.momentum - baseline.momentum).length()
+        / baseline.momentum.length().max(1.0);
+
+    let mut drifted = Vec::new();
+    if watchdog.energy_drift > watchdog.energy_tolerance {
+        drifted.push((DriftQuantity::Energy, watchdog.energy_drift));
+    }
+    if watchdog.watch_momentum && watchdog.momentum_drift > watchdog.momentum_tolerance {
+        drifted.push((DriftQuantity::Momentum, watchdog.momentum_drift));
+    }
+    for (quantity, relative_drift) in drifted {
+        if !watchdog.warned.contains(&quantity) {
+            watchdog.warned.push(quantity);
+            log::warn!("{quantity:?} drifted by {:.2}%", relative_drift * 100.0);
+            warnings.write(DriftWarning {
+                quantity,
+                relative_drift,
+            });
+        }
+    }
+}
+
+fn drift_warning_panel(mut contexts: EguiContexts, watchdog: Res<EnergyWatchdog>) -> Result {
+    if !watchdog.is_drifting() {
+        return Ok(());
+    }
+    let ctx = contexts.ctx_mut()?;
+    egui::Window::new("Conservation warning")
+        .anchor(egui::Align2::RIGHT_TOP, [-10.0, 10.0])
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let warning = egui::Color32::from_rgb(255, 120, 80);
+            if watchdog.warned.contains(&DriftQuantity::Energy) {
+                ui.colored_label(
+                    warning,
+                    format!(
+                        "Energy drifted {:.2}% (limit {:.2}%)",
+                        watchdog.energy_drift * 100.0,
+                        watchdog.energy_tolerance * 100.0
+                    ),
+                );
+            }
+            if watchdog.warned.contains(&DriftQuantity::Momentum) {
+                ui.colored_label(
+                    warning,
+                    format!(
+                        "Momentum drifted {:.2}% (limit {:.2}%)",
+                        watchdog.momentum_drift * 100.0,
+                        watchdog.momentum_tolerance * 100.0
+                    ),
+                );
+            }
+            ui.separator();
+            ui.label("The numerical integration is no longer conserving this system. Try:");
+            ui.label("• a smaller time step (higher physics rate)");
+            ui.label("• a symplectic integrator such as semi-implicit Euler or velocity Verlet");
+            ui.label("• a higher-order integrator such as RK4");
+        });
+    Ok(())
+}
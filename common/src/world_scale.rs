@@ -0,0 +1,83 @@
+//! One scale between world meters and screen pixels
+//!
+//! Chapters place and move everything in meters. [`WorldScalePlugin`] sets the
+//! orthographic scale of every 2D camera from [`WorldScale`], so a projectile launched
+//! at 30 m/s covers the right number of pixels without any conversion in the physics.
+//! Sizes that are about the screen rather than the world (marker dots, border
+//! thickness) go through [`WorldScale::pixels`], and code that needs the visible area
+//! or the cursor position in meters asks [`WorldScale`] instead of the window.
+use bevy::prelude::*;
+
+/// Couples the 2D cameras' projection to [`WorldScale`]
+pub struct WorldScalePlugin;
+
+impl Plugin for WorldScalePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldScale>()
+            .add_systems(PostUpdate, couple_camera_scale);
+    }
+}
+
+/// Meters covered by one (logical) pixel, with the camera at the origin
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct WorldScale {
+    pub meters_per_pixel: f32,
+}
+
+impl WorldScale {
+    pub fn new(meters_per_pixel: f32) -> Self {
+        Self { meters_per_pixel }
+    }
+
+    pub fn pixels_per_meter(pixels_per_meter: f32) -> Self {
+        Self::new(1.0 / pixels_per_meter)
+    }
+
+    /// World length shown as `pixels` on screen
+    pub fn pixels(&self, pixels: f32) -> f32 {
+        pixels * self.meters_per_pixel
+    }
+
+    /// Screen length of `meters`
+    pub fn to_pixels(&self, meters: f32) -> f32 {
+        meters / self.meters_per_pixel
+    }
+
+    /// Width and height of the window in meters
+    pub fn view_size(&self, window: &Window) -> Vec2 {
+        window.size() * self.meters_per_pixel
+    }
+
+    /// World position under the cursor, if it is in the window
+    pub fn cursor_position(&self, window: &Window) -> Option<Vec2> {
+        window.cursor_position().map(|screen| self.screen_to_world(window, screen))
+    }
+
+    /// World position of a window point, e.g. a touch or the `Pointer`
+    pub fn screen_to_world(&self, window: &Window, screen: Vec2) -> Vec2 {
+        // Screen has (0, 0) at the top left with y down; the world is centered with y up
+        let centered = Vec2::new(screen.x - window.width() / 2.0, window.height() / 2.0 - screen.y);
+        centered * self.meters_per_pixel
+    }
+}
+
+impl Default for WorldScale {
+    /// One meter per pixel
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+pub(crate) fn couple_camera_scale(
+    scale: Res<WorldScale>,
+    mut cameras: Query<(Ref<Camera2d>, &mut Projection)>,
+) {
+    for (camera, mut projection) in &mut cameras {
+        if !(scale.is_changed() || camera.is_added()) {
+            continue;
+        }
+        if let Projection::Orthographic(orthographic) = &mut *projection {
+            orthographic.scale = scale.meters_per_pixel;
+        }
+    }
+}
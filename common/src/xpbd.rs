@@ -0,0 +1,240 @@
+//! Position-based constraints (XPBD)
+//!
+//! Constraints are components on their own entities that join bodies with a
+//! `Position`: [`DistanceConstraint`] (rods, rope and cloth links), [`PinConstraint`]
+//! (fix a body to a point in the world) and [`AngleConstraint`] (bending stiffness
+//! between two links). After the integrator has predicted new positions,
+//! [`solve_constraints`] moves the bodies until the constraints hold, then sets their
+//! velocities from how far they actually moved. Each constraint has a compliance, the
+//! inverse of its stiffness: 0 is rigid, larger values stretch like a spring, and
+//! unlike plain projection the result doesn't depend on the iteration count or step.
+//!
+//! Bodies without a `Mass` don't move, so a chain can also hang from a massless body.
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use std::f32::consts::{PI, TAU};
+
+use crate::{Mass, Position, Velocity};
+
+/// Tuning for [`solve_constraints`]
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct ConstraintSolver {
+    /// Passes over every constraint per step; more makes long chains stiffer
+    pub iterations: usize,
+}
+
+impl Default for ConstraintSolver {
+    fn default() -> Self {
+        Self { iterations: 10 }
+    }
+}
+
+/// Keep two bodies `rest_length` apart
+#[derive(Component, Debug, Clone, Copy)]
+pub struct DistanceConstraint {
+    pub a: Entity,
+    pub b: Entity,
+    pub rest_length: f32,
+    /// Inverse stiffness (m/N); 0 is a rigid rod
+    pub compliance: f32,
+}
+
+impl DistanceConstraint {
+    pub fn new(a: Entity, b: Entity, rest_length: f32) -> Self {
+        Self { a, b, rest_length, compliance: 0.0 }
+    }
+
+    pub fn with_compliance(mut self, compliance: f32) -> Self {
+        self.compliance = compliance;
+        self
+    }
+}
+
+/// Hold a body at a point in the world
+#[derive(Component, Debug, Clone, Copy)]
+pub struct PinConstraint {
+    pub body: Entity,
+    pub anchor: Vec2,
+    /// Inverse stiffness (m/N); 0 fixes the body to the anchor
+    pub compliance: f32,
+}
+
+impl PinConstraint {
+    pub fn new(body: Entity, anchor: Vec2) -> Self {
+        Self { body, anchor, compliance: 0.0 }
+    }
+
+    pub fn with_compliance(mut self, compliance: f32) -> Self {
+        self.compliance = compliance;
+        self
+    }
+}
+
+/// Keep the angle at `b` between the links to `a` and to `c` at `rest_angle`
+/// (radians, counterclockwise from `b → a` to `b → c`)
+#[derive(Component, Debug, Clone, Copy)]
+pub struct AngleConstraint {
+    pub a: Entity,
+    pub b: Entity,
+    pub c: Entity,
+    pub rest_angle: f32,
+    /// Inverse stiffness (rad/(N·m)); 0 is a rigid joint
+    pub compliance: f32,
+}
+
+impl AngleConstraint {
+    pub fn new(a: Entity, b: Entity, c: Entity, rest_angle: f32) -> Self {
+        Self { a, b, c, rest_angle, compliance: 0.0 }
+    }
+
+    pub fn with_compliance(mut self, compliance: f32) -> Self {
+        self.compliance = compliance;
+        self
+    }
+}
+
+/// Where the constrained bodies started the current step
+#[derive(Resource, Default, Debug)]
+pub struct ConstrainedPositions {
+    previous: HashMap<Entity, Vec2>,
+}
+
+/// One constraint, with the bodies it moves and how
+enum Constraint {
+    Distance { a: Entity, b: Entity, rest_length: f32 },
+    Pin { body: Entity, anchor: Vec2 },
+    Angle { a: Entity, b: Entity, c: Entity, rest_angle: f32 },
+}
+
+impl Constraint {
+    fn bodies(&self) -> impl Iterator<Item = Entity> {
+        let (first, second, third) = match *self {
+            Constraint::Distance { a, b, .. } => (a, Some(b), None),
+            Constraint::Pin { body, .. } => (body, None, None),
+            Constraint::Angle { a, b, c, .. } => (a, Some(b), Some(c)),
+        };
+        std::iter::once(first).chain(second).chain(third)
+    }
+
+    /// The constraint error C and its gradient for each body
+    fn evaluate(&self, positions: &HashMap<Entity, (Vec2, f32)>) -> Option<(f32, Vec<(Entity, Vec2)>)> {
+        let position = |entity| positions.get(&entity).map(|(position, _)| *position);
+        match *self {
+            Constraint::Distance { a, b, rest_length } => {
+                let offset = position(b)? - position(a)?;
+                let normal = offset.try_normalize()?;
+                Some((offset.length() - rest_length, vec![(a, -normal), (b, normal)]))
+            }
+            Constraint::Pin { body, anchor } => {
+                let offset = position(body)? - anchor;
+                let normal = offset.try_normalize()?;
+                Some((offset.length(), vec![(body, normal)]))
+            }
+            Constraint::Angle { a, b, c, rest_angle } => {
+                let to_a = position(a)? - position(b)?;
+                let to_c = position(c)? - position(b)?;
+                if to_a.length_squared() <= f32::EPSILON || to_c.length_squared() <= f32::EPSILON {
+                    return None;
+                }
+                let angle = to_a.angle_to(to_c);
+                // Wrap so the joint turns back the short way
+                let error = (angle - rest_angle + PI).rem_euclid(TAU) - PI;
+                let gradient_a = -to_a.perp() / to_a.length_squared();
+                let gradient_c = to_c.perp() / to_c.length_squared();
+                Some((error, vec![(a, gradient_a), (b, -gradient_a - gradient_c), (c, gradient_c)]))
+            }
+        }
+    }
+}
+
+/// System to remember where every constrained body starts the step
+pub fn record_constrained_positions(
+    mut recorded: ResMut<ConstrainedPositions>,
+    bodies: Query<&Position>,
+    distances: Query<&DistanceConstraint>,
+    pins: Query<&PinConstraint>,
+    angles: Query<&AngleConstraint>,
+) {
+    recorded.previous.clear();
+    let referenced = distances
+        .iter()
+        .flat_map(|constraint| [constraint.a, constraint.b])
+        .chain(pins.iter().map(|constraint| constraint.body))
+        .chain(angles.iter().flat_map(|constraint| [constraint.a, constraint.b, constraint.c]));
+    for entity in referenced {
+        if let Ok(position) = bodies.get(entity) {
+            recorded.previous.insert(entity, position.0);
+        }
+    }
+}
+
+/// System to move constrained bodies until the constraints hold, then set their
+/// velocities from the distance moved over the step
+pub fn solve_constraints(
+    mut bodies: Query<(&mut Position, Option<&Mass>, Option<&mut Velocity>)>,
+    distances: Query<&DistanceConstraint>,
+    pins: Query<&PinConstraint>,
+    angles: Query<&AngleConstraint>,
+    recorded: Res<ConstrainedPositions>,
+    solver: Res<ConstraintSolver>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_secs();
+    if dt <= 0.0 {
+        return;
+    }
+    let constraints: Vec<(Constraint, f32)> = distances
+        .iter()
+        .map(|c| (Constraint::Distance { a: c.a, b: c.b, rest_length: c.rest_length }, c.compliance))
+        .chain(pins.iter().map(|c| (Constraint::Pin { body: c.body, anchor: c.anchor }, c.compliance)))
+        .chain(angles.iter().map(|c| {
+            (Constraint::Angle { a: c.a, b: c.b, c: c.c, rest_angle: c.rest_angle }, c.compliance)
+        }))
+        .collect();
+
+    // Predicted position and inverse mass of every constrained body
+    let mut positions: HashMap<Entity, (Vec2, f32)> = HashMap::default();
+    for entity in constraints.iter().flat_map(|(constraint, _)| constraint.bodies()) {
+        if let Ok((position, mass, _)) = bodies.get(entity) {
+            let inverse_mass = mass.map_or(0.0, |mass| if mass.0 > 0.0 { 1.0 / mass.0 } else { 0.0 });
+            positions.insert(entity, (position.0, inverse_mass));
+        }
+    }
+
+    // Lagrange multipliers, accumulated over the iterations of this step
+    let mut lambdas = vec![0.0; constraints.len()];
+    for _ in 0..solver.iterations {
+        for ((constraint, compliance), lambda) in constraints.iter().zip(&mut lambdas) {
+            let Some((error, gradients)) = constraint.evaluate(&positions) else {
+                continue;
+            };
+            let weight: f32 = gradients
+                .iter()
+                .map(|(entity, gradient)| positions[entity].1 * gradient.length_squared())
+                .sum();
+            let alpha = compliance / (dt * dt);
+            if weight + alpha <= f32::EPSILON {
+                continue;
+            }
+            let delta_lambda = (-error - alpha * *lambda) / (weight + alpha);
+            *lambda += delta_lambda;
+            for (entity, gradient) in gradients {
+                let (position, inverse_mass) = positions.get_mut(&entity).unwrap();
+                *position += gradient * (*inverse_mass * delta_lambda);
+            }
+        }
+    }
+
+    for (entity, (solved, inverse_mass)) in positions {
+        if inverse_mass == 0.0 {
+            continue;
+        }
+        let Ok((mut position, _, velocity)) = bodies.get_mut(entity) else {
+            continue;
+        };
+        position.0 = solved;
+        if let (Some(mut velocity), Some(previous)) = (velocity, recorded.previous.get(&entity)) {
+            velocity.0 = (solved - *previous) / dt;
+        }
+    }
+}
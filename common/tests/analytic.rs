@@ -0,0 +1,147 @@
+//! The integrators against the closed-form solutions in `analytic`
+//!
+//! Each test flies one motion with every [`Integrator`] from its exact starting state
+//! and checks the largest position error over the run against that scheme's tolerance.
+//! The tolerances are a little above what each scheme reaches today, so they hold the
+//! verification runs to first order for the Eulers and to rounding for Verlet and RK4
+//! where those are exact; a change that needs them loosened has lost accuracy.
+use bevy::prelude::*;
+use rhysics_common::analytic::{AnalyticSolution, Damping, DampedOscillator, HarmonicOscillator, KeplerOrbit, Projectile};
+use rhysics_common::integrator::{BodyState, Integrator};
+
+/// `(t, state)` after each of `steps` steps of `dt`, from the solution's state at t = 0
+fn simulate(
+    solution: &impl AnalyticSolution,
+    integrator: Integrator,
+    dt: f32,
+    steps: usize,
+    acceleration: impl Fn(BodyState) -> Vec2,
+) -> Vec<(f32, BodyState)> {
+    let mut state = solution.state(0.0);
+    (0..steps)
+        .map(|step| {
+            state = integrator.step(state, step as f32 * dt, dt, |_, state| acceleration(state));
+            ((step + 1) as f32 * dt, state)
+        })
+        .collect()
+}
+
+/// Fly `solution` with every integrator and check each against its `(integrator, tolerance)`
+fn assert_within(
+    name: &str,
+    solution: &impl AnalyticSolution,
+    dt: f32,
+    steps: usize,
+    tolerances: [(Integrator, f32); 4],
+    acceleration: impl Fn(BodyState) -> Vec2,
+) {
+    for (integrator, tolerance) in tolerances {
+        let error = solution.max_position_error(&simulate(solution, integrator, dt, steps, &acceleration));
+        assert!(
+            error <= tolerance,
+            "{name} with {}: position error {error} above the tolerance {tolerance}",
+            integrator.name()
+        );
+    }
+}
+
+#[test]
+fn projectile_in_vacuum() {
+    let gravity = -9.81;
+    let projectile = Projectile::new(Vec2::new(30.0, 30.0), gravity);
+    let dt = 1.0 / 60.0;
+    let steps = (projectile.flight_time().unwrap() / dt) as usize;
+    // The Eulers are off by ½|g|t·dt; Verlet and RK4 are exact for a constant pull
+    assert_within(
+        "Projectile",
+        &projectile,
+        dt,
+        steps,
+        [
+            (Integrator::Euler, 0.52),
+            (Integrator::SemiImplicitEuler, 0.52),
+            (Integrator::VelocityVerlet, 1e-3),
+            (Integrator::Rk4, 1e-3),
+        ],
+        |_| Vec2::new(0.0, gravity),
+    );
+}
+
+#[test]
+fn simple_harmonic_motion() {
+    let (k, mass) = (4.0, 1.0);
+    let oscillator = HarmonicOscillator::spring(k, mass, 1.0);
+    let dt = 0.01;
+    let steps = (3.0 * oscillator.period() / dt) as usize;
+    assert_within(
+        "Harmonic oscillator",
+        &oscillator,
+        dt,
+        steps,
+        [
+            (Integrator::Euler, 0.25),
+            (Integrator::SemiImplicitEuler, 0.015),
+            (Integrator::VelocityVerlet, 5e-4),
+            (Integrator::Rk4, 1e-5),
+        ],
+        |state| -k / mass * state.position,
+    );
+}
+
+#[test]
+fn damped_oscillator_in_every_regime() {
+    let omega0 = 2.0;
+    let dt = 0.01;
+    let steps = 500;
+    for (gamma, regime) in [(0.3, Damping::Under), (2.0, Damping::Critical), (5.0, Damping::Over)] {
+        let oscillator = DampedOscillator::new(omega0, gamma, 1.0, 0.5);
+        assert_eq!(oscillator.regime(), regime);
+        assert_within(
+            &format!("{regime:?}-damped oscillator"),
+            &oscillator,
+            dt,
+            steps,
+            [
+                (Integrator::Euler, 0.04),
+                (Integrator::SemiImplicitEuler, 0.015),
+                (Integrator::VelocityVerlet, 1e-4),
+                (Integrator::Rk4, 1e-6),
+            ],
+            |state| -2.0 * gamma * state.velocity - omega0 * omega0 * state.position,
+        );
+    }
+}
+
+#[test]
+fn kepler_orbit() {
+    let mu = 1.0;
+    // Starts at periapsis, where the pull is strongest
+    let orbit = KeplerOrbit::new(mu, 1.0, 0.5);
+    let dt = 1e-3;
+    let steps = (orbit.period() / dt) as usize;
+    assert_within(
+        "Kepler orbit",
+        &orbit,
+        dt,
+        steps,
+        [
+            (Integrator::Euler, 0.3),
+            (Integrator::SemiImplicitEuler, 0.01),
+            (Integrator::VelocityVerlet, 3e-4),
+            (Integrator::Rk4, 1e-4),
+        ],
+        |state| -mu * state.position / state.position.length().powi(3),
+    );
+}
+
+#[test]
+fn kepler_orbit_from_its_own_state() {
+    // An orbit rebuilt from one of its states is the same orbit
+    let orbit = KeplerOrbit::new(1.0, 2.0, 0.3);
+    let later = orbit.state(1.7);
+    let rebuilt = KeplerOrbit::from_state(1.0, later).unwrap();
+    for t in [0.0, 0.5, 3.0] {
+        let error = rebuilt.position(t).distance(orbit.position(1.7 + t));
+        assert!(error < 1e-3, "rebuilt orbit off by {error} at t = {t}");
+    }
+}
@@ -0,0 +1,58 @@
+//! The energy watchdog warns once when a total drifts past its tolerance
+use bevy::prelude::*;
+use rhysics_common::headless::{self, HeadlessPlugin};
+use rhysics_common::watchdog::{ConservedQuantities, DriftQuantity, DriftWarning, EnergyWatchdog, EnergyWatchdogPlugin};
+
+/// A headless app with the watchdog, its baseline taken from `energy`
+fn watched(energy: f32) -> App {
+    let mut app = App::new();
+    app.add_plugins(HeadlessPlugin::default())
+        .add_plugins(EnergyWatchdogPlugin)
+        .insert_resource(EnergyWatchdog::energy_only())
+        .insert_resource(ConservedQuantities { energy, momentum: Vec2::ZERO });
+    headless::run_steps(&mut app, 1);
+    // As in `step_with`, make sure a fixed step has taken the baseline
+    app.update();
+    app
+}
+
+/// Report `energy` for the next step and return the warnings it raised
+fn step_with(app: &mut App, energy: f32) -> Vec<DriftWarning> {
+    app.world_mut().resource_mut::<ConservedQuantities>().energy = energy;
+    // A frame can fall short of a fixed step; the change is picked up by the next one
+    app.update();
+    app.update();
+    app.world_mut().resource_mut::<Messages<DriftWarning>>().drain().collect()
+}
+
+#[test]
+fn warns_once_above_the_tolerance() {
+    let mut app = watched(10.0);
+    let tolerance = app.world().resource::<EnergyWatchdog>().energy_tolerance;
+
+    let within = step_with(&mut app, 10.0 * (1.0 + tolerance / 2.0));
+    assert!(within.is_empty(), "warned at half the tolerance: {within:?}");
+    assert!(!app.world().resource::<EnergyWatchdog>().is_drifting());
+
+    let beyond = step_with(&mut app, 10.0 * (1.0 + tolerance * 2.0));
+    assert_eq!(beyond.len(), 1, "expected one warning, got {beyond:?}");
+    assert_eq!(beyond[0].quantity, DriftQuantity::Energy);
+    assert!((beyond[0].relative_drift - tolerance * 2.0).abs() < 1e-4);
+    assert!(app.world().resource::<EnergyWatchdog>().is_drifting());
+
+    // Drifting further doesn't repeat the warning
+    assert!(step_with(&mut app, 10.0 * (1.0 + tolerance * 4.0)).is_empty());
+}
+
+#[test]
+fn warns_again_after_a_reset() {
+    let mut app = watched(10.0);
+    let tolerance = app.world().resource::<EnergyWatchdog>().energy_tolerance;
+    assert_eq!(step_with(&mut app, 10.0 * (1.0 + tolerance * 2.0)).len(), 1);
+
+    // The next sample is the new baseline
+    app.world_mut().resource_mut::<EnergyWatchdog>().reset();
+    assert!(step_with(&mut app, 20.0).is_empty());
+    let beyond = step_with(&mut app, 20.0 * (1.0 - tolerance * 2.0));
+    assert_eq!(beyond.len(), 1, "expected one warning, got {beyond:?}");
+}
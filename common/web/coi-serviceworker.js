@@ -0,0 +1,46 @@
+// Cross-origin isolation for static hosts
+//
+// Threaded wasm builds share memory between web workers through a SharedArrayBuffer,
+// which browsers only allow on cross-origin isolated pages. Hosts like GitHub Pages
+// can't send the COOP/COEP headers that turn isolation on, so this service worker
+// adds them to every response instead. Load it with a classic (non-module) script tag
+// before the simulation; the first visit reloads once so the page itself goes through
+// the worker.
+if (typeof window === 'undefined') {
+    self.addEventListener('install', () => self.skipWaiting());
+    self.addEventListener('activate', (event) => event.waitUntil(self.clients.claim()));
+
+    self.addEventListener('fetch', (event) => {
+        const request = event.request;
+        if (request.cache === 'only-if-cached' && request.mode !== 'same-origin') {
+            return;
+        }
+        event.respondWith(
+            fetch(request).then((response) => {
+                // Opaque responses can't be modified
+                if (response.status === 0) {
+                    return response;
+                }
+                const headers = new Headers(response.headers);
+                headers.set('Cross-Origin-Embedder-Policy', 'require-corp');
+                headers.set('Cross-Origin-Opener-Policy', 'same-origin');
+                headers.set('Cross-Origin-Resource-Policy', 'cross-origin');
+                return new Response(response.body, {
+                    status: response.status,
+                    statusText: response.statusText,
+                    headers,
+                });
+            })
+        );
+    });
+} else if (!window.crossOriginIsolated && window.isSecureContext && 'serviceWorker' in navigator) {
+    navigator.serviceWorker.register(document.currentScript.src).then(() => {
+        navigator.serviceWorker.ready.then(() => {
+            // Only reload once, so a browser that refuses isolation doesn't loop
+            if (!navigator.serviceWorker.controller && !sessionStorage.getItem('coi-reloaded')) {
+                sessionStorage.setItem('coi-reloaded', '1');
+                window.location.reload();
+            }
+        });
+    }).catch((err) => console.warn('Cross-origin isolation service worker failed to register:', err));
+}
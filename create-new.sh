@@ -62,6 +62,12 @@ bevy = { workspace = true }
 log = { workspace = true }
 rhysics-common = { path = "../../../common" }
 
+[features]
+default = ["standalone"]
+# Start on page load in its own WASM build; off when bundled into the launcher
+standalone = []
+wasm-threads = ["rhysics-common/wasm-threads"]
+
 [target.'cfg(target_arch = "wasm32")'.dependencies]
 wasm-bindgen = { workspace = true }
 web-sys = { workspace = true }
@@ -78,15 +84,29 @@ use rhysics_common::*;
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
-#[cfg_attr(target_arch = "wasm32", wasm_bindgen(start))]
+#[cfg_attr(all(target_arch = "wasm32", feature = "standalone"), wasm_bindgen(start))]
 pub fn run() {
-    App::new()
-        .add_plugins(DefaultPlugins.set(default_window_plugin(
-            "Chapter ${chapter_num}.${section_num} - ${display_title}"
-        )))
-        .add_systems(Startup, setup)
-        .add_systems(Update, update)
-        .run();
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins.set(default_window_plugin(
+        "Chapter ${chapter_num}.${section_num} - ${display_title}"
+    )));
+    add_simulation(&mut app);
+    app.run();
+}
+
+/// Step the simulation \`steps\` times without a window or renderer
+pub fn run_headless(steps: u32) -> App {
+    let mut app = App::new();
+    app.add_plugins(headless::HeadlessPlugin::default());
+    add_simulation(&mut app);
+    headless::run_steps(&mut app, steps);
+    app
+}
+
+/// Everything but the window and renderer, shared by [\`run\`] and [\`run_headless\`]
+fn add_simulation(app: &mut App) {
+    app.add_systems(Startup, setup)
+        .add_systems(Update, update);
 }
 
 fn setup(commands: Commands) {
@@ -103,9 +123,16 @@ EOF
 
 # Create main.rs
 cat > "$full_dir/src/main.rs" << EOF
-// Native binary entry point
+// Native binary entry point; \`--headless <steps>\` runs without a window
 fn main() {
-    ${sim_name}::run();
+    let args: Vec<String> = std::env::args().collect();
+    match args.iter().position(|arg| arg == "--headless") {
+        Some(index) => {
+            let steps = args.get(index + 1).and_then(|steps| steps.parse().ok()).unwrap_or(1000);
+            ${sim_name}::run_headless(steps);
+        }
+        None => ${sim_name}::run(),
+    }
 }
 EOF
 
@@ -169,11 +196,15 @@ cat > "$full_dir/index.html" << EOF
         <canvas id="bevy-canvas" style="display:none;"></canvas>
     </div>
     <script type="module">
-        import init from './pkg/${sim_name}.js';
+        import init, * as sim from './pkg/${sim_name}.js';
         init().then(() => {
             document.getElementById('loading').style.display = 'none';
             document.getElementById('bevy-canvas').style.display = 'block';
             console.log("Simulation loaded successfully!");
+            // Threaded builds (export.sh --threads) start their worker pool here
+            if (sim.initThreads && self.crossOriginIsolated) {
+                return sim.initThreads(navigator.hardwareConcurrency);
+            }
         }).catch(err => {
             document.getElementById('loading').textContent = 'Error loading simulation: ' + err;
             console.error(err);
@@ -192,6 +223,9 @@ echo "      \"${full_dir}\","
 echo ""
 echo "   2. Edit ${full_dir}/src/lib.rs to implement your simulation"
 echo ""
+echo "   To include it in launcher bundles, add it as an optional dependency and"
+echo "   feature in launcher/Cargo.toml and an entry in launcher/src/lib.rs"
+echo ""
 echo "   3. Test locally:"
 echo "      cargo run -p ${sim_name}"
 echo ""
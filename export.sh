@@ -17,6 +17,13 @@ echo "WASM Export"
 echo "===================================="
 echo ""
 
+# Optional --threads flag: build with wasm threads (SharedArrayBuffer + web workers)
+threads=false
+if [ "$1" == "--threads" ]; then
+    threads=true
+    shift
+fi
+
 # Check if arguments provided or ask interactively
 if [ $# -eq 3 ]; then
     chapter_num=$1
@@ -32,7 +39,7 @@ fi
 # Validate inputs
 if [ -z "$chapter_num" ] || [ -z "$section_num" ] || [ -z "$sim_name" ]; then
     echo -e "${RED}Error: Missing required parameters${NC}"
-    echo "Usage: $0 <chapter_num> <section_num> <sim_name>"
+    echo "Usage: $0 [--threads] <chapter_num> <section_num> <sim_name>"
     echo "Example: $0 1 1 orders_of_magnitude"
     exit 1
 fi
@@ -77,7 +84,16 @@ echo -e "${BLUE}📦 Building WASM package...${NC}"
 cd "$sim_dir"
 
 # Use wasm-pack to build with absolute path
-wasm-pack build --target web --out-dir "$output_dir/pkg" --release
+if [ "$threads" = true ]; then
+    # Shared memory needs std rebuilt with atomics, which is nightly-only.
+    # RUSTFLAGS replaces .cargo/config.toml, so repeat the getrandom backend here.
+    echo -e "${BLUE}🧵 Building with wasm threads (nightly)...${NC}"
+    RUSTFLAGS='--cfg getrandom_backend="wasm_js" -C target-feature=+atomics,+bulk-memory,+mutable-globals' \
+        rustup run nightly wasm-pack build --target web --out-dir "$output_dir/pkg" --release \
+        -- --features wasm-threads -Z build-std=panic_abort,std
+else
+    wasm-pack build --target web --out-dir "$output_dir/pkg" --release
+fi
 
 # Copy index.html if it exists
 if [ -f "index.html" ]; then
@@ -85,6 +101,17 @@ if [ -f "index.html" ]; then
     cp index.html "$output_dir/index.html"
 fi
 
+# Threads need a cross-origin isolated page; the service worker provides it on static hosts
+if [ "$threads" = true ]; then
+    echo -e "${BLUE}📄 Adding cross-origin isolation service worker...${NC}"
+    cp "${ORIGINAL_DIR}/common/web/coi-serviceworker.js" "$output_dir/coi-serviceworker.js"
+    if [ -f "$output_dir/index.html" ]; then
+        awk '{ print } /<head>/ { print "    <script src=\"coi-serviceworker.js\"></script>" }' \
+            "$output_dir/index.html" > "$output_dir/index.html.tmp"
+        mv "$output_dir/index.html.tmp" "$output_dir/index.html"
+    fi
+fi
+
 # Go back to original directory
 cd "$ORIGINAL_DIR"
 
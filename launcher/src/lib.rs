@@ -0,0 +1,64 @@
+//! Launcher for the simulations compiled into this bundle
+//!
+//! Each chapter is an optional dependency behind a feature of the same name, so
+//! `--no-default-features --features "boids,projectile"` builds a bundle with just
+//! those two. [`SIMULATIONS`] lists what made it in; the native binary takes an id on
+//! the command line and the WASM build exports `run_simulation` for the page's menu.
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// A simulation that can be started from the launcher
+pub struct Simulation {
+    /// Name on the command line and in `?sim=` links
+    pub id: &'static str,
+    pub title: &'static str,
+    pub run: fn(),
+    /// Steps the simulation without a window, see `rhysics_common::headless`
+    pub run_headless: fn(u32) -> bevy::app::App,
+}
+
+/// Every simulation enabled by this build's features
+pub const SIMULATIONS: &[Simulation] = &[
+    #[cfg(feature = "boids")]
+    Simulation {
+        id: "boids",
+        title: "Chapter 0.0 - Boids",
+        run: boids::run,
+        run_headless: boids::run_headless,
+    },
+    #[cfg(feature = "projectile")]
+    Simulation {
+        id: "projectile",
+        title: "Chapter 4.3 - Projectile Test",
+        run: projectile_test::run,
+        run_headless: projectile_test::run_headless,
+    },
+];
+
+pub fn find(id: &str) -> Option<&'static Simulation> {
+    SIMULATIONS.iter().find(|simulation| simulation.id == id)
+}
+
+/// Ids of the bundled simulations, for building the page's menu
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn simulations() -> Vec<String> {
+    SIMULATIONS.iter().map(|simulation| simulation.id.to_string()).collect()
+}
+
+/// Title shown for a bundled simulation
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn simulation_title(id: &str) -> Option<String> {
+    find(id).map(|simulation| simulation.title.to_string())
+}
+
+/// Start the simulation called `id`; it takes over the page's canvas
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn run_simulation(id: &str) -> Result<(), JsValue> {
+    let simulation = find(id).ok_or_else(|| JsValue::from_str(&format!("not in this bundle: {id}")))?;
+    (simulation.run)();
+    Ok(())
+}
@@ -0,0 +1,22 @@
+// Native launcher: `cargo run -p rhysics -- <simulation> [--headless <steps>]`
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let id = args.get(1);
+    let headless = args.iter().position(|arg| arg == "--headless");
+    match id.map(String::as_str).and_then(rhysics::find) {
+        Some(simulation) => match headless {
+            Some(index) => {
+                let steps = args.get(index + 1).and_then(|steps| steps.parse().ok()).unwrap_or(1000);
+                (simulation.run_headless)(steps);
+            }
+            None => (simulation.run)(),
+        },
+        None => {
+            eprintln!("Usage: rhysics <simulation> [--headless <steps>]\n\nSimulations in this build:");
+            for simulation in rhysics::SIMULATIONS {
+                eprintln!("  {:<12} {}", simulation.id, simulation.title);
+            }
+            std::process::exit(if id.is_none() { 0 } else { 1 });
+        }
+    }
+}
@@ -0,0 +1,12 @@
+//! Numerical methods for the simulations, independent of Bevy
+//!
+//! [`ode`] steps systems of ODEs with adaptive Runge-Kutta-Fehlberg, for orbits and
+//! stiff springs where a fixed step is either too slow or blows up. [`roots`] finds
+//! where a function crosses zero, e.g. the exact time a projectile reaches the ground.
+//! [`linalg`] has the few vector and matrix helpers the other two (and implicit
+//! solvers in chapters) need.
+//!
+//! Everything works in `f64` on plain slices, so states of any size fit.
+pub mod linalg;
+pub mod ode;
+pub mod roots;
@@ -0,0 +1,212 @@
+//! Small linear algebra helpers on slices
+
+/// Sum of `a[i] * b[i]`
+pub fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+/// Euclidean length
+pub fn norm(a: &[f64]) -> f64 {
+    dot(a, a).sqrt()
+}
+
+/// `y += alpha * x`
+pub fn axpy(alpha: f64, x: &[f64], y: &mut [f64]) {
+    for (y, x) in y.iter_mut().zip(x) {
+        *y += alpha * x;
+    }
+}
+
+/// Largest absolute entry, the max norm
+pub fn max_abs(a: &[f64]) -> f64 {
+    a.iter().fold(0.0, |max, value| max.max(value.abs()))
+}
+
+/// Dense square matrix stored row by row
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+    size: usize,
+    entries: Vec<f64>,
+}
+
+impl Matrix {
+    pub fn zeros(size: usize) -> Self {
+        Self {
+            size,
+            entries: vec![0.0; size * size],
+        }
+    }
+
+    pub fn identity(size: usize) -> Self {
+        let mut matrix = Self::zeros(size);
+        for i in 0..size {
+            matrix[(i, i)] = 1.0;
+        }
+        matrix
+    }
+
+    /// From rows of equal length; `None` unless there are as many rows as columns
+    pub fn from_rows(rows: &[Vec<f64>]) -> Option<Self> {
+        let size = rows.len();
+        if rows.iter().any(|row| row.len() != size) {
+            return None;
+        }
+        Some(Self {
+            size,
+            entries: rows.concat(),
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// `self * x`
+    pub fn mul_vec(&self, x: &[f64]) -> Vec<f64> {
+        self.entries.chunks(self.size).map(|row| dot(row, x)).collect()
+    }
+
+    /// Solve `self * x = b` by Gaussian elimination with partial pivoting; `None` if
+    /// the matrix is singular
+    pub fn solve(&self, b: &[f64]) -> Option<Vec<f64>> {
+        let n = self.size;
+        let mut a = self.clone();
+        let mut x = b.to_vec();
+        for column in 0..n {
+            // The largest pivot keeps the rounding errors small
+            let pivot = (column..n).max_by(|&i, &j| a[(i, column)].abs().total_cmp(&a[(j, column)].abs()))?;
+            if a[(pivot, column)].abs() <= f64::EPSILON {
+                return None;
+            }
+            if pivot != column {
+                for k in 0..n {
+                    a.entries.swap(pivot * n + k, column * n + k);
+                }
+                x.swap(pivot, column);
+            }
+            for row in column + 1..n {
+                let factor = a[(row, column)] / a[(column, column)];
+                for k in column..n {
+                    a[(row, k)] -= factor * a[(column, k)];
+                }
+                x[row] -= factor * x[column];
+            }
+        }
+        for row in (0..n).rev() {
+            let rest: f64 = (row + 1..n).map(|k| a[(row, k)] * x[k]).sum();
+            x[row] = (x[row] - rest) / a[(row, row)];
+        }
+        Some(x)
+    }
+}
+
+impl std::ops::Index<(usize, usize)> for Matrix {
+    type Output = f64;
+
+    fn index(&self, (row, column): (usize, usize)) -> &f64 {
+        &self.entries[row * self.size + column]
+    }
+}
+
+impl std::ops::IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (row, column): (usize, usize)) -> &mut f64 {
+        &mut self.entries[row * self.size + column]
+    }
+}
+
+/// Solve a tridiagonal system (Thomas algorithm), as implicit springs in a chain and
+/// cubic splines give. `lower[i]` and `upper[i]` sit left and right of `diagonal[i]`;
+/// `lower[0]` and the last `upper` are ignored. `None` if a pivot vanishes.
+pub fn solve_tridiagonal(lower: &[f64], diagonal: &[f64], upper: &[f64], rhs: &[f64]) -> Option<Vec<f64>> {
+    let n = diagonal.len();
+    let mut upper_prime = vec![0.0; n];
+    let mut x = vec![0.0; n];
+    let mut previous_upper = 0.0;
+    let mut previous_x = 0.0;
+    for i in 0..n {
+        let below = if i > 0 { lower[i] } else { 0.0 };
+        let pivot = diagonal[i] - below * previous_upper;
+        if pivot.abs() <= f64::EPSILON {
+            return None;
+        }
+        upper_prime[i] = if i + 1 < n { upper[i] / pivot } else { 0.0 };
+        x[i] = (rhs[i] - below * previous_x) / pivot;
+        previous_upper = upper_prime[i];
+        previous_x = x[i];
+    }
+    for i in (0..n.saturating_sub(1)).rev() {
+        x[i] -= upper_prime[i] * x[i + 1];
+    }
+    Some(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vector_helpers() {
+        assert_eq!(dot(&[1.0, 2.0, 3.0], &[4.0, -5.0, 6.0]), 12.0);
+        assert_eq!(norm(&[3.0, 4.0]), 5.0);
+        assert_eq!(max_abs(&[1.0, -7.0, 3.0]), 7.0);
+        assert_eq!(max_abs(&[]), 0.0);
+        let mut y = vec![1.0, 1.0];
+        axpy(2.0, &[3.0, -1.0], &mut y);
+        assert_eq!(y, vec![7.0, -1.0]);
+    }
+
+    #[test]
+    fn identity_leaves_vectors_alone() {
+        let identity = Matrix::identity(3);
+        assert_eq!(identity.size(), 3);
+        assert_eq!(identity.mul_vec(&[1.0, 2.0, 3.0]), vec![1.0, 2.0, 3.0]);
+        assert_eq!(identity.solve(&[1.0, 2.0, 3.0]), Some(vec![1.0, 2.0, 3.0]));
+    }
+
+    #[test]
+    fn from_rows_needs_a_square() {
+        assert!(Matrix::from_rows(&[vec![1.0, 2.0], vec![3.0]]).is_none());
+        assert!(Matrix::from_rows(&[vec![1.0, 2.0]]).is_none());
+    }
+
+    #[test]
+    fn solve_pivots_past_a_zero() {
+        // A zero in the first pivot position needs a row swap
+        let matrix = Matrix::from_rows(&[vec![0.0, 2.0, 1.0], vec![1.0, 1.0, 0.0], vec![2.0, 0.0, 3.0]]).unwrap();
+        let x = [1.0, -2.0, 3.0];
+        let solved = matrix.solve(&matrix.mul_vec(&x)).unwrap();
+        for (solved, x) in solved.iter().zip(x) {
+            assert!((solved - x).abs() < 1e-12, "{solved} vs {x}");
+        }
+    }
+
+    #[test]
+    fn singular_matrix_has_no_solution() {
+        let matrix = Matrix::from_rows(&[vec![1.0, 2.0], vec![2.0, 4.0]]).unwrap();
+        assert_eq!(matrix.solve(&[1.0, 2.0]), None);
+    }
+
+    #[test]
+    fn tridiagonal_matches_the_dense_solve() {
+        let (lower, diagonal, upper) = ([0.0, -1.0, -1.0, -1.0], [2.0; 4], [-1.0, -1.0, -1.0, 0.0]);
+        let rhs = [1.0, 0.0, 0.0, 1.0];
+        let mut dense = Matrix::zeros(4);
+        for i in 0..4 {
+            dense[(i, i)] = diagonal[i];
+            if i > 0 {
+                dense[(i, i - 1)] = lower[i];
+            }
+            if i < 3 {
+                dense[(i, i + 1)] = upper[i];
+            }
+        }
+        let thomas = solve_tridiagonal(&lower, &diagonal, &upper, &rhs).unwrap();
+        let gauss = dense.solve(&rhs).unwrap();
+        for (thomas, gauss) in thomas.iter().zip(&gauss) {
+            assert!((thomas - gauss).abs() < 1e-12);
+        }
+        // This chain of springs pulled at both ends sits still at 1 everywhere
+        assert!(thomas.iter().all(|x| (x - 1.0).abs() < 1e-12));
+        assert_eq!(solve_tridiagonal(&[0.0, 1.0], &[0.0, 1.0], &[1.0, 0.0], &[1.0, 1.0]), None);
+    }
+}
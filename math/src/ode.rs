@@ -0,0 +1,284 @@
+//! Adaptive Runge-Kutta-Fehlberg (RK45) for systems of ODEs
+//!
+//! Each step takes six derivative evaluations and gets both a 4th and a 5th order
+//! estimate; their difference estimates the error, which sets the next step size.
+//! Steps shrink through close approaches of an orbit or the fast part of a stiff
+//! spring and grow again where nothing happens, so the error stays near the
+//! tolerance with far fewer steps than a fixed-step method needs for the same
+//! accuracy.
+//!
+//! A system is `dy/dt = f(t, y)` with `f` writing the derivative of `y` into its
+//! last argument; a body's state is `[x, y, vx, vy]` and its derivative
+//! `[vx, vy, ax, ay]`.
+use crate::linalg::{axpy, max_abs};
+
+/// Fehlberg's nodes and coefficients
+const C: [f64; 6] = [0.0, 1.0 / 4.0, 3.0 / 8.0, 12.0 / 13.0, 1.0, 1.0 / 2.0];
+const A: [[f64; 5]; 6] = [
+    [0.0, 0.0, 0.0, 0.0, 0.0],
+    [1.0 / 4.0, 0.0, 0.0, 0.0, 0.0],
+    [3.0 / 32.0, 9.0 / 32.0, 0.0, 0.0, 0.0],
+    [1932.0 / 2197.0, -7200.0 / 2197.0, 7296.0 / 2197.0, 0.0, 0.0],
+    [439.0 / 216.0, -8.0, 3680.0 / 513.0, -845.0 / 4104.0, 0.0],
+    [-8.0 / 27.0, 2.0, -3544.0 / 2565.0, 1859.0 / 4104.0, -11.0 / 40.0],
+];
+/// Weights of the 4th order estimate, which is the one kept
+const B4: [f64; 6] = [25.0 / 216.0, 0.0, 1408.0 / 2565.0, 2197.0 / 4104.0, -1.0 / 5.0, 0.0];
+/// Weights of the 5th order estimate, only used for the error
+const B5: [f64; 6] = [16.0 / 135.0, 0.0, 6656.0 / 12825.0, 28561.0 / 56430.0, -9.0 / 50.0, 2.0 / 55.0];
+
+/// Why an integration stopped early
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OdeError {
+    /// The error was still too large at the smallest step allowed, at this time
+    StepTooSmall(f64),
+    /// More steps than allowed were taken; the time reached
+    TooManySteps(f64),
+}
+
+impl std::fmt::Display for OdeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OdeError::StepTooSmall(t) => write!(f, "step size underflow at t = {t}"),
+            OdeError::TooManySteps(t) => write!(f, "too many steps, stopped at t = {t}"),
+        }
+    }
+}
+
+impl std::error::Error for OdeError {}
+
+/// Adaptive RK45 stepper
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rk45 {
+    /// Error allowed per step, absolute for components below 1 and relative above
+    pub tolerance: f64,
+    pub min_step: f64,
+    pub max_step: f64,
+    /// Steps allowed in one [`Rk45::integrate`]
+    pub max_steps: usize,
+}
+
+impl Default for Rk45 {
+    fn default() -> Self {
+        Self::new(1e-6)
+    }
+}
+
+/// States at the accepted steps of an integration
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Solution {
+    pub times: Vec<f64>,
+    pub states: Vec<Vec<f64>>,
+    /// Steps retried with a smaller size because the error was too large
+    pub rejected: usize,
+}
+
+impl Solution {
+    /// Time and state at the end
+    pub fn last(&self) -> Option<(f64, &[f64])> {
+        Some((*self.times.last()?, self.states.last()?))
+    }
+}
+
+/// Outcome of one [`Rk45::attempt`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attempt {
+    /// The state after the step, to keep if it was accepted
+    pub state: Vec<f64>,
+    /// Whether the error estimate was within the tolerance
+    pub accepted: bool,
+    /// Step size the controller suggests trying next (positive)
+    pub next_step: f64,
+}
+
+impl Rk45 {
+    pub fn new(tolerance: f64) -> Self {
+        Self {
+            tolerance,
+            min_step: 1e-12,
+            max_step: f64::INFINITY,
+            max_steps: 100_000,
+        }
+    }
+
+    pub fn with_step_limits(mut self, min_step: f64, max_step: f64) -> Self {
+        self.min_step = min_step;
+        self.max_step = max_step;
+        self
+    }
+
+    /// One step of size `h` from `(t, y)`: the 4th order state and the error estimate
+    pub fn try_step(
+        &self,
+        f: &mut impl FnMut(f64, &[f64], &mut [f64]),
+        t: f64,
+        y: &[f64],
+        h: f64,
+    ) -> (Vec<f64>, Vec<f64>) {
+        let n = y.len();
+        let mut k = [(); 6].map(|_| vec![0.0; n]);
+        let mut stage = vec![0.0; n];
+        for (i, (c, a)) in C.iter().zip(A).enumerate() {
+            let (done, rest) = k.split_at_mut(i);
+            stage.copy_from_slice(y);
+            for (k_j, a_j) in done.iter().zip(a) {
+                axpy(h * a_j, k_j, &mut stage);
+            }
+            f(t + c * h, &stage, &mut rest[0]);
+        }
+        let mut next = y.to_vec();
+        let mut error = vec![0.0; n];
+        for (i, k_i) in k.iter().enumerate() {
+            axpy(h * B4[i], k_i, &mut next);
+            axpy(h * (B5[i] - B4[i]), k_i, &mut error);
+        }
+        (next, error)
+    }
+
+    /// One step of `h` (negative to go backwards) from `(t, y)`, judged against the
+    /// tolerance, for callers that drive the stepping themselves
+    pub fn attempt(
+        &self,
+        f: &mut impl FnMut(f64, &[f64], &mut [f64]),
+        t: f64,
+        y: &[f64],
+        h: f64,
+    ) -> Attempt {
+        let (state, error) = self.try_step(f, t, y, h);
+        // Error relative to the tolerance: at most 1 is accepted
+        let scale: Vec<f64> = error
+            .iter()
+            .zip(y)
+            .map(|(error, y)| error / (self.tolerance * y.abs().max(1.0)))
+            .collect();
+        let ratio = max_abs(&scale);
+        // The error scales as h⁵, so this factor aims for a ratio just under 1
+        let factor = if ratio > 0.0 { (0.9 * ratio.powf(-0.2)).clamp(0.2, 5.0) } else { 5.0 };
+        Attempt {
+            state,
+            accepted: ratio <= 1.0,
+            next_step: (h.abs() * factor).clamp(self.min_step, self.max_step),
+        }
+    }
+
+    /// Integrate from `(t0, y0)` to `t1` (which may be before `t0`), starting with step
+    /// `h0` and adapting it; every accepted step is in the [`Solution`]
+    pub fn integrate(
+        &self,
+        mut f: impl FnMut(f64, &[f64], &mut [f64]),
+        t0: f64,
+        y0: &[f64],
+        t1: f64,
+        h0: f64,
+    ) -> Result<Solution, OdeError> {
+        let direction = if t1 >= t0 { 1.0 } else { -1.0 };
+        let mut solution = Solution {
+            times: vec![t0],
+            states: vec![y0.to_vec()],
+            rejected: 0,
+        };
+        let (mut t, mut y) = (t0, y0.to_vec());
+        let mut h = h0.abs().clamp(self.min_step, self.max_step);
+        for _ in 0..self.max_steps {
+            let remaining = (t1 - t) * direction;
+            if remaining <= 0.0 {
+                return Ok(solution);
+            }
+            let last = h >= remaining;
+            let step = h.min(remaining);
+            let attempt = self.attempt(&mut f, t, &y, step * direction);
+            if attempt.accepted {
+                t = if last { t1 } else { t + step * direction };
+                y = attempt.state;
+                solution.times.push(t);
+                solution.states.push(y.clone());
+            } else {
+                solution.rejected += 1;
+                if step <= self.min_step {
+                    return Err(OdeError::StepTooSmall(t));
+                }
+            }
+            h = attempt.next_step;
+        }
+        if (t1 - t) * direction <= 0.0 {
+            Ok(solution)
+        } else {
+            Err(OdeError::TooManySteps(t))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `dy/dt = -y`, so `y = y0 e^(-t)`
+    fn decay(_t: f64, y: &[f64], dy: &mut [f64]) {
+        dy[0] = -y[0];
+    }
+
+    /// `x'' = -x` as `[x, v]`, so `x = cos t` from rest at 1
+    fn oscillator(_t: f64, y: &[f64], dy: &mut [f64]) {
+        dy[0] = y[1];
+        dy[1] = -y[0];
+    }
+
+    #[test]
+    fn decay_stays_near_the_tolerance() {
+        let tolerance = 1e-8;
+        let solution = Rk45::new(tolerance).integrate(decay, 0.0, &[1.0], 5.0, 0.1).unwrap();
+        let (t, y) = solution.last().unwrap();
+        assert_eq!(t, 5.0);
+        let error = (y[0] - (-5.0f64).exp()).abs();
+        // Each step is held under the tolerance, so the total can't be many times over
+        assert!(error < 10.0 * tolerance, "error {error}");
+        for (t, y) in solution.times.iter().zip(&solution.states) {
+            assert!((y[0] - (-t).exp()).abs() < 10.0 * tolerance, "off at t = {t}");
+        }
+    }
+
+    #[test]
+    fn oscillator_keeps_its_phase_and_amplitude() {
+        let tolerance = 1e-8;
+        let period = std::f64::consts::TAU;
+        let solution = Rk45::new(tolerance).integrate(oscillator, 0.0, &[1.0, 0.0], 3.0 * period, 0.1).unwrap();
+        let (t, y) = solution.last().unwrap();
+        assert_eq!(t, 3.0 * period);
+        // The local errors add up over the steps, but no further
+        let steps = solution.times.len() as f64;
+        let bound = steps * tolerance;
+        assert!((y[0] - 1.0).abs() < bound, "x = {} after {steps} steps", y[0]);
+        assert!(y[1].abs() < bound, "v = {} after {steps} steps", y[1]);
+    }
+
+    #[test]
+    fn integrates_backwards() {
+        let tolerance = 1e-8;
+        let start = (-1.0f64).exp();
+        let solution = Rk45::new(tolerance).integrate(decay, 1.0, &[start], 0.0, 0.1).unwrap();
+        let (t, y) = solution.last().unwrap();
+        assert_eq!(t, 0.0);
+        assert!((y[0] - 1.0).abs() < 10.0 * tolerance, "y(0) = {}", y[0]);
+        assert!(solution.times.windows(2).all(|pair| pair[1] < pair[0]));
+    }
+
+    #[test]
+    fn step_too_small_when_the_smallest_step_misses_the_tolerance() {
+        // Steps of a whole time constant can't reach this tolerance
+        let rk45 = Rk45::new(1e-12).with_step_limits(1.0, 1.0);
+        let result = rk45.integrate(decay, 0.0, &[1.0], 10.0, 1.0);
+        assert_eq!(result, Err(OdeError::StepTooSmall(0.0)));
+    }
+
+    #[test]
+    fn too_many_steps_reports_how_far_it_got() {
+        let rk45 = Rk45 {
+            max_steps: 10,
+            ..Rk45::new(1e-6).with_step_limits(1e-12, 0.01)
+        };
+        match rk45.integrate(decay, 0.0, &[1.0], 1.0, 0.01) {
+            Err(OdeError::TooManySteps(t)) => assert!(t > 0.0 && t < 1.0, "stopped at {t}"),
+            other => panic!("expected TooManySteps, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! Root finding in one variable
+//!
+//! [`bisection`] always converges once a sign change is bracketed, one bit per
+//! iteration. [`newton`] converges much faster near a simple root but can wander off
+//! from a poor start, so [`newton_bisection`] takes Newton steps and falls back to
+//! bisection whenever one would leave the bracket.
+
+/// Why a root wasn't found
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RootError {
+    /// `f` has the same sign at both ends of the interval
+    NotBracketed,
+    /// The derivative vanished at this point, so Newton's step is undefined
+    ZeroDerivative(f64),
+    /// Still not within tolerance after the allowed iterations; the best estimate
+    NoConvergence(f64),
+}
+
+impl std::fmt::Display for RootError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RootError::NotBracketed => write!(f, "the interval doesn't bracket a sign change"),
+            RootError::ZeroDerivative(x) => write!(f, "zero derivative at {x}"),
+            RootError::NoConvergence(x) => write!(f, "no convergence, last estimate {x}"),
+        }
+    }
+}
+
+impl std::error::Error for RootError {}
+
+/// Iterations allowed by default: enough for bisection to reach f64 precision on any
+/// reasonable interval
+pub const MAX_ITERATIONS: usize = 100;
+
+/// Root of `f` in `[a, b]`, to within `tolerance` in x
+pub fn bisection(mut f: impl FnMut(f64) -> f64, a: f64, b: f64, tolerance: f64) -> Result<f64, RootError> {
+    let (mut low, mut high) = (a.min(b), a.max(b));
+    let mut f_low = f(low);
+    let f_high = f(high);
+    if f_low == 0.0 {
+        return Ok(low);
+    }
+    if f_high == 0.0 {
+        return Ok(high);
+    }
+    if f_low.signum() == f_high.signum() {
+        return Err(RootError::NotBracketed);
+    }
+    for _ in 0..MAX_ITERATIONS {
+        let middle = 0.5 * (low + high);
+        if high - low <= 2.0 * tolerance {
+            return Ok(middle);
+        }
+        let f_middle = f(middle);
+        if f_middle == 0.0 {
+            return Ok(middle);
+        }
+        if f_middle.signum() == f_low.signum() {
+            low = middle;
+            f_low = f_middle;
+        } else {
+            high = middle;
+        }
+    }
+    Err(RootError::NoConvergence(0.5 * (low + high)))
+}
+
+/// Root of `f` near `x0` by Newton's method with derivative `df`, stopping when a
+/// step is smaller than `tolerance`
+pub fn newton(
+    mut f: impl FnMut(f64) -> f64,
+    mut df: impl FnMut(f64) -> f64,
+    x0: f64,
+    tolerance: f64,
+) -> Result<f64, RootError> {
+    let mut x = x0;
+    for _ in 0..MAX_ITERATIONS {
+        let slope = df(x);
+        if slope == 0.0 {
+            return Err(RootError::ZeroDerivative(x));
+        }
+        let step = f(x) / slope;
+        x -= step;
+        if step.abs() <= tolerance {
+            return Ok(x);
+        }
+    }
+    Err(RootError::NoConvergence(x))
+}
+
+/// Root of `f` in `[a, b]` by Newton steps kept inside a shrinking bracket, with
+/// bisection whenever a step would leave it; as safe as [`bisection`], as fast as
+/// [`newton`] near the root
+pub fn newton_bisection(
+    mut f: impl FnMut(f64) -> f64,
+    mut df: impl FnMut(f64) -> f64,
+    a: f64,
+    b: f64,
+    tolerance: f64,
+) -> Result<f64, RootError> {
+    let (mut low, mut high) = (a.min(b), a.max(b));
+    let f_low = f(low);
+    let f_high = f(high);
+    if f_low == 0.0 {
+        return Ok(low);
+    }
+    if f_high == 0.0 {
+        return Ok(high);
+    }
+    if f_low.signum() == f_high.signum() {
+        return Err(RootError::NotBracketed);
+    }
+    let low_sign = f_low.signum();
+    let mut x = 0.5 * (low + high);
+    for _ in 0..MAX_ITERATIONS {
+        let value = f(x);
+        if value == 0.0 {
+            return Ok(x);
+        }
+        if value.signum() == low_sign {
+            low = x;
+        } else {
+            high = x;
+        }
+        let slope = df(x);
+        let newton_x = x - value / slope;
+        let next = if slope != 0.0 && newton_x > low && newton_x < high {
+            newton_x
+        } else {
+            0.5 * (low + high)
+        };
+        if (next - x).abs() <= tolerance || high - low <= 2.0 * tolerance {
+            return Ok(next);
+        }
+        x = next;
+    }
+    Err(RootError::NoConvergence(x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `x² - 2`, whose positive root is √2
+    fn f(x: f64) -> f64 {
+        x * x - 2.0
+    }
+
+    fn df(x: f64) -> f64 {
+        2.0 * x
+    }
+
+    #[test]
+    fn bisection_finds_root_two() {
+        let root = bisection(f, 0.0, 2.0, 1e-12).unwrap();
+        assert!((root - 2f64.sqrt()).abs() <= 1e-12);
+        // The ends may come in either order
+        let root = bisection(f, 2.0, 0.0, 1e-12).unwrap();
+        assert!((root - 2f64.sqrt()).abs() <= 1e-12);
+    }
+
+    #[test]
+    fn bisection_needs_a_sign_change() {
+        assert_eq!(bisection(f, 2.0, 3.0, 1e-12), Err(RootError::NotBracketed));
+        assert_eq!(newton_bisection(f, df, -1.0, 1.0, 1e-12), Err(RootError::NotBracketed));
+    }
+
+    #[test]
+    fn newton_finds_root_two() {
+        let root = newton(f, df, 1.0, 1e-14).unwrap();
+        assert!((root - 2f64.sqrt()).abs() <= 1e-14);
+    }
+
+    #[test]
+    fn newton_stops_at_a_flat_point() {
+        assert_eq!(newton(f, df, 0.0, 1e-12), Err(RootError::ZeroDerivative(0.0)));
+    }
+
+    #[test]
+    fn newton_bisection_stays_in_the_bracket() {
+        // Newton from the flat point at 0 would fail; the bracket saves it
+        let root = newton_bisection(f, df, 0.0, 3.0, 1e-12).unwrap();
+        assert!((root - 2f64.sqrt()).abs() <= 1e-12);
+    }
+}